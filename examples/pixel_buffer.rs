@@ -0,0 +1,59 @@
+// Draws to a multisampled frame, reads the resolved pixels back into a `PixelBuffer`, inverts the
+// colors of the left half of the window and writes the result back to the frame.
+//
+// The window skips the frame intermediary, so the frame is only resolved to a single-sampled
+// texture when its pixels are loaded.
+
+use nannou::image::Rgba;
+use nannou::prelude::*;
+use std::cell::RefCell;
+
+fn main() {
+    nannou::app(model).view(view).run();
+}
+
+struct Model {
+    pixels: RefCell<wgpu::PixelBuffer>,
+}
+
+fn model(app: &App) -> Model {
+    let window_id = app
+        .new_window()
+        .size(640, 480)
+        .msaa_samples(4)
+        .frame_texture_format(wgpu::TextureFormat::Rgba8UnormSrgb)
+        .frame_intermediary(false)
+        .build()
+        .unwrap();
+    let window = app.window(window_id).unwrap();
+    let pixels = wgpu::PixelBuffer::new(window.swap_chain_device(), [1, 1]);
+    Model {
+        pixels: RefCell::new(pixels),
+    }
+}
+
+fn view(app: &App, model: &Model, frame: Frame) {
+    let draw = app.draw();
+    draw.background().color(BLACK);
+    let t = app.time;
+    draw.ellipse()
+        .x_y(t.cos() * 100.0, 0.0)
+        .radius(150.0)
+        .color(RED);
+    draw.to_frame(app, &frame).unwrap();
+
+    // Read the multisampled frame back. The centre of the window is always within the ellipse.
+    let mut pixels = model.pixels.borrow_mut();
+    frame.load_pixels(&mut pixels);
+    let [w, h] = pixels.size();
+    assert_eq!(pixels.get(w / 2, h / 2), Rgba([255, 0, 0, 255]));
+
+    // Invert the left half of the window.
+    for y in 0..h {
+        for x in 0..w / 2 {
+            let Rgba([r, g, b, a]) = pixels.get(x, y);
+            pixels.set(x, y, Rgba([255 - r, 255 - g, 255 - b, a]));
+        }
+    }
+    frame.update_pixels(&mut pixels);
+}
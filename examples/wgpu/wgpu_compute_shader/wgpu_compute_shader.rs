@@ -14,11 +14,9 @@ struct Model {
 }
 
 struct Compute {
-    oscillator_buffer: wgpu::Buffer,
-    oscillator_buffer_size: wgpu::BufferAddress,
-    uniform_buffer: wgpu::Buffer,
-    bind_group: wgpu::BindGroup,
-    pipeline: wgpu::ComputePipeline,
+    oscillator_buffer: wgpu::compute::Buffer<f32>,
+    uniform_buffer: wgpu::compute::Buffer<Uniforms>,
+    pipeline: wgpu::compute::Pipeline,
 }
 
 #[repr(C)]
@@ -46,38 +44,25 @@ fn model(app: &App) -> Model {
     let cs_mod = device.create_shader_module(&cs_spirv);
 
     // Create the buffer that will store the result of our compute operation.
-    let oscillator_buffer_size =
-        (OSCILLATOR_COUNT as usize * std::mem::size_of::<f32>()) as wgpu::BufferAddress;
-    let oscillator_buffer = device.create_buffer(&wgpu::BufferDescriptor {
-        size: oscillator_buffer_size,
-        usage: wgpu::BufferUsage::STORAGE
-            | wgpu::BufferUsage::COPY_DST
-            | wgpu::BufferUsage::COPY_SRC,
-    });
+    let oscillator_buffer = wgpu::compute::Buffer::new(
+        device,
+        OSCILLATOR_COUNT as usize,
+        wgpu::BufferUsage::STORAGE | wgpu::BufferUsage::COPY_SRC,
+    );
 
     // Create the buffer that will store time.
     let uniforms = create_uniforms(app.time, app.mouse.x, window.rect());
-    let uniform_buffer = device
-        .create_buffer_mapped(1, wgpu::BufferUsage::UNIFORM | wgpu::BufferUsage::COPY_DST)
-        .fill_from_slice(&[uniforms]);
+    let uniform_buffer = wgpu::compute::Buffer::uniform(device, &uniforms);
 
-    // Create the bind group and pipeline.
-    let bind_group_layout = create_bind_group_layout(device);
-    let bind_group = create_bind_group(
-        device,
-        &bind_group_layout,
-        &oscillator_buffer,
-        oscillator_buffer_size,
-        &uniform_buffer,
-    );
-    let pipeline_layout = create_pipeline_layout(device, &bind_group_layout);
-    let pipeline = create_compute_pipeline(device, &pipeline_layout, &cs_mod);
+    // Create the compute pipeline, declaring the resources accessed by the shader.
+    let pipeline = wgpu::compute::Builder::new(&cs_mod)
+        .storage_buffer(0, &oscillator_buffer)
+        .uniform_buffer(1, &uniform_buffer)
+        .build(device);
 
     let compute = Compute {
         oscillator_buffer,
-        oscillator_buffer_size,
         uniform_buffer,
-        bind_group,
         pipeline,
     };
 
@@ -96,43 +81,20 @@ fn update(app: &App, model: &mut Model, _update: Update) {
     let win_rect = window.rect();
     let compute = &mut model.compute;
 
-    // The buffer into which we'll read some data.
-    let read_buffer = device.create_buffer(&wgpu::BufferDescriptor {
-        size: compute.oscillator_buffer_size,
-        usage: wgpu::BufferUsage::MAP_READ
-            | wgpu::BufferUsage::COPY_DST
-            | wgpu::BufferUsage::COPY_SRC,
-    });
+    // The encoder we'll use to encode the compute pass.
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
 
     // An update for the uniform buffer with the current time.
     let uniforms = create_uniforms(app.time, app.mouse.x, win_rect);
-    let uniforms_size = std::mem::size_of::<Uniforms>() as wgpu::BufferAddress;
-    let new_uniform_buffer = device
-        .create_buffer_mapped(1, wgpu::BufferUsage::COPY_SRC)
-        .fill_from_slice(&[uniforms]);
+    compute
+        .uniform_buffer
+        .encode_write(device, &mut encoder, &[uniforms]);
 
-    // The encoder we'll use to encode the compute pass.
-    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
-    encoder.copy_buffer_to_buffer(
-        &new_uniform_buffer,
-        0,
-        &compute.uniform_buffer,
-        0,
-        uniforms_size,
-    );
-    {
-        let mut cpass = encoder.begin_compute_pass();
-        cpass.set_pipeline(&compute.pipeline);
-        cpass.set_bind_group(0, &compute.bind_group, &[]);
-        cpass.dispatch(OSCILLATOR_COUNT as u32, 1, 1);
-    }
-    encoder.copy_buffer_to_buffer(
-        &compute.oscillator_buffer,
-        0,
-        &read_buffer,
-        0,
-        compute.oscillator_buffer_size,
-    );
+    // Dispatch the compute shader and copy the result into a buffer that we can read.
+    compute
+        .pipeline
+        .encode_dispatch(&mut encoder, [OSCILLATOR_COUNT, 1, 1]);
+    let read = compute.oscillator_buffer.encode_read(device, &mut encoder);
 
     // Submit the compute pass to the device's queue.
     window
@@ -143,17 +105,13 @@ fn update(app: &App, model: &mut Model, _update: Update) {
 
     // Register a callback for reading the result of the compute pass.
     let oscillators = model.oscillators.clone();
-    read_buffer.map_read_async(
-        0,
-        compute.oscillator_buffer_size,
-        move |result: wgpu::BufferMapAsyncResult<&[f32]>| {
-            if let Ok(mapping) = result {
-                if let Ok(mut oscillators) = oscillators.lock() {
-                    oscillators.copy_from_slice(&mapping.data);
-                }
+    read.read(move |result| {
+        if let Ok(data) = result {
+            if let Ok(mut oscillators) = oscillators.lock() {
+                oscillators.copy_from_slice(data);
             }
-        },
-    );
+        }
+    });
 
     // Check for resource cleanups and mapping callbacks.
     //
@@ -200,72 +158,3 @@ fn create_uniforms(time: f32, mouse_x: f32, win_rect: geom::Rect) -> Uniforms {
         oscillator_count,
     }
 }
-
-fn create_bind_group_layout(device: &wgpu::Device) -> wgpu::BindGroupLayout {
-    let oscillator_buffer_binding = wgpu::BindGroupLayoutBinding {
-        binding: 0,
-        visibility: wgpu::ShaderStage::COMPUTE,
-        ty: wgpu::BindingType::StorageBuffer {
-            dynamic: false,
-            readonly: false,
-        },
-    };
-    let uniforms_binding = wgpu::BindGroupLayoutBinding {
-        binding: 1,
-        visibility: wgpu::ShaderStage::COMPUTE,
-        ty: wgpu::BindingType::UniformBuffer { dynamic: false },
-    };
-    device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-        bindings: &[oscillator_buffer_binding, uniforms_binding],
-    })
-}
-
-fn create_bind_group(
-    device: &wgpu::Device,
-    layout: &wgpu::BindGroupLayout,
-    oscillator_buffer: &wgpu::Buffer,
-    oscillator_buffer_size: wgpu::BufferAddress,
-    uniform_buffer: &wgpu::Buffer,
-) -> wgpu::BindGroup {
-    let oscillator_buffer_binding = wgpu::Binding {
-        binding: 0,
-        resource: wgpu::BindingResource::Buffer {
-            buffer: &oscillator_buffer,
-            range: 0..oscillator_buffer_size,
-        },
-    };
-    let uniforms_binding = wgpu::Binding {
-        binding: 1,
-        resource: wgpu::BindingResource::Buffer {
-            buffer: &uniform_buffer,
-            range: 0..std::mem::size_of::<Uniforms>() as wgpu::BufferAddress,
-        },
-    };
-    let bindings = &[oscillator_buffer_binding, uniforms_binding];
-    device.create_bind_group(&wgpu::BindGroupDescriptor { layout, bindings })
-}
-
-fn create_pipeline_layout(
-    device: &wgpu::Device,
-    bind_group_layout: &wgpu::BindGroupLayout,
-) -> wgpu::PipelineLayout {
-    device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-        bind_group_layouts: &[&bind_group_layout],
-    })
-}
-
-fn create_compute_pipeline(
-    device: &wgpu::Device,
-    layout: &wgpu::PipelineLayout,
-    cs_mod: &wgpu::ShaderModule,
-) -> wgpu::ComputePipeline {
-    let compute_stage = wgpu::ProgrammableStageDescriptor {
-        module: &cs_mod,
-        entry_point: "main",
-    };
-    let desc = wgpu::ComputePipelineDescriptor {
-        layout,
-        compute_stage,
-    };
-    device.create_compute_pipeline(&desc)
-}
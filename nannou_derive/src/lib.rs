@@ -0,0 +1,360 @@
+//! Custom derive macros for nannou.
+//!
+//! These are re-exported via the `nannou` crate and should be used from there, e.g.
+//! `nannou::param::Params`.
+
+extern crate proc_macro;
+
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::quote;
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::{parse_macro_input, Data, DeriveInput, Expr, Fields, Ident, Lit, Token};
+
+/// Derive `nannou::param::Fields` for a struct or `nannou::param::Field` for a fieldless enum.
+///
+/// The following options may be specified via a `#[param(...)]` attribute on each field:
+///
+/// - `range = min..=max`: the range of an `f32` or `f64` field. Defaults to `0.0..=1.0`.
+/// - `step = n`: the step by which an `f32` or `f64` field is quantised.
+/// - `label = "Label"`: the label displayed within a GUI. Defaults to the field name.
+/// - `skip`: do not expose the field as a parameter.
+///
+/// Enum variants may only specify a `label`.
+#[proc_macro_derive(Params, attributes(param))]
+pub fn derive_params(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match expand(&input) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+fn expand(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    match input.data {
+        Data::Struct(ref data) => impl_fields(input, &data.fields),
+        Data::Enum(ref data) => impl_field(input, data.variants.iter()),
+        Data::Union(_) => Err(syn::Error::new(
+            Span::call_site(),
+            "`Params` may only be derived for structs and enums",
+        )),
+    }
+}
+
+// The options specified via `#[param(...)]` attributes, along with the name of each option for
+// error reporting.
+#[derive(Default)]
+struct Attrs {
+    range: Option<(Ident, Expr, Expr)>,
+    step: Option<(Ident, Expr)>,
+    label: Option<String>,
+    skip: Option<Ident>,
+}
+
+// A single `name` or `name = value` option.
+struct Opt {
+    name: Ident,
+    value: Option<Expr>,
+}
+
+impl Parse for Opt {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let name = input.parse()?;
+        let value = if input.peek(Token![=]) {
+            input.parse::<Token![=]>()?;
+            Some(input.parse()?)
+        } else {
+            None
+        };
+        Ok(Opt { name, value })
+    }
+}
+
+impl Attrs {
+    fn from_attrs(attrs: &[syn::Attribute]) -> syn::Result<Self> {
+        let mut result = Attrs::default();
+        for attr in attrs.iter().filter(|a| a.path.is_ident("param")) {
+            let opts = attr.parse_args_with(Punctuated::<Opt, Token![,]>::parse_terminated)?;
+            for opt in opts {
+                let name = opt.name.to_string();
+                match (name.as_str(), opt.value) {
+                    ("skip", None) => result.skip = Some(opt.name),
+                    ("step", Some(expr)) => result.step = Some((opt.name, expr)),
+                    ("range", Some(Expr::Range(range))) => match (range.from, range.to) {
+                        (Some(from), Some(to)) => result.range = Some((opt.name, *from, *to)),
+                        _ => return Err(err(&opt.name, "expected a range like `0.0..=1.0`")),
+                    },
+                    ("label", Some(Expr::Lit(ref expr))) => match expr.lit {
+                        Lit::Str(ref s) => result.label = Some(s.value()),
+                        _ => return Err(err(&opt.name, "expected a string literal")),
+                    },
+                    _ => return Err(err(&opt.name, "unknown or malformed `param` option")),
+                }
+            }
+        }
+        Ok(result)
+    }
+
+    // The name of the first option that only applies to float fields, if any.
+    fn float_opt(&self) -> Option<&Ident> {
+        let range = self.range.as_ref().map(|(name, _, _)| name);
+        range.or_else(|| self.step.as_ref().map(|(name, _)| name))
+    }
+}
+
+// Whether or not the given type is `f32` or `f64`.
+fn is_float(ty: &syn::Type) -> bool {
+    match *ty {
+        syn::Type::Path(ref ty) if ty.qself.is_none() => {
+            ty.path.is_ident("f32") || ty.path.is_ident("f64")
+        }
+        _ => false,
+    }
+}
+
+fn err(name: &Ident, msg: &str) -> syn::Error {
+    syn::Error::new(name.span(), msg)
+}
+
+// Implement `nannou::param::Fields` for a struct.
+fn impl_fields(input: &DeriveInput, fields: &Fields) -> syn::Result<TokenStream2> {
+    let fields = match *fields {
+        Fields::Named(ref fields) => &fields.named,
+        _ => {
+            return Err(syn::Error::new(
+                input.ident.span(),
+                "`Params` may only be derived for structs with named fields",
+            ))
+        }
+    };
+
+    let mut register = vec![];
+    let mut read = vec![];
+    let mut write = vec![];
+    for field in fields {
+        let attrs = Attrs::from_attrs(&field.attrs)?;
+        if attrs.skip.is_some() {
+            continue;
+        }
+        let ty = &field.ty;
+        if let Some(opt) = attrs.float_opt() {
+            if !is_float(ty) {
+                return Err(err(opt, "may only be specified for `f32` and `f64` fields"));
+            }
+        }
+        let ident = field.ident.as_ref().expect("named field");
+        let name = ident.to_string();
+        let label = attrs.label.unwrap_or_else(|| name.clone());
+        let range = match attrs.range {
+            Some((_, min, max)) => quote! { Some(((#min) as f32, (#max) as f32)) },
+            None => quote! { None },
+        };
+        let step = match attrs.step {
+            Some((_, step)) => quote! { Some((#step) as f32) },
+            None => quote! { None },
+        };
+
+        register.push(quote! {
+            let attrs = ::nannou::param::Attrs { range: #range, step: #step };
+            let kind = <#ty as ::nannou::param::Field>::kind(&attrs);
+            let value = ::nannou::param::Field::to_value(&self.#ident);
+            params.insert(::nannou::param::Param::new(#name, kind, value).with_label(#label));
+        });
+        read.push(quote! {
+            if let Some(value) = params.get(#name) {
+                if let Some(field) = <#ty as ::nannou::param::Field>::from_value(&value) {
+                    self.#ident = field;
+                }
+            }
+        });
+        write.push(quote! {
+            params.set(#name, ::nannou::param::Field::to_value(&self.#ident)).ok();
+        });
+    }
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::nannou::param::Fields for #ident #ty_generics #where_clause {
+            fn register(&self, params: &::nannou::param::Params) {
+                #(#register)*
+            }
+
+            fn read(&mut self, params: &::nannou::param::Params) {
+                #(#read)*
+            }
+
+            fn write(&self, params: &::nannou::param::Params) {
+                #(#write)*
+            }
+        }
+    })
+}
+
+// Implement `nannou::param::Field` for an enum whose variants have no fields.
+fn impl_field<'a, I>(input: &DeriveInput, variants: I) -> syn::Result<TokenStream2>
+where
+    I: Iterator<Item = &'a syn::Variant>,
+{
+    let ident = &input.ident;
+    let mut labels = vec![];
+    let mut idents = vec![];
+    for variant in variants {
+        match variant.fields {
+            Fields::Unit => (),
+            _ => {
+                return Err(syn::Error::new(
+                    variant.ident.span(),
+                    "`Params` may only be derived for enums whose variants have no fields",
+                ))
+            }
+        }
+        let attrs = Attrs::from_attrs(&variant.attrs)?;
+        if let Some(opt) = attrs.skip.as_ref().or_else(|| attrs.float_opt()) {
+            return Err(err(opt, "only `label` may be specified for enum variants"));
+        }
+        labels.push(attrs.label.unwrap_or_else(|| variant.ident.to_string()));
+        idents.push(&variant.ident);
+    }
+    let indices: Vec<usize> = (0..idents.len()).collect();
+
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::nannou::param::Field for #ident #ty_generics #where_clause {
+            fn kind(_attrs: &::nannou::param::Attrs) -> ::nannou::param::Kind {
+                let variants = vec![#(#labels.to_string()),*];
+                ::nannou::param::Kind::Enum { variants }
+            }
+
+            fn to_value(&self) -> ::nannou::param::Value {
+                let index = match *self {
+                    #(#ident::#idents => #indices,)*
+                };
+                ::nannou::param::Value::Enum(index)
+            }
+
+            fn from_value(value: &::nannou::param::Value) -> Option<Self> {
+                match *value {
+                    #(::nannou::param::Value::Enum(#indices) => Some(#ident::#idents),)*
+                    _ => None,
+                }
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use syn::parse_quote;
+
+    fn expand_err(input: DeriveInput) -> String {
+        match expand(&input) {
+            Ok(_) => panic!("expected an error"),
+            Err(err) => err.to_string(),
+        }
+    }
+
+    #[test]
+    fn struct_options() {
+        let input: DeriveInput = parse_quote! {
+            struct Settings {
+                #[param(range = 1.0..=200.0, step = 1.0, label = "Radius")]
+                radius: f32,
+                #[param(skip)]
+                cache: Vec<f32>,
+                visible: bool,
+            }
+        };
+        let tokens = expand(&input).unwrap().to_string();
+        assert!(tokens.contains("\"Radius\""));
+        assert!(tokens.contains("200.0"));
+        assert!(!tokens.contains("cache"));
+        let radius = tokens.find("\"radius\"").unwrap();
+        let visible = tokens.find("\"visible\"").unwrap();
+        assert!(radius < visible);
+    }
+
+    #[test]
+    fn enum_labels() {
+        let input: DeriveInput = parse_quote! {
+            enum Shape {
+                Circle,
+                #[param(label = "Box")]
+                Square,
+            }
+        };
+        let tokens = expand(&input).unwrap().to_string();
+        assert!(tokens.contains("\"Circle\""));
+        assert!(tokens.contains("\"Box\""));
+    }
+
+    #[test]
+    fn float_options_on_non_float_field() {
+        let input: DeriveInput = parse_quote! {
+            struct Settings {
+                #[param(range = 0..=10)]
+                count: u32,
+            }
+        };
+        assert!(expand_err(input).contains("`f32` and `f64`"));
+        let input: DeriveInput = parse_quote! {
+            struct Settings {
+                #[param(step = 1.0)]
+                visible: bool,
+            }
+        };
+        assert!(expand_err(input).contains("`f32` and `f64`"));
+        let input: DeriveInput = parse_quote! {
+            struct Settings {
+                #[param(range = 0.0..=10.0, step = 0.5)]
+                a: f64,
+                #[param(skip, step = 0.5)]
+                b: String,
+            }
+        };
+        assert!(expand(&input).is_ok());
+    }
+
+    #[test]
+    fn invalid_enum_variant_options() {
+        let input: DeriveInput = parse_quote! {
+            enum Shape {
+                #[param(skip)]
+                Circle,
+                Square,
+            }
+        };
+        assert!(expand_err(input).contains("only `label`"));
+        let input: DeriveInput = parse_quote! {
+            enum Shape {
+                #[param(range = 0.0..=1.0)]
+                Circle,
+            }
+        };
+        assert!(expand_err(input).contains("only `label`"));
+    }
+
+    #[test]
+    fn malformed_options() {
+        let input: DeriveInput = parse_quote! {
+            struct Settings {
+                #[param(label = 5)]
+                a: f32,
+            }
+        };
+        assert!(expand_err(input).contains("string literal"));
+        let input: DeriveInput = parse_quote! {
+            struct Settings {
+                #[param(colour)]
+                a: f32,
+            }
+        };
+        assert!(expand_err(input).contains("unknown"));
+        let input: DeriveInput = parse_quote! {
+            struct Settings(f32);
+        };
+        assert!(expand_err(input).contains("named fields"));
+    }
+}
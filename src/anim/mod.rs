@@ -0,0 +1,404 @@
+//! Items related to animating values over time.
+//!
+//! - A **Tween** interpolates between a start and end value over a duration using an `ease`
+//!   curve, with support for a delay, repeats and "yoyo" playback.
+//! - A **Timeline** sequences and overlaps tweens and may be built from a set of **Keyframes**,
+//!   which may in turn be authored in code or loaded from JSON.
+//!
+//! Any type implementing the **Lerp** trait may be animated. Implementations are provided for
+//! floats, vectors, ranges, rects and linear colors.
+//!
+//! For physics-based motion that follows a moving target:
+//!
+//! - A **Spring** accelerates towards its target based on its stiffness, damping and mass.
+//! - A **SmoothDamp** follows its target with critically damped smoothing, never overshooting.
+//!
+//! Both operate on any type implementing the **Linear** trait, including floats, vectors and
+//! linear colors.
+//!
+//! All animations are driven by calling `update` with `Update::since_last`.
+
+use crate::color::{LinSrgb, LinSrgba};
+use crate::geom::{Range, Rect, Vector2, Vector3, Vector4};
+use crate::math::num_traits::cast;
+use crate::math::BaseFloat;
+use crate::serde_derive::{Deserialize, Serialize};
+
+pub use self::smooth::SmoothDamp;
+pub use self::spring::Spring;
+pub use self::timeline::{Keyframe, Keyframes, Timeline};
+pub use self::tween::{Repeat, Tween};
+
+pub mod smooth;
+pub mod spring;
+pub mod timeline;
+pub mod tween;
+
+/// The signature of the `ease` functions, e.g. `ease::cubic::ease_in_out`.
+///
+/// The arguments are the current time, the start value, the change in value and the duration.
+pub type Ease = fn(f64, f64, f64, f64) -> f64;
+
+/// Types that may be linearly interpolated.
+pub trait Lerp {
+    /// Interpolate between `self` and `target` by the given amount, where `0.0` produces `self`
+    /// and `1.0` produces `target`.
+    ///
+    /// Some ease curves may produce amounts outside of the range `0.0..=1.0`.
+    fn lerp(&self, target: &Self, amount: f64) -> Self;
+}
+
+/// Types that may be added, subtracted and scaled, allowing them to be driven by physics-based
+/// animations such as **Spring** and **SmoothDamp**.
+pub trait Linear: Copy {
+    /// The zero value, e.g. a velocity at rest.
+    fn zero() -> Self;
+    /// Add each component of `other` to `self`.
+    fn add(self, other: Self) -> Self;
+    /// Subtract each component of `other` from `self`.
+    fn sub(self, other: Self) -> Self;
+    /// Multiply each component by the given scalar.
+    fn scale(self, scalar: f64) -> Self;
+    /// The euclidean length of the value when treated as a vector of its components.
+    fn magnitude(self) -> f64;
+}
+
+/// A serializable description of an ease curve, useful for keyframes loaded from file.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Curve {
+    Linear,
+    QuadIn,
+    QuadOut,
+    QuadInOut,
+    CubicIn,
+    CubicOut,
+    CubicInOut,
+    QuartIn,
+    QuartOut,
+    QuartInOut,
+    QuintIn,
+    QuintOut,
+    QuintInOut,
+    SineIn,
+    SineOut,
+    SineInOut,
+    ExpoIn,
+    ExpoOut,
+    ExpoInOut,
+    CircIn,
+    CircOut,
+    CircInOut,
+    BackIn,
+    BackOut,
+    BackInOut,
+    ElasticIn,
+    ElasticOut,
+    ElasticInOut,
+    BounceIn,
+    BounceOut,
+    BounceInOut,
+}
+
+/// Linear interpolation without easing.
+pub fn linear(t: f64, b: f64, c: f64, d: f64) -> f64 {
+    c * t / d + b
+}
+
+impl Curve {
+    /// The `ease` function described by the curve.
+    pub fn ease(&self) -> Ease {
+        use crate::ease::*;
+        match *self {
+            Curve::Linear => self::linear,
+            Curve::QuadIn => quad::ease_in,
+            Curve::QuadOut => quad::ease_out,
+            Curve::QuadInOut => quad::ease_in_out,
+            Curve::CubicIn => cubic::ease_in,
+            Curve::CubicOut => cubic::ease_out,
+            Curve::CubicInOut => cubic::ease_in_out,
+            Curve::QuartIn => quart::ease_in,
+            Curve::QuartOut => quart::ease_out,
+            Curve::QuartInOut => quart::ease_in_out,
+            Curve::QuintIn => quint::ease_in,
+            Curve::QuintOut => quint::ease_out,
+            Curve::QuintInOut => quint::ease_in_out,
+            Curve::SineIn => sine::ease_in,
+            Curve::SineOut => sine::ease_out,
+            Curve::SineInOut => sine::ease_in_out,
+            Curve::ExpoIn => expo::ease_in,
+            Curve::ExpoOut => expo::ease_out,
+            Curve::ExpoInOut => expo::ease_in_out,
+            Curve::CircIn => circ::ease_in,
+            Curve::CircOut => circ::ease_out,
+            Curve::CircInOut => circ::ease_in_out,
+            Curve::BackIn => back::ease_in,
+            Curve::BackOut => back::ease_out,
+            Curve::BackInOut => back::ease_in_out,
+            Curve::ElasticIn => elastic::ease_in,
+            Curve::ElasticOut => elastic::ease_out,
+            Curve::ElasticInOut => elastic::ease_in_out,
+            Curve::BounceIn => bounce::ease_in,
+            Curve::BounceOut => bounce::ease_out,
+            Curve::BounceInOut => bounce::ease_in_out,
+        }
+    }
+}
+
+impl Default for Curve {
+    fn default() -> Self {
+        Curve::Linear
+    }
+}
+
+// Cast the interpolation amount to the given scalar type.
+fn amount<S>(amount: f64) -> S
+where
+    S: BaseFloat,
+{
+    cast(amount).expect("failed to cast interpolation amount")
+}
+
+fn lerp_scalar<S>(a: S, b: S, amount: S) -> S
+where
+    S: BaseFloat,
+{
+    a + (b - a) * amount
+}
+
+impl Lerp for f32 {
+    fn lerp(&self, target: &Self, amount: f64) -> Self {
+        lerp_scalar(*self, *target, amount as f32)
+    }
+}
+
+impl Lerp for f64 {
+    fn lerp(&self, target: &Self, amount: f64) -> Self {
+        lerp_scalar(*self, *target, amount)
+    }
+}
+
+impl<S> Lerp for Vector2<S>
+where
+    S: BaseFloat,
+{
+    fn lerp(&self, target: &Self, amount: f64) -> Self {
+        let t = self::amount(amount);
+        Vector2 {
+            x: lerp_scalar(self.x, target.x, t),
+            y: lerp_scalar(self.y, target.y, t),
+        }
+    }
+}
+
+impl<S> Lerp for Vector3<S>
+where
+    S: BaseFloat,
+{
+    fn lerp(&self, target: &Self, amount: f64) -> Self {
+        let t = self::amount(amount);
+        Vector3 {
+            x: lerp_scalar(self.x, target.x, t),
+            y: lerp_scalar(self.y, target.y, t),
+            z: lerp_scalar(self.z, target.z, t),
+        }
+    }
+}
+
+impl<S> Lerp for Vector4<S>
+where
+    S: BaseFloat,
+{
+    fn lerp(&self, target: &Self, amount: f64) -> Self {
+        let t = self::amount(amount);
+        Vector4 {
+            x: lerp_scalar(self.x, target.x, t),
+            y: lerp_scalar(self.y, target.y, t),
+            z: lerp_scalar(self.z, target.z, t),
+            w: lerp_scalar(self.w, target.w, t),
+        }
+    }
+}
+
+impl<S> Lerp for Range<S>
+where
+    S: BaseFloat,
+{
+    fn lerp(&self, target: &Self, amount: f64) -> Self {
+        let t = self::amount(amount);
+        Range {
+            start: lerp_scalar(self.start, target.start, t),
+            end: lerp_scalar(self.end, target.end, t),
+        }
+    }
+}
+
+impl<S> Lerp for Rect<S>
+where
+    S: BaseFloat,
+{
+    fn lerp(&self, target: &Self, amount: f64) -> Self {
+        Rect {
+            x: Lerp::lerp(&self.x, &target.x, amount),
+            y: Lerp::lerp(&self.y, &target.y, amount),
+        }
+    }
+}
+
+impl Lerp for LinSrgb {
+    fn lerp(&self, target: &Self, amount: f64) -> Self {
+        let t = amount as f32;
+        LinSrgb::new(
+            lerp_scalar(self.red, target.red, t),
+            lerp_scalar(self.green, target.green, t),
+            lerp_scalar(self.blue, target.blue, t),
+        )
+    }
+}
+
+impl Lerp for LinSrgba {
+    fn lerp(&self, target: &Self, amount: f64) -> Self {
+        let t = amount as f32;
+        LinSrgba::new(
+            lerp_scalar(self.red, target.red, t),
+            lerp_scalar(self.green, target.green, t),
+            lerp_scalar(self.blue, target.blue, t),
+            lerp_scalar(self.alpha, target.alpha, t),
+        )
+    }
+}
+
+// Implement `Linear` for a vector type with the given fields.
+macro_rules! impl_linear_for_vector {
+    ($Vector:ident { $($field:ident),* }) => {
+        impl<S> Linear for $Vector<S>
+        where
+            S: BaseFloat,
+        {
+            fn zero() -> Self {
+                $Vector { $($field: S::zero()),* }
+            }
+
+            fn add(self, other: Self) -> Self {
+                $Vector { $($field: self.$field + other.$field),* }
+            }
+
+            fn sub(self, other: Self) -> Self {
+                $Vector { $($field: self.$field - other.$field),* }
+            }
+
+            fn scale(self, scalar: f64) -> Self {
+                let scalar: S = self::amount(scalar);
+                $Vector { $($field: self.$field * scalar),* }
+            }
+
+            fn magnitude(self) -> f64 {
+                let sum = S::zero() $(+ self.$field * self.$field)*;
+                cast::<S, f64>(sum).expect("failed to cast magnitude").sqrt()
+            }
+        }
+    };
+}
+
+impl_linear_for_vector!(Vector2 { x, y });
+impl_linear_for_vector!(Vector3 { x, y, z });
+impl_linear_for_vector!(Vector4 { x, y, z, w });
+
+impl Linear for f32 {
+    fn zero() -> Self {
+        0.0
+    }
+
+    fn add(self, other: Self) -> Self {
+        self + other
+    }
+
+    fn sub(self, other: Self) -> Self {
+        self - other
+    }
+
+    fn scale(self, scalar: f64) -> Self {
+        self * scalar as f32
+    }
+
+    fn magnitude(self) -> f64 {
+        self.abs() as f64
+    }
+}
+
+impl Linear for f64 {
+    fn zero() -> Self {
+        0.0
+    }
+
+    fn add(self, other: Self) -> Self {
+        self + other
+    }
+
+    fn sub(self, other: Self) -> Self {
+        self - other
+    }
+
+    fn scale(self, scalar: f64) -> Self {
+        self * scalar
+    }
+
+    fn magnitude(self) -> f64 {
+        self.abs()
+    }
+}
+
+impl Linear for LinSrgb {
+    fn zero() -> Self {
+        LinSrgb::new(0.0, 0.0, 0.0)
+    }
+
+    fn add(self, o: Self) -> Self {
+        LinSrgb::new(self.red + o.red, self.green + o.green, self.blue + o.blue)
+    }
+
+    fn sub(self, o: Self) -> Self {
+        LinSrgb::new(self.red - o.red, self.green - o.green, self.blue - o.blue)
+    }
+
+    fn scale(self, scalar: f64) -> Self {
+        let s = scalar as f32;
+        LinSrgb::new(self.red * s, self.green * s, self.blue * s)
+    }
+
+    fn magnitude(self) -> f64 {
+        let sum = self.red * self.red + self.green * self.green + self.blue * self.blue;
+        (sum as f64).sqrt()
+    }
+}
+
+impl Linear for LinSrgba {
+    fn zero() -> Self {
+        LinSrgba::new(0.0, 0.0, 0.0, 0.0)
+    }
+
+    fn add(self, o: Self) -> Self {
+        let c = self.color.add(o.color);
+        LinSrgba::new(c.red, c.green, c.blue, self.alpha + o.alpha)
+    }
+
+    fn sub(self, o: Self) -> Self {
+        let c = self.color.sub(o.color);
+        LinSrgba::new(c.red, c.green, c.blue, self.alpha - o.alpha)
+    }
+
+    fn scale(self, scalar: f64) -> Self {
+        let c = self.color.scale(scalar);
+        LinSrgba::new(c.red, c.green, c.blue, self.alpha * scalar as f32)
+    }
+
+    fn magnitude(self) -> f64 {
+        let rgb = self.color.magnitude();
+        (rgb * rgb + (self.alpha * self.alpha) as f64).sqrt()
+    }
+}
+
+// Shorthand for a `Duration` of the given number of seconds, shared by the tests of each module.
+#[cfg(test)]
+fn secs(secs: f64) -> std::time::Duration {
+    std::time::Duration::from_secs_f64(secs)
+}
@@ -0,0 +1,192 @@
+//! Critically damped smoothing towards a moving target.
+
+use super::Linear;
+use crate::time::DurationF64;
+use std::time::Duration;
+
+/// Smoothly follows a target using a critically damped spring, never overshooting.
+///
+/// Unlike a **Spring**, the motion is described by the approximate time taken to reach the
+/// target rather than by physical properties. This makes it well suited to cameras, cursors and
+/// other values that should trail behind a moving target.
+///
+/// The smoothing is computed in closed form and remains stable for any update duration.
+///
+/// ```ignore
+/// // Within `update`.
+/// model.follow.set_target(app.mouse.position());
+/// model.follow.update(update.since_last);
+/// let position = model.follow.value;
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct SmoothDamp<T> {
+    /// The current value.
+    pub value: T,
+    /// The rate of change of the value per second.
+    pub velocity: T,
+    /// The value being followed.
+    pub target: T,
+    /// The approximate time in seconds taken to reach the target.
+    pub smooth_time: f64,
+    /// The maximum speed in units per second, if any.
+    pub max_speed: Option<f64>,
+}
+
+impl<T> SmoothDamp<T>
+where
+    T: Linear,
+{
+    /// The smallest smooth time used, avoiding division by zero.
+    const MIN_SMOOTH_TIME: f64 = 0.0001;
+
+    /// Follow a target, beginning at rest at the given value.
+    pub fn new(value: T, smooth_time: Duration) -> Self {
+        SmoothDamp {
+            value,
+            velocity: T::zero(),
+            target: value,
+            smooth_time: smooth_time.secs(),
+            max_speed: None,
+        }
+    }
+
+    /// Specify the value being followed.
+    pub fn target(mut self, target: T) -> Self {
+        self.target = target;
+        self
+    }
+
+    /// Limit the speed at which the value may move towards the target.
+    pub fn max_speed(mut self, max_speed: f64) -> Self {
+        self.max_speed = Some(max_speed);
+        self
+    }
+
+    /// Update the value being followed.
+    pub fn set_target(&mut self, target: T) {
+        self.target = target;
+    }
+
+    /// Advance the smoothing by the given duration, e.g. `update.since_last`.
+    pub fn update(&mut self, since_last: Duration) {
+        let dt = since_last.secs();
+        if dt <= 0.0 {
+            return;
+        }
+        let smooth_time = self.smooth_time.max(Self::MIN_SMOOTH_TIME);
+        let omega = 2.0 / smooth_time;
+        let x = omega * dt;
+        let exp = 1.0 / (1.0 + x + 0.48 * x * x + 0.235 * x * x * x);
+
+        // Limit the distance covered according to the max speed.
+        let mut change = self.value.sub(self.target);
+        if let Some(max_speed) = self.max_speed {
+            let max_change = max_speed * smooth_time;
+            let magnitude = change.magnitude();
+            if magnitude > max_change && magnitude > 0.0 {
+                change = change.scale(max_change / magnitude);
+            }
+        }
+        let target = self.value.sub(change);
+
+        let temp = self.velocity.add(change.scale(omega)).scale(dt);
+        self.velocity = self.velocity.sub(temp.scale(omega)).scale(exp);
+        let mut value = target.add(change.add(temp).scale(exp));
+
+        // Prevent overshooting the original target.
+        let to_target = self.target.sub(self.value);
+        let to_value = value.sub(self.target);
+        if dot(to_target, to_value) > 0.0 {
+            value = self.target;
+            self.velocity = T::zero();
+        }
+        self.value = value;
+    }
+
+    /// Move the value directly to the target and stop all motion.
+    pub fn snap_to_target(&mut self) {
+        self.value = self.target;
+        self.velocity = T::zero();
+    }
+}
+
+// The dot product of two values via the polarization identity.
+fn dot<T>(a: T, b: T) -> f64
+where
+    T: Linear,
+{
+    let sum = a.add(b).magnitude();
+    let diff = a.sub(b).magnitude();
+    (sum * sum - diff * diff) / 4.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SmoothDamp;
+    use crate::anim::secs;
+
+    #[test]
+    fn converges_without_overshoot() {
+        let mut smooth = SmoothDamp::<f64>::new(0.0, secs(0.3)).target(10.0);
+        let mut prev = smooth.value;
+        for _ in 0..180 {
+            smooth.update(secs(1.0 / 60.0));
+            assert!(smooth.value >= prev);
+            assert!(smooth.value <= 10.0);
+            prev = smooth.value;
+        }
+        assert!((smooth.value - 10.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn independent_of_update_rate() {
+        let smooth = SmoothDamp::<f64>::new(0.0, secs(0.3)).target(10.0);
+        let mut a = smooth;
+        for _ in 0..30 {
+            a.update(secs(1.0 / 60.0));
+        }
+        let mut b = smooth;
+        for _ in 0..60 {
+            b.update(secs(1.0 / 120.0));
+        }
+        assert!((a.value - b.value).abs() < 1e-2);
+    }
+
+    #[test]
+    fn max_speed() {
+        let mut smooth = SmoothDamp::<f64>::new(0.0, secs(0.3))
+            .target(100.0)
+            .max_speed(1.0);
+        for _ in 0..60 {
+            smooth.update(secs(1.0 / 60.0));
+            assert!(smooth.velocity <= 1.0 + 1e-9);
+        }
+        assert!(smooth.value > 0.0);
+        assert!(smooth.value <= 1.0);
+    }
+
+    #[test]
+    fn zero_dt() {
+        let mut smooth = SmoothDamp::<f64>::new(0.0, secs(0.3)).target(10.0);
+        smooth.update(secs(0.0));
+        assert_eq!(smooth.value, 0.0);
+        assert_eq!(smooth.velocity, 0.0);
+    }
+
+    #[test]
+    fn large_dt() {
+        let mut smooth = SmoothDamp::<f64>::new(0.0, secs(0.3)).target(10.0);
+        smooth.update(secs(1e6));
+        assert!((smooth.value - 10.0).abs() < 1e-9);
+        assert!(smooth.velocity.abs() < 1e-9);
+    }
+
+    #[test]
+    fn zero_smooth_time() {
+        // The value jumps almost entirely to the target without producing NaN.
+        let mut smooth = SmoothDamp::<f64>::new(0.0, secs(0.0)).target(10.0);
+        smooth.update(secs(1.0 / 60.0));
+        assert!(smooth.value.is_finite() && smooth.velocity.is_finite());
+        assert!((smooth.value - 10.0).abs() < 1e-3);
+    }
+}
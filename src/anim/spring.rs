@@ -0,0 +1,311 @@
+//! A damped spring that accelerates a value towards a target.
+
+use super::Linear;
+use crate::time::DurationF64;
+use std::time::Duration;
+
+/// A damped spring that pulls its value towards a target.
+///
+/// Each update, the spring applies a force proportional to the distance from its target
+/// (Hooke's law) along with a damping force proportional to its velocity. A low damping produces
+/// a bouncy spring that overshoots its target, while a high damping produces a slow, sluggish one.
+///
+/// The simulation advances in fixed sub-steps so that the motion is the same regardless of the
+/// rate at which `update` is called.
+///
+/// ```ignore
+/// // Within `update`.
+/// model.spring.set_target(app.mouse.position());
+/// model.spring.update(update.since_last);
+/// let position = model.spring.value;
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Spring<T> {
+    /// The current value.
+    pub value: T,
+    /// The rate of change of the value per second.
+    pub velocity: T,
+    /// The value towards which the spring pulls.
+    pub target: T,
+    /// The strength of the force pulling the value towards the target.
+    pub stiffness: f64,
+    /// The strength of the force resisting the velocity.
+    pub damping: f64,
+    /// The mass of the value. A greater mass is slower to accelerate.
+    ///
+    /// Masses that are not positive, e.g. zero or `NaN`, are invalid and simulated as
+    /// `DEFAULT_MASS`.
+    pub mass: f64,
+    sub_step: f64,
+    accumulator: f64,
+}
+
+impl<T> Spring<T>
+where
+    T: Linear,
+{
+    /// The default stiffness of a new spring.
+    pub const DEFAULT_STIFFNESS: f64 = 100.0;
+    /// The default damping of a new spring.
+    pub const DEFAULT_DAMPING: f64 = 10.0;
+    /// The default mass of a new spring.
+    pub const DEFAULT_MASS: f64 = 1.0;
+    /// The default duration of each fixed simulation step in seconds.
+    pub const DEFAULT_SUB_STEP_SECS: f64 = 1.0 / 240.0;
+    /// The maximum number of sub-steps simulated by a single `update`.
+    ///
+    /// Any time beyond this is dropped, so that a huge duration (e.g. after the app was suspended)
+    /// or a tiny sub-step cannot stall the update.
+    pub const MAX_SUB_STEPS: u32 = 1024;
+
+    /// A spring at rest at the given value.
+    pub fn new(value: T) -> Self {
+        Spring {
+            value,
+            velocity: T::zero(),
+            target: value,
+            stiffness: Self::DEFAULT_STIFFNESS,
+            damping: Self::DEFAULT_DAMPING,
+            mass: Self::DEFAULT_MASS,
+            sub_step: Self::DEFAULT_SUB_STEP_SECS,
+            accumulator: 0.0,
+        }
+    }
+
+    /// Specify the target towards which the spring pulls.
+    pub fn target(mut self, target: T) -> Self {
+        self.target = target;
+        self
+    }
+
+    /// Specify the stiffness of the spring.
+    pub fn stiffness(mut self, stiffness: f64) -> Self {
+        self.stiffness = stiffness;
+        self
+    }
+
+    /// Specify the damping of the spring.
+    pub fn damping(mut self, damping: f64) -> Self {
+        self.damping = damping;
+        self
+    }
+
+    /// Specify the mass of the value.
+    ///
+    /// Masses that are not positive, e.g. zero or `NaN`, are invalid and simulated as
+    /// `DEFAULT_MASS`.
+    pub fn mass(mut self, mass: f64) -> Self {
+        self.mass = mass;
+        self
+    }
+
+    /// Specify the duration of each fixed simulation step.
+    ///
+    /// Stiff springs may require shorter steps to remain stable.
+    pub fn sub_step(mut self, step: Duration) -> Self {
+        self.sub_step = step.secs();
+        self
+    }
+
+    /// Produce a spring whose damping is critical for its stiffness and mass, reaching its target
+    /// as quickly as possible without overshooting.
+    pub fn critically_damped(mut self) -> Self {
+        self.damping = 2.0 * (self.stiffness * self.simulated_mass()).sqrt();
+        self
+    }
+
+    /// Update the target towards which the spring pulls.
+    pub fn set_target(&mut self, target: T) {
+        self.target = target;
+    }
+
+    /// Advance the simulation by the given duration, e.g. `update.since_last`.
+    ///
+    /// The simulation advances in fixed sub-steps. Any remaining time is carried over to the next
+    /// update, unless more than `MAX_SUB_STEPS` would be required in which case it is dropped.
+    pub fn update(&mut self, since_last: Duration) {
+        if self.sub_step <= 0.0 {
+            self.step(since_last.secs());
+            return;
+        }
+        self.accumulator += since_last.secs();
+        let mut steps = 0;
+        while self.accumulator >= self.sub_step {
+            if steps == Self::MAX_SUB_STEPS {
+                self.accumulator = 0.0;
+                break;
+            }
+            let dt = self.sub_step;
+            self.step(dt);
+            self.accumulator -= dt;
+            steps += 1;
+        }
+    }
+
+    /// Advance the simulation by a single step of the given number of seconds.
+    pub fn step(&mut self, dt: f64) {
+        let displacement = self.value.sub(self.target);
+        let spring_force = displacement.scale(-self.stiffness);
+        let damping_force = self.velocity.scale(-self.damping);
+        let acceleration = spring_force
+            .add(damping_force)
+            .scale(1.0 / self.simulated_mass());
+        self.velocity = self.velocity.add(acceleration.scale(dt));
+        self.value = self.value.add(self.velocity.scale(dt));
+    }
+
+    /// Whether or not the spring has come to rest at its target, within the given tolerance for
+    /// both distance and speed.
+    pub fn is_at_rest(&self, epsilon: f64) -> bool {
+        let distance = self.value.sub(self.target).magnitude();
+        distance <= epsilon && self.velocity.magnitude() <= epsilon
+    }
+
+    /// Move the value directly to the target and stop all motion.
+    pub fn snap_to_target(&mut self) {
+        self.value = self.target;
+        self.velocity = T::zero();
+        self.accumulator = 0.0;
+    }
+
+    // The mass used by the simulation, avoiding division by zero for invalid masses.
+    fn simulated_mass(&self) -> f64 {
+        if self.mass > 0.0 {
+            self.mass
+        } else {
+            Self::DEFAULT_MASS
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Spring;
+    use crate::anim::secs;
+
+    // A sub-step that is exactly representable so that the accumulated time divides evenly.
+    const SUB_STEP: f64 = 1.0 / 256.0;
+
+    #[test]
+    fn at_rest() {
+        let mut spring = Spring::<f64>::new(1.0);
+        spring.update(secs(1.0));
+        assert_eq!(spring.value, 1.0);
+        assert_eq!(spring.velocity, 0.0);
+        assert!(spring.is_at_rest(0.0));
+    }
+
+    #[test]
+    fn fixed_sub_steps() {
+        let spring = Spring::<f64>::new(0.0).target(1.0).sub_step(secs(SUB_STEP));
+        let mut stepped = spring;
+        for _ in 0..4 {
+            stepped.step(SUB_STEP);
+        }
+        let mut updated = spring;
+        updated.update(secs(SUB_STEP * 4.0));
+        assert_eq!(updated.value, stepped.value);
+        assert_eq!(updated.velocity, stepped.velocity);
+
+        // Splitting the same duration across several updates produces the same motion.
+        let mut split = spring;
+        split.update(secs(SUB_STEP * 1.5));
+        split.update(secs(SUB_STEP * 2.5));
+        assert_eq!(split.value, stepped.value);
+    }
+
+    #[test]
+    fn partial_sub_step_is_carried_over() {
+        let spring = Spring::<f64>::new(0.0).target(1.0).sub_step(secs(SUB_STEP));
+        let mut updated = spring;
+        updated.update(secs(SUB_STEP * 0.5));
+        assert_eq!(updated.value, 0.0);
+        updated.update(secs(SUB_STEP * 0.5));
+        let mut stepped = spring;
+        stepped.step(SUB_STEP);
+        assert_eq!(updated.value, stepped.value);
+    }
+
+    #[test]
+    fn zero_dt() {
+        let mut spring = Spring::<f64>::new(0.0).target(1.0);
+        spring.update(secs(0.0));
+        assert_eq!(spring.value, 0.0);
+        assert_eq!(spring.velocity, 0.0);
+    }
+
+    #[test]
+    fn critically_damped_converges_without_overshoot() {
+        let mut spring = Spring::<f64>::new(0.0).target(1.0).critically_damped();
+        assert_eq!(spring.damping, 20.0);
+        let mut prev = spring.value;
+        for _ in 0..120 {
+            spring.update(secs(1.0 / 60.0));
+            assert!(spring.value >= prev);
+            assert!(spring.value <= 1.0);
+            prev = spring.value;
+        }
+        assert!(spring.is_at_rest(1e-3));
+    }
+
+    #[test]
+    fn underdamped_overshoots() {
+        let mut spring = Spring::<f64>::new(0.0).target(1.0).damping(2.0);
+        let mut max = spring.value;
+        for _ in 0..60 {
+            spring.update(secs(1.0 / 60.0));
+            max = max.max(spring.value);
+        }
+        assert!(max > 1.0);
+    }
+
+    #[test]
+    fn large_dt() {
+        let mut spring = Spring::<f64>::new(0.0).target(1.0).critically_damped();
+        spring.update(secs(60.0));
+        assert!(spring.is_at_rest(1e-9));
+    }
+
+    #[test]
+    fn sub_steps_are_capped() {
+        let spring = Spring::<f64>::new(0.0).target(1.0).sub_step(secs(SUB_STEP));
+        let mut stepped = spring;
+        for _ in 0..Spring::<f64>::MAX_SUB_STEPS {
+            stepped.step(SUB_STEP);
+        }
+        let mut updated = spring;
+        updated.update(secs(SUB_STEP * 10_000.5));
+        assert_eq!(updated.value, stepped.value);
+
+        // The excess time is dropped rather than carried over.
+        updated.update(secs(SUB_STEP * 0.5));
+        assert_eq!(updated.value, stepped.value);
+
+        // A tiny sub-step does not stall a long update.
+        let mut tiny = Spring::<f64>::new(0.0).target(1.0).sub_step(secs(1e-9));
+        tiny.update(secs(3600.0));
+        assert!(tiny.value.is_finite());
+    }
+
+    #[test]
+    fn invalid_mass_uses_default() {
+        let mut valid = Spring::<f64>::new(0.0).target(1.0);
+        valid.update(secs(0.1));
+        for &mass in &[0.0, -1.0, f64::NAN] {
+            let mut spring = Spring::<f64>::new(0.0).target(1.0).mass(mass);
+            spring.update(secs(0.1));
+            assert_eq!(spring.value, valid.value);
+            assert_eq!(spring.velocity, valid.velocity);
+            assert_eq!(spring.critically_damped().damping, 20.0);
+        }
+    }
+
+    #[test]
+    fn snap_to_target() {
+        let mut spring = Spring::<f64>::new(0.0).target(1.0);
+        spring.update(secs(0.1));
+        spring.snap_to_target();
+        assert_eq!(spring.value, 1.0);
+        assert_eq!(spring.velocity, 0.0);
+    }
+}
@@ -0,0 +1,413 @@
+//! Sequencing and overlapping tweens over time.
+
+use super::{Curve, Lerp, Tween};
+use crate::io;
+use crate::serde_derive::{Deserialize, Serialize};
+use crate::time::DurationF64;
+use std::path::Path;
+use std::time::Duration;
+
+/// A sequence of tweens, each beginning at a specific time.
+///
+/// Tweens may be sequenced one after another via `then`, played alongside the previous tween via
+/// `with` or placed at an exact time via `at`. Where tweens overlap, the value is taken from the
+/// tween that began most recently. Before any tween has begun, the timeline produces its initial
+/// value. After a tween completes, its end value is held until the next tween begins.
+///
+/// ```ignore
+/// let timeline = Timeline::new(0.0)
+///     .then(Tween::new(0.0, 1.0, secs(1.0)).ease(ease::quad::ease_out))
+///     .then(Tween::new(1.0, 0.5, secs(0.5)))
+///     .at(secs(0.75), Tween::new(0.2, 0.8, secs(1.0)))
+///     .looping(true);
+/// ```
+#[derive(Clone, Debug)]
+pub struct Timeline<T> {
+    initial: T,
+    entries: Vec<Entry<T>>,
+    elapsed: f64,
+    looping: bool,
+}
+
+#[derive(Clone, Debug)]
+struct Entry<T> {
+    start: f64,
+    tween: Tween<T>,
+}
+
+/// A set of values at specific times, from which a **Timeline** may be produced.
+///
+/// Keyframes may be authored in code or loaded from JSON, e.g.
+///
+/// ```json
+/// {
+///   "frames": [
+///     { "time": 0.0, "value": 0.0 },
+///     { "time": 1.5, "value": 100.0, "curve": "CubicInOut" },
+///     { "time": 2.0, "value": 50.0, "curve": "BounceOut" }
+///   ]
+/// }
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Keyframes<T> {
+    /// All keyframes in order of time.
+    pub frames: Vec<Keyframe<T>>,
+}
+
+/// A single value at a specific time.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Keyframe<T> {
+    /// The time of the keyframe in seconds.
+    pub time: f64,
+    /// The value at the time of the keyframe.
+    pub value: T,
+    /// The curve used to ease from the previous keyframe to this one.
+    #[serde(default)]
+    pub curve: Curve,
+}
+
+impl<T> Timeline<T>
+where
+    T: Clone + Lerp,
+{
+    /// A new, empty timeline producing the given value until the first tween begins.
+    pub fn new(initial: T) -> Self {
+        Timeline {
+            initial,
+            entries: vec![],
+            elapsed: 0.0,
+            looping: false,
+        }
+    }
+
+    /// Begin the given tween once all previously added tweens have completed.
+    ///
+    /// Tweens that repeat forever are considered to complete after their first play.
+    pub fn then(self, tween: Tween<T>) -> Self {
+        let start = self.end_secs();
+        self.push(start, tween)
+    }
+
+    /// Begin the given tween at the same time as the previously added tween.
+    pub fn with(self, tween: Tween<T>) -> Self {
+        let start = self.entries.last().map(|e| e.start).unwrap_or(0.0);
+        self.push(start, tween)
+    }
+
+    /// Begin the given tween at the given time since the start of the timeline.
+    pub fn at(self, start: Duration, tween: Tween<T>) -> Self {
+        self.push(start.secs(), tween)
+    }
+
+    /// Whether or not the timeline returns to the beginning once complete.
+    pub fn looping(mut self, looping: bool) -> Self {
+        self.looping = looping;
+        self
+    }
+
+    fn push(mut self, start: f64, mut tween: Tween<T>) -> Self {
+        tween.reset();
+        self.entries.push(Entry { start, tween });
+        self
+    }
+
+    /// Advance the timeline by the given duration, e.g. `update.since_last`.
+    pub fn update(&mut self, since_last: Duration) {
+        self.elapsed += since_last.secs();
+        let end = self.end_secs();
+        if self.looping {
+            if end > 0.0 {
+                self.elapsed %= end;
+            }
+        } else if self.is_finite() {
+            self.elapsed = self.elapsed.min(end);
+        }
+    }
+
+    /// Move the timeline to the given time.
+    pub fn seek(&mut self, time: Duration) {
+        self.elapsed = time.secs();
+    }
+
+    /// Return the timeline to its beginning.
+    pub fn reset(&mut self) {
+        self.elapsed = 0.0;
+    }
+
+    /// The time that has passed since the timeline began.
+    pub fn elapsed(&self) -> Duration {
+        Duration::from_secs_f64(self.elapsed)
+    }
+
+    /// The total duration of the timeline.
+    ///
+    /// Returns `None` if the timeline contains a tween that repeats forever.
+    pub fn duration(&self) -> Option<Duration> {
+        if self.is_finite() {
+            Some(Duration::from_secs_f64(self.end_secs()))
+        } else {
+            None
+        }
+    }
+
+    /// Whether or not the timeline has finished playing.
+    ///
+    /// A looping timeline never completes.
+    pub fn is_complete(&self) -> bool {
+        !self.looping && self.is_finite() && self.elapsed >= self.end_secs()
+    }
+
+    /// The current value of the timeline.
+    pub fn value(&self) -> T {
+        self.value_at_secs(self.elapsed)
+    }
+
+    /// The value of the timeline at the given time.
+    pub fn value_at(&self, time: Duration) -> T {
+        self.value_at_secs(time.secs())
+    }
+
+    fn value_at_secs(&self, time: f64) -> T {
+        // Find the tween that began most recently, preferring those added later.
+        let mut current: Option<&Entry<T>> = None;
+        for entry in &self.entries {
+            let begin = entry.start + entry.tween.delay_secs();
+            if begin > time {
+                continue;
+            }
+            match current {
+                Some(c) if c.start + c.tween.delay_secs() > begin => (),
+                _ => current = Some(entry),
+            }
+        }
+        match current {
+            None => self.initial.clone(),
+            Some(entry) => entry.tween.value_at_secs(time - entry.start),
+        }
+    }
+
+    fn is_finite(&self) -> bool {
+        self.entries.iter().all(|e| e.tween.total_secs().is_some())
+    }
+
+    // The time at which the last tween completes, treating endless tweens as a single play.
+    fn end_secs(&self) -> f64 {
+        self.entries
+            .iter()
+            .map(|e| {
+                let tween = &e.tween;
+                let total = tween.total_secs();
+                e.start + total.unwrap_or(tween.delay_secs() + tween.duration().secs())
+            })
+            .fold(0.0, f64::max)
+    }
+}
+
+impl<T> Keyframes<T> {
+    /// An empty set of keyframes.
+    pub fn new() -> Self {
+        Keyframes { frames: vec![] }
+    }
+
+    /// Add a keyframe with the given value at the given time, eased into via the given curve.
+    pub fn key(mut self, time: Duration, value: T, curve: Curve) -> Self {
+        let time = time.secs();
+        let ix = self
+            .frames
+            .iter()
+            .position(|f| f.time > time)
+            .unwrap_or(self.frames.len());
+        self.frames.insert(ix, Keyframe { time, value, curve });
+        self
+    }
+
+    /// Load keyframes from the JSON file at the given path.
+    pub fn load<P>(path: P) -> Result<Self, io::JsonFileError>
+    where
+        P: AsRef<Path>,
+        T: for<'de> serde::Deserialize<'de>,
+    {
+        io::load_from_json(path)
+    }
+
+    /// Save the keyframes as JSON to the given path.
+    pub fn save<P>(&self, path: P) -> Result<(), io::JsonFileError>
+    where
+        P: AsRef<Path>,
+        T: serde::Serialize,
+    {
+        io::save_to_json(path, self)
+    }
+}
+
+impl<T> Keyframes<T>
+where
+    T: Clone + Lerp,
+{
+    /// Produce a timeline that tweens between each consecutive keyframe.
+    ///
+    /// Keyframes whose time is not finite, e.g. `NaN`, are ignored. Returns `None` if there are no
+    /// remaining keyframes.
+    pub fn timeline(&self) -> Option<Timeline<T>> {
+        let mut frames: Vec<&Keyframe<T>> =
+            self.frames.iter().filter(|f| f.time.is_finite()).collect();
+        frames.sort_by(|a, b| {
+            a.time
+                .partial_cmp(&b.time)
+                .expect("keyframe times are finite")
+        });
+        let first = frames.first()?;
+        let mut timeline = Timeline::new(first.value.clone());
+        for pair in frames.windows(2) {
+            let (a, b) = (pair[0], pair[1]);
+            let duration = Duration::from_secs_f64(b.time - a.time);
+            let tween = Tween::new(a.value.clone(), b.value.clone(), duration).ease(b.curve.ease());
+            timeline = timeline.push(a.time, tween);
+        }
+        Some(timeline)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Keyframe, Keyframes, Timeline};
+    use crate::anim::{secs, Curve, Tween};
+
+    fn tween(start: f64, end: f64, duration: f64) -> Tween<f64> {
+        Tween::new(start, end, secs(duration))
+    }
+
+    #[test]
+    fn sequence() {
+        let timeline = Timeline::new(0.0)
+            .then(tween(0.0, 1.0, 1.0))
+            .then(tween(1.0, 3.0, 1.0));
+        assert_eq!(timeline.value_at(secs(0.5)), 0.5);
+        assert_eq!(timeline.value_at(secs(1.0)), 1.0);
+        assert_eq!(timeline.value_at(secs(1.5)), 2.0);
+        assert_eq!(timeline.value_at(secs(10.0)), 3.0);
+        assert_eq!(timeline.duration(), Some(secs(2.0)));
+    }
+
+    #[test]
+    fn overlap_prefers_most_recent_tween() {
+        let timeline = Timeline::new(0.0)
+            .then(tween(0.0, 10.0, 2.0))
+            .at(secs(1.0), tween(100.0, 200.0, 1.0));
+        assert_eq!(timeline.value_at(secs(0.5)), 2.5);
+        assert_eq!(timeline.value_at(secs(1.5)), 150.0);
+        assert_eq!(timeline.value_at(secs(2.5)), 200.0);
+        assert_eq!(timeline.duration(), Some(secs(2.0)));
+
+        // A delayed tween added `with` the previous one only takes over once its delay elapses.
+        let timeline = Timeline::new(0.0)
+            .then(tween(0.0, 10.0, 2.0))
+            .with(tween(100.0, 200.0, 1.0).delay(secs(0.5)));
+        assert_eq!(timeline.value_at(secs(0.25)), 1.25);
+        assert_eq!(timeline.value_at(secs(1.0)), 150.0);
+    }
+
+    #[test]
+    fn initial_and_held_values() {
+        let timeline = Timeline::new(7.0)
+            .at(secs(1.0), tween(0.0, 1.0, 1.0))
+            .at(secs(3.0), tween(5.0, 6.0, 1.0));
+        assert_eq!(timeline.value_at(secs(0.5)), 7.0);
+        assert_eq!(timeline.value_at(secs(2.5)), 1.0);
+        assert_eq!(timeline.value_at(secs(3.5)), 5.5);
+    }
+
+    #[test]
+    fn update() {
+        let mut timeline = Timeline::new(0.0).then(tween(0.0, 2.0, 2.0));
+        timeline.update(secs(0.0));
+        assert_eq!(timeline.value(), 0.0);
+        timeline.update(secs(0.5));
+        assert_eq!(timeline.value(), 0.5);
+        timeline.update(secs(1e9));
+        assert!(timeline.is_complete());
+        assert_eq!(timeline.elapsed(), secs(2.0));
+        assert_eq!(timeline.value(), 2.0);
+    }
+
+    #[test]
+    fn looping() {
+        let mut timeline = Timeline::new(0.0).then(tween(0.0, 2.0, 2.0)).looping(true);
+        timeline.update(secs(2.5));
+        assert_eq!(timeline.value(), 0.5);
+        timeline.update(secs(1_000_000.0));
+        assert_eq!(timeline.elapsed(), secs(0.5));
+        assert!(!timeline.is_complete());
+    }
+
+    #[test]
+    fn keyframes() {
+        let keyframes = Keyframes::new()
+            .key(secs(2.0), 50.0, Curve::Linear)
+            .key(secs(0.0), 0.0, Curve::Linear)
+            .key(secs(1.0), 100.0, Curve::Linear);
+        let times: Vec<_> = keyframes.frames.iter().map(|f| f.time).collect();
+        assert_eq!(times, vec![0.0, 1.0, 2.0]);
+        let timeline = keyframes.timeline().unwrap();
+        assert_eq!(timeline.value_at(secs(0.0)), 0.0);
+        assert_eq!(timeline.value_at(secs(0.5)), 50.0);
+        assert_eq!(timeline.value_at(secs(1.5)), 75.0);
+        assert_eq!(timeline.value_at(secs(3.0)), 50.0);
+        assert_eq!(timeline.duration(), Some(secs(2.0)));
+    }
+
+    #[test]
+    fn keyframes_edge_cases() {
+        assert!(Keyframes::<f64>::new().timeline().is_none());
+        let timeline = Keyframes::new()
+            .key(secs(1.0), 4.0, Curve::Linear)
+            .timeline()
+            .unwrap();
+        assert_eq!(timeline.value_at(secs(0.0)), 4.0);
+        assert_eq!(timeline.value_at(secs(2.0)), 4.0);
+    }
+
+    #[test]
+    fn keyframes_ignore_non_finite_times() {
+        let mut keyframes = Keyframes::new().key(secs(0.0), 0.0, Curve::Linear).key(
+            secs(1.0),
+            100.0,
+            Curve::Linear,
+        );
+        for &time in &[f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+            let curve = Curve::Linear;
+            keyframes.frames.push(Keyframe {
+                time,
+                value: -1.0,
+                curve,
+            });
+        }
+        let timeline = keyframes.timeline().unwrap();
+        assert_eq!(timeline.value_at(secs(0.5)), 50.0);
+        assert_eq!(timeline.duration(), Some(secs(1.0)));
+
+        let nan = Keyframe {
+            time: f64::NAN,
+            value: 0.0,
+            curve: Curve::Linear,
+        };
+        let keyframes = Keyframes { frames: vec![nan] };
+        assert!(keyframes.timeline().is_none());
+    }
+
+    #[test]
+    fn keyframes_from_json() {
+        let json = r#"{
+            "frames": [
+                { "time": 0.0, "value": 0.0 },
+                { "time": 1.5, "value": 100.0, "curve": "CubicInOut" }
+            ]
+        }"#;
+        let keyframes: Keyframes<f64> = serde_json::from_str(json).unwrap();
+        assert_eq!(keyframes.frames[0].curve, Curve::Linear);
+        assert_eq!(keyframes.frames[1].curve, Curve::CubicInOut);
+        let timeline = keyframes.timeline().unwrap();
+        assert_eq!(timeline.value_at(secs(0.75)), 50.0);
+        assert_eq!(timeline.value_at(secs(1.5)), 100.0);
+    }
+}
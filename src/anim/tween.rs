@@ -0,0 +1,294 @@
+//! Interpolating between two values over time.
+
+use super::{Ease, Lerp};
+use crate::time::DurationF64;
+use std::time::Duration;
+
+/// Interpolates from a start value to an end value over a duration.
+///
+/// ```ignore
+/// let tween = Tween::new(pt2(-100.0, 0.0), pt2(100.0, 0.0), Duration::from_secs(2))
+///     .ease(ease::cubic::ease_in_out)
+///     .delay(Duration::from_millis(500))
+///     .repeat(Repeat::Forever)
+///     .yoyo(true);
+/// ```
+#[derive(Clone, Debug)]
+pub struct Tween<T> {
+    /// The value at the beginning of the tween.
+    pub start: T,
+    /// The value at the end of the tween.
+    pub end: T,
+    duration: f64,
+    delay: f64,
+    ease: Ease,
+    repeat: Repeat,
+    yoyo: bool,
+    elapsed: f64,
+}
+
+/// Describes how many times a **Tween** plays.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Repeat {
+    /// Play the given number of additional times after the first.
+    Count(u32),
+    /// Play indefinitely.
+    Forever,
+}
+
+impl<T> Tween<T>
+where
+    T: Clone + Lerp,
+{
+    /// A tween from `start` to `end` that plays once over the given duration, without easing.
+    pub fn new(start: T, end: T, duration: Duration) -> Self {
+        Tween {
+            start,
+            end,
+            duration: duration.secs(),
+            delay: 0.0,
+            ease: super::linear,
+            repeat: Repeat::Count(0),
+            yoyo: false,
+            elapsed: 0.0,
+        }
+    }
+
+    /// The curve used to ease between the start and end values, e.g. `ease::cubic::ease_in_out`.
+    pub fn ease(mut self, ease: Ease) -> Self {
+        self.ease = ease;
+        self
+    }
+
+    /// A delay before the tween begins.
+    pub fn delay(mut self, delay: Duration) -> Self {
+        self.delay = delay.secs();
+        self
+    }
+
+    /// How many times the tween plays.
+    pub fn repeat(mut self, repeat: Repeat) -> Self {
+        self.repeat = repeat;
+        self
+    }
+
+    /// Whether or not every second repetition plays in reverse, from the end value to the start.
+    pub fn yoyo(mut self, yoyo: bool) -> Self {
+        self.yoyo = yoyo;
+        self
+    }
+
+    /// Advance the tween by the given duration, e.g. `update.since_last`.
+    pub fn update(&mut self, since_last: Duration) {
+        self.elapsed += since_last.secs();
+        if let Some(total) = self.total_secs() {
+            self.elapsed = self.elapsed.min(total);
+        }
+    }
+
+    /// Move the tween to the given time since it was started, including the delay.
+    pub fn seek(&mut self, time: Duration) {
+        self.elapsed = time.secs();
+    }
+
+    /// Return the tween to its beginning.
+    pub fn reset(&mut self) {
+        self.elapsed = 0.0;
+    }
+
+    /// The time that has passed since the tween was started, including the delay.
+    pub fn elapsed(&self) -> Duration {
+        Duration::from_secs_f64(self.elapsed)
+    }
+
+    /// The duration of a single play of the tween.
+    pub fn duration(&self) -> Duration {
+        Duration::from_secs_f64(self.duration)
+    }
+
+    /// The total duration of the tween, including the delay and all repetitions.
+    ///
+    /// Returns `None` if the tween repeats forever.
+    pub fn total_duration(&self) -> Option<Duration> {
+        self.total_secs().map(Duration::from_secs_f64)
+    }
+
+    /// Whether or not the tween has finished playing.
+    pub fn is_complete(&self) -> bool {
+        match self.total_secs() {
+            None => false,
+            Some(total) => self.elapsed >= total,
+        }
+    }
+
+    /// The current value of the tween.
+    pub fn value(&self) -> T {
+        self.value_at_secs(self.elapsed)
+    }
+
+    /// The value of the tween at the given time since it was started, including the delay.
+    pub fn value_at(&self, time: Duration) -> T {
+        self.value_at_secs(time.secs())
+    }
+
+    pub(crate) fn value_at_secs(&self, time: f64) -> T {
+        let amount = self.amount_at(time);
+        self.start.lerp(&self.end, amount)
+    }
+
+    pub(crate) fn delay_secs(&self) -> f64 {
+        self.delay
+    }
+
+    pub(crate) fn total_secs(&self) -> Option<f64> {
+        match self.repeat {
+            Repeat::Forever => None,
+            Repeat::Count(n) => Some(self.delay + self.duration * (n as f64 + 1.0)),
+        }
+    }
+
+    // The eased interpolation amount at the given time.
+    fn amount_at(&self, time: f64) -> f64 {
+        let t = time - self.delay;
+        if t <= 0.0 {
+            return 0.0;
+        }
+        if self.duration <= 0.0 {
+            return self.final_amount();
+        }
+
+        let mut cycle = (t / self.duration).floor();
+        let mut local = t - cycle * self.duration;
+        if let Repeat::Count(n) = self.repeat {
+            if cycle > n as f64 {
+                cycle = n as f64;
+                local = self.duration;
+            }
+        }
+
+        let reverse = self.yoyo && cycle as u64 % 2 == 1;
+        let local = if reverse {
+            self.duration - local
+        } else {
+            local
+        };
+        (self.ease)(local, 0.0, 1.0, self.duration)
+    }
+
+    // The amount at the end of the final repetition.
+    fn final_amount(&self) -> f64 {
+        match self.repeat {
+            Repeat::Count(n) if self.yoyo && n % 2 == 1 => 0.0,
+            _ => 1.0,
+        }
+    }
+}
+
+impl Default for Repeat {
+    fn default() -> Self {
+        Repeat::Count(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Repeat, Tween};
+    use crate::anim::secs;
+
+    fn quad_in(t: f64, b: f64, c: f64, d: f64) -> f64 {
+        let t = t / d;
+        c * t * t + b
+    }
+
+    #[test]
+    fn endpoints() {
+        let tween = Tween::new(0.0, 10.0, secs(1.0));
+        assert_eq!(tween.value_at(secs(0.0)), 0.0);
+        assert_eq!(tween.value_at(secs(0.5)), 5.0);
+        assert_eq!(tween.value_at(secs(1.0)), 10.0);
+        assert_eq!(tween.value_at(secs(3.0)), 10.0);
+        assert_eq!(tween.total_duration(), Some(secs(1.0)));
+    }
+
+    #[test]
+    fn ease_and_delay() {
+        let tween = Tween::new(0.0, 10.0, secs(1.0))
+            .ease(quad_in)
+            .delay(secs(0.5));
+        assert_eq!(tween.value_at(secs(0.25)), 0.0);
+        assert_eq!(tween.value_at(secs(1.0)), 2.5);
+        assert_eq!(tween.value_at(secs(1.5)), 10.0);
+        assert_eq!(tween.total_duration(), Some(secs(1.5)));
+    }
+
+    #[test]
+    fn repeat() {
+        let tween = Tween::new(0.0, 10.0, secs(1.0)).repeat(Repeat::Count(2));
+        assert_eq!(tween.value_at(secs(1.25)), 2.5);
+        assert_eq!(tween.value_at(secs(2.75)), 7.5);
+        assert_eq!(tween.value_at(secs(3.0)), 10.0);
+        assert_eq!(tween.total_duration(), Some(secs(3.0)));
+    }
+
+    #[test]
+    fn yoyo_reverses_every_second_play() {
+        let tween = Tween::new(0.0, 10.0, secs(1.0))
+            .repeat(Repeat::Count(2))
+            .yoyo(true);
+        assert_eq!(tween.value_at(secs(0.25)), 2.5);
+        assert_eq!(tween.value_at(secs(1.0)), 10.0);
+        assert_eq!(tween.value_at(secs(1.25)), 7.5);
+        assert_eq!(tween.value_at(secs(2.0)), 0.0);
+        assert_eq!(tween.value_at(secs(2.25)), 2.5);
+        assert_eq!(tween.value_at(secs(4.0)), 10.0);
+
+        // An odd number of repeats ends back at the start value.
+        let tween = Tween::new(0.0, 10.0, secs(1.0))
+            .repeat(Repeat::Count(1))
+            .yoyo(true);
+        assert_eq!(tween.value_at(secs(2.0)), 0.0);
+        assert_eq!(tween.value_at(secs(5.0)), 0.0);
+    }
+
+    #[test]
+    fn repeat_forever() {
+        let mut tween = Tween::new(0.0, 10.0, secs(1.0))
+            .repeat(Repeat::Forever)
+            .yoyo(true);
+        assert_eq!(tween.total_duration(), None);
+        tween.update(secs(1_000_000.25));
+        assert!(!tween.is_complete());
+        assert_eq!(tween.value(), 2.5);
+    }
+
+    #[test]
+    fn update() {
+        let mut tween = Tween::new(0.0, 10.0, secs(1.0));
+        tween.update(secs(0.0));
+        assert_eq!(tween.value(), 0.0);
+        tween.update(secs(0.25));
+        tween.update(secs(0.25));
+        assert_eq!(tween.value(), 5.0);
+        assert!(!tween.is_complete());
+        tween.update(secs(0.0));
+        assert_eq!(tween.value(), 5.0);
+
+        // Very large updates are clamped to the end of the tween.
+        tween.update(secs(1e9));
+        assert!(tween.is_complete());
+        assert_eq!(tween.elapsed(), secs(1.0));
+        assert_eq!(tween.value(), 10.0);
+
+        tween.reset();
+        assert_eq!(tween.value(), 0.0);
+    }
+
+    #[test]
+    fn zero_duration() {
+        let mut tween = Tween::new(0.0, 10.0, secs(0.0));
+        assert_eq!(tween.value(), 0.0);
+        tween.update(secs(0.0));
+        assert!(tween.is_complete());
+        assert_eq!(tween.value_at(secs(0.1)), 10.0);
+    }
+}
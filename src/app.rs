@@ -14,6 +14,9 @@ use crate::draw;
 use crate::event::{self, Event, Key, LoopEvent, Update};
 use crate::frame::{Frame, RawFrame};
 use crate::geom;
+use crate::input;
+use crate::rand;
+use crate::record;
 use crate::state;
 use crate::time::DurationF64;
 use crate::ui;
@@ -21,11 +24,11 @@ use crate::wgpu;
 use crate::window::{self, Window};
 use find_folder;
 use std;
-use std::cell::{RefCell, RefMut};
+use std::cell::{Cell, RefCell, RefMut};
 use std::collections::HashMap;
 use std::ops::{Deref, DerefMut};
 use std::path::PathBuf;
-use std::sync::atomic::{self, AtomicBool};
+use std::sync::atomic::{self, AtomicBool, AtomicU64};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use winit;
@@ -66,6 +69,9 @@ pub struct Builder<M = (), E = Event> {
     exit: Option<ExitFn<M>>,
     create_default_window: bool,
     default_window_size: Option<winit::dpi::LogicalSize<u32>>,
+    input_session: record::Session,
+    seed: Option<u64>,
+    capture_metadata: bool,
 }
 
 /// A nannou `Sketch` builder.
@@ -104,8 +110,22 @@ pub struct App {
     pub(crate) ui: ui::Arrangement,
     /// The window that is currently in focus.
     pub(crate) focused_window: RefCell<Option<window::Id>>,
+    /// The input recording or replay session, if any.
+    pub(crate) input_session: RefCell<record::Session>,
+    /// The map from named actions to input bindings.
+    action_map: RefCell<input::ActionMap>,
+    /// The app's seeded random number generator.
+    rng: RefCell<rand::SeededRng>,
+    /// The seed of `rng`, shared with windows so that it may be recorded alongside captures.
+    pub(crate) seed: Arc<AtomicU64>,
+    /// Whether or not windows write metadata alongside captured frames.
+    pub(crate) capture_metadata: Cell<bool>,
     /// The current state of the `Mouse`.
     pub mouse: state::Mouse,
+    /// The currently active touches, used to recognise gestures.
+    ///
+    /// Recognised gestures are emitted via `window::Builder::gesture`.
+    pub touches: state::Touches,
     /// State of the keyboard keys.
     ///
     /// `mods` provides state of each of the modifier keys: `shift`, `ctrl`, `alt`, `logo`.
@@ -261,6 +281,9 @@ where
             exit: None,
             create_default_window: false,
             default_window_size: None,
+            input_session: record::Session::None,
+            seed: None,
+            capture_metadata: false,
         }
     }
 
@@ -283,6 +306,9 @@ where
             exit,
             create_default_window,
             default_window_size,
+            input_session,
+            seed,
+            capture_metadata,
             ..
         } = self;
         Builder {
@@ -293,6 +319,9 @@ where
             exit,
             create_default_window,
             default_window_size,
+            input_session,
+            seed,
+            capture_metadata,
         }
     }
 }
@@ -364,6 +393,53 @@ where
         self
     }
 
+    /// Record all user input received by the app's windows and save it to the JSON file at the
+    /// given path when the app exits.
+    ///
+    /// The resulting file may be loaded via `record::Recording::load` and passed to `replay` in
+    /// order to reproduce the session. See the `record` module for details.
+    pub fn record<P>(mut self, path: P) -> Self
+    where
+        P: Into<PathBuf>,
+    {
+        self.input_session = record::Session::record(path.into());
+        self
+    }
+
+    /// Replay the given recording of user input in place of live input.
+    ///
+    /// Each recorded event is emitted before the same update in which it was originally received.
+    /// With `record::Timing::Recorded`, updates are also timed as they were during recording.
+    /// Windows are matched to those of the recording in the order in which they are created.
+    ///
+    /// Live input resumes once all recorded events have been emitted. Note that replayed input is
+    /// not submitted to **Ui**s.
+    pub fn replay(mut self, recording: record::Recording, timing: record::Timing) -> Self {
+        self.input_session = record::Session::replay(recording, timing);
+        self
+    }
+
+    /// Seed the app's random number generator, accessible via `app.rng()`.
+    ///
+    /// A seed specified via the `--seed` command line argument or the `NANNOU_SEED` environment
+    /// variable takes priority, allowing a run to be reproduced without changing code. If no seed
+    /// is specified, a random seed is used. Either way, the seed is printed at startup.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Whether or not to write a `frame::CaptureMetadata` JSON file alongside each captured frame,
+    /// recording the seed and algorithm of the app's random number generator.
+    ///
+    /// Along with the seed printed at startup, this allows a captured frame to be regenerated
+    /// exactly. By default, no metadata is written so that capture loops do not write twice as
+    /// many files.
+    pub fn capture_metadata(mut self, enabled: bool) -> Self {
+        self.capture_metadata = enabled;
+        self
+    }
+
     /// Build and run an `App` with the specified parameters.
     ///
     /// This function will not return until the application has exited.
@@ -390,6 +466,15 @@ where
             event_loop_window_target,
             self.default_window_size,
         );
+        *app.input_session.borrow_mut() = self.input_session;
+        app.capture_metadata.set(self.capture_metadata);
+
+        // Seed the app's random number generator.
+        let seed = rand::seed_override()
+            .or(self.seed)
+            .unwrap_or_else(rand::random);
+        println!("nannou seed: {}", seed);
+        app.set_seed(seed);
 
         // Create the default window if necessary
         if self.create_default_window {
@@ -431,6 +516,33 @@ where
         self
     }
 
+    /// Record all user input to the given path. See `app::Builder::record`.
+    pub fn record<P>(mut self, path: P) -> Self
+    where
+        P: Into<PathBuf>,
+    {
+        self.builder = self.builder.record(path);
+        self
+    }
+
+    /// Replay the given recording of user input. See `app::Builder::replay`.
+    pub fn replay(mut self, recording: record::Recording, timing: record::Timing) -> Self {
+        self.builder = self.builder.replay(recording, timing);
+        self
+    }
+
+    /// Seed the app's random number generator. See `app::Builder::seed`.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.builder = self.builder.seed(seed);
+        self
+    }
+
+    /// Write metadata alongside each captured frame. See `app::Builder::capture_metadata`.
+    pub fn capture_metadata(mut self, enabled: bool) -> Self {
+        self.builder = self.builder.capture_metadata(enabled);
+        self
+    }
+
     /// Build and run a `Sketch` with the specified parameters.
     ///
     /// This calls `App::run` internally. See that method for details!
@@ -454,6 +566,9 @@ impl Builder<(), Event> {
             exit: None,
             create_default_window: true,
             default_window_size: None,
+            input_session: record::Session::None,
+            seed: None,
+            capture_metadata: false,
         };
         SketchBuilder { builder }
     }
@@ -544,8 +659,14 @@ impl App {
         let renderers = RefCell::new(Default::default());
         let draw_state = DrawState { draw, renderers };
         let focused_window = RefCell::new(None);
+        let input_session = RefCell::new(record::Session::None);
+        let action_map = RefCell::new(input::ActionMap::default());
+        let rng = RefCell::new(rand::SeededRng::new(0));
+        let seed = Arc::new(AtomicU64::new(0));
+        let capture_metadata = Cell::new(false);
         let ui = ui::Arrangement::new();
         let mouse = state::Mouse::new();
+        let touches = state::Touches::default();
         let keys = state::Keys::default();
         let duration = state::Time::default();
         let time = duration.since_start.secs() as _;
@@ -554,12 +675,18 @@ impl App {
             event_loop_window_target,
             default_window_size,
             focused_window,
+            input_session,
+            action_map,
+            rng,
+            seed,
+            capture_metadata,
             adapters,
             windows,
             config,
             draw_state,
             ui,
             mouse,
+            touches,
             keys,
             duration,
             time,
@@ -631,6 +758,15 @@ impl App {
         }
     }
 
+    /// Begin building a new offscreen **Layer** on the device of the window currently in focus.
+    ///
+    /// This is shorthand for `app.main_window().new_layer()`.
+    ///
+    /// **Panics** if there are no windows open.
+    pub fn new_layer(&self) -> draw::layer::Builder {
+        self.main_window().new_layer()
+    }
+
     /// The number of windows currently in the application.
     pub fn window_count(&self) -> usize {
         self.windows.borrow().len()
@@ -710,6 +846,63 @@ impl App {
         self.config.borrow_mut().exit_on_escape = b;
     }
 
+    /// The map from named actions to input bindings queried via `action` and `action_active`.
+    pub fn action_map(&self) -> std::cell::Ref<input::ActionMap> {
+        self.action_map.borrow()
+    }
+
+    /// Replace the map from named actions to input bindings, e.g. with one loaded via
+    /// `input::ActionMap::load`.
+    pub fn set_action_map(&self, map: input::ActionMap) {
+        *self.action_map.borrow_mut() = map;
+    }
+
+    /// Whether or not the named action was triggered since the end of the previous update, e.g.
+    /// `app.action("toggle_gui")`.
+    ///
+    /// Returns `false` if no action with the given name exists in the action map.
+    pub fn action(&self, name: &str) -> bool {
+        self.action_map
+            .borrow()
+            .is_triggered(name, &self.keys, &self.mouse)
+    }
+
+    /// Whether or not any binding for the named action is currently held.
+    pub fn action_active(&self, name: &str) -> bool {
+        self.action_map
+            .borrow()
+            .is_active(name, &self.keys, &self.mouse)
+    }
+
+    /// The app's seeded random number generator.
+    ///
+    /// Unlike the functions within the `rand` module, the values produced are the same on every
+    /// run with the same seed. See `app::Builder::seed`.
+    ///
+    /// **Panics** if the generator is already borrowed.
+    pub fn rng(&self) -> RefMut<rand::SeededRng> {
+        self.rng.borrow_mut()
+    }
+
+    /// Derive an independent random number generator for the given name.
+    ///
+    /// The stream depends only on the app's seed and the name, so each part of a sketch may use
+    /// its own stream without affecting the values produced by the others.
+    pub fn rng_stream(&self, name: &str) -> rand::SeededRng {
+        self.rng.borrow().stream(name)
+    }
+
+    /// The seed of the app's random number generator.
+    pub fn seed(&self) -> u64 {
+        self.seed.load(atomic::Ordering::Relaxed)
+    }
+
+    /// Re-seed the app's random number generator, restarting its sequence.
+    pub fn set_seed(&self, seed: u64) {
+        *self.rng.borrow_mut() = rand::SeededRng::new(seed);
+        self.seed.store(seed, atomic::Ordering::Relaxed);
+    }
+
     /// Returns whether or not the `App` is currently allows the focused window to enter or exit
     /// fullscreen via typical platform-specific shortcuts.
     ///
@@ -779,19 +972,9 @@ impl App {
 
         let renderers = self.draw_state.renderers.borrow_mut();
         let renderer = RefMut::map(renderers, |renderers| {
-            renderers.entry(window_id).or_insert_with(|| {
-                let device = window.swap_chain_device();
-                let frame_dims: [u32; 2] = window.tracked_state.physical_size.into();
-                let msaa_samples = window.msaa_samples();
-                let target_format = crate::frame::Frame::TEXTURE_FORMAT;
-                let renderer = draw::backend::wgpu::Renderer::new(
-                    device,
-                    frame_dims,
-                    msaa_samples,
-                    target_format,
-                );
-                RefCell::new(renderer)
-            })
+            renderers
+                .entry(window_id)
+                .or_insert_with(|| RefCell::new(draw_renderer(&window)))
         });
         Some(Draw {
             window_id,
@@ -875,9 +1058,18 @@ impl<'a> Draw<'a> {
         );
         let scale_factor = window.tracked_state.scale_factor as _;
         let mut renderer = self.renderer.borrow_mut();
+        renderer.set_viewport(window.viewport());
         renderer.render_to_frame(window.swap_chain_device(), &self.draw, scale_factor, frame);
         Ok(())
     }
+
+    /// Statistics describing the work done by the window's renderer during the most recent call
+    /// to `to_frame`.
+    ///
+    /// Useful for finding out where time goes when rendering heavy scenes.
+    pub fn render_stats(&self) -> draw::backend::wgpu::RenderStats {
+        *self.renderer.borrow().stats()
+    }
 }
 
 impl<'a> Deref for Draw<'a> {
@@ -1012,12 +1204,12 @@ fn run_loop<M, E>(
                     let loop_mode = app.loop_mode();
                     let now = Instant::now();
                     let mut do_update = |loop_state: &mut LoopState| {
-                        apply_update(&mut app, model, event_fn, update_fn, loop_state, now);
+                        apply_update(&mut app, model, event_fn, update_fn, loop_state, now)
                     };
                     match loop_mode {
                         LoopMode::NTimes { number_of_updates }
                             if loop_state.total_updates >= number_of_updates as u64 => {}
-                        _ => do_update(&mut loop_state),
+                        _ => exit |= do_update(&mut loop_state),
                     }
                 }
             }
@@ -1026,6 +1218,9 @@ fn run_loop<M, E>(
             //
             // TODO: Only request a frame from the user if this redraw was requested following an
             // update. Otherwise, just use the existing intermediary frame.
+            // Windows that mirror another window are drawn to along with their source window.
+            winit::event::Event::RedrawRequested(window_id) if is_mirror(&app, window_id) => {}
+
             winit::event::Event::RedrawRequested(window_id) => {
                 // Take the render data and swapchain.
                 // We'll replace them before the end of this block.
@@ -1117,12 +1312,19 @@ fn run_loop<M, E>(
                 }
 
                 // Replace the render data and swap chain.
-                let mut windows = app.windows.borrow_mut();
-                let window = windows
-                    .get_mut(&window_id)
-                    .expect("no window for redraw request ID");
+                {
+                    let mut windows = app.windows.borrow_mut();
+                    let window = windows
+                        .get_mut(&window_id)
+                        .expect("no window for redraw request ID");
 
-                window.swap_chain.swap_chain = Some(swap_chain);
+                    window.swap_chain.swap_chain = Some(swap_chain);
+                }
+
+                // Render the app's draw state to any windows mirroring this one.
+                if model.is_some() {
+                    draw_to_mirrors(&app, window_id);
+                }
             }
 
             // Clear any inactive adapters and devices and poll those remaining.
@@ -1204,6 +1406,7 @@ fn run_loop<M, E>(
 
         // If we need to exit, call the user's function and update control flow.
         if exit {
+            app.input_session.borrow_mut().finish();
             if let Some(model) = model.take() {
                 if let Some(exit_fn) = exit_fn {
                     exit_fn(&app, model);
@@ -1225,6 +1428,8 @@ fn run_loop<M, E>(
 
 // Apply an update to the model via the user's function and update the app and loop state
 // accordingly.
+//
+// Returns whether or not the app should exit, e.g. due to a replayed escape key press.
 fn apply_update<M, E>(
     app: &mut App,
     model: &mut M,
@@ -1232,20 +1437,31 @@ fn apply_update<M, E>(
     update_fn: Option<UpdateFn<M>>,
     loop_state: &mut LoopState,
     now: Instant,
-) where
+) -> bool
+where
     M: 'static,
     E: LoopEvent,
 {
+    // Emit any replayed input that was received prior to this update.
+    let exit = emit_replayed_events(app, model, event_fn, loop_state.total_updates);
+
     // Update the app's durations.
     let since_last = now.duration_since(loop_state.last_update);
     let since_start = now.duration_since(loop_state.loop_start);
-    app.duration.since_prev_update = since_last;
-    app.duration.since_start = since_start;
-    app.time = since_start.secs() as _;
     let update = crate::event::Update {
         since_start,
         since_last,
     };
+    let update = app
+        .input_session
+        .borrow_mut()
+        .update(loop_state.total_updates, update);
+    app.duration.since_prev_update = update.since_last;
+    app.duration.since_start = update.since_start;
+    app.time = update.since_start.secs() as _;
+    app.mouse.update(&update);
+    app.touches.update(update.since_start);
+    emit_gestures(app, model);
     // User event function.
     if let Some(event_fn) = event_fn {
         let event = E::from(update.clone());
@@ -1255,6 +1471,9 @@ fn apply_update<M, E>(
     if let Some(update_fn) = update_fn {
         update_fn(app, model, update);
     }
+    // Input edges only persist for a single update.
+    app.keys.clear_edges();
+    app.mouse.clear_edges();
     loop_state.last_update = now;
     loop_state.total_updates += 1;
     loop_state.updates_since_event += 1;
@@ -1263,6 +1482,317 @@ fn apply_update<M, E>(
     for window in windows.values() {
         window.window.request_redraw();
     }
+    exit
+}
+
+// Emit all replayed input events that are due before the given update.
+//
+// Returns whether or not the app should exit.
+fn emit_replayed_events<M, E>(
+    app: &mut App,
+    model: &mut M,
+    event_fn: Option<EventFn<M, E>>,
+    nth_update: u64,
+) -> bool
+where
+    M: 'static,
+    E: LoopEvent,
+{
+    let mut exit = false;
+    loop {
+        let next = app
+            .input_session
+            .borrow_mut()
+            .next_replayed_event(nth_update);
+        let (window_id, input) = match next {
+            None => break,
+            Some(next) => next,
+        };
+        if app.window(window_id).is_none() {
+            continue;
+        }
+        let simple = match input {
+            record::RecordedInput::Window(simple) => simple,
+            record::RecordedInput::ModifiersChanged(mods) => {
+                app.keys.mods = mods;
+                continue;
+            }
+        };
+        if let event::WindowEvent::KeyPressed(Key::Escape) = simple {
+            exit |= app.exit_on_escape();
+        }
+        *app.focused_window.borrow_mut() = Some(window_id);
+        update_input_state(app, window_id, &simple);
+        if let Some(event_fn) = event_fn {
+            if let Some(event) = E::from_window_event(window_id, &simple, app) {
+                event_fn(&app, model, event);
+            }
+        }
+        emit_window_event(app, model, window_id, simple, None);
+    }
+    exit
+}
+
+// Call the user's window event functions associated with the given simplified window event.
+//
+// The `removed_window` is checked for functions in the case that the window was just closed.
+fn emit_window_event<M>(
+    app: &App,
+    model: &mut M,
+    window_id: window::Id,
+    simple: event::WindowEvent,
+    removed_window: Option<&Window>,
+) where
+    M: 'static,
+{
+    // Nannou window events.
+    if let Some(window_event_fn) = {
+        let windows = app.windows.borrow();
+        windows
+            .get(&window_id)
+            .and_then(|w| w.user_functions.event.clone())
+            .or_else(|| {
+                removed_window
+                    .as_ref()
+                    .and_then(|w| w.user_functions.event.clone())
+            })
+    } {
+        let window_event_fn = window_event_fn
+            .to_fn_ptr::<M>()
+            .expect("unexpected model argument given to window event function");
+        (*window_event_fn)(&app, model, simple.clone());
+    }
+
+    // A macro to simplify calling event-specific user functions.
+    macro_rules! call_user_function {
+        ($fn_name:ident $(,$arg:expr)*) => {{
+            if let Some(event_fn) = {
+                let windows = app.windows.borrow();
+                windows
+                    .get(&window_id)
+                    .and_then(|w| w.user_functions.$fn_name.clone())
+                    .or_else(|| {
+                        removed_window
+                            .as_ref()
+                            .and_then(|w| w.user_functions.$fn_name.clone())
+                    })
+            } {
+                let event_fn = event_fn
+                    .to_fn_ptr::<M>()
+                    .unwrap_or_else(|| {
+                        panic!(
+                            "unexpected model argument given to {} function",
+                            stringify!($fn_name),
+                        );
+                    });
+                (*event_fn)(&app, model, $($arg),*);
+            }
+        }};
+    }
+
+    // Check for more specific event functions.
+    match simple {
+        event::WindowEvent::KeyPressed(key) => call_user_function!(key_pressed, key),
+        event::WindowEvent::KeyReleased(key) => call_user_function!(key_released, key),
+        event::WindowEvent::MouseMoved(pos) => call_user_function!(mouse_moved, pos),
+        event::WindowEvent::MousePressed(button) => {
+            call_user_function!(mouse_pressed, button)
+        }
+        event::WindowEvent::MouseReleased(button) => {
+            call_user_function!(mouse_released, button)
+        }
+        event::WindowEvent::MouseEntered => call_user_function!(mouse_entered),
+        event::WindowEvent::MouseExited => call_user_function!(mouse_exited),
+        event::WindowEvent::MouseWheel(amount, phase) => {
+            call_user_function!(mouse_wheel, amount, phase)
+        }
+        event::WindowEvent::Moved(pos) => call_user_function!(moved, pos),
+        event::WindowEvent::Resized(size) => call_user_function!(resized, size),
+        event::WindowEvent::Touch(touch) => call_user_function!(touch, touch),
+        event::WindowEvent::TouchPressure(pressure) => {
+            call_user_function!(touchpad_pressure, pressure)
+        }
+        event::WindowEvent::HoveredFile(path) => call_user_function!(hovered_file, path),
+        event::WindowEvent::HoveredFileCancelled => {
+            call_user_function!(hovered_file_cancelled)
+        }
+        event::WindowEvent::DroppedFile(path) => call_user_function!(dropped_file, path),
+        event::WindowEvent::Focused => call_user_function!(focused),
+        event::WindowEvent::Unfocused => call_user_function!(unfocused),
+        event::WindowEvent::Closed => call_user_function!(closed),
+    }
+}
+
+// Emit the gestures recognised by the app's touch state via the associated window's `gesture`
+// function.
+fn emit_gestures<M>(app: &mut App, model: &mut M)
+where
+    M: 'static,
+{
+    let gestures = std::mem::replace(&mut app.touches.pending, vec![]);
+    for (window_id, gesture) in gestures {
+        let gesture_fn = app
+            .windows
+            .borrow()
+            .get(&window_id)
+            .and_then(|w| w.user_functions.gesture.clone());
+        if let Some(gesture_fn) = gesture_fn {
+            let gesture_fn = gesture_fn
+                .to_fn_ptr::<M>()
+                .expect("unexpected model argument given to window gesture function");
+            (*gesture_fn)(&app, model, gesture);
+        }
+    }
+}
+
+// Update the app's mouse and keyboard state from a simplified window event.
+//
+// Live input updates this state from the raw winit events.
+fn update_input_state(app: &mut App, window_id: window::Id, event: &event::WindowEvent) {
+    match *event {
+        event::WindowEvent::MouseMoved(p) => {
+            app.mouse.move_to(p);
+            app.mouse.window = Some(window_id);
+        }
+        event::WindowEvent::MousePressed(button) => {
+            app.mouse.press(button);
+            app.mouse.window = Some(window_id);
+        }
+        event::WindowEvent::MouseReleased(button) => {
+            app.mouse.release(button);
+            app.mouse.window = Some(window_id);
+        }
+        event::WindowEvent::MouseWheel(delta, _) => app.mouse.scroll_by(scroll_points(delta)),
+        event::WindowEvent::Touch(touch) => {
+            app.touches
+                .touch(window_id, touch.id, touch.phase, touch.position)
+        }
+        event::WindowEvent::KeyPressed(key) => app.keys.press(key),
+        event::WindowEvent::KeyReleased(key) => app.keys.release(key),
+        _ => (),
+    }
+}
+
+// Convert a scroll delta to points.
+fn scroll_points(delta: event::MouseScrollDelta) -> geom::Vector2 {
+    let (x, y) = match delta {
+        event::MouseScrollDelta::LineDelta(x, y) => {
+            let points = state::mouse::POINTS_PER_SCROLL_LINE;
+            (x as f64 * points, y as f64 * points)
+        }
+        event::MouseScrollDelta::PixelDelta(p) => (p.x, p.y),
+    };
+    geom::vec2(x as _, y as _)
+}
+
+// Whether or not the raw window event is user input that is replaced while replaying a recording.
+fn is_input_event(event: &winit::event::WindowEvent) -> bool {
+    match *event {
+        winit::event::WindowEvent::KeyboardInput { .. }
+        | winit::event::WindowEvent::ReceivedCharacter(_)
+        | winit::event::WindowEvent::CursorMoved { .. }
+        | winit::event::WindowEvent::CursorEntered { .. }
+        | winit::event::WindowEvent::CursorLeft { .. }
+        | winit::event::WindowEvent::MouseWheel { .. }
+        | winit::event::WindowEvent::MouseInput { .. }
+        | winit::event::WindowEvent::TouchpadPressure { .. }
+        | winit::event::WindowEvent::AxisMotion { .. }
+        | winit::event::WindowEvent::Touch(_)
+        | winit::event::WindowEvent::HoveredFile(_)
+        | winit::event::WindowEvent::DroppedFile(_)
+        | winit::event::WindowEvent::HoveredFileCancelled => true,
+        _ => false,
+    }
+}
+
+// Create a renderer for the app's **Draw** targeting the given window's frames.
+fn draw_renderer(window: &Window) -> draw::backend::wgpu::Renderer {
+    let device = window.swap_chain_device();
+    let frame_dims: [u32; 2] = window.tracked_state.physical_size.into();
+    let msaa_samples = window.msaa_samples();
+    let target_format = window.frame_texture_format();
+    draw::backend::wgpu::Renderer::new(device, frame_dims, msaa_samples, target_format)
+}
+
+// Whether or not the window with the given ID mirrors the output of another window.
+fn is_mirror(app: &App, window_id: window::Id) -> bool {
+    app.window(window_id)
+        .map(|window| window.mirror().is_some())
+        .unwrap_or(false)
+}
+
+// Render the app's **Draw** to each window that mirrors the given source window.
+//
+// The draw state is left as it was following the source window's `view`, so the same geometry is
+// shared between all windows. Each mirror shows the region described by its own viewport, falling
+// back to the viewport of the source window.
+fn draw_to_mirrors(app: &App, source_id: window::Id) {
+    let (mirror_ids, source_viewport) = {
+        let windows = app.windows.borrow();
+        let source_viewport = match windows.get(&source_id) {
+            None => return,
+            Some(source) => source.viewport().unwrap_or_else(|| source.rect()),
+        };
+        let mirror_ids: Vec<_> = windows
+            .iter()
+            .filter(|(_, window)| window.mirror() == Some(source_id))
+            .map(|(&id, _)| id)
+            .collect();
+        (mirror_ids, source_viewport)
+    };
+
+    for window_id in mirror_ids {
+        // Take the swap chain, replacing it once the frame is submitted.
+        let (mut swap_chain, nth_frame) = {
+            let mut windows = app.windows.borrow_mut();
+            let window = windows
+                .get_mut(&window_id)
+                .expect("no window for mirror ID");
+            if window.frame_data.is_none() {
+                continue;
+            }
+            let swap_chain = match window.swap_chain.swap_chain.take() {
+                None => continue,
+                Some(swap_chain) => swap_chain,
+            };
+            let nth_frame = window.frame_count;
+            window.frame_count += 1;
+            (swap_chain, nth_frame)
+        };
+
+        {
+            let swap_chain_output = swap_chain.get_next_texture();
+            let windows = app.windows.borrow();
+            let window = windows.get(&window_id).expect("no window for mirror ID");
+            let data = window.frame_data.as_ref().expect("missing `frame_data`");
+            let raw_frame = RawFrame::new_empty(
+                window.swap_chain_device_queue_pair().clone(),
+                window_id,
+                nth_frame,
+                &swap_chain_output.view,
+                window.swap_chain.descriptor.format,
+                window.rect(),
+            );
+            let frame = Frame::new_empty(raw_frame, &data.render, &data.capture);
+
+            let mut renderers = app.draw_state.renderers.borrow_mut();
+            let mut renderer = renderers
+                .entry(window_id)
+                .or_insert_with(|| RefCell::new(draw_renderer(&window)))
+                .borrow_mut();
+            let viewport = window.viewport().unwrap_or(source_viewport);
+            renderer.set_viewport(Some(viewport));
+            let draw = app.draw_state.draw.borrow();
+            let scale_factor = window.tracked_state.scale_factor as _;
+            renderer.render_to_frame(window.swap_chain_device(), &draw, scale_factor, &frame);
+        }
+
+        let mut windows = app.windows.borrow_mut();
+        let window = windows
+            .get_mut(&window_id)
+            .expect("no window for mirror ID");
+        window.swap_chain.swap_chain = Some(swap_chain);
+    }
 }
 
 // Whether or not the given event should toggle fullscreen.
@@ -1321,6 +1851,13 @@ where
     M: 'static,
     E: LoopEvent,
 {
+    // While replaying a recording, live input is replaced by the recorded input.
+    if let winit::event::Event::WindowEvent { ref event, .. } = *winit_event {
+        if is_input_event(event) && app.input_session.borrow().is_replaying_input() {
+            return false;
+        }
+    }
+
     // Inspect the event to see if it would require closing the App.
     let mut exit_on_escape = false;
     let mut removed_window = None;
@@ -1395,33 +1932,34 @@ where
                     let (x, y) = position.to_logical::<f32>(scale_factor).into();
                     let x = tx(x);
                     let y = ty(y);
-                    app.mouse.x = x;
-                    app.mouse.y = y;
+                    app.mouse.move_to(geom::pt2(x, y));
                     app.mouse.window = Some(window_id);
                 }
 
                 winit::event::WindowEvent::MouseInput { state, button, .. } => {
                     match state {
-                        event::ElementState::Pressed => {
-                            let p = app.mouse.position();
-                            app.mouse.buttons.press(button, p);
-                        }
-                        event::ElementState::Released => {
-                            app.mouse.buttons.release(button);
-                        }
+                        event::ElementState::Pressed => app.mouse.press(button),
+                        event::ElementState::Released => app.mouse.release(button),
                     }
                     app.mouse.window = Some(window_id);
                 }
 
+                winit::event::WindowEvent::MouseWheel { delta, .. } => {
+                    app.mouse.scroll_by(scroll_points(delta));
+                }
+
+                winit::event::WindowEvent::Touch(touch) => {
+                    let (x, y) = touch.location.to_logical::<f32>(scale_factor).into();
+                    let position = geom::pt2(tx(x), ty(y));
+                    app.touches
+                        .touch(window_id, touch.id, touch.phase, position);
+                }
+
                 winit::event::WindowEvent::KeyboardInput { input, .. } => {
                     if let Some(key) = input.virtual_keycode {
                         match input.state {
-                            event::ElementState::Pressed => {
-                                app.keys.down.keys.insert(key);
-                            }
-                            event::ElementState::Released => {
-                                app.keys.down.keys.remove(&key);
-                            }
+                            event::ElementState::Pressed => app.keys.press(key),
+                            event::ElementState::Released => app.keys.release(key),
                         }
                     }
                 }
@@ -1446,9 +1984,18 @@ where
     }
 
     // Update the modifier keys within the app if necessary.
+    //
+    // While replaying, the modifier state is driven by the recording instead.
     if let winit::event::Event::DeviceEvent { event, .. } = winit_event {
         if let winit::event::DeviceEvent::ModifiersChanged(new_mods) = event {
-            app.keys.mods = new_mods.clone();
+            let mut session = app.input_session.borrow_mut();
+            if !session.is_replaying_input() {
+                app.keys.mods = new_mods.clone();
+                if let Some(window_id) = *app.focused_window.borrow() {
+                    let since_start = app.duration.since_start;
+                    session.record_modifiers(window_id, app.keys.mods, since_start);
+                }
+            }
         }
     }
 
@@ -1502,85 +2049,19 @@ where
         if let Some(simple) =
             event::WindowEvent::from_winit_window_event(event, win_w, win_h, scale_factor)
         {
-            // Nannou window events.
-            if let Some(window_event_fn) = {
-                let windows = app.windows.borrow();
-                windows
-                    .get(&window_id)
-                    .and_then(|w| w.user_functions.event.clone())
-                    .or_else(|| {
-                        removed_window
-                            .as_ref()
-                            .and_then(|w| w.user_functions.event.clone())
-                    })
-            } {
-                let window_event_fn = window_event_fn
-                    .to_fn_ptr::<M>()
-                    .expect("unexpected model argument given to window event function");
-                (*window_event_fn)(&app, model, simple.clone());
-            }
+            // Record the event if necessary.
+            let since_start = app.duration.since_start;
+            app.input_session
+                .borrow_mut()
+                .record_event(window_id, &simple, since_start);
 
-            // A macro to simplify calling event-specific user functions.
-            macro_rules! call_user_function {
-                ($fn_name:ident $(,$arg:expr)*) => {{
-                    if let Some(event_fn) = {
-                        let windows = app.windows.borrow();
-                        windows
-                            .get(&window_id)
-                            .and_then(|w| w.user_functions.$fn_name.clone())
-                            .or_else(|| {
-                                removed_window
-                                    .as_ref()
-                                    .and_then(|w| w.user_functions.$fn_name.clone())
-                            })
-                    } {
-                        let event_fn = event_fn
-                            .to_fn_ptr::<M>()
-                            .unwrap_or_else(|| {
-                                panic!(
-                                    "unexpected model argument given to {} function",
-                                    stringify!($fn_name),
-                                );
-                            });
-                        (*event_fn)(&app, model, $($arg),*);
-                    }
-                }};
-            }
-
-            // Check for more specific event functions.
-            match simple {
-                event::WindowEvent::KeyPressed(key) => call_user_function!(key_pressed, key),
-                event::WindowEvent::KeyReleased(key) => call_user_function!(key_released, key),
-                event::WindowEvent::MouseMoved(pos) => call_user_function!(mouse_moved, pos),
-                event::WindowEvent::MousePressed(button) => {
-                    call_user_function!(mouse_pressed, button)
-                }
-                event::WindowEvent::MouseReleased(button) => {
-                    call_user_function!(mouse_released, button)
-                }
-                event::WindowEvent::MouseEntered => call_user_function!(mouse_entered),
-                event::WindowEvent::MouseExited => call_user_function!(mouse_exited),
-                event::WindowEvent::MouseWheel(amount, phase) => {
-                    call_user_function!(mouse_wheel, amount, phase)
-                }
-                event::WindowEvent::Moved(pos) => call_user_function!(moved, pos),
-                event::WindowEvent::Resized(size) => call_user_function!(resized, size),
-                event::WindowEvent::Touch(touch) => call_user_function!(touch, touch),
-                event::WindowEvent::TouchPressure(pressure) => {
-                    call_user_function!(touchpad_pressure, pressure)
-                }
-                event::WindowEvent::HoveredFile(path) => call_user_function!(hovered_file, path),
-                event::WindowEvent::HoveredFileCancelled => {
-                    call_user_function!(hovered_file_cancelled)
-                }
-                event::WindowEvent::DroppedFile(path) => call_user_function!(dropped_file, path),
-                event::WindowEvent::Focused => call_user_function!(focused),
-                event::WindowEvent::Unfocused => call_user_function!(unfocused),
-                event::WindowEvent::Closed => call_user_function!(closed),
-            }
+            emit_window_event(app, model, window_id, simple, removed_window.as_ref());
         }
     }
 
+    // Emit any gestures recognised from touch events.
+    emit_gestures(app, model);
+
     // If the loop was destroyed, we'll need to exit.
     let loop_destroyed = match winit_event {
         winit::event::Event::LoopDestroyed => true,
@@ -1,25 +1,206 @@
-use crate::draw;
+use crate::draw::context::{Mask, TextureSource};
+use crate::draw::{self, BlendMode, Transparency};
 use crate::frame::Frame;
-use crate::math::{BaseFloat, NumCast};
+use crate::geom;
+use crate::math::cgmath::Vector3;
+use crate::math::{BaseFloat, Matrix4, NumCast, SquareMatrix};
 use crate::wgpu;
+use std::collections::HashMap;
+
+mod oit;
 
 /// A helper type aimed at simplifying the rendering of conrod primitives via wgpu.
 #[derive(Debug)]
 pub struct Renderer {
-    _vs_mod: wgpu::ShaderModule,
-    _fs_mod: wgpu::ShaderModule,
-    render_pipeline: wgpu::RenderPipeline,
+    vs_mod: wgpu::ShaderModule,
+    vs_instanced_mod: wgpu::ShaderModule,
+    fs_mod: wgpu::ShaderModule,
+    fs_textured_mod: wgpu::ShaderModule,
+    render_pipelines: Pipelines,
+    instanced_render_pipelines: Pipelines,
+    textured_render_pipelines: HashMap<BlendMode, Pipelines>,
+    oit: Option<oit::Oit>,
+    output_attachment_color_format: wgpu::TextureFormat,
     depth_texture: wgpu::Texture,
     depth_texture_view: wgpu::TextureView,
     bind_group_layout: wgpu::BindGroupLayout,
     bind_group: wgpu::BindGroup,
+    pipeline_layout: wgpu::PipelineLayout,
+    textured_bind_group_layout: wgpu::BindGroupLayout,
+    textured_pipeline_layout: wgpu::PipelineLayout,
+    // Samplers are created on demand and cached for the lifetime of the renderer.
+    samplers: Vec<(wgpu::SamplerDescriptor, wgpu::Sampler)>,
+    textured_bind_groups: Vec<wgpu::BindGroup>,
+    instanced_pipeline_layout: wgpu::PipelineLayout,
+    instanced_uniforms: wgpu::compute::Buffer<[[f32; 4]; 4]>,
+    instanced_bind_group: wgpu::BindGroup,
+    instance_buffer_sources: Vec<draw::InstanceBuffer>,
     vertices: Vec<Vertex>,
     indices: Vec<u32>,
+    instance_vertices: Vec<Vertex>,
+    instance_indices: Vec<u32>,
+    instances: Vec<Instance>,
+    instance_batches: Vec<InstanceBatch>,
+    render_commands: Vec<RenderCommand>,
+    transparent_triangles: Vec<(f32, [u32; 3])>,
+    vertex_buffer: GrowableBuffer,
+    index_buffer: GrowableBuffer,
+    instance_vertex_buffer: GrowableBuffer,
+    instance_index_buffer: GrowableBuffer,
+    instance_buffer: GrowableBuffer,
+    stats: RenderStats,
+    // The region of the drawing in points that is mapped to the output attachment. If `None`,
+    // the output attachment is centred on the origin.
+    viewport: Option<geom::Rect>,
 }
 
 #[derive(Debug)]
 pub struct DrawError;
 
+/// Statistics describing the work done by the **Renderer** during the most recent render pass.
+///
+/// Useful for finding out where time goes when rendering heavy scenes.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct RenderStats {
+    /// The number of vertices uploaded for the main mesh.
+    pub vertices: usize,
+    /// The number of indices uploaded for the main mesh.
+    pub indices: usize,
+    /// The total number of vertices uploaded for the base meshes of all instanced batches.
+    pub instance_vertices: usize,
+    /// The total number of instances across all instanced batches.
+    pub instances: usize,
+    /// The number of triangles rendered in a separate transparent pass.
+    ///
+    /// This is always `0` unless the **Draw**'s `Transparency` is `DepthSorted` or
+    /// `WeightedBlended`.
+    pub transparent_triangles: usize,
+    /// The number of draw calls encoded.
+    pub draw_calls: usize,
+    /// The number of bytes copied into the renderer's GPU buffers.
+    pub bytes_uploaded: wgpu::BufferAddress,
+    /// The number of GPU buffers that had to be reallocated in order to fit the uploaded data.
+    pub buffer_reallocations: usize,
+    /// The number of staging buffers allocated in order to upload data to the GPU.
+    ///
+    /// All data uploaded during a render pass shares a single staging buffer. Each composited
+    /// **Layer** is rendered with its own renderer, so its staging buffers are counted here too.
+    pub staging_allocations: usize,
+    /// The combined capacity of the renderer's GPU buffers in bytes.
+    pub buffer_capacity: wgpu::BufferAddress,
+    /// The CPU time spent encoding the render pass.
+    pub encode_duration: std::time::Duration,
+}
+
+// A GPU buffer that persists between frames and is only reallocated when the data to be uploaded
+// exceeds its capacity. The capacity grows geometrically in order to amortise reallocations.
+#[derive(Debug)]
+struct GrowableBuffer {
+    buffer: Option<wgpu::Buffer>,
+    capacity: wgpu::BufferAddress,
+    usage: wgpu::BufferUsage,
+}
+
+// Render pipelines sharing the same shaders and vertex layout, each created on demand for a
+// unique combination of depth and stencil state.
+#[derive(Debug)]
+struct Pipelines {
+    kind: PipelineKind,
+    pipelines: HashMap<PipelineState, wgpu::RenderPipeline>,
+}
+
+// Determines the vertex layout and color states of a set of **Pipelines**.
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+enum PipelineKind {
+    // Vertices of the main mesh.
+    Mesh,
+    // Vertices of an instanced base mesh along with per-instance data.
+    Instanced,
+    // Vertices of the main mesh accumulated for weighted blended order-independent transparency.
+    OitAccum,
+    // Vertices of the main mesh textured with a layer or atlas and blended via the given mode.
+    Textured(BlendMode),
+}
+
+// The depth and stencil state of a render pipeline.
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+struct PipelineState {
+    depth: DepthMode,
+    stencil: StencilMode,
+}
+
+// How a render pipeline uses the depth buffer.
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+enum DepthMode {
+    // Depth tested and written.
+    Test,
+    // Depth tested but not written.
+    TestReadOnly,
+    // No depth testing or writing.
+    Disabled,
+}
+
+// How a render pipeline uses the stencil buffer.
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+enum StencilMode {
+    // The stencil buffer is ignored.
+    Ignore,
+    // The stencil reference is written wherever a fragment is produced. No color is written.
+    Write,
+    // Fragments are only produced where the stencil buffer equals the stencil reference.
+    Equal,
+    // Fragments are only produced where the stencil buffer does not equal the stencil reference.
+    NotEqual,
+}
+
+// The resources shared by all render pipelines.
+struct PipelineDesc<'a> {
+    device: &'a wgpu::Device,
+    layout: &'a wgpu::PipelineLayout,
+    dst_format: wgpu::TextureFormat,
+    depth_format: wgpu::TextureFormat,
+    msaa_samples: u32,
+}
+
+// A range of the index buffer drawn with the same scissor and mask.
+#[derive(Clone, Debug)]
+struct RenderCommand {
+    // The opaque triangles followed by the semi-transparent triangles.
+    indices: std::ops::Range<u32>,
+    // The index at which the semi-transparent triangles begin.
+    transparent_start: u32,
+    // The scissor rectangle in pixels as `[x, y, w, h]`.
+    scissor: [u32; 4],
+    mask: Option<Mask>,
+    // The index of the texture bind group and the blend mode if the command is textured.
+    texture: Option<(usize, BlendMode)>,
+}
+
+// The state required to encode the draw calls for batches of instances.
+struct InstancedDraw<'a> {
+    // `None` if there are no batches to draw.
+    pipeline: Option<&'a wgpu::RenderPipeline>,
+    bind_group: &'a wgpu::BindGroup,
+    vertex_buffer: Option<&'a wgpu::Buffer>,
+    index_buffer: Option<&'a wgpu::Buffer>,
+    instance_buffer: Option<&'a wgpu::Buffer>,
+    instance_buffer_sources: &'a [draw::InstanceBuffer],
+    size: [u32; 2],
+}
+
+// The range of the instance buffers occupied by a single batch submitted via `Draw::instances`.
+#[derive(Clone, Debug)]
+struct InstanceBatch {
+    // The number of the main mesh's indices drawn before the batch.
+    draw_index: u32,
+    index_range: std::ops::Range<u32>,
+    base_vertex: i32,
+    instance_range: std::ops::Range<u32>,
+    // The index of the GPU instance buffer if the batch was submitted via
+    // `Draw::instances_from_buffer` rather than the shared instance buffer.
+    buffer: Option<usize>,
+}
+
 /// The `Vertex` type passed to the vertex shader.
 #[repr(C)]
 #[derive(Copy, Clone, Debug, Default, PartialEq, PartialOrd)]
@@ -50,6 +231,22 @@ pub struct Vertex {
     // pub mode: u32,
 }
 
+/// The per-instance data passed to the instanced vertex shader.
+///
+/// This is also the layout of each element of a **draw::InstanceBuffer**.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Default, PartialEq, PartialOrd)]
+pub struct Instance {
+    /// The columns of the matrix transforming the base mesh vertices, in points.
+    ///
+    /// The mapping from points to normalised device coordinates is applied by the vertex shader.
+    pub transform: [[f32; 4]; 4],
+    /// The color by which the base mesh vertex colors are multiplied.
+    ///
+    /// These values should be in the linear sRGB format.
+    pub color: [f32; 4],
+}
+
 impl Vertex {
     /// Create a vertex from the given mesh vertex.
     pub fn from_mesh_vertex<S>(
@@ -58,6 +255,16 @@ impl Vertex {
         framebuffer_height: f32,
         dpi_factor: f32,
     ) -> Self
+    where
+        S: BaseFloat,
+    {
+        let viewport = default_viewport([framebuffer_width, framebuffer_height], dpi_factor);
+        Self::from_mesh_vertex_in_viewport(v, &viewport)
+    }
+
+    /// Create a vertex from the given mesh vertex, mapping the given viewport (in points) to the
+    /// full extent of the framebuffer.
+    pub fn from_mesh_vertex_in_viewport<S>(v: draw::mesh::Vertex<S>, viewport: &geom::Rect) -> Self
     where
         S: BaseFloat,
     {
@@ -65,11 +272,11 @@ impl Vertex {
         let x_f: f32 = NumCast::from(point.x).unwrap();
         let y_f: f32 = NumCast::from(point.y).unwrap();
         let z_f: f32 = NumCast::from(point.z).unwrap();
-        // Map coords from (-fb_dim, +fb_dim) to (-1.0, 1.0)
+        // Map coords from the viewport to (-1.0, 1.0)
         // In wgpu, *y* increases in the downwards direction, so we negate it.
-        let x = 2.0 * x_f * dpi_factor / framebuffer_width;
-        let y = -(2.0 * y_f * dpi_factor / framebuffer_height);
-        let z = 2.0 * z_f * dpi_factor / framebuffer_height;
+        let x = 2.0 * (x_f - viewport.x()) / viewport.w();
+        let y = -(2.0 * (y_f - viewport.y()) / viewport.h());
+        let z = 2.0 * z_f / viewport.h();
         let tex_x = NumCast::from(v.tex_coords.x).unwrap();
         let tex_y = NumCast::from(v.tex_coords.y).unwrap();
         let position = [x, y, z];
@@ -84,9 +291,49 @@ impl Vertex {
     }
 }
 
+impl Vertex {
+    /// Create a vertex for an instanced base mesh from the given mesh vertex.
+    ///
+    /// Unlike `from_mesh_vertex`, the position is left untransformed as the mapping to normalised
+    /// device coordinates is applied via each instance's transform.
+    pub fn from_instance_mesh_vertex<S>(v: draw::mesh::Vertex<S>) -> Self
+    where
+        S: BaseFloat,
+    {
+        let point = v.point();
+        let x = NumCast::from(point.x).unwrap();
+        let y = NumCast::from(point.y).unwrap();
+        let z = NumCast::from(point.z).unwrap();
+        let tex_x = NumCast::from(v.tex_coords.x).unwrap();
+        let tex_y = NumCast::from(v.tex_coords.y).unwrap();
+        let (r, g, b, a) = v.color.into();
+        Vertex {
+            position: [x, y, z],
+            color: [r, g, b, a],
+            tex_coords: [tex_x, tex_y],
+        }
+    }
+}
+
+impl Instance {
+    /// Create an instance from the given **draw::Instance**.
+    pub fn from_draw_instance<S>(instance: &draw::Instance<S>) -> Self
+    where
+        S: BaseFloat,
+    {
+        let transform: Matrix4<f32> = instance.transform.cast().expect("failed to cast transform");
+        let transform = transform.into();
+        let (r, g, b, a) = instance.color.into();
+        let color = [r, g, b, a];
+        Instance { transform, color }
+    }
+}
+
 impl Renderer {
-    /// The default depth format
-    pub const DEFAULT_DEPTH_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth32Float;
+    /// The default depth format.
+    ///
+    /// Includes a stencil aspect, required for the masks produced via `Draw::mask`.
+    pub const DEFAULT_DEPTH_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth24PlusStencil8;
 
     /// Create a **Renderer** targeting an output attachment texture of the given description.
     pub fn from_texture_descriptor(
@@ -118,6 +365,8 @@ impl Renderer {
     }
 
     /// The same as **new**, but allows for manually specifying the depth format.
+    ///
+    /// The depth format must include a stencil aspect in order to support `Draw::mask`.
     pub fn with_depth_format(
         device: &wgpu::Device,
         output_attachment_size: [u32; 2],
@@ -130,45 +379,148 @@ impl Renderer {
         let vs_spirv = wgpu::read_spirv(std::io::Cursor::new(&vs[..]))
             .expect("failed to read hard-coded SPIRV");
         let vs_mod = device.create_shader_module(&vs_spirv);
+        let vs_instanced = include_bytes!("shaders/vert_instanced.spv");
+        let vs_instanced_spirv = wgpu::read_spirv(std::io::Cursor::new(&vs_instanced[..]))
+            .expect("failed to read hard-coded SPIRV");
+        let vs_instanced_mod = device.create_shader_module(&vs_instanced_spirv);
         let fs = include_bytes!("shaders/frag.spv");
         let fs_spirv = wgpu::read_spirv(std::io::Cursor::new(&fs[..]))
             .expect("failed to read hard-coded SPIRV");
         let fs_mod = device.create_shader_module(&fs_spirv);
+        let fs_textured = include_bytes!("shaders/frag_textured.spv");
+        let fs_textured_spirv = wgpu::read_spirv(std::io::Cursor::new(&fs_textured[..]))
+            .expect("failed to read hard-coded SPIRV");
+        let fs_textured_mod = device.create_shader_module(&fs_textured_spirv);
 
         // Create the depth texture.
         let depth_texture =
             create_depth_texture(device, output_attachment_size, depth_format, msaa_samples);
         let depth_texture_view = depth_texture.create_default_view();
 
-        // Create the render pipeline.
+        // Create the render pipelines for the default depth and stencil state. Pipelines for other
+        // states are created the first time they are required.
         let bind_group_layout = bind_group_layout(device);
         let bind_group = bind_group(device, &bind_group_layout);
         let pipeline_layout = pipeline_layout(device, &bind_group_layout);
-        let render_pipeline = render_pipeline(
+        let desc = PipelineDesc {
             device,
-            &pipeline_layout,
-            &vs_mod,
-            &fs_mod,
-            output_attachment_color_format,
+            layout: &pipeline_layout,
+            dst_format: output_attachment_color_format,
             depth_format,
             msaa_samples,
+        };
+        let default_state = PipelineState {
+            depth: DepthMode::Test,
+            stencil: StencilMode::Ignore,
+        };
+        let mut render_pipelines = Pipelines::new(PipelineKind::Mesh);
+        render_pipelines.prepare(&desc, &vs_mod, &fs_mod, default_state);
+        // Instanced batches are mapped to normalised device coordinates via a uniform matrix, so
+        // that instance buffers written on the GPU may be specified in points.
+        let instanced_bind_group_layout = instanced_bind_group_layout(device);
+        let instanced_pipeline_layout = pipeline_layout(device, &instanced_bind_group_layout);
+        let identity: [[f32; 4]; 4] = Matrix4::identity().into();
+        let instanced_uniforms = wgpu::compute::Buffer::uniform(device, &identity);
+        let instanced_bind_group =
+            instanced_bind_group(device, &instanced_bind_group_layout, &instanced_uniforms);
+        let instance_buffer_sources = vec![];
+        let instanced_desc = PipelineDesc {
+            layout: &instanced_pipeline_layout,
+            ..desc
+        };
+        let mut instanced_render_pipelines = Pipelines::new(PipelineKind::Instanced);
+        instanced_render_pipelines.prepare(
+            &instanced_desc,
+            &vs_instanced_mod,
+            &fs_mod,
+            default_state,
         );
+        let textured_render_pipelines = HashMap::new();
+        let oit = None;
+
+        // Textures are sampled via their own bind group, created each render for every pair of
+        // layer or atlas and sampler in use.
+        let textured_bind_group_layout = textured_bind_group_layout(device);
+        let textured_pipeline_layout = pipeline_layout(device, &textured_bind_group_layout);
+        let samplers = vec![];
+        let textured_bind_groups = vec![];
+
         let vertices = vec![];
         let indices = vec![];
+        let instance_vertices = vec![];
+        let instance_indices = vec![];
+        let instances = vec![];
+        let instance_batches = vec![];
+        let render_commands = vec![];
+        let transparent_triangles = vec![];
+        let vertex_buffer = GrowableBuffer::new(wgpu::BufferUsage::VERTEX);
+        let index_buffer = GrowableBuffer::new(wgpu::BufferUsage::INDEX);
+        let instance_vertex_buffer = GrowableBuffer::new(wgpu::BufferUsage::VERTEX);
+        let instance_index_buffer = GrowableBuffer::new(wgpu::BufferUsage::INDEX);
+        let instance_buffer = GrowableBuffer::new(wgpu::BufferUsage::VERTEX);
+        let stats = RenderStats::default();
+        let viewport = None;
 
         Self {
-            _vs_mod: vs_mod,
-            _fs_mod: fs_mod,
-            render_pipeline,
+            vs_mod,
+            vs_instanced_mod,
+            fs_mod,
+            fs_textured_mod,
+            render_pipelines,
+            instanced_render_pipelines,
+            textured_render_pipelines,
+            oit,
+            output_attachment_color_format,
             depth_texture,
             depth_texture_view,
             bind_group_layout,
             bind_group,
+            pipeline_layout,
+            textured_bind_group_layout,
+            textured_pipeline_layout,
+            samplers,
+            textured_bind_groups,
+            instanced_pipeline_layout,
+            instanced_uniforms,
+            instanced_bind_group,
+            instance_buffer_sources,
             vertices,
             indices,
+            instance_vertices,
+            instance_indices,
+            instances,
+            instance_batches,
+            render_commands,
+            transparent_triangles,
+            vertex_buffer,
+            index_buffer,
+            instance_vertex_buffer,
+            instance_index_buffer,
+            instance_buffer,
+            stats,
+            viewport,
         }
     }
 
+    /// Statistics describing the work done during the most recent call to `encode_render_pass`.
+    pub fn stats(&self) -> &RenderStats {
+        &self.stats
+    }
+
+    /// The region of the drawing in points that is mapped to the output attachment.
+    pub fn viewport(&self) -> Option<geom::Rect> {
+        self.viewport
+    }
+
+    /// Specify the region of the drawing in points that is mapped to the full extent of the
+    /// output attachment, e.g. to display a crop of a larger canvas.
+    ///
+    /// If `None` (the default), the output attachment displays the region of its own size in
+    /// points, centred on the origin.
+    pub fn set_viewport(&mut self, viewport: Option<geom::Rect>) {
+        self.viewport = viewport;
+    }
+
     /// Encode a render pass with the given **Draw**ing to the given `output_attachment`.
     ///
     /// If the **Draw**ing has been scaled for handling DPI, specify the necessary `scale_factor`
@@ -176,6 +528,9 @@ impl Renderer {
     ///
     /// If the `output_attachment` is multisampled and should be resolved to another texture,
     /// include the `resolve_target`.
+    ///
+    /// If the **Draw**'s `Transparency` is `WeightedBlended` and semi-transparent triangles are
+    /// present, two additional render passes are encoded for accumulating and compositing them.
     pub fn encode_render_pass<S>(
         &mut self,
         device: &wgpu::Device,
@@ -188,28 +543,60 @@ impl Renderer {
     ) where
         S: BaseFloat,
     {
+        let encode_start = std::time::Instant::now();
         let Renderer {
-            ref render_pipeline,
+            ref vs_mod,
+            ref vs_instanced_mod,
+            ref fs_mod,
+            ref fs_textured_mod,
+            ref mut render_pipelines,
+            ref mut instanced_render_pipelines,
+            ref mut textured_render_pipelines,
+            ref mut oit,
+            output_attachment_color_format,
             ref mut vertices,
             ref mut indices,
+            ref mut instance_vertices,
+            ref mut instance_indices,
+            ref mut instances,
+            ref mut instance_batches,
+            ref mut render_commands,
+            ref mut transparent_triangles,
+            ref mut vertex_buffer,
+            ref mut index_buffer,
+            ref mut instance_vertex_buffer,
+            ref mut instance_index_buffer,
+            ref mut instance_buffer,
+            ref mut stats,
+            viewport,
             ref mut depth_texture,
             ref mut depth_texture_view,
+            ref pipeline_layout,
+            ref textured_bind_group_layout,
+            ref textured_pipeline_layout,
+            ref mut samplers,
+            ref mut textured_bind_groups,
+            ref instanced_pipeline_layout,
+            ref instanced_uniforms,
+            ref instanced_bind_group,
+            ref mut instance_buffer_sources,
             ref bind_group,
             ..
         } = *self;
 
         // Resize the depth texture if the output attachment size has changed.
         let depth_size = depth_texture.size();
+        let depth_format = depth_texture.format();
+        let msaa_samples = depth_texture.sample_count();
         if output_attachment_size != depth_size {
-            let depth_format = depth_texture.format();
-            let sample_count = depth_texture.sample_count();
             *depth_texture =
-                create_depth_texture(device, output_attachment_size, depth_format, sample_count);
+                create_depth_texture(device, output_attachment_size, depth_format, msaa_samples);
             *depth_texture_view = depth_texture.create_default_view();
         }
 
         // Retrieve the clear values based on the bg color.
         let bg_color = draw.state.borrow().background_color;
+        let transparency = draw.state.borrow().transparency;
         let (load_op, clear_color) = match bg_color {
             None => (wgpu::LoadOp::Load, wgpu::Color::TRANSPARENT),
             Some(color) => {
@@ -243,29 +630,369 @@ impl Renderer {
             depth_stencil_attachment: Some(depth_stencil_attachment_desc),
         };
 
-        // Create the vertex and index buffers.
+        // Collect the vertices and indices of the main mesh.
         let [img_w, img_h] = output_attachment_size;
-        let map_vertex = |v| Vertex::from_mesh_vertex(v, img_w as _, img_h as _, scale_factor);
+        let viewport =
+            viewport.unwrap_or_else(|| default_viewport([img_w as _, img_h as _], scale_factor));
+        let map_vertex = |v| Vertex::from_mesh_vertex_in_viewport(v, &viewport);
         vertices.clear();
         vertices.extend(draw.raw_vertices().map(map_vertex));
-        let vertex_buffer = device
-            .create_buffer_mapped(vertices.len(), wgpu::BufferUsage::VERTEX)
-            .fill_from_slice(&vertices[..]);
         indices.clear();
         indices.extend(draw.inner_mesh().indices().iter().map(|&u| u as u32));
-        let index_buffer = device
-            .create_buffer_mapped(indices.len(), wgpu::BufferUsage::INDEX)
-            .fill_from_slice(&indices[..]);
 
-        let mut render_pass = encoder.begin_render_pass(&render_pass_desc);
-        render_pass.set_pipeline(render_pipeline);
-        render_pass.set_bind_group(0, bind_group, &[]);
-        render_pass.set_index_buffer(&index_buffer, 0);
-        render_pass.set_vertex_buffers(0, &[(&vertex_buffer, 0)]);
-        let index_range = 0..indices.len() as u32;
-        let start_vertex = 0;
-        let instance_range = 0..1;
-        render_pass.draw_indexed(index_range, start_vertex, instance_range);
+        // Collect the ranges of indices sharing the same scissor, mask and texture. Within each
+        // range, move semi-transparent triangles to the end if necessary.
+        // Each textured command refers to a binding, i.e. a pair of texture source and sampler.
+        let mut texture_sources: Vec<TextureSource> = vec![];
+        let mut texture_bindings: Vec<(usize, usize)> = vec![];
+        // Commands are split wherever an instanced batch was submitted so that the batch may be
+        // drawn between them.
+        let batch_indices: Vec<usize> = draw.instance_batches().iter().map(|b| b.index()).collect();
+        render_commands.clear();
+        for command in draw.state.borrow().commands.iter() {
+            let command_range = command.indices.clone();
+            let texture = command.context.texture.as_ref().map(|textured| {
+                let source_ix = match texture_sources
+                    .iter()
+                    .position(|s| s.ptr_eq(&textured.source))
+                {
+                    Some(ix) => ix,
+                    None => {
+                        texture_sources.push(textured.source.clone());
+                        texture_sources.len() - 1
+                    }
+                };
+                let sampler_ix = match samplers
+                    .iter()
+                    .position(|(desc, _)| wgpu::sampler_descriptor_eq(desc, &textured.sampler))
+                {
+                    Some(ix) => ix,
+                    None => {
+                        let sampler = device.create_sampler(&textured.sampler);
+                        samplers.push((textured.sampler.clone(), sampler));
+                        samplers.len() - 1
+                    }
+                };
+                let binding = (source_ix, sampler_ix);
+                let ix = match texture_bindings.iter().position(|&b| b == binding) {
+                    Some(ix) => ix,
+                    None => {
+                        texture_bindings.push(binding);
+                        texture_bindings.len() - 1
+                    }
+                };
+                (ix, textured.blend)
+            });
+            let scissor = match command.context.scissor {
+                None => [0, 0, img_w, img_h],
+                Some(ref rect) => scissor_rect(rect, output_attachment_size, &viewport),
+            };
+            let splits = batch_indices
+                .iter()
+                .cloned()
+                .filter(|&ix| command_range.start < ix && ix < command_range.end)
+                .chain(Some(command_range.end));
+            let mut start = command_range.start;
+            for end in splits {
+                let range = start..end;
+                start = end;
+                let opaque_len = match (transparency, command.context.mask) {
+                    _ if texture.is_some() => range.len(),
+                    (_, Some(Mask::Write(_))) => range.len(),
+                    (Transparency::DepthTest, _) | (Transparency::Painter, _) => range.len(),
+                    (Transparency::DepthSorted, _) => partition_transparent_triangles(
+                        vertices,
+                        &mut indices[range.clone()],
+                        transparent_triangles,
+                        true,
+                    ),
+                    (Transparency::WeightedBlended, _) => partition_transparent_triangles(
+                        vertices,
+                        &mut indices[range.clone()],
+                        transparent_triangles,
+                        false,
+                    ),
+                };
+                render_commands.push(RenderCommand {
+                    indices: range.start as u32..range.end as u32,
+                    transparent_start: (range.start + opaque_len) as u32,
+                    scissor,
+                    mask: command.context.mask,
+                    texture,
+                });
+            }
+        }
+
+        // Render the contents of each composited layer to its texture.
+        let mut layer_staging_allocations = 0;
+        for source in &texture_sources {
+            if let TextureSource::Layer(ref layer) = *source {
+                let layer_stats = layer.encode_render_pass(device, encoder);
+                layer_staging_allocations += layer_stats.staging_allocations;
+            }
+        }
+
+        // Prepare the bind group used to sample each texture with each sampler.
+        textured_bind_groups.clear();
+        for &(source_ix, sampler_ix) in &texture_bindings {
+            let layout = textured_bind_group_layout;
+            let sampler = &samplers[sampler_ix].1;
+            let bind_group = match texture_sources[source_ix] {
+                TextureSource::Layer(ref layer) => {
+                    textured_bind_group(device, layout, layer.texture_view(), sampler)
+                }
+                TextureSource::Atlas(ref atlas) => {
+                    let view = atlas.texture().create_default_view();
+                    textured_bind_group(device, layout, &view, sampler)
+                }
+            };
+            textured_bind_groups.push(bind_group);
+        }
+
+        // Collect the base mesh vertices, indices and instances of all instanced batches into
+        // shared buffers, tracking the range occupied by each batch.
+        // Batches with a GPU instance buffer refer to it rather than the shared instance buffer.
+        instance_vertices.clear();
+        instance_indices.clear();
+        instances.clear();
+        instance_batches.clear();
+        instance_buffer_sources.clear();
+        let mut gpu_instances = 0;
+        for batch in draw.instance_batches().iter() {
+            let mesh = batch.mesh();
+            let len = match batch.buffer() {
+                Some(buffer) => buffer.len(),
+                None => batch.instances().len(),
+            };
+            if len == 0 || mesh.indices().is_empty() {
+                continue;
+            }
+            let base_vertex = instance_vertices.len() as i32;
+            let index_start = instance_indices.len() as u32;
+            instance_vertices.extend(mesh.raw_vertices().map(Vertex::from_instance_mesh_vertex));
+            instance_indices.extend(mesh.indices().iter().map(|&u| u as u32));
+            let index_range = index_start..instance_indices.len() as u32;
+            let (instance_range, buffer) = match batch.buffer() {
+                Some(buffer) => {
+                    gpu_instances += len;
+                    instance_buffer_sources.push(buffer.clone());
+                    (0..len as u32, Some(instance_buffer_sources.len() - 1))
+                }
+                None => {
+                    let instance_start = instances.len() as u32;
+                    let batch_instances =
+                        batch.instances().iter().map(Instance::from_draw_instance);
+                    instances.extend(batch_instances);
+                    (instance_start..instances.len() as u32, None)
+                }
+            };
+            instance_batches.push(InstanceBatch {
+                draw_index: batch.index() as u32,
+                index_range,
+                base_vertex,
+                instance_range,
+                buffer,
+            });
+        }
+
+        // Map points to normalised device coordinates in the same manner as
+        // `Vertex::from_mesh_vertex_in_viewport`. In wgpu, *y* increases in the downwards
+        // direction, so we negate it. This is only uploaded when there are instances to draw.
+        let x = 2.0 / viewport.w();
+        let y = -(2.0 / viewport.h());
+        let z = 2.0 / viewport.h();
+        let translation = Vector3::new(-viewport.x(), -viewport.y(), 0.0);
+        let ndc = Matrix4::from_nonuniform_scale(x, y, z) * Matrix4::from_translation(translation);
+        let ndc: [[f32; 4]; 4] = ndc.into();
+        let ndc_bytes = match instance_batches.is_empty() {
+            true => &[][..],
+            false => slice_bytes(std::slice::from_ref(&ndc)),
+        };
+
+        // Upload the data into the persistent GPU buffers, growing them if necessary.
+        *stats = RenderStats::default();
+        stats.staging_allocations = layer_staging_allocations;
+        let mut uploads = [
+            (&mut *vertex_buffer, slice_bytes(&vertices[..])),
+            (&mut *index_buffer, slice_bytes(&indices[..])),
+            (
+                &mut *instance_vertex_buffer,
+                slice_bytes(&instance_vertices[..]),
+            ),
+            (
+                &mut *instance_index_buffer,
+                slice_bytes(&instance_indices[..]),
+            ),
+            (&mut *instance_buffer, slice_bytes(&instances[..])),
+        ];
+        let fixed = [(instanced_uniforms.inner(), ndc_bytes)];
+        upload_buffers(device, encoder, &mut uploads, &fixed, stats);
+        stats.vertices = vertices.len();
+        stats.indices = indices.len();
+        stats.instance_vertices = instance_vertices.len();
+        stats.instances = instances.len() + gpu_instances;
+        stats.transparent_triangles = render_commands
+            .iter()
+            .map(|cmd| (cmd.indices.end - cmd.transparent_start) as usize / 3)
+            .sum();
+        stats.buffer_capacity = vertex_buffer.capacity
+            + index_buffer.capacity
+            + instance_vertex_buffer.capacity
+            + instance_index_buffer.capacity
+            + instance_buffer.capacity;
+
+        // Ensure the render pipelines required by each command exist.
+        let desc = PipelineDesc {
+            device,
+            layout: pipeline_layout,
+            dst_format: output_attachment_color_format,
+            depth_format,
+            msaa_samples,
+        };
+        let textured_desc = PipelineDesc {
+            layout: textured_pipeline_layout,
+            ..desc
+        };
+        for cmd in render_commands.iter() {
+            let state = cmd.opaque_pipeline_state(transparency);
+            if let Some((_, blend)) = cmd.texture {
+                textured_render_pipelines
+                    .entry(blend)
+                    .or_insert_with(|| Pipelines::new(PipelineKind::Textured(blend)))
+                    .prepare(&textured_desc, vs_mod, fs_textured_mod, state);
+                continue;
+            }
+            render_pipelines.prepare(&desc, vs_mod, fs_mod, state);
+            if transparency == Transparency::DepthSorted && cmd.has_transparent_triangles() {
+                let state = cmd.transparent_pipeline_state();
+                render_pipelines.prepare(&desc, vs_mod, fs_mod, state);
+            }
+        }
+        let instanced_state = PipelineState {
+            depth: DepthMode::from_transparency(transparency),
+            stencil: StencilMode::Ignore,
+        };
+        if !instance_batches.is_empty() {
+            let instanced_desc = PipelineDesc {
+                layout: instanced_pipeline_layout,
+                ..desc
+            };
+            instanced_render_pipelines.prepare(
+                &instanced_desc,
+                vs_instanced_mod,
+                fs_mod,
+                instanced_state,
+            );
+        }
+
+        {
+            let mut render_pass = encoder.begin_render_pass(&render_pass_desc);
+            let start_vertex = 0;
+
+            // Draw the opaque triangles of each command, or all triangles if they need not be
+            // separated. Each batch of instances is drawn via a single instanced draw call
+            // between the commands submitted before and after it.
+            let instanced = InstancedDraw {
+                pipeline: if instance_batches.is_empty() {
+                    None
+                } else {
+                    Some(instanced_render_pipelines.get(instanced_state))
+                },
+                bind_group: instanced_bind_group,
+                vertex_buffer: instance_vertex_buffer.get(),
+                index_buffer: instance_index_buffer.get(),
+                instance_buffer: instance_buffer.get(),
+                instance_buffer_sources: &instance_buffer_sources[..],
+                size: output_attachment_size,
+            };
+            let mut batches = &instance_batches[..];
+            for cmd in render_commands.iter() {
+                let n = batches
+                    .iter()
+                    .take_while(|b| b.draw_index <= cmd.indices.start)
+                    .count();
+                instanced.encode(&mut render_pass, &batches[..n], stats);
+                batches = &batches[n..];
+
+                let (vbuf, ibuf) = match (vertex_buffer.get(), index_buffer.get()) {
+                    (Some(vbuf), Some(ibuf)) => (vbuf, ibuf),
+                    _ => continue,
+                };
+                let index_range = cmd.indices.start..cmd.transparent_start;
+                if index_range.start == index_range.end || !cmd.is_visible() {
+                    continue;
+                }
+                let state = cmd.opaque_pipeline_state(transparency);
+                match cmd.texture {
+                    None => {
+                        render_pass.set_pipeline(render_pipelines.get(state));
+                        render_pass.set_bind_group(0, bind_group, &[]);
+                    }
+                    Some((ix, blend)) => {
+                        render_pass.set_pipeline(textured_render_pipelines[&blend].get(state));
+                        render_pass.set_bind_group(0, &textured_bind_groups[ix], &[]);
+                    }
+                }
+                render_pass.set_index_buffer(ibuf, 0);
+                render_pass.set_vertex_buffers(0, &[(vbuf, 0)]);
+                cmd.set_scissor_and_stencil_reference(&mut render_pass);
+                let instance_range = 0..1;
+                render_pass.draw_indexed(index_range, start_vertex, instance_range);
+                stats.draw_calls += 1;
+            }
+            instanced.encode(&mut render_pass, batches, stats);
+
+            // Blend the sorted semi-transparent triangles of each command over the top.
+            if transparency == Transparency::DepthSorted {
+                if let (Some(vbuf), Some(ibuf)) = (vertex_buffer.get(), index_buffer.get()) {
+                    for cmd in render_commands.iter() {
+                        if !cmd.has_transparent_triangles() || !cmd.is_visible() {
+                            continue;
+                        }
+                        let state = cmd.transparent_pipeline_state();
+                        render_pass.set_pipeline(render_pipelines.get(state));
+                        render_pass.set_bind_group(0, bind_group, &[]);
+                        render_pass.set_index_buffer(ibuf, 0);
+                        render_pass.set_vertex_buffers(0, &[(vbuf, 0)]);
+                        cmd.set_scissor_and_stencil_reference(&mut render_pass);
+                        let index_range = cmd.transparent_start..cmd.indices.end;
+                        let instance_range = 0..1;
+                        render_pass.draw_indexed(index_range, start_vertex, instance_range);
+                        stats.draw_calls += 1;
+                    }
+                }
+            }
+        }
+
+        // Accumulate and composite the semi-transparent triangles in separate passes.
+        if transparency == Transparency::WeightedBlended && stats.transparent_triangles > 0 {
+            if let (Some(vbuf), Some(ibuf)) = (vertex_buffer.get(), index_buffer.get()) {
+                let oit = oit.get_or_insert_with(|| {
+                    oit::Oit::new(
+                        device,
+                        output_attachment_size,
+                        output_attachment_color_format,
+                        msaa_samples,
+                    )
+                });
+                oit.encode_render_passes(
+                    &desc,
+                    encoder,
+                    vs_mod,
+                    bind_group,
+                    vbuf,
+                    ibuf,
+                    render_commands,
+                    output_attachment_size,
+                    output_attachment,
+                    resolve_target,
+                    depth_texture_view,
+                    stats,
+                );
+            }
+        }
+
+        stats.encode_duration = encode_start.elapsed();
     }
 
     /// Encode the necessary commands to render the contents of the given **Draw**ing to the given
@@ -306,7 +1033,7 @@ impl Renderer {
     ) where
         S: BaseFloat,
     {
-        let size = frame.texture().size();
+        let size = frame.texture_size();
         let attachment = frame.texture_view();
         let resolve_target = None;
         let mut command_encoder = frame.command_encoder();
@@ -322,6 +1049,284 @@ impl Renderer {
     }
 }
 
+impl Pipelines {
+    fn new(kind: PipelineKind) -> Self {
+        Pipelines {
+            kind,
+            pipelines: Default::default(),
+        }
+    }
+
+    // Create the pipeline for the given state if it does not yet exist.
+    fn prepare(
+        &mut self,
+        desc: &PipelineDesc,
+        vs_mod: &wgpu::ShaderModule,
+        fs_mod: &wgpu::ShaderModule,
+        state: PipelineState,
+    ) {
+        if self.pipelines.contains_key(&state) {
+            return;
+        }
+        let vertex_attrs = vertex_attrs();
+        let instance_attrs = instance_attrs();
+        let mesh_buffers = [vertex_buffer_descriptor(&vertex_attrs)];
+        let instanced_buffers = [
+            vertex_buffer_descriptor(&vertex_attrs),
+            instance_buffer_descriptor(&instance_attrs),
+        ];
+        let vertex_buffers = match self.kind {
+            PipelineKind::Instanced => &instanced_buffers[..],
+            PipelineKind::Mesh | PipelineKind::OitAccum | PipelineKind::Textured(_) => {
+                &mesh_buffers[..]
+            }
+        };
+        let mut color_states = match self.kind {
+            PipelineKind::OitAccum => vec![oit::accum_color_state(), oit::revealage_color_state()],
+            PipelineKind::Textured(blend) => {
+                vec![textured_color_state_descriptor(desc.dst_format, blend)]
+            }
+            PipelineKind::Mesh | PipelineKind::Instanced => {
+                vec![color_state_descriptor(desc.dst_format)]
+            }
+        };
+        if state.stencil == StencilMode::Write {
+            for color_state in &mut color_states {
+                color_state.write_mask = wgpu::ColorWrite::empty();
+            }
+        }
+        let depth_stencil_state = depth_stencil_state_descriptor(desc.depth_format, state);
+        let pipeline = render_pipeline(
+            desc.device,
+            desc.layout,
+            vs_mod,
+            fs_mod,
+            vertex_buffers,
+            &color_states,
+            Some(depth_stencil_state),
+            desc.msaa_samples,
+        );
+        self.pipelines.insert(state, pipeline);
+    }
+
+    // The pipeline for the given state.
+    //
+    // **Panics** if the pipeline has not yet been created via `prepare`.
+    fn get(&self, state: PipelineState) -> &wgpu::RenderPipeline {
+        &self.pipelines[&state]
+    }
+}
+
+impl DepthMode {
+    // The depth mode used for opaque geometry under the given transparency mode.
+    fn from_transparency(transparency: Transparency) -> Self {
+        match transparency {
+            Transparency::Painter => DepthMode::Disabled,
+            Transparency::DepthTest | Transparency::DepthSorted | Transparency::WeightedBlended => {
+                DepthMode::Test
+            }
+        }
+    }
+}
+
+impl RenderCommand {
+    fn stencil_mode(&self) -> StencilMode {
+        match self.mask {
+            None => StencilMode::Ignore,
+            Some(Mask::Write(_)) => StencilMode::Write,
+            Some(Mask::Test {
+                inverted: false, ..
+            }) => StencilMode::Equal,
+            Some(Mask::Test { inverted: true, .. }) => StencilMode::NotEqual,
+        }
+    }
+
+    fn stencil_reference(&self) -> u32 {
+        match self.mask {
+            None => 0,
+            Some(Mask::Write(id)) | Some(Mask::Test { id, .. }) => id as u32,
+        }
+    }
+
+    // The pipeline state for the opaque triangles of the command.
+    fn opaque_pipeline_state(&self, transparency: Transparency) -> PipelineState {
+        let stencil = self.stencil_mode();
+        let depth = match stencil {
+            StencilMode::Write => DepthMode::Disabled,
+            _ => DepthMode::from_transparency(transparency),
+        };
+        PipelineState { depth, stencil }
+    }
+
+    // The pipeline state for the semi-transparent triangles of the command.
+    fn transparent_pipeline_state(&self) -> PipelineState {
+        let depth = DepthMode::TestReadOnly;
+        let stencil = self.stencil_mode();
+        PipelineState { depth, stencil }
+    }
+
+    fn has_transparent_triangles(&self) -> bool {
+        self.transparent_start < self.indices.end
+    }
+
+    // Whether or not the scissor rectangle covers any pixels.
+    fn is_visible(&self) -> bool {
+        let [_, _, w, h] = self.scissor;
+        w > 0 && h > 0
+    }
+
+    fn set_scissor_and_stencil_reference(&self, render_pass: &mut wgpu::RenderPass) {
+        let [x, y, w, h] = self.scissor;
+        render_pass.set_scissor_rect(x, y, w, h);
+        render_pass.set_stencil_reference(self.stencil_reference());
+    }
+}
+
+impl<'a> InstancedDraw<'a> {
+    // Encode an instanced draw call for each of the given batches.
+    fn encode(
+        &self,
+        render_pass: &mut wgpu::RenderPass,
+        batches: &[InstanceBatch],
+        stats: &mut RenderStats,
+    ) {
+        let (pipeline, vbuf, ibuf) = match (self.pipeline, self.vertex_buffer, self.index_buffer) {
+            (Some(pipeline), Some(vbuf), Some(ibuf)) if !batches.is_empty() => {
+                (pipeline, vbuf, ibuf)
+            }
+            _ => return,
+        };
+        let [w, h] = self.size;
+        render_pass.set_pipeline(pipeline);
+        render_pass.set_bind_group(0, self.bind_group, &[]);
+        render_pass.set_index_buffer(ibuf, 0);
+        render_pass.set_scissor_rect(0, 0, w, h);
+        for batch in batches {
+            let instbuf = match batch.buffer {
+                Some(ix) => self.instance_buffer_sources[ix].inner(),
+                None => match self.instance_buffer {
+                    Some(instbuf) => instbuf,
+                    None => continue,
+                },
+            };
+            render_pass.set_vertex_buffers(0, &[(vbuf, 0), (instbuf, 0)]);
+            let index_range = batch.index_range.clone();
+            let instance_range = batch.instance_range.clone();
+            render_pass.draw_indexed(index_range, batch.base_vertex, instance_range);
+            stats.draw_calls += 1;
+        }
+    }
+}
+
+impl GrowableBuffer {
+    // The capacity in bytes of a buffer upon its first allocation.
+    const MIN_CAPACITY: wgpu::BufferAddress = 1024;
+
+    fn new(usage: wgpu::BufferUsage) -> Self {
+        GrowableBuffer {
+            buffer: None,
+            capacity: 0,
+            usage,
+        }
+    }
+
+    // The inner buffer, if any data has been uploaded yet.
+    fn get(&self) -> Option<&wgpu::Buffer> {
+        self.buffer.as_ref()
+    }
+
+    // Ensure the buffer can fit the given number of bytes, reallocating the buffer with double
+    // the capacity until the data fits if necessary.
+    fn reserve(
+        &mut self,
+        device: &wgpu::Device,
+        size: wgpu::BufferAddress,
+        stats: &mut RenderStats,
+    ) {
+        if size == 0 || (self.buffer.is_some() && size <= self.capacity) {
+            return;
+        }
+        let mut capacity = std::cmp::max(self.capacity, Self::MIN_CAPACITY);
+        while capacity < size {
+            capacity *= 2;
+        }
+        let desc = wgpu::BufferDescriptor {
+            size: capacity,
+            usage: self.usage | wgpu::BufferUsage::COPY_DST,
+        };
+        self.buffer = Some(device.create_buffer(&desc));
+        self.capacity = capacity;
+        stats.buffer_reallocations += 1;
+    }
+}
+
+// Encode a copy of each slice of bytes into the start of its associated buffer, growing the
+// buffers where necessary.
+//
+// The `fixed` buffers are never grown, so each must be large enough to hold its data.
+//
+// All data is written into a single staging buffer, so that only one staging allocation is made
+// per render pass regardless of the number of buffers.
+fn upload_buffers(
+    device: &wgpu::Device,
+    encoder: &mut wgpu::CommandEncoder,
+    uploads: &mut [(&mut GrowableBuffer, &[u8])],
+    fixed: &[(&wgpu::Buffer, &[u8])],
+    stats: &mut RenderStats,
+) {
+    // Buffer copy offsets must be aligned to 4 bytes.
+    const ALIGN: usize = 4;
+    let datas = uploads
+        .iter()
+        .map(|&(_, data)| data)
+        .chain(fixed.iter().map(|&(_, data)| data));
+    let mut offsets = Vec::with_capacity(uploads.len() + fixed.len());
+    let mut total = 0;
+    for data in datas.clone() {
+        offsets.push(total);
+        total += (data.len() + ALIGN - 1) / ALIGN * ALIGN;
+    }
+    if total == 0 {
+        return;
+    }
+
+    let mut staging = device.create_buffer_mapped::<u8>(total, wgpu::BufferUsage::COPY_SRC);
+    for (data, &offset) in datas.zip(&offsets) {
+        staging.data[offset..offset + data.len()].copy_from_slice(data);
+    }
+    let staging = staging.finish();
+    stats.staging_allocations += 1;
+
+    let (upload_offsets, fixed_offsets) = offsets.split_at(uploads.len());
+    for (&mut (ref mut buffer, data), &offset) in uploads.iter_mut().zip(upload_offsets) {
+        let size = data.len() as wgpu::BufferAddress;
+        if size == 0 {
+            continue;
+        }
+        buffer.reserve(device, size, stats);
+        let dst = buffer.get().expect("no buffer allocated");
+        encoder.copy_buffer_to_buffer(&staging, offset as wgpu::BufferAddress, dst, 0, size);
+        stats.bytes_uploaded += size;
+    }
+    for (&(dst, data), &offset) in fixed.iter().zip(fixed_offsets) {
+        let size = data.len() as wgpu::BufferAddress;
+        if size == 0 {
+            continue;
+        }
+        encoder.copy_buffer_to_buffer(&staging, offset as wgpu::BufferAddress, dst, 0, size);
+        stats.bytes_uploaded += size;
+    }
+}
+
+// View a slice of plain data as its raw bytes.
+fn slice_bytes<T>(data: &[T]) -> &[u8]
+where
+    T: 'static + Copy,
+{
+    let len = data.len() * std::mem::size_of::<T>();
+    unsafe { std::slice::from_raw_parts(data.as_ptr() as *const u8, len) }
+}
+
 fn create_depth_texture(
     device: &wgpu::Device,
     size: [u32; 2],
@@ -348,6 +1353,72 @@ fn bind_group(device: &wgpu::Device, layout: &wgpu::BindGroupLayout) -> wgpu::Bi
     device.create_bind_group(&desc)
 }
 
+fn instanced_bind_group_layout(device: &wgpu::Device) -> wgpu::BindGroupLayout {
+    let uniforms_binding = wgpu::BindGroupLayoutBinding {
+        binding: 0,
+        visibility: wgpu::ShaderStage::VERTEX,
+        ty: wgpu::BindingType::UniformBuffer { dynamic: false },
+    };
+    let bindings = &[uniforms_binding];
+    let desc = wgpu::BindGroupLayoutDescriptor { bindings };
+    device.create_bind_group_layout(&desc)
+}
+
+fn instanced_bind_group(
+    device: &wgpu::Device,
+    layout: &wgpu::BindGroupLayout,
+    uniforms: &wgpu::compute::Buffer<[[f32; 4]; 4]>,
+) -> wgpu::BindGroup {
+    let bindings = &[wgpu::Binding {
+        binding: 0,
+        resource: wgpu::BindingResource::Buffer {
+            buffer: uniforms.inner(),
+            range: 0..uniforms.size_bytes(),
+        },
+    }];
+    let desc = wgpu::BindGroupDescriptor { layout, bindings };
+    device.create_bind_group(&desc)
+}
+
+fn textured_bind_group_layout(device: &wgpu::Device) -> wgpu::BindGroupLayout {
+    let texture_binding = wgpu::BindGroupLayoutBinding {
+        binding: 0,
+        visibility: wgpu::ShaderStage::FRAGMENT,
+        ty: wgpu::BindingType::SampledTexture {
+            multisampled: false,
+            dimension: wgpu::TextureViewDimension::D2,
+        },
+    };
+    let sampler_binding = wgpu::BindGroupLayoutBinding {
+        binding: 1,
+        visibility: wgpu::ShaderStage::FRAGMENT,
+        ty: wgpu::BindingType::Sampler,
+    };
+    let bindings = &[texture_binding, sampler_binding];
+    let desc = wgpu::BindGroupLayoutDescriptor { bindings };
+    device.create_bind_group_layout(&desc)
+}
+
+fn textured_bind_group(
+    device: &wgpu::Device,
+    layout: &wgpu::BindGroupLayout,
+    texture_view: &wgpu::TextureView,
+    sampler: &wgpu::Sampler,
+) -> wgpu::BindGroup {
+    let bindings = &[
+        wgpu::Binding {
+            binding: 0,
+            resource: wgpu::BindingResource::TextureView(texture_view),
+        },
+        wgpu::Binding {
+            binding: 1,
+            resource: wgpu::BindingResource::Sampler(sampler),
+        },
+    ];
+    let desc = wgpu::BindGroupDescriptor { layout, bindings };
+    device.create_bind_group(&desc)
+}
+
 fn pipeline_layout(
     device: &wgpu::Device,
     bind_group_layout: &wgpu::BindGroupLayout,
@@ -386,17 +1457,138 @@ fn vertex_attrs() -> [wgpu::VertexAttributeDescriptor; 3] {
     ]
 }
 
+fn instance_attrs() -> [wgpu::VertexAttributeDescriptor; 5] {
+    let column_size = std::mem::size_of::<[f32; 4]>() as wgpu::BufferAddress;
+    let transform_offset = 0;
+    let rgba_offset = transform_offset + column_size * 4;
+    let column = |i: u32| wgpu::VertexAttributeDescriptor {
+        format: wgpu::VertexFormat::Float4,
+        offset: transform_offset + column_size * i as wgpu::BufferAddress,
+        shader_location: 3 + i,
+    };
+    [
+        // transform columns
+        column(0),
+        column(1),
+        column(2),
+        column(3),
+        // rgba
+        wgpu::VertexAttributeDescriptor {
+            format: wgpu::VertexFormat::Float4,
+            offset: rgba_offset,
+            shader_location: 7,
+        },
+    ]
+}
+
+fn vertex_buffer_descriptor(
+    attributes: &[wgpu::VertexAttributeDescriptor],
+) -> wgpu::VertexBufferDescriptor {
+    wgpu::VertexBufferDescriptor {
+        stride: std::mem::size_of::<Vertex>() as wgpu::BufferAddress,
+        step_mode: wgpu::InputStepMode::Vertex,
+        attributes,
+    }
+}
+
+fn instance_buffer_descriptor(
+    attributes: &[wgpu::VertexAttributeDescriptor],
+) -> wgpu::VertexBufferDescriptor {
+    wgpu::VertexBufferDescriptor {
+        stride: std::mem::size_of::<Instance>() as wgpu::BufferAddress,
+        step_mode: wgpu::InputStepMode::Instance,
+        attributes,
+    }
+}
+
 fn depth_stencil_state_descriptor(
     format: wgpu::TextureFormat,
+    state: PipelineState,
 ) -> wgpu::DepthStencilStateDescriptor {
+    let (depth_write_enabled, depth_compare) = match state.depth {
+        DepthMode::Test => (true, wgpu::CompareFunction::LessEqual),
+        DepthMode::TestReadOnly => (false, wgpu::CompareFunction::LessEqual),
+        DepthMode::Disabled => (false, wgpu::CompareFunction::Always),
+    };
+    let (stencil_compare, stencil_pass_op) = match state.stencil {
+        StencilMode::Ignore => (wgpu::CompareFunction::Always, wgpu::StencilOperation::Keep),
+        StencilMode::Write => (
+            wgpu::CompareFunction::Always,
+            wgpu::StencilOperation::Replace,
+        ),
+        StencilMode::Equal => (wgpu::CompareFunction::Equal, wgpu::StencilOperation::Keep),
+        StencilMode::NotEqual => (
+            wgpu::CompareFunction::NotEqual,
+            wgpu::StencilOperation::Keep,
+        ),
+    };
+    let (stencil_read_mask, stencil_write_mask) = match state.stencil {
+        StencilMode::Ignore => (0, 0),
+        StencilMode::Write => (0, !0),
+        StencilMode::Equal | StencilMode::NotEqual => (!0, 0),
+    };
+    let stencil_face = wgpu::StencilStateFaceDescriptor {
+        compare: stencil_compare,
+        fail_op: wgpu::StencilOperation::Keep,
+        depth_fail_op: wgpu::StencilOperation::Keep,
+        pass_op: stencil_pass_op,
+    };
     wgpu::DepthStencilStateDescriptor {
         format: format,
-        depth_write_enabled: true,
-        depth_compare: wgpu::CompareFunction::LessEqual,
-        stencil_front: wgpu::StencilStateFaceDescriptor::IGNORE,
-        stencil_back: wgpu::StencilStateFaceDescriptor::IGNORE,
-        stencil_read_mask: 0,
-        stencil_write_mask: 0,
+        depth_write_enabled,
+        depth_compare,
+        stencil_front: stencil_face.clone(),
+        stencil_back: stencil_face,
+        stencil_read_mask,
+        stencil_write_mask,
+    }
+}
+
+fn color_state_descriptor(format: wgpu::TextureFormat) -> wgpu::ColorStateDescriptor {
+    wgpu::ColorStateDescriptor {
+        format,
+        color_blend: wgpu::BlendDescriptor {
+            src_factor: wgpu::BlendFactor::SrcAlpha,
+            dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
+            operation: wgpu::BlendOperation::Add,
+        },
+        alpha_blend: wgpu::BlendDescriptor {
+            src_factor: wgpu::BlendFactor::One,
+            dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
+            operation: wgpu::BlendOperation::Add,
+        },
+        write_mask: wgpu::ColorWrite::ALL,
+    }
+}
+
+// Layer and atlas textures contain premultiplied alpha, so the source color is never multiplied by
+// the source alpha.
+fn textured_color_state_descriptor(
+    format: wgpu::TextureFormat,
+    blend: BlendMode,
+) -> wgpu::ColorStateDescriptor {
+    let (src_factor, dst_factor) = match blend {
+        BlendMode::Normal => (wgpu::BlendFactor::One, wgpu::BlendFactor::OneMinusSrcAlpha),
+        BlendMode::Add => (wgpu::BlendFactor::One, wgpu::BlendFactor::One),
+        BlendMode::Multiply => (
+            wgpu::BlendFactor::DstColor,
+            wgpu::BlendFactor::OneMinusSrcAlpha,
+        ),
+        BlendMode::Screen => (wgpu::BlendFactor::One, wgpu::BlendFactor::OneMinusSrcColor),
+    };
+    wgpu::ColorStateDescriptor {
+        format,
+        color_blend: wgpu::BlendDescriptor {
+            src_factor,
+            dst_factor,
+            operation: wgpu::BlendOperation::Add,
+        },
+        alpha_blend: wgpu::BlendDescriptor {
+            src_factor: wgpu::BlendFactor::One,
+            dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
+            operation: wgpu::BlendOperation::Add,
+        },
+        write_mask: wgpu::ColorWrite::ALL,
     }
 }
 
@@ -405,8 +1597,9 @@ fn render_pipeline(
     layout: &wgpu::PipelineLayout,
     vs_mod: &wgpu::ShaderModule,
     fs_mod: &wgpu::ShaderModule,
-    dst_format: wgpu::TextureFormat,
-    depth_format: wgpu::TextureFormat,
+    vertex_buffers: &[wgpu::VertexBufferDescriptor],
+    color_states: &[wgpu::ColorStateDescriptor],
+    depth_stencil_state: Option<wgpu::DepthStencilStateDescriptor>,
     msaa_samples: u32,
 ) -> wgpu::RenderPipeline {
     let vs_desc = wgpu::ProgrammableStageDescriptor {
@@ -424,40 +1617,91 @@ fn render_pipeline(
         depth_bias_slope_scale: 0.0,
         depth_bias_clamp: 0.0,
     };
-    let color_state_desc = wgpu::ColorStateDescriptor {
-        format: dst_format,
-        color_blend: wgpu::BlendDescriptor {
-            src_factor: wgpu::BlendFactor::SrcAlpha,
-            dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
-            operation: wgpu::BlendOperation::Add,
-        },
-        alpha_blend: wgpu::BlendDescriptor {
-            src_factor: wgpu::BlendFactor::One,
-            dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
-            operation: wgpu::BlendOperation::Add,
-        },
-        write_mask: wgpu::ColorWrite::ALL,
-    };
-    let vertex_attrs = vertex_attrs();
-    let vertex_buffer_desc = wgpu::VertexBufferDescriptor {
-        stride: std::mem::size_of::<Vertex>() as wgpu::BufferAddress,
-        step_mode: wgpu::InputStepMode::Vertex,
-        attributes: &vertex_attrs[..],
-    };
-    let depth_stencil_state_desc = depth_stencil_state_descriptor(depth_format);
     let desc = wgpu::RenderPipelineDescriptor {
         layout,
         vertex_stage: vs_desc,
         fragment_stage: Some(fs_desc),
         rasterization_state: Some(raster_desc),
         primitive_topology: wgpu::PrimitiveTopology::TriangleList,
-        color_states: &[color_state_desc],
-        depth_stencil_state: Some(depth_stencil_state_desc),
+        color_states,
+        depth_stencil_state,
         index_format: wgpu::IndexFormat::Uint32,
-        vertex_buffers: &[vertex_buffer_desc],
+        vertex_buffers,
         sample_count: msaa_samples,
         sample_mask: !0,
         alpha_to_coverage_enabled: false,
     };
     device.create_render_pipeline(&desc)
 }
+
+// The viewport in points covering a framebuffer of the given size in pixels, centred on the origin.
+fn default_viewport([w, h]: [f32; 2], scale_factor: f32) -> geom::Rect {
+    geom::Rect::from_w_h(w / scale_factor, h / scale_factor)
+}
+
+// Convert the given scissor rectangle in **Draw** coordinates to a rectangle in pixels within the
+// output attachment, described as `[x, y, w, h]`.
+fn scissor_rect<S>(rect: &geom::Rect<S>, [w, h]: [u32; 2], viewport: &geom::Rect) -> [u32; 4]
+where
+    S: BaseFloat,
+{
+    let to_f32 = |s: S| -> f32 { NumCast::from(s).unwrap() };
+    let (w_f, h_f) = (w as f32, h as f32);
+    let clamp_x = |x: f32| x.max(0.0).min(w_f);
+    let clamp_y = |y: f32| y.max(0.0).min(h_f);
+    // Map from the y-up viewport coordinates to top-left, y-down pixel coordinates.
+    let (sx, sy) = (w_f / viewport.w(), h_f / viewport.h());
+    let left = clamp_x(((to_f32(rect.left()) - viewport.left()) * sx).floor());
+    let right = clamp_x(((to_f32(rect.right()) - viewport.left()) * sx).ceil());
+    let top = clamp_y(((viewport.top() - to_f32(rect.top())) * sy).floor());
+    let bottom = clamp_y(((viewport.top() - to_f32(rect.bottom())) * sy).ceil());
+    [
+        left as u32,
+        top as u32,
+        (right - left) as u32,
+        (bottom - top) as u32,
+    ]
+}
+
+// Reorder the triangles described by `indices` so that all opaque triangles come first in the
+// order in which they were drawn, followed by all semi-transparent triangles.
+//
+// If `sort` is `true`, the semi-transparent triangles are sorted from back to front by their
+// average depth. The sort is stable so that triangles at the same depth (e.g. all 2D geometry)
+// retain the order in which they were drawn.
+//
+// Returns the number of opaque indices.
+fn partition_transparent_triangles(
+    vertices: &[Vertex],
+    indices: &mut [u32],
+    transparent_triangles: &mut Vec<(f32, [u32; 3])>,
+    sort: bool,
+) -> usize {
+    transparent_triangles.clear();
+    let mut opaque_len = 0;
+    for i in 0..indices.len() / 3 {
+        let tri = [indices[i * 3], indices[i * 3 + 1], indices[i * 3 + 2]];
+        let [a, b, c] = [
+            vertices[tri[0] as usize],
+            vertices[tri[1] as usize],
+            vertices[tri[2] as usize],
+        ];
+        if a.color[3] >= 1.0 && b.color[3] >= 1.0 && c.color[3] >= 1.0 {
+            indices[opaque_len..opaque_len + 3].copy_from_slice(&tri);
+            opaque_len += 3;
+        } else {
+            let depth = (a.position[2] + b.position[2] + c.position[2]) / 3.0;
+            transparent_triangles.push((depth, tri));
+        }
+    }
+    if sort {
+        // Greater depth values are further away, so these are drawn first.
+        transparent_triangles
+            .sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(std::cmp::Ordering::Equal));
+    }
+    for (i, &(_, ref tri)) in transparent_triangles.iter().enumerate() {
+        let start = opaque_len + i * 3;
+        indices[start..start + 3].copy_from_slice(tri);
+    }
+    opaque_len
+}
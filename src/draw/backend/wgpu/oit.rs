@@ -0,0 +1,373 @@
+//! Weighted blended order-independent transparency.
+//!
+//! Semi-transparent triangles are first rendered to an accumulation target and a revealage target
+//! in a single pass, each fragment weighted by its depth and alpha. The two targets are then
+//! composited over the output attachment in a second pass. See McGuire and Bavoil, "Weighted
+//! Blended Order-Independent Transparency", JCGT 2013.
+
+use super::{PipelineDesc, PipelineKind, Pipelines, RenderCommand, RenderStats};
+use crate::wgpu;
+
+/// The format of the target into which weighted, premultiplied colors are accumulated.
+const ACCUM_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
+/// The format of the target into which the product of `1.0 - alpha` is accumulated.
+const REVEALAGE_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::R8Unorm;
+
+// The pipelines and targets required for weighted blended order-independent transparency.
+//
+// These are only created by the `Renderer` the first time `Transparency::WeightedBlended` is used.
+#[derive(Debug)]
+pub(crate) struct Oit {
+    fs_mod: wgpu::ShaderModule,
+    _composite_vs_mod: wgpu::ShaderModule,
+    _composite_fs_mod: wgpu::ShaderModule,
+    accum_render_pipelines: Pipelines,
+    composite_render_pipeline: wgpu::RenderPipeline,
+    composite_bind_group_layout: wgpu::BindGroupLayout,
+    sampler: wgpu::Sampler,
+    targets: Targets,
+}
+
+// The accumulation targets along with the bind group used to sample them during composition.
+#[derive(Debug)]
+struct Targets {
+    size: [u32; 2],
+    accum: Target,
+    revealage: Target,
+    composite_bind_group: wgpu::BindGroup,
+}
+
+// A render target along with its single-sampled resolve target if the target is multisampled.
+#[derive(Debug)]
+struct Target {
+    _texture: wgpu::Texture,
+    view: wgpu::TextureView,
+    resolve: Option<(wgpu::Texture, wgpu::TextureView)>,
+}
+
+impl Oit {
+    // Create the composite pipeline and targets for the given output attachment.
+    //
+    // The accumulation pipelines are created on demand for each depth and stencil state in use.
+    // These use the same vertex shader and bind group layout as the main render pipelines.
+    pub(super) fn new(
+        device: &wgpu::Device,
+        output_attachment_size: [u32; 2],
+        output_attachment_color_format: wgpu::TextureFormat,
+        msaa_samples: u32,
+    ) -> Self {
+        // Load shader modules.
+        let fs = include_bytes!("shaders/frag_oit.spv");
+        let fs_spirv = wgpu::read_spirv(std::io::Cursor::new(&fs[..]))
+            .expect("failed to read hard-coded SPIRV");
+        let fs_mod = device.create_shader_module(&fs_spirv);
+        let composite_vs = include_bytes!("shaders/vert_oit_composite.spv");
+        let composite_vs_spirv = wgpu::read_spirv(std::io::Cursor::new(&composite_vs[..]))
+            .expect("failed to read hard-coded SPIRV");
+        let composite_vs_mod = device.create_shader_module(&composite_vs_spirv);
+        let composite_fs = include_bytes!("shaders/frag_oit_composite.spv");
+        let composite_fs_spirv = wgpu::read_spirv(std::io::Cursor::new(&composite_fs[..]))
+            .expect("failed to read hard-coded SPIRV");
+        let composite_fs_mod = device.create_shader_module(&composite_fs_spirv);
+
+        let accum_render_pipelines = Pipelines::new(PipelineKind::OitAccum);
+
+        // Create the composite pipeline.
+        let composite_bind_group_layout = composite_bind_group_layout(device);
+        let composite_pipeline_layout =
+            super::pipeline_layout(device, &composite_bind_group_layout);
+        let composite_render_pipeline = super::render_pipeline(
+            device,
+            &composite_pipeline_layout,
+            &composite_vs_mod,
+            &composite_fs_mod,
+            &[],
+            &[super::color_state_descriptor(
+                output_attachment_color_format,
+            )],
+            None,
+            msaa_samples,
+        );
+
+        let sampler = wgpu::SamplerBuilder::new().build(device);
+        let targets = Targets::new(
+            device,
+            &composite_bind_group_layout,
+            &sampler,
+            output_attachment_size,
+            msaa_samples,
+        );
+
+        Oit {
+            fs_mod,
+            _composite_vs_mod: composite_vs_mod,
+            _composite_fs_mod: composite_fs_mod,
+            accum_render_pipelines,
+            composite_render_pipeline,
+            composite_bind_group_layout,
+            sampler,
+            targets,
+        }
+    }
+
+    // Encode the accumulation and composite passes.
+    //
+    // The semi-transparent triangles of each command are accumulated within the command's
+    // scissor and mask. The depth attachment must already contain the depth of all opaque
+    // geometry along with the stencil values of all masks.
+    pub(super) fn encode_render_passes(
+        &mut self,
+        desc: &PipelineDesc,
+        encoder: &mut wgpu::CommandEncoder,
+        vs_mod: &wgpu::ShaderModule,
+        bind_group: &wgpu::BindGroup,
+        vertex_buffer: &wgpu::Buffer,
+        index_buffer: &wgpu::Buffer,
+        commands: &[RenderCommand],
+        output_attachment_size: [u32; 2],
+        output_attachment: &wgpu::TextureView,
+        resolve_target: Option<&wgpu::TextureView>,
+        depth_texture_view: &wgpu::TextureView,
+        stats: &mut RenderStats,
+    ) {
+        // Recreate the targets if the output attachment size has changed.
+        if self.targets.size != output_attachment_size {
+            self.targets = Targets::new(
+                desc.device,
+                &self.composite_bind_group_layout,
+                &self.sampler,
+                output_attachment_size,
+                desc.msaa_samples,
+            );
+        }
+
+        // Ensure the accumulation pipelines required by each command exist.
+        for cmd in commands
+            .iter()
+            .filter(|cmd| cmd.has_transparent_triangles())
+        {
+            let state = cmd.transparent_pipeline_state();
+            self.accum_render_pipelines
+                .prepare(desc, vs_mod, &self.fs_mod, state);
+        }
+
+        // Accumulate the semi-transparent triangles.
+        let accum_attachment_desc = wgpu::RenderPassColorAttachmentDescriptor {
+            attachment: &self.targets.accum.view,
+            resolve_target: self.targets.accum.resolve.as_ref().map(|(_, view)| view),
+            load_op: wgpu::LoadOp::Clear,
+            store_op: wgpu::StoreOp::Store,
+            clear_color: wgpu::Color::TRANSPARENT,
+        };
+        let revealage_attachment_desc = wgpu::RenderPassColorAttachmentDescriptor {
+            attachment: &self.targets.revealage.view,
+            resolve_target: self
+                .targets
+                .revealage
+                .resolve
+                .as_ref()
+                .map(|(_, view)| view),
+            load_op: wgpu::LoadOp::Clear,
+            store_op: wgpu::StoreOp::Store,
+            clear_color: wgpu::Color {
+                r: 1.0,
+                g: 1.0,
+                b: 1.0,
+                a: 1.0,
+            },
+        };
+        let depth_stencil_attachment_desc = wgpu::RenderPassDepthStencilAttachmentDescriptor {
+            attachment: depth_texture_view,
+            depth_load_op: wgpu::LoadOp::Load,
+            depth_store_op: wgpu::StoreOp::Store,
+            clear_depth: 1.0,
+            stencil_load_op: wgpu::LoadOp::Load,
+            stencil_store_op: wgpu::StoreOp::Store,
+            clear_stencil: 0,
+        };
+        let render_pass_desc = wgpu::RenderPassDescriptor {
+            color_attachments: &[accum_attachment_desc, revealage_attachment_desc],
+            depth_stencil_attachment: Some(depth_stencil_attachment_desc),
+        };
+        {
+            let mut render_pass = encoder.begin_render_pass(&render_pass_desc);
+            for cmd in commands.iter() {
+                if !cmd.has_transparent_triangles() || !cmd.is_visible() {
+                    continue;
+                }
+                let state = cmd.transparent_pipeline_state();
+                render_pass.set_pipeline(self.accum_render_pipelines.get(state));
+                render_pass.set_bind_group(0, bind_group, &[]);
+                render_pass.set_index_buffer(index_buffer, 0);
+                render_pass.set_vertex_buffers(0, &[(vertex_buffer, 0)]);
+                cmd.set_scissor_and_stencil_reference(&mut render_pass);
+                let index_range = cmd.transparent_start..cmd.indices.end;
+                render_pass.draw_indexed(index_range, 0, 0..1);
+                stats.draw_calls += 1;
+            }
+        }
+
+        // Composite the accumulated fragments over the output attachment.
+        let color_attachment_desc = wgpu::RenderPassColorAttachmentDescriptor {
+            attachment: output_attachment,
+            resolve_target,
+            load_op: wgpu::LoadOp::Load,
+            store_op: wgpu::StoreOp::Store,
+            clear_color: wgpu::Color::TRANSPARENT,
+        };
+        let render_pass_desc = wgpu::RenderPassDescriptor {
+            color_attachments: &[color_attachment_desc],
+            depth_stencil_attachment: None,
+        };
+        let mut render_pass = encoder.begin_render_pass(&render_pass_desc);
+        render_pass.set_pipeline(&self.composite_render_pipeline);
+        render_pass.set_bind_group(0, &self.targets.composite_bind_group, &[]);
+        render_pass.draw(0..3, 0..1);
+        stats.draw_calls += 1;
+    }
+}
+
+impl Targets {
+    fn new(
+        device: &wgpu::Device,
+        composite_bind_group_layout: &wgpu::BindGroupLayout,
+        sampler: &wgpu::Sampler,
+        size: [u32; 2],
+        msaa_samples: u32,
+    ) -> Self {
+        let accum = Target::new(device, size, ACCUM_FORMAT, msaa_samples);
+        let revealage = Target::new(device, size, REVEALAGE_FORMAT, msaa_samples);
+        let composite_bind_group = composite_bind_group(
+            device,
+            composite_bind_group_layout,
+            accum.sampled_view(),
+            revealage.sampled_view(),
+            sampler,
+        );
+        Targets {
+            size,
+            accum,
+            revealage,
+            composite_bind_group,
+        }
+    }
+}
+
+impl Target {
+    fn new(
+        device: &wgpu::Device,
+        size: [u32; 2],
+        format: wgpu::TextureFormat,
+        msaa_samples: u32,
+    ) -> Self {
+        let builder = || wgpu::TextureBuilder::new().size(size).format(format);
+        let sampled_usage = wgpu::TextureUsage::OUTPUT_ATTACHMENT | wgpu::TextureUsage::SAMPLED;
+        if msaa_samples > 1 {
+            let texture = builder()
+                .usage(wgpu::TextureUsage::OUTPUT_ATTACHMENT)
+                .sample_count(msaa_samples)
+                .build(device);
+            let view = texture.create_default_view();
+            let resolve_texture = builder().usage(sampled_usage).build(device);
+            let resolve_view = resolve_texture.create_default_view();
+            Target {
+                _texture: texture,
+                view,
+                resolve: Some((resolve_texture, resolve_view)),
+            }
+        } else {
+            let texture = builder().usage(sampled_usage).build(device);
+            let view = texture.create_default_view();
+            Target {
+                _texture: texture,
+                view,
+                resolve: None,
+            }
+        }
+    }
+
+    // The single-sampled view that may be sampled during composition.
+    fn sampled_view(&self) -> &wgpu::TextureView {
+        match self.resolve {
+            Some((_, ref view)) => view,
+            None => &self.view,
+        }
+    }
+}
+
+// Weighted, premultiplied colors and alphas are summed.
+pub(crate) fn accum_color_state() -> wgpu::ColorStateDescriptor {
+    let blend = wgpu::BlendDescriptor {
+        src_factor: wgpu::BlendFactor::One,
+        dst_factor: wgpu::BlendFactor::One,
+        operation: wgpu::BlendOperation::Add,
+    };
+    wgpu::ColorStateDescriptor {
+        format: ACCUM_FORMAT,
+        color_blend: blend.clone(),
+        alpha_blend: blend,
+        write_mask: wgpu::ColorWrite::ALL,
+    }
+}
+
+// The product of `1.0 - alpha` over all fragments.
+pub(crate) fn revealage_color_state() -> wgpu::ColorStateDescriptor {
+    wgpu::ColorStateDescriptor {
+        format: REVEALAGE_FORMAT,
+        color_blend: wgpu::BlendDescriptor {
+            src_factor: wgpu::BlendFactor::Zero,
+            dst_factor: wgpu::BlendFactor::OneMinusSrcColor,
+            operation: wgpu::BlendOperation::Add,
+        },
+        alpha_blend: wgpu::BlendDescriptor {
+            src_factor: wgpu::BlendFactor::Zero,
+            dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
+            operation: wgpu::BlendOperation::Add,
+        },
+        write_mask: wgpu::ColorWrite::ALL,
+    }
+}
+
+fn composite_bind_group_layout(device: &wgpu::Device) -> wgpu::BindGroupLayout {
+    let texture_binding = |binding| wgpu::BindGroupLayoutBinding {
+        binding,
+        visibility: wgpu::ShaderStage::FRAGMENT,
+        ty: wgpu::BindingType::SampledTexture {
+            multisampled: false,
+            dimension: wgpu::TextureViewDimension::D2,
+        },
+    };
+    let sampler_binding = wgpu::BindGroupLayoutBinding {
+        binding: 2,
+        visibility: wgpu::ShaderStage::FRAGMENT,
+        ty: wgpu::BindingType::Sampler,
+    };
+    let bindings = &[texture_binding(0), texture_binding(1), sampler_binding];
+    let desc = wgpu::BindGroupLayoutDescriptor { bindings };
+    device.create_bind_group_layout(&desc)
+}
+
+fn composite_bind_group(
+    device: &wgpu::Device,
+    layout: &wgpu::BindGroupLayout,
+    accum: &wgpu::TextureView,
+    revealage: &wgpu::TextureView,
+    sampler: &wgpu::Sampler,
+) -> wgpu::BindGroup {
+    let bindings = &[
+        wgpu::Binding {
+            binding: 0,
+            resource: wgpu::BindingResource::TextureView(accum),
+        },
+        wgpu::Binding {
+            binding: 1,
+            resource: wgpu::BindingResource::TextureView(revealage),
+        },
+        wgpu::Binding {
+            binding: 2,
+            resource: wgpu::BindingResource::Sampler(sampler),
+        },
+    ];
+    let desc = wgpu::BindGroupDescriptor { layout, bindings };
+    device.create_bind_group(&desc)
+}
@@ -0,0 +1,32 @@
+// NOTE: This shader requires being manually compiled to SPIR-V in order to
+// avoid having downstream users require building shaderc and compiling the
+// shader themselves. If you update this shader, be sure to also re-compile it
+// and update `vert_instanced.spv`. You can do so using `glslangValidator` with
+// the following command:
+// `glslangValidator -V -o vert_instanced.spv shader_instanced.vert`
+
+#version 450
+
+layout(location = 0) in vec3 position;
+layout(location = 1) in vec4 color;
+layout(location = 2) in vec2 tex_coords;
+layout(location = 3) in vec4 transform_x;
+layout(location = 4) in vec4 transform_y;
+layout(location = 5) in vec4 transform_z;
+layout(location = 6) in vec4 transform_w;
+layout(location = 7) in vec4 instance_color;
+
+layout(location = 0) out vec4 v_color;
+layout(location = 1) out vec2 v_tex_coords;
+
+// Maps points to normalised device coordinates.
+layout(set = 0, binding = 0) uniform Data {
+    mat4 ndc;
+};
+
+void main() {
+    mat4 transform = mat4(transform_x, transform_y, transform_z, transform_w);
+    gl_Position = ndc * transform * vec4(position, 1.0);
+    v_color = color * instance_color;
+    v_tex_coords = tex_coords;
+}
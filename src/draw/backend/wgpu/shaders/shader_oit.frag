@@ -0,0 +1,26 @@
+// NOTE: This shader requires being manually compiled to SPIR-V in order to
+// avoid having downstream users require building shaderc and compiling the
+// shader themselves. If you update this shader, be sure to also re-compile it
+// and update `frag_oit.spv`. You can do so using `glslangValidator` with the
+// following command: `glslangValidator -V -o frag_oit.spv shader_oit.frag`
+
+#version 450
+
+layout(location = 0) in vec4 v_color;
+layout(location = 1) in vec2 v_tex_coords;
+
+layout(location = 0) out vec4 f_accum;
+layout(location = 1) out vec4 f_revealage;
+
+// Weighted blended order-independent transparency.
+//
+// See McGuire and Bavoil, "Weighted Blended Order-Independent Transparency", JCGT 2013.
+void main() {
+    vec4 color = v_color;
+    float z = gl_FragCoord.z;
+    float a = min(1.0, color.a * 10.0) + 0.01;
+    float b = 1.0 - z * 0.9;
+    float weight = clamp(a * a * a * 1e8 * b * b * b, 1e-2, 3e3);
+    f_accum = vec4(color.rgb * color.a, color.a) * weight;
+    f_revealage = vec4(color.a);
+}
@@ -0,0 +1,21 @@
+// NOTE: This shader requires being manually compiled to SPIR-V in order to
+// avoid having downstream users require building shaderc and compiling the
+// shader themselves. If you update this shader, be sure to also re-compile it
+// and update `frag_oit_composite.spv`. You can do so using `glslangValidator`
+// with the following command:
+// `glslangValidator -V -o frag_oit_composite.spv shader_oit_composite.frag`
+
+#version 450
+
+layout(location = 0) out vec4 f_color;
+
+layout(set = 0, binding = 0) uniform texture2D accum_tex;
+layout(set = 0, binding = 1) uniform texture2D revealage_tex;
+layout(set = 0, binding = 2) uniform sampler tex_sampler;
+
+void main() {
+    ivec2 coords = ivec2(gl_FragCoord.xy);
+    vec4 accum = texelFetch(sampler2D(accum_tex, tex_sampler), coords, 0);
+    float revealage = texelFetch(sampler2D(revealage_tex, tex_sampler), coords, 0).r;
+    f_color = vec4(accum.rgb / max(accum.a, 1e-5), 1.0 - revealage);
+}
@@ -0,0 +1,15 @@
+// NOTE: This shader requires being manually compiled to SPIR-V in order to
+// avoid having downstream users require building shaderc and compiling the
+// shader themselves. If you update this shader, be sure to also re-compile it
+// and update `vert_oit_composite.spv`. You can do so using `glslangValidator`
+// with the following command:
+// `glslangValidator -V -o vert_oit_composite.spv shader_oit_composite.vert`
+
+#version 450
+
+// A single triangle covering the entire output attachment.
+void main() {
+    float x = float((gl_VertexIndex << 1) & 2);
+    float y = float(gl_VertexIndex & 2);
+    gl_Position = vec4(x * 2.0 - 1.0, y * 2.0 - 1.0, 0.0, 1.0);
+}
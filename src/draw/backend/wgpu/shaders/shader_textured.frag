@@ -0,0 +1,20 @@
+// NOTE: This shader requires being manually compiled to SPIR-V in order to
+// avoid having downstream users require building shaderc and compiling the
+// shader themselves. If you update this shader, be sure to also re-compile it
+// and update `frag_textured.spv`. You can do so using `glslangValidator` with the
+// following command: `glslangValidator -V -o frag_textured.spv shader_textured.frag`
+
+#version 450
+
+layout(location = 0) in vec4 v_color;
+layout(location = 1) in vec2 v_tex_coords;
+
+layout(location = 0) out vec4 f_color;
+
+layout(set = 0, binding = 0) uniform texture2D tex;
+layout(set = 0, binding = 1) uniform sampler tex_sampler;
+
+void main() {
+    // Textures contain premultiplied alpha, so the vertex color is expected to be premultiplied.
+    f_color = v_color * texture(sampler2D(tex, tex_sampler), v_tex_coords);
+}
@@ -0,0 +1,75 @@
+//! Items related to retaining tessellated geometry between frames.
+//!
+//! Tessellating complex paths, polygons and text can be expensive. For geometry that rarely
+//! changes (e.g. a static background made up of thousands of paths) it can be more efficient to
+//! tessellate once via `Draw::record` and re-emit the resulting mesh each frame via `Draw::cached`.
+
+use crate::draw::{Draw, Mesh};
+use crate::geom;
+use crate::math::BaseFloat;
+use std::sync::Arc;
+
+/// A handle to geometry that has been tessellated once and may be redrawn over many frames.
+///
+/// Create a **Cached** via `Draw::record` and draw it via `Draw::cached`. The handle is cheap to
+/// clone as the inner mesh is shared.
+///
+/// The geometry is only re-tessellated when explicitly requested. Use `invalidate` to mark the
+/// geometry as stale and `record_if_invalid` to re-record it.
+#[derive(Clone, Debug)]
+pub struct Cached<S = geom::scalar::Default> {
+    mesh: Arc<Mesh<S>>,
+    valid: bool,
+}
+
+impl<S> Cached<S>
+where
+    S: BaseFloat,
+{
+    /// Create a **Cached** from an already tessellated mesh.
+    pub fn from_mesh(mesh: Mesh<S>) -> Self {
+        let mesh = Arc::new(mesh);
+        let valid = true;
+        Cached { mesh, valid }
+    }
+
+    /// The tessellated mesh.
+    ///
+    /// Vertex positions are relative to the origin of the **Draw** used to record them.
+    pub fn mesh(&self) -> &Mesh<S> {
+        &self.mesh
+    }
+
+    /// Whether or not the geometry is still valid.
+    ///
+    /// This is `true` until `invalidate` is called.
+    pub fn is_valid(&self) -> bool {
+        self.valid
+    }
+
+    /// Mark the geometry as stale.
+    ///
+    /// The existing mesh will continue to be drawn until the geometry is re-recorded via
+    /// `record_if_invalid`.
+    pub fn invalidate(&mut self) {
+        self.valid = false;
+    }
+
+    /// Re-record the geometry using the given function if it has been invalidated.
+    ///
+    /// Returns `true` if the geometry was re-recorded.
+    pub fn record_if_invalid<F>(&mut self, draw: &Draw<S>, f: F) -> bool
+    where
+        F: FnOnce(&Draw<S>),
+    {
+        if self.valid {
+            return false;
+        }
+        *self = draw.record(f);
+        true
+    }
+
+    pub(crate) fn shared_mesh(&self) -> Arc<Mesh<S>> {
+        self.mesh.clone()
+    }
+}
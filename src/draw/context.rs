@@ -0,0 +1,117 @@
+//! Items related to the scissor and mask state in which primitives are drawn.
+//!
+//! A scoped **Draw** produced via `Draw::scissor` or `Draw::mask` shares the state of the **Draw**
+//! from which it was produced but has its own **Context**. Every primitive records the context in
+//! which it was drawn so that the renderer may apply it to the primitive's range of indices.
+
+use crate::draw::layer::{BlendMode, Layer};
+use crate::geom;
+use crate::math::BaseFloat;
+use crate::wgpu;
+use std::ops;
+
+/// The state applied to every primitive drawn via a **Draw** instance.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Context<S = geom::scalar::Default> {
+    /// If `Some`, primitives are clipped to this rectangle.
+    pub scissor: Option<geom::Rect<S>>,
+    /// If `Some`, describes how primitives interact with the stencil buffer.
+    pub mask: Option<Mask>,
+    /// If `Some`, the primitive samples the given texture.
+    ///
+    /// This is only ever set for the context of an individual `Composite` or `Sprite` primitive.
+    pub texture: Option<Textured>,
+}
+
+/// A texture sampled by a primitive along with the sampler used and the mode used to blend it.
+#[derive(Clone, Debug)]
+pub(crate) struct Textured {
+    pub source: TextureSource,
+    pub sampler: wgpu::SamplerDescriptor,
+    pub blend: BlendMode,
+}
+
+/// The source of a texture sampled by a primitive.
+#[derive(Clone, Debug)]
+pub(crate) enum TextureSource {
+    /// A layer that must be rendered before it is sampled.
+    Layer(Layer),
+    /// A texture atlas, e.g. for drawing sprites.
+    Atlas(wgpu::TextureAtlas),
+}
+
+/// Describes how a primitive interacts with the stencil buffer.
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+pub(crate) enum Mask {
+    /// The primitive is not drawn to the output attachment. Instead, the given mask ID is written
+    /// to the stencil buffer wherever the primitive covers.
+    Write(u8),
+    /// The primitive is only drawn where the stencil buffer contains the given mask ID, or where
+    /// it does not if `inverted` is `true`.
+    Test { id: u8, inverted: bool },
+}
+
+/// A range of the **Draw**'s mesh indices that share the same **Context**.
+#[derive(Clone, Debug)]
+pub(crate) struct Command<S = geom::scalar::Default> {
+    pub indices: ops::Range<usize>,
+    pub context: Context<S>,
+}
+
+impl<S> Default for Context<S> {
+    fn default() -> Self {
+        Context {
+            scissor: None,
+            mask: None,
+            texture: None,
+        }
+    }
+}
+
+impl TextureSource {
+    /// Whether or not both sources refer to the same texture.
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (TextureSource::Layer(a), TextureSource::Layer(b)) => a.ptr_eq(b),
+            (TextureSource::Atlas(a), TextureSource::Atlas(b)) => a.ptr_eq(b),
+            _ => false,
+        }
+    }
+}
+
+impl PartialEq for TextureSource {
+    fn eq(&self, other: &Self) -> bool {
+        self.ptr_eq(other)
+    }
+}
+
+impl PartialEq for Textured {
+    fn eq(&self, other: &Self) -> bool {
+        self.source == other.source
+            && wgpu::sampler_descriptor_eq(&self.sampler, &other.sampler)
+            && self.blend == other.blend
+    }
+}
+
+// Record that the given range of indices should be drawn within the given context.
+//
+// If the range directly follows the last command and shares the same context, the last command
+// is extended rather than adding a new one.
+pub(crate) fn push_command<S>(
+    commands: &mut Vec<Command<S>>,
+    indices: ops::Range<usize>,
+    context: Context<S>,
+) where
+    S: BaseFloat,
+{
+    if indices.start == indices.end {
+        return;
+    }
+    if let Some(last) = commands.last_mut() {
+        if last.indices.end == indices.start && last.context == context {
+            last.indices.end = indices.end;
+            return;
+        }
+    }
+    commands.push(Command { indices, context });
+}
@@ -9,7 +9,9 @@ use crate::geom::graph::node;
 use crate::geom::{self, Point2, Point3, Vector2, Vector3};
 use crate::math::{Angle, BaseFloat, Euler, Quaternion, Rad};
 use lyon::path::PathEvent;
-use lyon::tessellation::{FillOptions, FillTessellator, LineCap, LineJoin, StrokeOptions};
+use lyon::tessellation::{
+    FillOptions, FillTessellator, LineCap, LineJoin, StrokeOptions, StrokeTessellator,
+};
 use std::marker::PhantomData;
 
 /// A **Drawing** in progress.
@@ -49,6 +51,8 @@ pub struct DrawingContext<'a, S> {
     pub mesh: &'a mut draw::IntermediaryMesh<S>,
     /// A re-usable fill tessellator for 2D paths.
     pub fill_tessellator: &'a mut FillTessellator,
+    /// A re-usable stroke tessellator for 2D paths.
+    pub stroke_tessellator: &'a mut StrokeTessellator,
     /// A re-usable buffer for collecting path events.
     pub path_event_buffer: &'a mut Vec<PathEvent>,
     /// A re-usable buffer for collecting text.
@@ -92,6 +96,7 @@ impl<'a, S> DrawingContext<'a, S> {
         let super::IntermediaryState {
             ref mut intermediary_mesh,
             ref mut fill_tessellator,
+            ref mut stroke_tessellator,
             ref mut path_event_buffer,
             ref mut text_buffer,
             ref mut glyph_cache,
@@ -99,6 +104,7 @@ impl<'a, S> DrawingContext<'a, S> {
         DrawingContext {
             mesh: intermediary_mesh,
             fill_tessellator: &mut fill_tessellator.0,
+            stroke_tessellator: &mut stroke_tessellator.0,
             path_event_buffer: path_event_buffer,
             text_buffer: text_buffer,
             glyph_cache: glyph_cache,
@@ -0,0 +1,169 @@
+//! Items related to drawing many instances of a single mesh.
+//!
+//! Rather than tessellating and transforming the mesh once for every instance on the CPU, the
+//! base mesh is uploaded once and each **Instance** is submitted as a transform and colour. The
+//! renderer then draws every instance of the batch via a single GPU instanced draw call.
+//!
+//! Alternatively, the per-instance data may be provided via an **InstanceBuffer** that already
+//! resides on the GPU, e.g. one written by a compute shader, avoiding a round-trip via the CPU.
+
+use crate::color::conv::IntoLinSrgba;
+use crate::draw::backend::wgpu::Instance as RawInstance;
+use crate::draw::mesh::Mesh;
+use crate::draw::properties::{ColorScalar, LinSrgba};
+use crate::geom::{self, Point2, Point3};
+use crate::math::{BaseFloat, Matrix4, SquareMatrix};
+use crate::wgpu;
+
+/// The per-instance data used when drawing a batch of **Instances**.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Instance<S = geom::scalar::Default> {
+    /// The transform applied to every vertex of the base mesh.
+    pub transform: Matrix4<S>,
+    /// The colour by which the colour of every vertex of the base mesh is multiplied.
+    pub color: LinSrgba,
+}
+
+/// A GPU buffer of per-instance data that may be drawn via `Draw::instances_from_buffer`.
+///
+/// Each instance's transform is in points, the same as that of an **Instance**.
+pub type InstanceBuffer = wgpu::compute::Buffer<RawInstance>;
+
+/// A base mesh along with the set of instances with which it should be drawn.
+#[derive(Clone, Debug)]
+pub struct Instances<S = geom::scalar::Default> {
+    mesh: Mesh<S>,
+    instances: Vec<Instance<S>>,
+    buffer: Option<InstanceBuffer>,
+    // The number of the **Draw**'s mesh indices submitted before the batch. The batch is drawn
+    // after these indices and before any that follow.
+    index: usize,
+}
+
+impl<S> Instance<S>
+where
+    S: BaseFloat,
+{
+    /// An instance of the base mesh with the given transform and an opaque white colour.
+    pub fn new(transform: Matrix4<S>) -> Self {
+        let color = LinSrgba::new(1.0, 1.0, 1.0, 1.0);
+        Instance { transform, color }
+    }
+
+    /// An instance of the base mesh translated to the given point.
+    pub fn translation<P>(point: P) -> Self
+    where
+        P: Into<Point3<S>>,
+    {
+        let point: Point3<S> = point.into();
+        Self::new(Matrix4::from_translation(point.into()))
+    }
+
+    /// Specify the colour by which the colour of every vertex of the base mesh is multiplied.
+    pub fn color<C>(mut self, color: C) -> Self
+    where
+        C: IntoLinSrgba<ColorScalar>,
+    {
+        self.color = color.into_lin_srgba();
+        self
+    }
+}
+
+impl<S> Instances<S> {
+    /// Create a new batch of instances from the given base mesh.
+    pub fn new<I>(mesh: Mesh<S>, instances: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<Instance<S>>,
+    {
+        let instances = instances.into_iter().map(Into::into).collect();
+        let buffer = None;
+        let index = 0;
+        Instances {
+            mesh,
+            instances,
+            buffer,
+            index,
+        }
+    }
+
+    /// Create a new batch of instances whose per-instance data is read from the given buffer.
+    ///
+    /// The buffer must have been created with `BufferUsage::VERTEX`.
+    pub fn from_buffer(mesh: Mesh<S>, buffer: &InstanceBuffer) -> Self {
+        let instances = vec![];
+        let buffer = Some(buffer.clone());
+        let index = 0;
+        Instances {
+            mesh,
+            instances,
+            buffer,
+            index,
+        }
+    }
+
+    /// The base mesh drawn for each instance.
+    pub fn mesh(&self) -> &Mesh<S> {
+        &self.mesh
+    }
+
+    /// The transform and colour of each instance.
+    ///
+    /// This is empty if the batch was created via `from_buffer`.
+    pub fn instances(&self) -> &[Instance<S>] {
+        &self.instances
+    }
+
+    /// The GPU buffer containing the per-instance data if the batch was created via
+    /// `from_buffer`.
+    pub fn buffer(&self) -> Option<&InstanceBuffer> {
+        self.buffer.as_ref()
+    }
+
+    // Specify the number of mesh indices submitted to the **Draw** before the batch.
+    pub(crate) fn at_index(mut self, index: usize) -> Self {
+        self.index = index;
+        self
+    }
+
+    // The number of mesh indices submitted to the **Draw** before the batch.
+    pub(crate) fn index(&self) -> usize {
+        self.index
+    }
+}
+
+impl<S> Default for Instance<S>
+where
+    S: BaseFloat,
+{
+    fn default() -> Self {
+        Self::new(Matrix4::identity())
+    }
+}
+
+impl<S> From<Matrix4<S>> for Instance<S>
+where
+    S: BaseFloat,
+{
+    fn from(transform: Matrix4<S>) -> Self {
+        Self::new(transform)
+    }
+}
+
+impl<S> From<Point2<S>> for Instance<S>
+where
+    S: BaseFloat,
+{
+    fn from(point: Point2<S>) -> Self {
+        Self::translation(point)
+    }
+}
+
+impl<S> From<Point3<S>> for Instance<S>
+where
+    S: BaseFloat,
+{
+    fn from(point: Point3<S>) -> Self {
+        Self::translation(point)
+    }
+}
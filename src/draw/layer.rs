@@ -0,0 +1,267 @@
+//! Items related to drawing to an offscreen texture and compositing the result via **Draw**.
+//!
+//! A **Layer** owns a texture along with its own **Draw** and **Renderer**. Geometry submitted via
+//! `layer.draw()` is rendered to the layer's texture the next time the layer is composited via
+//! `Draw::layer`. Unless a background color is specified, the texture retains its contents between
+//! renders, allowing for feedback and accumulation effects across frames.
+
+use crate::draw::backend::wgpu::RenderStats;
+use crate::draw::properties::LinSrgba;
+use crate::draw::{Draw, Renderer};
+use crate::frame::Frame;
+use crate::geom;
+use crate::wgpu;
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+use std::sync::Arc;
+
+/// An offscreen texture that may be drawn to via its own **Draw** and composited via `Draw::layer`.
+///
+/// Cloning a **Layer** produces a new handle to the same texture and **Draw**.
+///
+/// The layer's **Draw** always uses the default scalar type, however a layer may be composited via
+/// a **Draw** of any scalar type.
+#[derive(Clone, Debug)]
+pub struct Layer {
+    inner: Rc<Inner>,
+}
+
+#[derive(Debug)]
+struct Inner {
+    draw: Draw,
+    renderer: RefCell<Renderer>,
+    texture: wgpu::Texture,
+    texture_view: wgpu::TextureView,
+    resolve: Option<(wgpu::Texture, wgpu::TextureView)>,
+    device_queue_pair: Arc<wgpu::DeviceQueuePair>,
+    scale_factor: f32,
+    // Whether or not the texture has been rendered to at least once.
+    initialised: Cell<bool>,
+}
+
+/// A context for building a **Layer**.
+#[derive(Debug)]
+pub struct Builder {
+    device_queue_pair: Arc<wgpu::DeviceQueuePair>,
+    size: [u32; 2],
+    format: wgpu::TextureFormat,
+    msaa_samples: u32,
+    scale_factor: f32,
+}
+
+/// Describes how a composited **Layer** is blended with the geometry beneath it.
+///
+/// The layer's texture is assumed to contain premultiplied alpha, as is the case when drawing to a
+/// layer cleared to a transparent background.
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+pub enum BlendMode {
+    /// The layer is drawn over the top of the geometry beneath it.
+    Normal,
+    /// The color of the layer is added to the color beneath it.
+    Add,
+    /// The color of the layer is multiplied with the color beneath it, darkening the result.
+    Multiply,
+    /// The inverse colors are multiplied, lightening the result.
+    Screen,
+}
+
+impl Builder {
+    /// The default texture format used for a layer. This matches the format of the intermediary
+    /// texture used by the **Frame**.
+    pub const DEFAULT_FORMAT: wgpu::TextureFormat = Frame::TEXTURE_FORMAT;
+    /// The default size of a layer in pixels.
+    pub const DEFAULT_SIZE: [u32; 2] = [1024, 768];
+    /// The default number of MSAA samples used by a layer.
+    pub const DEFAULT_MSAA_SAMPLES: u32 = 1;
+    /// The default ratio between pixels and points.
+    pub const DEFAULT_SCALE_FACTOR: f32 = 1.0;
+
+    /// Begin building a **Layer** for use on the given device.
+    ///
+    /// Generally `Window::new_layer` or `App::new_layer` are more convenient.
+    pub fn new(device_queue_pair: Arc<wgpu::DeviceQueuePair>) -> Self {
+        Builder {
+            device_queue_pair,
+            size: Self::DEFAULT_SIZE,
+            format: Self::DEFAULT_FORMAT,
+            msaa_samples: Self::DEFAULT_MSAA_SAMPLES,
+            scale_factor: Self::DEFAULT_SCALE_FACTOR,
+        }
+    }
+
+    /// The size of the layer's texture in pixels.
+    pub fn size(mut self, width: u32, height: u32) -> Self {
+        self.size = [width, height];
+        self
+    }
+
+    /// The format of the layer's texture.
+    pub fn format(mut self, format: wgpu::TextureFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// The number of samples used for multisample anti-aliasing when drawing to the layer.
+    pub fn msaa_samples(mut self, msaa_samples: u32) -> Self {
+        self.msaa_samples = msaa_samples;
+        self
+    }
+
+    /// The ratio between pixels and the points in which geometry is drawn to the layer.
+    pub fn scale_factor(mut self, scale_factor: f32) -> Self {
+        self.scale_factor = scale_factor;
+        self
+    }
+
+    /// Build the **Layer**.
+    pub fn build(self) -> Layer {
+        let Builder {
+            device_queue_pair,
+            size,
+            format,
+            msaa_samples,
+            scale_factor,
+        } = self;
+        let device = device_queue_pair.device();
+        let builder = || wgpu::TextureBuilder::new().size(size).format(format);
+        let sampled_usage = wgpu::TextureUsage::OUTPUT_ATTACHMENT
+            | wgpu::TextureUsage::SAMPLED
+            | wgpu::TextureUsage::COPY_SRC;
+        let (texture, resolve) = if msaa_samples > 1 {
+            let texture = builder()
+                .usage(wgpu::TextureUsage::OUTPUT_ATTACHMENT)
+                .sample_count(msaa_samples)
+                .build(device);
+            let resolve_texture = builder().usage(sampled_usage).build(device);
+            let resolve_view = resolve_texture.create_default_view();
+            (texture, Some((resolve_texture, resolve_view)))
+        } else {
+            (builder().usage(sampled_usage).build(device), None)
+        };
+        let texture_view = texture.create_default_view();
+        let renderer = RefCell::new(Renderer::new(device, size, msaa_samples, format));
+        let inner = Rc::new(Inner {
+            draw: Draw::new(),
+            renderer,
+            texture,
+            texture_view,
+            resolve,
+            device_queue_pair,
+            scale_factor,
+            initialised: Cell::new(false),
+        });
+        Layer { inner }
+    }
+}
+
+impl Layer {
+    /// A **Draw** used for drawing to the layer.
+    ///
+    /// The drawn geometry is rendered to the layer's texture the next time the layer is
+    /// composited via `Draw::layer`, after which the layer's **Draw** is reset.
+    pub fn draw(&self) -> Draw {
+        self.inner.draw.share()
+    }
+
+    /// The size of the layer's texture in pixels.
+    pub fn size(&self) -> [u32; 2] {
+        self.inner.texture.size()
+    }
+
+    /// The size of the layer in points.
+    pub fn size_points(&self) -> [geom::scalar::Default; 2] {
+        let [w, h] = self.size();
+        let scale_factor = self.inner.scale_factor;
+        [w as f32 / scale_factor, h as f32 / scale_factor]
+    }
+
+    /// The bounding **Rect** of the layer in points, centred on the origin.
+    pub fn rect(&self) -> geom::Rect {
+        let [w, h] = self.size_points();
+        geom::Rect::from_w_h(w, h)
+    }
+
+    /// The format of the layer's texture.
+    pub fn format(&self) -> wgpu::TextureFormat {
+        self.inner.texture.format()
+    }
+
+    /// The number of samples used for multisample anti-aliasing when drawing to the layer.
+    pub fn msaa_samples(&self) -> u32 {
+        self.inner.texture.sample_count()
+    }
+
+    /// The ratio between pixels and the points in which geometry is drawn to the layer.
+    pub fn scale_factor(&self) -> f32 {
+        self.inner.scale_factor
+    }
+
+    /// The single-sampled texture containing the result of the most recent render.
+    ///
+    /// If the layer is multisampled, this is the texture to which the result is resolved.
+    pub fn texture(&self) -> &wgpu::Texture {
+        match self.inner.resolve {
+            Some((ref texture, _)) => texture,
+            None => &self.inner.texture,
+        }
+    }
+
+    /// A view of the texture returned by `texture`.
+    pub fn texture_view(&self) -> &wgpu::TextureView {
+        match self.inner.resolve {
+            Some((_, ref view)) => view,
+            None => &self.inner.texture_view,
+        }
+    }
+
+    /// The device queue pair on which the layer's texture was created.
+    pub fn device_queue_pair(&self) -> &Arc<wgpu::DeviceQueuePair> {
+        &self.inner.device_queue_pair
+    }
+
+    /// Whether or not both handles refer to the same layer.
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.inner, &other.inner)
+    }
+
+    // Encode the commands necessary to render the contents of the layer's **Draw** to its texture
+    // and reset the **Draw**.
+    //
+    // Returns the stats of the layer's renderer, or the default stats if the layer is already
+    // being rendered, e.g. if a layer is composited within itself.
+    pub(crate) fn encode_render_pass(
+        &self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+    ) -> RenderStats {
+        let mut renderer = match self.inner.renderer.try_borrow_mut() {
+            Ok(renderer) => renderer,
+            Err(_) => return Default::default(),
+        };
+        // The initial contents of the texture are undefined, so clear it on the first render.
+        if !self.inner.initialised.replace(true) {
+            let mut state = self.inner.draw.state.borrow_mut();
+            if state.background_color.is_none() {
+                state.background_color = Some(LinSrgba::new(0.0, 0.0, 0.0, 0.0));
+            }
+        }
+        let resolve_target = self.inner.resolve.as_ref().map(|(_, view)| view);
+        renderer.encode_render_pass(
+            device,
+            encoder,
+            &self.inner.draw,
+            self.inner.scale_factor,
+            self.inner.texture.size(),
+            &self.inner.texture_view,
+            resolve_target,
+        );
+        self.inner.draw.reset();
+        *renderer.stats()
+    }
+}
+
+impl Default for BlendMode {
+    fn default() -> Self {
+        BlendMode::Normal
+    }
+}
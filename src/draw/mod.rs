@@ -5,15 +5,21 @@ use crate::geom::graph::{edge, node};
 use crate::geom::{self, Vector3};
 use crate::math::BaseFloat;
 use crate::text;
+use crate::wgpu;
 use lyon::path::PathEvent;
-use lyon::tessellation::FillTessellator;
+use lyon::tessellation::{FillTessellator, StrokeTessellator};
 use std::cell::{Ref, RefCell};
 use std::collections::HashMap;
+use std::rc::Rc;
 use std::{fmt, mem, ops};
 
 pub use self::backend::wgpu::Renderer;
 pub use self::background::Background;
+pub use self::cached::Cached;
+use self::context::{Context, Mask};
 pub use self::drawing::{Drawing, DrawingContext};
+pub use self::instances::{Instance, InstanceBuffer, Instances};
+pub use self::layer::{BlendMode, Layer};
 pub use self::mesh::intermediary::{
     IntermediaryMesh, IntermediaryMeshBuilder, IntermediaryVertexData, IntermediaryVertexDataRanges,
 };
@@ -26,7 +32,11 @@ pub use self::theme::Theme;
 
 pub mod backend;
 pub mod background;
+pub mod cached;
+mod context;
 mod drawing;
+pub mod instances;
+pub mod layer;
 pub mod mesh;
 pub mod primitive;
 pub mod properties;
@@ -50,10 +60,14 @@ pub mod theme;
 ///    frame for the current display or to a list of vertices or triangles for lower-level, more
 ///    flexible access.
 ///
+/// Cloning a **Draw** produces a copy of its inner state. Scoped handles that share the state of
+/// the **Draw** and clip everything drawn to them may be produced via the `scissor` and `mask`
+/// methods.
+///
 /// See the
 /// [simple_draw.rs](https://github.com/nannou-org/nannou/blob/master/examples/simple_draw.rs)
 /// example for a demonstration of how to use the **App**'s custom **Draw** type.
-#[derive(Clone, Debug)]
+#[derive(Debug)]
 pub struct Draw<S = geom::scalar::Default>
 where
     S: BaseFloat,
@@ -63,9 +77,20 @@ where
     // high-level API for drawing stuff. In order to be friendlier to new users, we want to avoid
     // them having to think about mutability and focus on creativity. Rust-lang nuances can come
     // later.
-    state: RefCell<State<S>>,
+    //
+    // The state is shared between a **Draw** and any scoped **Draw**s produced from it.
+    state: Shared<S>,
+    // The scissor and mask applied to all primitives drawn via this handle.
+    context: Context<S>,
 }
 
+// A handle to the **State** of a **Draw** that is shared with any scoped **Draw**s produced from
+// it. Unlike **Draw**, cloning the handle does not copy the state.
+#[derive(Debug)]
+struct Shared<S>(Rc<RefCell<State<S>>>)
+where
+    S: BaseFloat;
+
 /// The inner state of the **Draw** type.
 ///
 /// The **Draw** type stores its **State** behind a **RefCell** - a type used for moving mutability
@@ -88,6 +113,14 @@ where
     mesh: Mesh<S>,
     /// The map from node indices to their vertex and index ranges within the mesh.
     ranges: HashMap<node::Index, Ranges>,
+    /// Batches of instanced meshes, each drawn via a single instanced draw call.
+    instances: Vec<Instances<S>>,
+    /// The context in which each primitive that is in the process of being drawn was created.
+    contexts: HashMap<node::Index, Context<S>>,
+    /// Ranges of the mesh indices along with the context in which they should be drawn.
+    commands: Vec<context::Command<S>>,
+    /// The ID of the most recently created mask.
+    last_mask_id: u8,
     /// Primitives that are in the process of being drawn.
     drawing: HashMap<node::Index, Primitive<S>>,
     /// The last node that was **Drawn**.
@@ -96,6 +129,38 @@ where
     theme: Theme,
     /// If `Some`, the **Draw** should first clear the frame's gl context with the given color.
     background_color: Option<properties::LinSrgba>,
+    /// The method used to render overlapping and semi-transparent geometry.
+    transparency: Transparency,
+}
+
+/// The method used by the renderer to handle overlapping and semi-transparent geometry.
+///
+/// See `Draw::transparency`.
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+pub enum Transparency {
+    /// All triangles are depth tested and write to the depth buffer in the order in which they
+    /// were drawn.
+    ///
+    /// This is the default. Opaque geometry is always correctly occluded, however semi-transparent
+    /// geometry drawn after other geometry at the same or greater depth is discarded.
+    DepthTest,
+    /// Depth testing is disabled and triangles are blended in the order in which they were
+    /// drawn, i.e. later drawings always appear on top of earlier ones.
+    ///
+    /// Well suited to 2D work where the drawing order describes the layering.
+    Painter,
+    /// Opaque triangles are drawn first with depth testing. Semi-transparent triangles are then
+    /// sorted from back to front by their average depth and blended over the top.
+    ///
+    /// Triangles at the same depth retain the order in which they were drawn. Intersecting
+    /// semi-transparent triangles may still blend incorrectly.
+    DepthSorted,
+    /// Opaque triangles are drawn first with depth testing. Semi-transparent triangles are then
+    /// composited via weighted blended order-independent transparency.
+    ///
+    /// This requires no sorting and handles intersecting geometry, at the cost of two extra
+    /// render passes and an approximation of the true blending order.
+    WeightedBlended,
 }
 
 /// The CPU half of the glyph cache used for caching text.
@@ -116,6 +181,8 @@ pub struct IntermediaryState<S> {
     intermediary_mesh: IntermediaryMesh<S>,
     /// A fill tessellator that may be re-used for 2D paths, meshes, etc between view calls.
     fill_tessellator: FillTessellatorWrapper,
+    /// A stroke tessellator that may be re-used for 2D paths between view calls.
+    stroke_tessellator: StrokeTessellatorWrapper,
     /// A re-usable buffer for collecting path events.
     path_event_buffer: Vec<PathEvent>,
     /// A buffer containing all text.
@@ -128,6 +195,10 @@ pub struct IntermediaryState<S> {
 #[derive(Default)]
 pub(crate) struct FillTessellatorWrapper(pub(crate) FillTessellator);
 
+// Simple wrapper providing Clone and Debug.
+#[derive(Default)]
+pub(crate) struct StrokeTessellatorWrapper(pub(crate) StrokeTessellator);
+
 // A wrapper providing debug and clone implementations for the glyph cache.
 pub(crate) struct GlyphCacheWrapper(pub(crate) text::GlyphCache<'static>);
 
@@ -222,6 +293,10 @@ where
         self.geom_graph_dfs.borrow_mut().reset(&self.geom_graph);
         self.drawing.clear();
         self.ranges.clear();
+        self.instances.clear();
+        self.contexts.clear();
+        self.commands.clear();
+        self.last_mask_id = 0;
         self.intermediary_state.borrow_mut().reset();
         self.mesh.clear();
         self.background_color = None;
@@ -344,6 +419,103 @@ where
         background::new(self)
     }
 
+    /// Produce a new **Draw** that shares this **Draw**'s state but clips all geometry drawn to
+    /// it to the given rectangle.
+    ///
+    /// The rectangle is described in the same coordinate space as the drawn geometry. If this
+    /// **Draw** is already scissored, geometry is clipped to the overlap of both rectangles.
+    ///
+    /// Scissoring is performed by the GPU per-pixel and is very cheap.
+    ///
+    /// **Note:** Batches submitted via `instances` are not clipped.
+    pub fn scissor(&self, rect: geom::Rect<S>) -> Self {
+        let rect = match self.context.scissor {
+            None => rect,
+            Some(parent) => parent
+                .overlap(rect)
+                .unwrap_or_else(|| geom::Rect::from_w_h(S::zero(), S::zero())),
+        };
+        let mut context = self.context.clone();
+        context.scissor = Some(rect);
+        self.with_context(context)
+    }
+
+    /// Produce a new **Draw** that shares this **Draw**'s state but only draws geometry within
+    /// the shape of the mask described by the given function.
+    ///
+    /// The function is called with a **Draw** to which the mask shape should be drawn. Mask
+    /// geometry is written to the stencil buffer and does not appear in the frame.
+    ///
+    /// ```ignore
+    /// let masked = draw.mask(|m| {
+    ///     m.ellipse().w_h(200.0, 200.0);
+    /// });
+    /// masked.rect().w_h(300.0, 100.0).color(PLUM);
+    /// ```
+    ///
+    /// Masks do not nest. Masking an already masked **Draw** replaces the previous mask. Where
+    /// two masks overlap, geometry drawn to the earlier mask after the later mask was created is
+    /// not drawn within the overlap. Up to 255 masks are distinguished per frame.
+    ///
+    /// **Note:** Batches submitted via `instances` are not masked.
+    pub fn mask<F>(&self, f: F) -> Self
+    where
+        F: FnOnce(&Draw<S>),
+    {
+        self.mask_inner(f, false)
+    }
+
+    /// The same as `mask`, but geometry is only drawn *outside* of the shape of the mask.
+    pub fn mask_inverted<F>(&self, f: F) -> Self
+    where
+        F: FnOnce(&Draw<S>),
+    {
+        self.mask_inner(f, true)
+    }
+
+    fn mask_inner<F>(&self, f: F, inverted: bool) -> Self
+    where
+        F: FnOnce(&Draw<S>),
+    {
+        let id = {
+            let mut state = self.state.borrow_mut();
+            state.last_mask_id = std::cmp::max(state.last_mask_id.wrapping_add(1), 1);
+            state.last_mask_id
+        };
+
+        // Draw the mask geometry, ensuring it is submitted before any masked geometry.
+        let mut context = self.context.clone();
+        context.mask = Some(Mask::Write(id));
+        let mask = self.with_context(context.clone());
+        f(&mask);
+        mask.finish_remaining_drawings().expect(WOULD_CYCLE);
+
+        context.mask = Some(Mask::Test { id, inverted });
+        self.with_context(context)
+    }
+
+    // Produce a new handle to the same state with the given context.
+    fn with_context(&self, context: Context<S>) -> Self {
+        let state = Shared(self.state.0.clone());
+        Draw { state, context }
+    }
+
+    // Produce a new handle to the same state and context.
+    fn share(&self) -> Self {
+        self.with_context(self.context.clone())
+    }
+
+    /// Specify the method used to render overlapping and semi-transparent geometry.
+    ///
+    /// Unlike the background color, the transparency mode persists between calls to `reset`.
+    ///
+    /// **Note:** Batches submitted via `instances` are always treated as opaque.
+    pub fn transparency(&self, transparency: Transparency) {
+        if let Ok(mut state) = self.state.try_borrow_mut() {
+            state.transparency = transparency;
+        }
+    }
+
     /// Add the given type to be drawn.
     pub fn a<T>(&self, primitive: T) -> Drawing<T, S>
     where
@@ -356,7 +528,10 @@ where
             .geom_graph
             .add_node(geom::graph::Node::Point);
         let primitive: Primitive<S> = primitive.into();
-        self.state.borrow_mut().drawing.insert(index, primitive);
+        let mut state = self.state.borrow_mut();
+        state.drawing.insert(index, primitive);
+        state.contexts.insert(index, self.context.clone());
+        drop(state);
         drawing::new(self, index)
     }
 
@@ -370,6 +545,11 @@ where
         self.a(Default::default())
     }
 
+    /// Begin drawing an **Arrow**.
+    pub fn arrow(&self) -> Drawing<primitive::Arrow<S>, S> {
+        self.a(Default::default())
+    }
+
     /// Begin drawing a **Line**.
     pub fn line(&self) -> Drawing<primitive::Line<S>, S> {
         self.a(Default::default())
@@ -422,6 +602,155 @@ where
         self.a(text)
     }
 
+    /// Draw an instance of the given base **Mesh** for each of the given **Instance**s.
+    ///
+    /// Rather than adding the vertices of every instance to the inner mesh, the base mesh and the
+    /// transform and colour of each instance are submitted to the renderer so that the whole batch
+    /// may be drawn via a single GPU instanced draw call. This makes it practical to draw many
+    /// thousands of copies of the same shape, e.g. particles.
+    ///
+    /// The positions of the base mesh vertices are relative to each instance's transform. Each
+    /// instance's colour is multiplied with the colour of the base mesh vertices.
+    ///
+    /// **Note:** Instanced batches are drawn in the order in which they were submitted relative
+    /// to the rest of the **Draw**'s primitives, so painter's order is respected when depth
+    /// testing is disabled.
+    pub fn instances<I>(&self, mesh: &Mesh<S>, instances: I)
+    where
+        I: IntoIterator,
+        I::Item: Into<Instance<S>>,
+    {
+        self.push_instances(Instances::new(mesh.clone(), instances));
+    }
+
+    /// Draw an instance of the given base **Mesh** for each element of the given GPU buffer.
+    ///
+    /// This is the same as `instances`, but the per-instance data is read directly from a buffer
+    /// on the GPU, e.g. one written by a compute shader via `wgpu::compute::Pipeline`. This avoids
+    /// reading the data back to the CPU each frame for large particle simulations.
+    ///
+    /// The buffer must have been created with `BufferUsage::VERTEX` on the same device as the
+    /// **Renderer** that renders this **Draw**.
+    pub fn instances_from_buffer(&self, mesh: &Mesh<S>, buffer: &InstanceBuffer) {
+        self.push_instances(Instances::from_buffer(mesh.clone(), buffer));
+    }
+
+    // Finish any drawings in progress so that the batch is ordered after them.
+    fn push_instances(&self, instances: Instances<S>) {
+        if let Ok(mut state) = self.state.try_borrow_mut() {
+            state.finish_remaining_drawings().expect(WOULD_CYCLE);
+            let index = state.mesh.indices().len();
+            state.instances.push(instances.at_index(index));
+        }
+    }
+
+    /// Produce a **Mesh** containing all vertices and indices from the inner mesh, with each
+    /// vertex transformed via the inner geometry graph.
+    ///
+    /// Shared vertices remain shared, so the resulting mesh is no larger than the inner mesh.
+    ///
+    /// This is useful for describing the base mesh of a batch of `instances` via the familiar
+    /// drawing API.
+    ///
+    /// **Note:** If there are any **Drawing**s in progress, these will first be drained and
+    /// completed before any vertices are yielded.
+    pub fn to_mesh(&self) -> Mesh<S> {
+        self.finish_remaining_drawings().expect(WOULD_CYCLE);
+        let state = self.state.borrow();
+        let mut dfs = state.geom_graph_dfs.borrow_mut();
+        dfs.reset(&state.geom_graph);
+        let mut mesh = Mesh::default();
+        while let Some((n, transform)) = dfs.next_transform(&state.geom_graph) {
+            let ranges = match state.ranges.get(&n) {
+                None => continue,
+                Some(ranges) => ranges,
+            };
+            // Copy the node's vertices once, offsetting its indices into the new mesh.
+            let start_index = mesh.raw_vertex_count();
+            let vertices = crate::mesh::raw_vertices(&state.mesh).range(ranges.vertices.clone());
+            let indices = state.mesh.indices()[ranges.indices.clone()]
+                .iter()
+                .map(|&i| start_index + i - ranges.vertices.start);
+            mesh.extend(transform.vertices(vertices), indices);
+        }
+        mesh
+    }
+
+    /// Record the geometry drawn within the given function so that it may be drawn again in
+    /// future frames without re-tessellation.
+    ///
+    /// The function is called with a new, empty **Draw** that shares this **Draw**'s theme. All
+    /// geometry drawn to it is tessellated into a single mesh and returned as a **Cached** handle
+    /// that may be drawn any number of times via `cached`.
+    ///
+    /// ```ignore
+    /// let cached = draw.record(|d| {
+    ///     d.ellipse().w_h(100.0, 60.0).color(STEELBLUE);
+    ///     d.rect().x(80.0).w_h(40.0, 40.0).color(PLUM);
+    /// });
+    /// draw.cached(&cached).xy(mouse).rotate(t);
+    /// ```
+    pub fn record<F>(&self, f: F) -> Cached<S>
+    where
+        F: FnOnce(&Draw<S>),
+    {
+        let recording = Draw::new();
+        recording.state.borrow_mut().theme = self.state.borrow().theme.clone();
+        f(&recording);
+        Cached::from_mesh(recording.to_mesh())
+    }
+
+    /// Begin drawing the geometry retained by the given **Cached** handle.
+    ///
+    /// The retained mesh is not re-tessellated. Only its position and orientation may be
+    /// changed, e.g. `draw.cached(&cached).xy(p).rotate(radians)`.
+    pub fn cached(&self, cached: &Cached<S>) -> Drawing<primitive::Retained<S>, S> {
+        self.a(primitive::Retained::new(cached))
+    }
+
+    /// Composite the contents of the given **Layer**.
+    ///
+    /// Any geometry drawn via `layer.draw()` is first rendered to the layer's texture, after which
+    /// the texture is drawn as a rectangle that is the size of the layer in points by default.
+    /// The rectangle may be positioned, rotated and resized like any other primitive, e.g.
+    /// `draw.layer(&layer).wh(win.wh()).blend(BlendMode::Add).opacity(0.5)`.
+    ///
+    /// **Note:** The layer is rendered using the device of the **Renderer** that renders this
+    /// **Draw**, so must have been created on the same device.
+    pub fn layer(&self, layer: &Layer) -> Drawing<primitive::Composite<S>, S> {
+        self.a(primitive::Composite::new(layer))
+    }
+
+    /// Draw a region of the given texture atlas, identified either by name or by index.
+    ///
+    /// The sprite is drawn as a rectangle that is the size of the region in pixels by default and
+    /// may be tinted via the usual color methods, e.g. `draw.sprite(&atlas, "idle").color(RED)`.
+    /// Animations may be drawn by passing `animation.frame_at(t)` as the key.
+    ///
+    /// Panics if the atlas contains no region for the given key.
+    ///
+    /// **Note:** The atlas must have been created on the same device as the **Renderer** that
+    /// renders this **Draw**.
+    pub fn sprite<'k, K>(
+        &self,
+        atlas: &wgpu::TextureAtlas,
+        key: K,
+    ) -> Drawing<primitive::Sprite<S>, S>
+    where
+        K: Into<wgpu::TextureAtlasKey<'k>>,
+    {
+        let key = key.into();
+        let region = atlas
+            .region(key)
+            .unwrap_or_else(|| panic!("no region for {:?} within the texture atlas", key));
+        self.a(primitive::Sprite::new(atlas, region))
+    }
+
+    /// Borrow the batches of **Instances** submitted via the `instances` method.
+    pub fn instance_batches(&self) -> Ref<[Instances<S>]> {
+        Ref::map(self.state.borrow(), |s| &s.instances[..])
+    }
+
     /// Produce the transformed mesh vertices for the node at the given index.
     ///
     /// Returns **None** if there is no node for the given index.
@@ -583,12 +912,14 @@ impl<S> Default for IntermediaryState<S> {
     fn default() -> Self {
         let intermediary_mesh = Default::default();
         let fill_tessellator = Default::default();
+        let stroke_tessellator = Default::default();
         let path_event_buffer = Default::default();
         let text_buffer = Default::default();
         let glyph_cache = Default::default();
         IntermediaryState {
             intermediary_mesh,
             fill_tessellator,
+            stroke_tessellator,
             path_event_buffer,
             text_buffer,
             glyph_cache,
@@ -607,7 +938,12 @@ where
         let intermediary_state = RefCell::new(Default::default());
         let mesh = Default::default();
         let ranges = Default::default();
+        let instances = Default::default();
+        let contexts = Default::default();
+        let commands = Default::default();
+        let last_mask_id = 0;
         let theme = Default::default();
+        let transparency = Default::default();
         let last_node_drawn = Default::default();
         let background_color = Default::default();
         State {
@@ -617,20 +953,53 @@ where
             mesh,
             drawing,
             ranges,
+            instances,
+            contexts,
+            commands,
+            last_mask_id,
             theme,
             last_node_drawn,
             background_color,
+            transparency,
         }
     }
 }
 
+impl Default for Transparency {
+    fn default() -> Self {
+        Transparency::DepthTest
+    }
+}
+
 impl<S> Default for Draw<S>
 where
     S: BaseFloat,
 {
     fn default() -> Self {
-        let state = RefCell::new(Default::default());
-        Draw { state }
+        let state = Shared(Rc::new(RefCell::new(Default::default())));
+        let context = Default::default();
+        Draw { state, context }
+    }
+}
+
+impl<S> Clone for Draw<S>
+where
+    S: BaseFloat,
+{
+    fn clone(&self) -> Self {
+        let state = Shared(Rc::new(RefCell::new(self.state.borrow().clone())));
+        let context = self.context.clone();
+        Draw { state, context }
+    }
+}
+
+impl<S> ops::Deref for Shared<S>
+where
+    S: BaseFloat,
+{
+    type Target = RefCell<State<S>>;
+    fn deref(&self) -> &Self::Target {
+        &self.0
     }
 }
 
@@ -711,6 +1080,12 @@ impl Clone for FillTessellatorWrapper {
     }
 }
 
+impl Clone for StrokeTessellatorWrapper {
+    fn clone(&self) -> Self {
+        Default::default()
+    }
+}
+
 impl Clone for GlyphCacheWrapper {
     fn clone(&self) -> Self {
         GlyphCacheWrapper(self.0.to_builder().build())
@@ -723,6 +1098,12 @@ impl fmt::Debug for FillTessellatorWrapper {
     }
 }
 
+impl fmt::Debug for StrokeTessellatorWrapper {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "StrokeTessellator")
+    }
+}
+
 impl fmt::Debug for GlyphCacheWrapper {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "GlyphCache")
@@ -909,6 +1290,8 @@ where
     let indices_end_index = draw.mesh.indices().len();
     let vertices = vertices_start_index..vertices_end_index;
     let indices = indices_start_index..indices_end_index;
+    let context = draw.contexts.remove(&node_index).unwrap_or_default();
+    context::push_command(&mut draw.commands, indices.clone(), context);
     let ranges = Ranges { vertices, indices };
     draw.ranges.insert(node_index, ranges);
 
@@ -968,6 +1351,16 @@ where
     S: BaseFloat,
 {
     match primitive {
+        Primitive::Arrow(prim) => into_drawn(draw, node_index, prim),
+        Primitive::Composite(prim) => {
+            let textured = context::Textured {
+                source: context::TextureSource::Layer(prim.layer().clone()),
+                sampler: prim.sampler_descriptor().clone(),
+                blend: prim.blend_mode(),
+            };
+            draw.contexts.entry(node_index).or_default().texture = Some(textured);
+            into_drawn(draw, node_index, prim)
+        }
         Primitive::Ellipse(prim) => into_drawn(draw, node_index, prim),
         Primitive::Line(prim) => into_drawn(draw, node_index, prim),
         Primitive::Mesh(prim) => into_drawn(draw, node_index, prim),
@@ -975,6 +1368,16 @@ where
         Primitive::Polygon(prim) => into_drawn(draw, node_index, prim),
         Primitive::Quad(prim) => into_drawn(draw, node_index, prim),
         Primitive::Rect(prim) => into_drawn(draw, node_index, prim),
+        Primitive::Retained(prim) => into_drawn(draw, node_index, prim),
+        Primitive::Sprite(prim) => {
+            let textured = context::Textured {
+                source: context::TextureSource::Atlas(prim.atlas().clone()),
+                sampler: prim.sampler_descriptor().clone(),
+                blend: BlendMode::Normal,
+            };
+            draw.contexts.entry(node_index).or_default().texture = Some(textured);
+            into_drawn(draw, node_index, prim)
+        }
         Primitive::Text(prim) => into_drawn(draw, node_index, prim),
         Primitive::Tri(prim) => into_drawn(draw, node_index, prim),
 
@@ -0,0 +1,693 @@
+use crate::color::LinSrgba;
+use crate::draw::primitive::path::Tessellators;
+use crate::draw::primitive::Primitive;
+use crate::draw::properties::spatial::{self, orientation, position};
+use crate::draw::properties::{
+    ColorScalar, Draw, Drawn, IndicesFromRange, IntoDrawn, SetColor, SetOrientation, SetPosition,
+    SetStroke, VerticesFromRanges,
+};
+use crate::draw::{self, theme, Drawing, DrawingContext};
+use crate::geom::{self, pt2, Point2, Point3, Vector2, Vector3};
+use crate::math::{BaseFloat, InnerSpace, NumCast};
+use lyon::path::PathEvent;
+use lyon::tessellation::{FillOptions, StrokeOptions};
+use std::ops;
+
+/// The shape drawn at either end of an **Arrow**.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Head {
+    /// No head. The connector simply ends at the point.
+    None,
+    /// A filled triangle whose tip lies on the point.
+    Triangle,
+    /// Two stroked barbs that meet at the point.
+    Open,
+    /// A filled circle centred on the point.
+    Dot,
+    /// A stroked bar perpendicular to the connector, centred on the point.
+    Bar,
+}
+
+/// Properties related to drawing an **Arrow**.
+///
+/// An arrow is a stroked connector between a `start` and `end` point with a configurable **Head**
+/// at either end. The connector may be curved by specifying a `control` point.
+///
+/// The arrow is tessellated within the plane that contains the connector and faces the viewer as
+/// closely as possible. For arrows whose points all lie on the same *z* plane this is simply the
+/// 2D plane, while arrows between arbitrary 3D points are billboarded toward the *z* axis.
+#[derive(Clone, Debug)]
+pub struct Arrow<S = geom::scalar::Default> {
+    position: position::Properties<S>,
+    orientation: orientation::Properties<S>,
+    color: Option<LinSrgba>,
+    stroke: StrokeOptions,
+    start: Option<Point3<S>>,
+    end: Option<Point3<S>>,
+    control: Option<Point3<S>>,
+    head_length: Option<S>,
+    head_width: Option<S>,
+    start_head: Head,
+    end_head: Head,
+}
+
+/// The drawing context for an arrow.
+pub type DrawingArrow<'a, S = geom::scalar::Default> = Drawing<'a, Arrow<S>, S>;
+
+// The arrow described within its local plane, where the connector begins at the origin and ends
+// along the positive *x* axis.
+struct Shape {
+    start: Point2<f32>,
+    end: Point2<f32>,
+    control: Option<Point2<f32>>,
+    head_length: f32,
+    head_width: f32,
+    start_head: Head,
+    end_head: Head,
+}
+
+// Arrow-specific methods.
+
+impl<S> Arrow<S> {
+    /// Short-hand for the `stroke_weight` method.
+    pub fn weight(self, weight: f32) -> Self {
+        self.stroke_weight(weight)
+    }
+
+    /// Short-hand for the `stroke_tolerance` method.
+    pub fn tolerance(self, tolerance: f32) -> Self {
+        self.stroke_tolerance(tolerance)
+    }
+
+    /// Specify the start point of the arrow.
+    ///
+    /// Accepts both 2D and 3D points.
+    pub fn start<P>(mut self, start: P) -> Self
+    where
+        P: Into<Point3<S>>,
+    {
+        self.start = Some(start.into());
+        self
+    }
+
+    /// Specify the end point of the arrow.
+    ///
+    /// Accepts both 2D and 3D points.
+    pub fn end<P>(mut self, end: P) -> Self
+    where
+        P: Into<Point3<S>>,
+    {
+        self.end = Some(end.into());
+        self
+    }
+
+    /// Specify the start and end points of the arrow.
+    pub fn points<P>(self, start: P, end: P) -> Self
+    where
+        P: Into<Point3<S>>,
+    {
+        self.start(start).end(end)
+    }
+
+    /// Curve the connector toward the given control point.
+    ///
+    /// The connector is described by a quadratic bezier curve from `start` to `end`.
+    pub fn control<P>(mut self, control: P) -> Self
+    where
+        P: Into<Point3<S>>,
+    {
+        self.control = Some(control.into());
+        self
+    }
+
+    /// The length of the heads along the connector.
+    ///
+    /// By default this is four times the stroke weight, and no less than `10.0`.
+    pub fn head_length(mut self, length: S) -> Self {
+        self.head_length = Some(length);
+        self
+    }
+
+    /// The width of the heads perpendicular to the connector.
+    ///
+    /// By default this is three times the stroke weight, and no less than `8.0`.
+    pub fn head_width(mut self, width: S) -> Self {
+        self.head_width = Some(width);
+        self
+    }
+
+    /// The head drawn at the start point. By default this is `Head::None`.
+    pub fn start_head(mut self, head: Head) -> Self {
+        self.start_head = head;
+        self
+    }
+
+    /// The head drawn at the end point. By default this is `Head::Triangle`.
+    pub fn end_head(mut self, head: Head) -> Self {
+        self.end_head = head;
+        self
+    }
+
+    /// Use the given head at both the start and end points.
+    pub fn heads(self, head: Head) -> Self {
+        self.start_head(head).end_head(head)
+    }
+}
+
+// Trait implementations.
+
+impl<S> IntoDrawn<S> for Arrow<S>
+where
+    S: BaseFloat,
+{
+    type Vertices = VerticesFromRanges;
+    type Indices = IndicesFromRange;
+
+    fn into_drawn(self, mut draw: Draw<S>) -> Drawn<S, Self::Vertices, Self::Indices> {
+        let Arrow {
+            position,
+            orientation,
+            color,
+            stroke,
+            start,
+            end,
+            control,
+            head_length,
+            head_width,
+            start_head,
+            end_head,
+        } = self;
+
+        let origin = || Point3::new(S::zero(), S::zero(), S::zero());
+        let start = start.unwrap_or_else(origin);
+        let end = end.unwrap_or_else(origin);
+        let (x_axis, y_axis) = plane_axes(start, end);
+
+        // Project the points into the local plane of the arrow.
+        let to_local = |p: Point3<S>| -> Point2<f32> {
+            let d = p - start;
+            let x = NumCast::from(d.dot(x_axis)).unwrap();
+            let y = NumCast::from(d.dot(y_axis)).unwrap();
+            pt2(x, y)
+        };
+
+        let weight = stroke.line_width;
+        let head_length = head_length
+            .map(|l| NumCast::from(l).unwrap())
+            .unwrap_or_else(|| default_head_length(weight));
+        let head_width = head_width
+            .map(|w| NumCast::from(w).unwrap())
+            .unwrap_or_else(|| default_head_width(weight));
+
+        let shape = Shape {
+            start: pt2(0.0, 0.0),
+            end: to_local(end),
+            control: control.map(to_local),
+            head_length,
+            head_width,
+            start_head,
+            end_head,
+        };
+
+        let (vertex_data_ranges, index_range, min_index) = draw.drawing_context(|ctxt| {
+            let DrawingContext {
+                mesh,
+                fill_tessellator,
+                stroke_tessellator,
+                ..
+            } = ctxt;
+            let tessellators = Tessellators {
+                fill: fill_tessellator,
+                stroke: stroke_tessellator,
+            };
+            let (vertex_data_ranges, index_range, min_index) =
+                shape.tessellate(mesh, tessellators, &stroke);
+            // Move the local tessellation into the plane of the arrow.
+            for p in &mut mesh.vertex_data.points[vertex_data_ranges.points.clone()] {
+                *p = start + x_axis * p.x + y_axis * p.y;
+            }
+            (vertex_data_ranges, index_range, min_index)
+        });
+
+        let color = color.or_else(|| Some(draw.theme().fill_lin_srgba(&theme::Primitive::Arrow)));
+        let vertices = VerticesFromRanges::new(vertex_data_ranges, color);
+        let indices = IndicesFromRange::new(index_range, min_index);
+        let dimensions = spatial::dimension::Properties::default();
+        let spatial = spatial::Properties {
+            dimensions,
+            orientation,
+            position,
+        };
+        (spatial, vertices, indices)
+    }
+}
+
+impl Shape {
+    // The unit vector pointing away from the connector at the start point.
+    fn start_direction(&self) -> Vector2<f32> {
+        let from = self.control.unwrap_or(self.end);
+        direction(from, self.start)
+            .or_else(|| direction(self.end, self.start))
+            .unwrap_or_else(|| -Vector2::unit_x())
+    }
+
+    // The unit vector pointing away from the connector at the end point.
+    fn end_direction(&self) -> Vector2<f32> {
+        let from = self.control.unwrap_or(self.start);
+        direction(from, self.end)
+            .or_else(|| direction(self.start, self.end))
+            .unwrap_or_else(Vector2::unit_x)
+    }
+
+    // How far the connector should be pulled back from the point so that it does not poke through
+    // the tip of the head.
+    fn inset(&self, head: Head) -> f32 {
+        match head {
+            Head::Triangle => self.head_length,
+            Head::None | Head::Open | Head::Dot | Head::Bar => 0.0,
+        }
+    }
+
+    // The path events describing the connector.
+    fn connector_events(&self) -> Option<Vec<PathEvent>> {
+        let length = (self.end - self.start).magnitude();
+        let start_inset = self.inset(self.start_head);
+        let end_inset = self.inset(self.end_head);
+        if start_inset + end_inset >= length {
+            return None;
+        }
+        let from = self.start - self.start_direction() * start_inset;
+        let to = self.end - self.end_direction() * end_inset;
+        let from: lyon::math::Point = from.into();
+        let to: lyon::math::Point = to.into();
+        let segment = match self.control {
+            None => PathEvent::Line(lyon::geom::LineSegment { from, to }),
+            Some(ctrl) => {
+                let ctrl = ctrl.into();
+                PathEvent::Quadratic(lyon::geom::QuadraticBezierSegment { from, ctrl, to })
+            }
+        };
+        Some(vec![PathEvent::MoveTo(from), segment])
+    }
+
+    // The outline of the given head with its tip at `tip`, where `dir` is the unit vector pointing
+    // away from the connector.
+    //
+    // Returns whether or not the outline is closed along with its points, or `None` for no head.
+    fn head_polyline(
+        &self,
+        head: Head,
+        tip: Point2<f32>,
+        dir: Vector2<f32>,
+    ) -> Option<(bool, Vec<Point2<f32>>)> {
+        let normal = Vector2::new(-dir.y, dir.x);
+        let half_width = self.head_width * 0.5;
+        let base = tip - dir * self.head_length;
+        let polyline = match head {
+            Head::None => return None,
+            Head::Triangle => {
+                let points = vec![tip, base + normal * half_width, base - normal * half_width];
+                (true, points)
+            }
+            Head::Open => {
+                let points = vec![base + normal * half_width, tip, base - normal * half_width];
+                (false, points)
+            }
+            Head::Dot => {
+                const RESOLUTION: usize = 32;
+                let points = (0..RESOLUTION)
+                    .map(|i| {
+                        let radians = i as f32 / RESOLUTION as f32 * std::f32::consts::PI * 2.0;
+                        tip + Vector2::new(radians.cos(), radians.sin()) * half_width
+                    })
+                    .collect::<Vec<_>>();
+                (true, points)
+            }
+            Head::Bar => {
+                let points = vec![tip + normal * half_width, tip - normal * half_width];
+                (false, points)
+            }
+        };
+        Some(polyline)
+    }
+
+    // Tessellate the connector and both heads into the intermediary mesh.
+    //
+    // Returns the ranges spanning all of the tessellated geometry.
+    fn tessellate<S>(
+        &self,
+        mesh: &mut draw::IntermediaryMesh<S>,
+        tessellators: Tessellators,
+        stroke: &StrokeOptions,
+    ) -> (draw::IntermediaryVertexDataRanges, ops::Range<usize>, usize)
+    where
+        S: BaseFloat,
+    {
+        let mut vertex_data_ranges = draw::IntermediaryVertexDataRanges::default();
+        vertex_data_ranges.points.start = mesh.vertex_data.points.len();
+        vertex_data_ranges.colors.start = mesh.vertex_data.colors.len();
+        vertex_data_ranges.tex_coords.start = mesh.vertex_data.tex_coords.len();
+        let mut index_range = mesh.indices.len()..mesh.indices.len();
+        let min_index = vertex_data_ranges.points.start;
+
+        let Tessellators {
+            fill: fill_tessellator,
+            stroke: stroke_tessellator,
+        } = tessellators;
+        let fill_opts = FillOptions::default();
+
+        // The connector.
+        if let Some(events) = self.connector_events() {
+            let mut builder = mesh.builder();
+            let res = stroke_tessellator.tessellate_path(events, stroke, &mut builder);
+            if let Err(err) = res {
+                eprintln!("arrow connector tessellation failed: {:?}", err);
+            }
+        }
+
+        // The heads.
+        let heads = [
+            (self.start_head, self.start, self.start_direction()),
+            (self.end_head, self.end, self.end_direction()),
+        ];
+        for &(head, tip, dir) in heads.iter() {
+            let (close, points) = match self.head_polyline(head, tip, dir) {
+                None => continue,
+                Some(polyline) => polyline,
+            };
+            let points = points.into_iter().map(Into::<lyon::math::Point>::into);
+            let events = lyon::path::iterator::FromPolyline::new(close, points).path_events();
+            let mut builder = mesh.builder();
+            let res = match head {
+                Head::Triangle | Head::Dot => {
+                    fill_tessellator.tessellate_path(events, &fill_opts, &mut builder)
+                }
+                _ => stroke_tessellator.tessellate_path(events, stroke, &mut builder),
+            };
+            if let Err(err) = res {
+                eprintln!("arrow head tessellation failed: {:?}", err);
+            }
+        }
+
+        vertex_data_ranges.points.end = mesh.vertex_data.points.len();
+        vertex_data_ranges.colors.end = mesh.vertex_data.colors.len();
+        vertex_data_ranges.tex_coords.end = mesh.vertex_data.tex_coords.len();
+        index_range.end = mesh.indices.len();
+        (vertex_data_ranges, index_range, min_index)
+    }
+}
+
+impl<S> Default for Arrow<S> {
+    fn default() -> Self {
+        Arrow {
+            position: Default::default(),
+            orientation: Default::default(),
+            color: Default::default(),
+            stroke: Default::default(),
+            start: Default::default(),
+            end: Default::default(),
+            control: Default::default(),
+            head_length: Default::default(),
+            head_width: Default::default(),
+            start_head: Head::None,
+            end_head: Head::Triangle,
+        }
+    }
+}
+
+impl<S> SetStroke for Arrow<S> {
+    fn stroke_options_mut(&mut self) -> &mut StrokeOptions {
+        &mut self.stroke
+    }
+}
+
+impl<S> SetOrientation<S> for Arrow<S> {
+    fn properties(&mut self) -> &mut orientation::Properties<S> {
+        SetOrientation::properties(&mut self.orientation)
+    }
+}
+
+impl<S> SetPosition<S> for Arrow<S> {
+    fn properties(&mut self) -> &mut position::Properties<S> {
+        SetPosition::properties(&mut self.position)
+    }
+}
+
+impl<S> SetColor<ColorScalar> for Arrow<S> {
+    fn rgba_mut(&mut self) -> &mut Option<LinSrgba> {
+        SetColor::rgba_mut(&mut self.color)
+    }
+}
+
+// Primitive conversion.
+
+impl<S> From<Arrow<S>> for Primitive<S> {
+    fn from(prim: Arrow<S>) -> Self {
+        Primitive::Arrow(prim)
+    }
+}
+
+impl<S> Into<Option<Arrow<S>>> for Primitive<S> {
+    fn into(self) -> Option<Arrow<S>> {
+        match self {
+            Primitive::Arrow(prim) => Some(prim),
+            _ => None,
+        }
+    }
+}
+
+// Drawing methods.
+
+impl<'a, S> DrawingArrow<'a, S>
+where
+    S: BaseFloat,
+{
+    /// Short-hand for the `stroke_weight` method.
+    pub fn weight(self, weight: f32) -> Self {
+        self.map_ty(|ty| ty.weight(weight))
+    }
+
+    /// Short-hand for the `stroke_tolerance` method.
+    pub fn tolerance(self, tolerance: f32) -> Self {
+        self.map_ty(|ty| ty.tolerance(tolerance))
+    }
+
+    /// Specify the start point of the arrow.
+    ///
+    /// Accepts both 2D and 3D points.
+    pub fn start<P>(self, start: P) -> Self
+    where
+        P: Into<Point3<S>>,
+    {
+        self.map_ty(|ty| ty.start(start))
+    }
+
+    /// Specify the end point of the arrow.
+    ///
+    /// Accepts both 2D and 3D points.
+    pub fn end<P>(self, end: P) -> Self
+    where
+        P: Into<Point3<S>>,
+    {
+        self.map_ty(|ty| ty.end(end))
+    }
+
+    /// Specify the start and end points of the arrow.
+    pub fn points<P>(self, start: P, end: P) -> Self
+    where
+        P: Into<Point3<S>>,
+    {
+        self.map_ty(|ty| ty.points(start, end))
+    }
+
+    /// Curve the connector toward the given control point.
+    ///
+    /// The connector is described by a quadratic bezier curve from `start` to `end`.
+    pub fn control<P>(self, control: P) -> Self
+    where
+        P: Into<Point3<S>>,
+    {
+        self.map_ty(|ty| ty.control(control))
+    }
+
+    /// The length of the heads along the connector.
+    ///
+    /// By default this is four times the stroke weight, and no less than `10.0`.
+    pub fn head_length(self, length: S) -> Self {
+        self.map_ty(|ty| ty.head_length(length))
+    }
+
+    /// The width of the heads perpendicular to the connector.
+    ///
+    /// By default this is three times the stroke weight, and no less than `8.0`.
+    pub fn head_width(self, width: S) -> Self {
+        self.map_ty(|ty| ty.head_width(width))
+    }
+
+    /// The head drawn at the start point. By default this is `Head::None`.
+    pub fn start_head(self, head: Head) -> Self {
+        self.map_ty(|ty| ty.start_head(head))
+    }
+
+    /// The head drawn at the end point. By default this is `Head::Triangle`.
+    pub fn end_head(self, head: Head) -> Self {
+        self.map_ty(|ty| ty.end_head(head))
+    }
+
+    /// Use the given head at both the start and end points.
+    pub fn heads(self, head: Head) -> Self {
+        self.map_ty(|ty| ty.heads(head))
+    }
+}
+
+// The head length used when none is specified, scaled with the stroke weight so that heads remain
+// visible on thick connectors.
+fn default_head_length(weight: f32) -> f32 {
+    const MIN_HEAD_LENGTH: f32 = 10.0;
+    MIN_HEAD_LENGTH.max(weight * 4.0)
+}
+
+// The head width used when none is specified, scaled with the stroke weight.
+fn default_head_width(weight: f32) -> f32 {
+    const MIN_HEAD_WIDTH: f32 = 8.0;
+    MIN_HEAD_WIDTH.max(weight * 3.0)
+}
+
+// The unit vector pointing from `a` to `b`, or `None` if the points are equal.
+fn direction(a: Point2<f32>, b: Point2<f32>) -> Option<Vector2<f32>> {
+    let d = b - a;
+    if d.is_zero() {
+        None
+    } else {
+        Some(d.normalize())
+    }
+}
+
+// The *x* and *y* axes of the plane in which the arrow from `start` to `end` is tessellated.
+//
+// The *x* axis runs along the connector while the *y* axis is perpendicular to both the connector
+// and the *z* axis, so that the plane faces the viewer as closely as possible.
+fn plane_axes<S>(start: Point3<S>, end: Point3<S>) -> (Vector3<S>, Vector3<S>)
+where
+    S: BaseFloat,
+{
+    let d = end - start;
+    let x_axis = if d.is_zero() {
+        Vector3::unit_x()
+    } else {
+        d.normalize()
+    };
+    let y_axis = Vector3::unit_z().cross(x_axis);
+    let y_axis = if y_axis.is_zero() {
+        Vector3::unit_y()
+    } else {
+        y_axis.normalize()
+    };
+    (x_axis, y_axis)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A horizontal arrow from the origin to `(100, 0)` with a 10 x 8 head at either end.
+    fn shape(start_head: Head, end_head: Head) -> Shape {
+        Shape {
+            start: pt2(0.0, 0.0),
+            end: pt2(100.0, 0.0),
+            control: None,
+            head_length: 10.0,
+            head_width: 8.0,
+            start_head,
+            end_head,
+        }
+    }
+
+    fn end_head(shape: &Shape) -> Option<(bool, Vec<Point2<f32>>)> {
+        shape.head_polyline(shape.end_head, shape.end, shape.end_direction())
+    }
+
+    #[test]
+    fn default_head_size_scales_with_weight() {
+        assert_eq!(default_head_length(1.0), 10.0);
+        assert_eq!(default_head_width(1.0), 8.0);
+        assert_eq!(default_head_length(5.0), 20.0);
+        assert_eq!(default_head_width(5.0), 15.0);
+    }
+
+    #[test]
+    fn default_heads() {
+        let arrow = Arrow::<f32>::default();
+        assert_eq!(arrow.start_head, Head::None);
+        assert_eq!(arrow.end_head, Head::Triangle);
+        assert!(arrow.head_length.is_none());
+        assert!(arrow.head_width.is_none());
+    }
+
+    #[test]
+    fn triangle_heads() {
+        let shape = shape(Head::Triangle, Head::Triangle);
+        let expected = vec![pt2(100.0, 0.0), pt2(90.0, 4.0), pt2(90.0, -4.0)];
+        assert_eq!(end_head(&shape), Some((true, expected)));
+        let start = shape.head_polyline(shape.start_head, shape.start, shape.start_direction());
+        let expected = vec![pt2(0.0, 0.0), pt2(10.0, -4.0), pt2(10.0, 4.0)];
+        assert_eq!(start, Some((true, expected)));
+    }
+
+    #[test]
+    fn open_bar_and_no_heads() {
+        let open = shape(Head::None, Head::Open);
+        let expected = vec![pt2(90.0, 4.0), pt2(100.0, 0.0), pt2(90.0, -4.0)];
+        assert_eq!(end_head(&open), Some((false, expected)));
+        let bar = shape(Head::None, Head::Bar);
+        let expected = vec![pt2(100.0, 4.0), pt2(100.0, -4.0)];
+        assert_eq!(end_head(&bar), Some((false, expected)));
+        assert_eq!(end_head(&shape(Head::None, Head::None)), None);
+    }
+
+    #[test]
+    fn dot_head_is_centred_on_the_point() {
+        let (close, points) = end_head(&shape(Head::None, Head::Dot)).unwrap();
+        assert!(close);
+        for p in points {
+            assert!(((p - pt2(100.0, 0.0)).magnitude() - 4.0).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn connector_is_inset_by_triangle_heads() {
+        let line_end = |shape: &Shape| match shape.connector_events().unwrap()[1] {
+            PathEvent::Line(ref segment) => (segment.from, segment.to),
+            ref event => panic!("unexpected event: {:?}", event),
+        };
+        let (from, to) = line_end(&shape(Head::None, Head::Triangle));
+        assert_eq!((from.x, from.y, to.x, to.y), (0.0, 0.0, 90.0, 0.0));
+        let (from, to) = line_end(&shape(Head::Triangle, Head::Open));
+        assert_eq!((from.x, from.y, to.x, to.y), (10.0, 0.0, 100.0, 0.0));
+
+        // The connector is omitted when the heads cover its entire length.
+        let mut short = shape(Head::Triangle, Head::Triangle);
+        short.end = pt2(20.0, 0.0);
+        assert!(short.connector_events().is_none());
+    }
+
+    #[test]
+    fn curved_heads_follow_the_control_point() {
+        let mut shape = shape(Head::None, Head::Triangle);
+        shape.control = Some(pt2(100.0, 100.0));
+        assert_eq!(shape.end_direction(), Vector2::new(0.0, -1.0));
+        let expected = vec![pt2(100.0, 0.0), pt2(104.0, 10.0), pt2(96.0, 10.0)];
+        assert_eq!(end_head(&shape), Some((true, expected)));
+    }
+
+    #[test]
+    fn plane_axes_face_the_viewer() {
+        let (x, y) = plane_axes(Point3::new(0.0, 0.0, 0.0), Point3::new(0.0, 5.0, 0.0));
+        assert_eq!(x, Vector3::new(0.0, 1.0, 0.0));
+        assert_eq!(y, Vector3::new(-1.0, 0.0, 0.0));
+        // A connector along the *z* axis falls back to the *y* axis.
+        let (x, y) = plane_axes(Point3::new(0.0, 0.0, 0.0), Point3::new(0.0, 0.0, 5.0));
+        assert_eq!(x, Vector3::new(0.0, 0.0, 1.0));
+        assert_eq!(y, Vector3::new(0.0, 1.0, 0.0));
+    }
+}
@@ -0,0 +1,198 @@
+use crate::draw::layer::BlendMode;
+use crate::draw::mesh::vertex::{self, Vertex};
+use crate::draw::primitive::Primitive;
+use crate::draw::properties::spatial::{self, dimension, orientation, position};
+use crate::draw::properties::{
+    ColorScalar, Draw, Drawn, IntoDrawn, SetDimensions, SetOrientation, SetPosition,
+};
+use crate::draw::{self, Drawing};
+use crate::geom::{self, Point2};
+use crate::math::BaseFloat;
+use crate::wgpu;
+
+/// Properties related to compositing a **Layer** via `Draw::layer`.
+#[derive(Clone, Debug)]
+pub struct Composite<S = geom::scalar::Default> {
+    position: position::Properties<S>,
+    orientation: orientation::Properties<S>,
+    dimensions: dimension::Properties<S>,
+    layer: draw::Layer,
+    blend: BlendMode,
+    opacity: ColorScalar,
+    sampler: wgpu::SamplerDescriptor,
+}
+
+/// The drawing context for a composited layer.
+pub type DrawingComposite<'a, S = geom::scalar::Default> = Drawing<'a, Composite<S>, S>;
+
+impl<S> Composite<S> {
+    /// Composite the given layer.
+    pub fn new(layer: &draw::Layer) -> Self {
+        Composite {
+            position: Default::default(),
+            orientation: Default::default(),
+            dimensions: Default::default(),
+            layer: layer.clone(),
+            blend: Default::default(),
+            opacity: 1.0,
+            sampler: wgpu::SamplerBuilder::DEFAULT_DESCRIPTOR,
+        }
+    }
+
+    /// Specify how the layer is blended with the geometry beneath it.
+    ///
+    /// By default, this is `BlendMode::Normal`.
+    pub fn blend(mut self, blend: BlendMode) -> Self {
+        self.blend = blend;
+        self
+    }
+
+    /// The opacity of the layer where `0.0` is fully transparent and `1.0` is fully opaque.
+    ///
+    /// By default, this is `1.0`.
+    pub fn opacity(mut self, opacity: ColorScalar) -> Self {
+        self.opacity = opacity;
+        self
+    }
+
+    /// Specify how the layer is sampled, e.g. `SamplerBuilder::new().filter(FilterMode::Nearest)`
+    /// for crisp pixel art. The address mode determines how the edges of the texture are sampled.
+    ///
+    /// By default, the layer is sampled with linear filtering and clamped to its edges.
+    pub fn sampler(mut self, sampler: wgpu::SamplerBuilder) -> Self {
+        self.sampler = sampler.into_descriptor();
+        self
+    }
+
+    /// The layer being composited.
+    pub(crate) fn layer(&self) -> &draw::Layer {
+        &self.layer
+    }
+
+    /// The blend mode with which the layer is composited.
+    pub(crate) fn blend_mode(&self) -> BlendMode {
+        self.blend
+    }
+
+    /// Describes the sampler with which the layer is sampled.
+    pub(crate) fn sampler_descriptor(&self) -> &wgpu::SamplerDescriptor {
+        &self.sampler
+    }
+}
+
+impl<'a, S> DrawingComposite<'a, S>
+where
+    S: BaseFloat,
+{
+    /// Specify how the layer is blended with the geometry beneath it.
+    ///
+    /// By default, this is `BlendMode::Normal`.
+    pub fn blend(self, blend: BlendMode) -> Self {
+        self.map_ty(|ty| ty.blend(blend))
+    }
+
+    /// The opacity of the layer where `0.0` is fully transparent and `1.0` is fully opaque.
+    ///
+    /// By default, this is `1.0`.
+    pub fn opacity(self, opacity: ColorScalar) -> Self {
+        self.map_ty(|ty| ty.opacity(opacity))
+    }
+
+    /// Specify how the layer is sampled, e.g. `SamplerBuilder::new().filter(FilterMode::Nearest)`
+    /// for crisp pixel art. The address mode determines how the edges of the texture are sampled.
+    ///
+    /// By default, the layer is sampled with linear filtering and clamped to its edges.
+    pub fn sampler(self, sampler: wgpu::SamplerBuilder) -> Self {
+        self.map_ty(|ty| ty.sampler(sampler))
+    }
+}
+
+impl<S> IntoDrawn<S> for Composite<S>
+where
+    S: BaseFloat,
+{
+    type Vertices = std::vec::IntoIter<Vertex<S>>;
+    type Indices = std::iter::Cloned<std::slice::Iter<'static, usize>>;
+    fn into_drawn(self, draw: Draw<S>) -> Drawn<S, Self::Vertices, Self::Indices> {
+        let Composite {
+            position,
+            orientation,
+            dimensions,
+            layer,
+            opacity,
+            ..
+        } = self;
+
+        // By default, the layer is drawn at its size in points.
+        let (maybe_x, maybe_y, maybe_z) = dimensions.to_scalars(&draw);
+        assert!(
+            maybe_z.is_none(),
+            "z dimension support for layer is unimplemented"
+        );
+        let [layer_w, layer_h] = layer.size_points();
+        let w = maybe_x.unwrap_or_else(|| S::from(layer_w).unwrap());
+        let h = maybe_y.unwrap_or_else(|| S::from(layer_h).unwrap());
+        let rect = geom::Rect::from_w_h(w, h);
+
+        // The layer's opacity is applied to all channels as the texture is premultiplied.
+        let color = vertex::Color::new(opacity, opacity, opacity, opacity);
+        let (zero, one) = (S::zero(), S::one());
+        // Texture coordinates begin at the top left of the texture.
+        let corners = [
+            (rect.top_left(), Point2 { x: zero, y: zero }),
+            (rect.bottom_left(), Point2 { x: zero, y: one }),
+            (rect.bottom_right(), Point2 { x: one, y: one }),
+            (rect.top_right(), Point2 { x: one, y: zero }),
+        ];
+        let vertices: Vec<_> = corners
+            .iter()
+            .map(|&(p, tex_coords)| {
+                let point = [p.x, p.y, zero].into();
+                vertex::new(point, color, tex_coords)
+            })
+            .collect();
+        static INDICES: [usize; 6] = [0, 1, 2, 0, 2, 3];
+        let indices = INDICES.iter().cloned();
+
+        let dimensions = Default::default();
+        let spatial = spatial::Properties {
+            dimensions,
+            orientation,
+            position,
+        };
+        (spatial, vertices.into_iter(), indices)
+    }
+}
+
+impl<S> SetOrientation<S> for Composite<S> {
+    fn properties(&mut self) -> &mut orientation::Properties<S> {
+        SetOrientation::properties(&mut self.orientation)
+    }
+}
+
+impl<S> SetPosition<S> for Composite<S> {
+    fn properties(&mut self) -> &mut position::Properties<S> {
+        SetPosition::properties(&mut self.position)
+    }
+}
+
+impl<S> SetDimensions<S> for Composite<S> {
+    fn properties(&mut self) -> &mut dimension::Properties<S> {
+        SetDimensions::properties(&mut self.dimensions)
+    }
+}
+
+impl<S> From<Composite<S>> for Primitive<S> {
+    fn from(prim: Composite<S>) -> Self {
+        Primitive::Composite(prim)
+    }
+}
+
+impl<S> Into<Option<Composite<S>>> for Primitive<S> {
+    fn into(self) -> Option<Composite<S>> {
+        match self {
+            Primitive::Composite(prim) => Some(prim),
+            _ => None,
+        }
+    }
+}
@@ -1,3 +1,5 @@
+pub mod arrow;
+pub mod composite;
 pub mod ellipse;
 pub mod line;
 pub mod mesh;
@@ -5,11 +7,15 @@ pub mod path;
 pub mod polygon;
 pub mod quad;
 pub mod rect;
+pub mod retained;
+pub mod sprite;
 pub mod text;
 pub mod tri;
 
 use crate::geom;
 
+pub use self::arrow::Arrow;
+pub use self::composite::Composite;
 pub use self::ellipse::Ellipse;
 pub use self::line::Line;
 pub use self::mesh::Mesh;
@@ -17,6 +23,8 @@ pub use self::path::{Path, PathFill, PathInit, PathStroke};
 pub use self::polygon::{Polygon, PolygonInit};
 pub use self::quad::Quad;
 pub use self::rect::Rect;
+pub use self::retained::Retained;
+pub use self::sprite::Sprite;
 pub use self::text::Text;
 pub use self::tri::Tri;
 
@@ -27,6 +35,8 @@ pub use self::tri::Tri;
 /// before their respective **Drawing** types are dropped.
 #[derive(Clone, Debug)]
 pub enum Primitive<S = geom::scalar::Default> {
+    Arrow(Arrow<S>),
+    Composite(Composite<S>),
     Ellipse(Ellipse<S>),
     Line(Line<S>),
     MeshVertexless(mesh::Vertexless),
@@ -39,6 +49,8 @@ pub enum Primitive<S = geom::scalar::Default> {
     Polygon(Polygon<S>),
     Quad(Quad<S>),
     Rect(Rect<S>),
+    Retained(Retained<S>),
+    Sprite(Sprite<S>),
     Text(Text<S>),
     Tri(Tri<S>),
 }
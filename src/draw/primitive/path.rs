@@ -195,13 +195,13 @@ where
         let DrawingContext {
             mesh,
             fill_tessellator,
+            stroke_tessellator,
             ..
         } = ctxt;
         let color = Cell::new(None);
-        let stroke = &mut StrokeTessellator::default();
         let tessellators = Tessellators {
             fill: fill_tessellator,
-            stroke,
+            stroke: stroke_tessellator,
         };
         let mut tessellator = T::tessellator(tessellators);
         let mut builder = mesh.builder();
@@ -266,6 +266,7 @@ where
         let DrawingContext {
             mesh,
             fill_tessellator,
+            stroke_tessellator,
             ..
         } = ctxt;
         let color = Cell::new(None);
@@ -274,10 +275,9 @@ where
             lyon::math::point(p.x, p.y)
         });
         let events = lyon::path::iterator::FromPolyline::new(close, iter).path_events();
-        let stroke = &mut StrokeTessellator::default();
         let tessellators = Tessellators {
             fill: fill_tessellator,
-            stroke,
+            stroke: stroke_tessellator,
         };
         let mut tessellator = T::tessellator(tessellators);
         let mut builder = mesh.builder();
@@ -318,6 +318,7 @@ where
         let DrawingContext {
             mesh,
             fill_tessellator,
+            stroke_tessellator,
             ..
         } = ctxt;
         let color = Cell::new(None);
@@ -327,10 +328,9 @@ where
             lyon::math::point(p.x, p.y)
         });
         let events = lyon::path::iterator::FromPolyline::new(close, iter).path_events();
-        let stroke = &mut StrokeTessellator::default();
         let tessellators = Tessellators {
             fill: fill_tessellator,
-            stroke,
+            stroke: stroke_tessellator,
         };
         let mut tessellator = T::tessellator(tessellators);
         let mut builder = mesh.builder();
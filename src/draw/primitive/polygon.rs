@@ -11,7 +11,7 @@ use crate::geom::{self, Point2};
 use crate::math::BaseFloat;
 use lyon::path::iterator::FlattenedIterator;
 use lyon::path::PathEvent;
-use lyon::tessellation::{StrokeOptions, StrokeTessellator};
+use lyon::tessellation::StrokeOptions;
 use std::ops;
 
 /// A trait implemented for all polygon draw primitives.
@@ -105,6 +105,7 @@ impl<S> PolygonInit<S> {
         let DrawingContext {
             mesh,
             fill_tessellator,
+            stroke_tessellator,
             path_event_buffer,
             ..
         } = ctxt;
@@ -140,7 +141,6 @@ impl<S> PolygonInit<S> {
             (options, color) if options.is_some() || color.is_some() => {
                 let opts = options.unwrap_or_else(Default::default);
                 let mut builder = mesh.builder();
-                let mut stroke_tessellator = StrokeTessellator::default();
                 let events = path_event_buffer.drain(..);
                 let res = stroke_tessellator.tessellate_path(events, &opts, &mut builder);
                 if let Err(err) = res {
@@ -0,0 +1,127 @@
+use crate::draw::mesh::vertex::Vertex;
+use crate::draw::primitive::Primitive;
+use crate::draw::properties::spatial::{self, orientation, position};
+use crate::draw::properties::{Draw, Drawn, IntoDrawn, SetOrientation, SetPosition};
+use crate::draw::{self, Drawing};
+use crate::geom;
+use crate::math::BaseFloat;
+use crate::mesh::GetVertex;
+use std::sync::Arc;
+
+/// Properties related to re-drawing geometry that was tessellated via `Draw::record`.
+#[derive(Clone, Debug)]
+pub struct Retained<S = geom::scalar::Default> {
+    position: position::Properties<S>,
+    orientation: orientation::Properties<S>,
+    mesh: Arc<draw::Mesh<S>>,
+}
+
+/// The drawing context for retained geometry.
+pub type DrawingRetained<'a, S = geom::scalar::Default> = Drawing<'a, Retained<S>, S>;
+
+/// An iterator yielding the vertices of a retained mesh.
+#[derive(Clone, Debug)]
+pub struct RetainedVertices<S = geom::scalar::Default> {
+    mesh: Arc<draw::Mesh<S>>,
+    index: usize,
+}
+
+/// An iterator yielding the indices of a retained mesh.
+#[derive(Clone, Debug)]
+pub struct RetainedIndices<S = geom::scalar::Default> {
+    mesh: Arc<draw::Mesh<S>>,
+    index: usize,
+}
+
+impl<S> Retained<S> {
+    /// Re-draw the geometry of the given **Cached** handle.
+    pub fn new(cached: &draw::Cached<S>) -> Self
+    where
+        S: BaseFloat,
+    {
+        Retained {
+            position: Default::default(),
+            orientation: Default::default(),
+            mesh: cached.shared_mesh(),
+        }
+    }
+}
+
+impl<S> IntoDrawn<S> for Retained<S>
+where
+    S: BaseFloat,
+{
+    type Vertices = RetainedVertices<S>;
+    type Indices = RetainedIndices<S>;
+    fn into_drawn(self, _draw: Draw<S>) -> Drawn<S, Self::Vertices, Self::Indices> {
+        let Retained {
+            position,
+            orientation,
+            mesh,
+        } = self;
+        let dimensions = spatial::dimension::Properties::default();
+        let spatial = spatial::Properties {
+            dimensions,
+            orientation,
+            position,
+        };
+        let vertices = RetainedVertices {
+            mesh: mesh.clone(),
+            index: 0,
+        };
+        let indices = RetainedIndices { mesh, index: 0 };
+        (spatial, vertices, indices)
+    }
+}
+
+impl<S> Iterator for RetainedVertices<S>
+where
+    S: BaseFloat,
+{
+    type Item = Vertex<S>;
+    fn next(&mut self) -> Option<Self::Item> {
+        let vertex = self.mesh.get_vertex(self.index);
+        if vertex.is_some() {
+            self.index += 1;
+        }
+        vertex
+    }
+}
+
+impl<S> Iterator for RetainedIndices<S> {
+    type Item = usize;
+    fn next(&mut self) -> Option<Self::Item> {
+        let index = self.mesh.indices().get(self.index).cloned();
+        if index.is_some() {
+            self.index += 1;
+        }
+        index
+    }
+}
+
+impl<S> SetOrientation<S> for Retained<S> {
+    fn properties(&mut self) -> &mut orientation::Properties<S> {
+        SetOrientation::properties(&mut self.orientation)
+    }
+}
+
+impl<S> SetPosition<S> for Retained<S> {
+    fn properties(&mut self) -> &mut position::Properties<S> {
+        SetPosition::properties(&mut self.position)
+    }
+}
+
+impl<S> From<Retained<S>> for Primitive<S> {
+    fn from(prim: Retained<S>) -> Self {
+        Primitive::Retained(prim)
+    }
+}
+
+impl<S> Into<Option<Retained<S>>> for Primitive<S> {
+    fn into(self) -> Option<Retained<S>> {
+        match self {
+            Primitive::Retained(prim) => Some(prim),
+            _ => None,
+        }
+    }
+}
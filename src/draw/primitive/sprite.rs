@@ -0,0 +1,174 @@
+use crate::draw::mesh::vertex::{self, Vertex};
+use crate::draw::primitive::Primitive;
+use crate::draw::properties::spatial::{self, dimension, orientation, position};
+use crate::draw::properties::{
+    ColorScalar, Draw, Drawn, IntoDrawn, LinSrgba, SetColor, SetDimensions, SetOrientation,
+    SetPosition,
+};
+use crate::draw::Drawing;
+use crate::geom::{self, Point2};
+use crate::math::BaseFloat;
+use crate::wgpu;
+
+/// Properties related to drawing a region of a **TextureAtlas** via `Draw::sprite`.
+///
+/// Sprites are flat, so any depth specified via the dimension methods is ignored.
+#[derive(Clone, Debug)]
+pub struct Sprite<S = geom::scalar::Default> {
+    position: position::Properties<S>,
+    orientation: orientation::Properties<S>,
+    dimensions: dimension::Properties<S>,
+    atlas: wgpu::TextureAtlas,
+    region: wgpu::TextureAtlasRegion,
+    color: Option<LinSrgba>,
+    sampler: wgpu::SamplerDescriptor,
+}
+
+/// The drawing context for a sprite.
+pub type DrawingSprite<'a, S = geom::scalar::Default> = Drawing<'a, Sprite<S>, S>;
+
+impl<S> Sprite<S> {
+    /// Draw the given region of the atlas.
+    pub fn new(atlas: &wgpu::TextureAtlas, region: wgpu::TextureAtlasRegion) -> Self {
+        Sprite {
+            position: Default::default(),
+            orientation: Default::default(),
+            dimensions: Default::default(),
+            atlas: atlas.clone(),
+            region,
+            color: None,
+            sampler: wgpu::SamplerBuilder::DEFAULT_DESCRIPTOR,
+        }
+    }
+
+    /// Specify how the atlas is sampled, e.g. `SamplerBuilder::new().filter(FilterMode::Nearest)`
+    /// for crisp pixel art. The address mode determines how the edges of the texture are sampled.
+    ///
+    /// By default, the atlas is sampled with linear filtering and clamped to its edges.
+    pub fn sampler(mut self, sampler: wgpu::SamplerBuilder) -> Self {
+        self.sampler = sampler.into_descriptor();
+        self
+    }
+
+    /// The atlas from which the sprite is sampled.
+    pub(crate) fn atlas(&self) -> &wgpu::TextureAtlas {
+        &self.atlas
+    }
+
+    /// Describes the sampler with which the atlas is sampled.
+    pub(crate) fn sampler_descriptor(&self) -> &wgpu::SamplerDescriptor {
+        &self.sampler
+    }
+}
+
+impl<'a, S> DrawingSprite<'a, S>
+where
+    S: BaseFloat,
+{
+    /// Specify how the atlas is sampled, e.g. `SamplerBuilder::new().filter(FilterMode::Nearest)`
+    /// for crisp pixel art. The address mode determines how the edges of the texture are sampled.
+    ///
+    /// By default, the atlas is sampled with linear filtering and clamped to its edges.
+    pub fn sampler(self, sampler: wgpu::SamplerBuilder) -> Self {
+        self.map_ty(|ty| ty.sampler(sampler))
+    }
+}
+
+impl<S> IntoDrawn<S> for Sprite<S>
+where
+    S: BaseFloat,
+{
+    type Vertices = std::vec::IntoIter<Vertex<S>>;
+    type Indices = std::iter::Cloned<std::slice::Iter<'static, usize>>;
+    fn into_drawn(self, draw: Draw<S>) -> Drawn<S, Self::Vertices, Self::Indices> {
+        let Sprite {
+            position,
+            orientation,
+            dimensions,
+            atlas,
+            region,
+            color,
+            ..
+        } = self;
+
+        // By default, the sprite is drawn at the size of its region in pixels. Sprites are flat, so
+        // any depth is ignored rather than treated as an error.
+        let (maybe_x, maybe_y, _) = dimensions.to_scalars(&draw);
+        let w = maybe_x.unwrap_or_else(|| S::from(region.w).unwrap());
+        let h = maybe_y.unwrap_or_else(|| S::from(region.h).unwrap());
+        let rect = geom::Rect::from_w_h(w, h);
+
+        // The atlas is premultiplied, so the tint is premultiplied to match. White by default.
+        let color = color.unwrap_or_else(|| LinSrgba::new(1.0, 1.0, 1.0, 1.0));
+        let a = color.alpha;
+        let color = vertex::Color::new(color.red * a, color.green * a, color.blue * a, a);
+        let [[l, t], [r, b]] = region.tex_coords(atlas.size());
+        let tc = |x: f32, y: f32| Point2 {
+            x: S::from(x).unwrap(),
+            y: S::from(y).unwrap(),
+        };
+        let corners = [
+            (rect.top_left(), tc(l, t)),
+            (rect.bottom_left(), tc(l, b)),
+            (rect.bottom_right(), tc(r, b)),
+            (rect.top_right(), tc(r, t)),
+        ];
+        let zero = S::zero();
+        let vertices: Vec<_> = corners
+            .iter()
+            .map(|&(p, tex_coords)| {
+                let point = [p.x, p.y, zero].into();
+                vertex::new(point, color, tex_coords)
+            })
+            .collect();
+        static INDICES: [usize; 6] = [0, 1, 2, 0, 2, 3];
+        let indices = INDICES.iter().cloned();
+
+        let dimensions = Default::default();
+        let spatial = spatial::Properties {
+            dimensions,
+            orientation,
+            position,
+        };
+        (spatial, vertices.into_iter(), indices)
+    }
+}
+
+impl<S> SetColor<ColorScalar> for Sprite<S> {
+    fn rgba_mut(&mut self) -> &mut Option<LinSrgba> {
+        SetColor::rgba_mut(&mut self.color)
+    }
+}
+
+impl<S> SetOrientation<S> for Sprite<S> {
+    fn properties(&mut self) -> &mut orientation::Properties<S> {
+        SetOrientation::properties(&mut self.orientation)
+    }
+}
+
+impl<S> SetPosition<S> for Sprite<S> {
+    fn properties(&mut self) -> &mut position::Properties<S> {
+        SetPosition::properties(&mut self.position)
+    }
+}
+
+impl<S> SetDimensions<S> for Sprite<S> {
+    fn properties(&mut self) -> &mut dimension::Properties<S> {
+        SetDimensions::properties(&mut self.dimensions)
+    }
+}
+
+impl<S> From<Sprite<S>> for Primitive<S> {
+    fn from(prim: Sprite<S>) -> Self {
+        Primitive::Sprite(prim)
+    }
+}
+
+impl<S> Into<Option<Sprite<S>>> for Primitive<S> {
+    fn into(self) -> Option<Sprite<S>> {
+        match self {
+            Primitive::Sprite(prim) => Some(prim),
+            _ => None,
+        }
+    }
+}
@@ -268,6 +268,7 @@ where
             let DrawingContext {
                 mesh,
                 fill_tessellator,
+                stroke_tessellator,
                 path_event_buffer,
                 text_buffer,
                 glyph_cache,
@@ -289,6 +290,7 @@ where
             let ctxt = DrawingContext {
                 mesh,
                 fill_tessellator,
+                stroke_tessellator,
                 path_event_buffer,
                 glyph_cache,
                 text_buffer: &mut empty_text,
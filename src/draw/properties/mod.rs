@@ -164,6 +164,7 @@ where
         let super::IntermediaryState {
             ref mut intermediary_mesh,
             ref mut fill_tessellator,
+            ref mut stroke_tessellator,
             ref mut path_event_buffer,
             ref mut text_buffer,
             ref mut glyph_cache,
@@ -171,6 +172,7 @@ where
         f(DrawingContext {
             mesh: intermediary_mesh,
             fill_tessellator: &mut fill_tessellator.0,
+            stroke_tessellator: &mut stroke_tessellator.0,
             path_event_buffer: path_event_buffer,
             text_buffer: text_buffer,
             glyph_cache: glyph_cache,
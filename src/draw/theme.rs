@@ -23,6 +23,7 @@ pub struct Color {
 /// These are used as keys into the **Theme**'s geometry primitive default values.
 #[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
 pub enum Primitive {
+    Arrow,
     Cuboid,
     Ellipse,
     Line,
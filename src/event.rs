@@ -6,6 +6,7 @@
 //!   newcomer-friendly version of the **raw**, low-level winit event.
 
 use crate::geom::{self, Point2, Vector2};
+use crate::serde_derive::{Deserialize, Serialize};
 use crate::window;
 use crate::App;
 use std::path::PathBuf;
@@ -20,10 +21,18 @@ pub use winit::event::{
 pub trait LoopEvent: 'static + From<Update> {
     /// Produce a loop event from the given winit event.
     fn from_winit_event<'a, T>(_: &winit::event::Event<'a, T>, _: &App) -> Option<Self>;
+
+    /// Produce a loop event from a simplified window event that did not originate from winit,
+    /// e.g. one replayed from a `record::Recording`.
+    ///
+    /// By default, no loop event is produced.
+    fn from_window_event(_id: window::Id, _event: &WindowEvent, _app: &App) -> Option<Self> {
+        None
+    }
 }
 
 /// Update event, emitted on each pass of an application loop.
-#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct Update {
     /// The duration since the last update was emitted.
     ///
@@ -64,7 +73,7 @@ pub enum Event {
 }
 
 /// The event associated with a touch at a single point.
-#[derive(Copy, Clone, Debug, PartialEq)]
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct TouchEvent {
     /// The unique ID associated with this touch, e.g. useful for distinguishing between fingers.
     pub id: u64,
@@ -74,6 +83,41 @@ pub struct TouchEvent {
     pub position: Point2<geom::scalar::Default>,
 }
 
+/// A higher-level gesture recognised from one or more touches.
+///
+/// Gestures are recognised by the **App**'s `touches` state. See `state::touch` for details.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Gesture {
+    /// One or more touches moved together by the given delta.
+    ///
+    /// For more than one touch, the delta describes the movement of their centroid.
+    Pan {
+        delta: Vector2<geom::scalar::Default>,
+        touches: usize,
+    },
+    /// The distance between two touches changed by the given factor, e.g. `2.0` if the touches
+    /// moved twice as far apart.
+    Pinch {
+        center: Point2<geom::scalar::Default>,
+        scale: geom::scalar::Default,
+    },
+    /// Two touches rotated about their centre by the given angle in radians.
+    ///
+    /// Positive values describe counter-clockwise rotation.
+    Rotate {
+        center: Point2<geom::scalar::Default>,
+        radians: geom::scalar::Default,
+    },
+    /// A single touch was briefly pressed and released without moving.
+    Tap(Point2<geom::scalar::Default>),
+    /// A tap occurred shortly after and near to a previous tap.
+    ///
+    /// This is emitted following the `Tap` for the second touch.
+    DoubleTap(Point2<geom::scalar::Default>),
+    /// A single touch was held in place.
+    LongPress(Point2<geom::scalar::Default>),
+}
+
 /// Pressure on a touch pad.
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub struct TouchpadPressure {
@@ -106,7 +150,7 @@ pub struct AxisMotion {
 /// - positive `x` points to the right, negative `x` points to the left.
 /// - positive `y` points upwards, negative `y` points downwards.
 /// - positive `z` points into the screen, negative `z` points out of the screen.
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum WindowEvent {
     /// The window has been moved to a new position.
     Moved(Point2<geom::scalar::Default>),
@@ -155,6 +199,9 @@ pub enum WindowEvent {
     /// At the moment, only supported on Apple forcetouch-capable macbooks.
     /// The parameters are: pressure level (value between 0 and 1 representing how hard the touchpad
     /// is being pressed) and stage (integer representing the click level).
+    ///
+    /// As this event refers to a specific device, it cannot be serialized.
+    #[serde(skip)]
     TouchPressure(TouchpadPressure),
 
     /// The window gained focus.
@@ -337,6 +384,12 @@ impl LoopEvent for Event {
         };
         Some(event)
     }
+
+    /// Produce a nannou `Event::WindowEvent` from the given simplified window event.
+    fn from_window_event(id: window::Id, event: &WindowEvent, _app: &App) -> Option<Self> {
+        let simple = Some(event.clone());
+        Some(Event::WindowEvent { id, simple })
+    }
 }
 
 impl From<Update> for Event {
@@ -1,10 +1,14 @@
 //! Items related to the **Frame** type, describing a single frame of graphics for a single window.
 
 use crate::color::IntoLinSrgba;
+use crate::io;
+use crate::rand;
+use crate::serde_derive::{Deserialize, Serialize};
 use crate::wgpu;
-use std::ops;
-use std::path::PathBuf;
-use std::sync::Mutex;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{self, AtomicU64};
+use std::sync::{Arc, Mutex};
+use std::{fmt, ops};
 
 pub mod raw;
 
@@ -27,27 +31,56 @@ pub struct Frame<'swap_chain> {
 /// Data specific to the intermediary textures.
 #[derive(Debug)]
 pub struct RenderData {
+    // Whether or not the window was built with a frame intermediary.
+    intermediary: bool,
     intermediary_lin_srgba: IntermediaryLinSrgba,
     msaa_samples: u32,
     size: [u32; 2],
-    // For writing the intermediary linear sRGBA texture to the swap chain texture.
-    texture_reshaper: wgpu::TextureReshaper,
+    texture_format: wgpu::TextureFormat,
+    // Whether or not the frame's texture is cleared at the beginning of each frame.
+    clear: bool,
+    // For writing the intermediary linear sRGBA texture to the swap chain texture. `None` if the
+    // MSAA texture is resolved directly to the swap chain texture.
+    texture_reshaper: Option<wgpu::TextureReshaper>,
+    // The tonemapping applied by the reshaper and whether it has changed since it was applied.
+    tonemapping: Mutex<(wgpu::Tonemapping, bool)>,
 }
 
 /// Data related to the capturing of a frame.
-#[derive(Debug, Default)]
+#[derive(Debug)]
 pub(crate) struct CaptureData {
     pub(crate) next_frame_path: Mutex<Option<(PathBuf, bool)>>,
     texture_capturer: wgpu::TextureCapturer,
+    // The seed of the app's random number generator, if metadata is written alongside captures.
+    seed: Option<Arc<AtomicU64>>,
+}
+
+/// Metadata describing how a captured frame was produced.
+///
+/// When enabled via `app::Builder::capture_metadata`, the metadata is written alongside each
+/// captured image, e.g. for an image captured to `frame_0001.png` the metadata is written to
+/// `frame_0001.json`. Run the sketch with the recorded seed (e.g. via `--seed`) to regenerate the
+/// frame exactly.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CaptureMetadata {
+    /// The seed of the app's random number generator at the time of capture.
+    pub seed: u64,
+    /// The algorithm used by the app's random number generator, e.g. `"ChaCha8"`.
+    pub rng: String,
 }
 
 /// Intermediary textures used as a target before resolving multisampling and writing to the
 /// swapchain texture.
+///
+/// If the window opts out of the intermediary and the MSAA texture exists, `texture` is `None`
+/// and the MSAA texture is resolved directly to the swapchain texture. In this case, `readback`
+/// is a single-sampled texture into which the MSAA texture is only resolved when the frame is
+/// captured or read. Otherwise, `readback` is `None` and `texture` always exists.
 #[derive(Debug)]
 pub(crate) struct IntermediaryLinSrgba {
     msaa_texture: Option<(wgpu::Texture, wgpu::TextureView)>,
-    texture: wgpu::Texture,
-    texture_view: wgpu::TextureView,
+    texture: Option<(wgpu::Texture, wgpu::TextureView)>,
+    readback: Option<(wgpu::Texture, wgpu::TextureView)>,
 }
 
 impl<'swap_chain> ops::Deref for Frame<'swap_chain> {
@@ -67,6 +100,12 @@ impl<'swap_chain> Frame<'swap_chain> {
     /// converting from the linear representation to the swapchain format (normally a non-linear
     /// representation).
     pub const TEXTURE_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Unorm;
+    /// The texture format used by the intermediary linear sRGBA image when HDR is enabled for the
+    /// window.
+    ///
+    /// Unlike `TEXTURE_FORMAT`, colors are not clamped to the `[0.0, 1.0]` range, allowing for
+    /// additive blending to exceed `1.0`. The result is tonemapped when written to the swapchain.
+    pub const HDR_TEXTURE_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
 
     // Initialise a new empty frame ready for "drawing".
     pub(crate) fn new_empty(
@@ -74,11 +113,16 @@ impl<'swap_chain> Frame<'swap_chain> {
         render_data: &'swap_chain RenderData,
         capture_data: &'swap_chain CaptureData,
     ) -> Self {
-        Frame {
+        let frame = Frame {
             raw_frame,
             render_data,
             capture_data,
+        };
+        if render_data.clear {
+            let color = wgpu::Color::TRANSPARENT;
+            wgpu::clear_texture(frame.texture_view(), color, &mut *frame.command_encoder());
         }
+        frame
     }
 
     // The private implementation of `submit`, allowing it to be called during `drop` if submission
@@ -90,74 +134,81 @@ impl<'swap_chain> Frame<'swap_chain> {
             ref mut raw_frame,
         } = *self;
 
-        // Resolve the MSAA if necessary.
-        if let Some((_, ref msaa_texture_view)) = render_data.intermediary_lin_srgba.msaa_texture {
+        // Resolve the MSAA if necessary, directly to the swapchain image if there is no
+        // intermediary.
+        let intermediary = &render_data.intermediary_lin_srgba;
+        if let Some((_, ref msaa_texture_view)) = intermediary.msaa_texture {
+            let resolve_target = match intermediary.texture {
+                Some((_, ref texture_view)) => texture_view,
+                None => raw_frame.swap_chain_texture(),
+            };
             let mut encoder = raw_frame.command_encoder();
-            wgpu::resolve_texture(
-                msaa_texture_view,
-                &render_data.intermediary_lin_srgba.texture_view,
-                &mut *encoder,
-            );
+            wgpu::resolve_texture(msaa_texture_view, resolve_target, &mut *encoder);
         }
 
         // Check to see if the user specified capturing the frame.
+        //
+        // Without an intermediary, the frame is captured from its texture as drawn. Otherwise, the
+        // intermediary is captured with the same tonemapping as is applied to the swapchain image
+        // so that captured images match what is displayed.
         let mut snapshot_capture = None;
         if let Ok(mut guard) = capture_data.next_frame_path.lock() {
             if let Some((path, threaded)) = guard.take() {
                 let device = raw_frame.device_queue_pair().device();
                 let mut encoder = raw_frame.command_encoder();
-                let snapshot = capture_data.texture_capturer.capture(
-                    device,
-                    &mut *encoder,
-                    &render_data.intermediary_lin_srgba.texture,
-                );
-                snapshot_capture = Some((path, threaded, snapshot));
+                let texture = match intermediary.readback {
+                    Some(_) => intermediary.encode_resolve_readable(&mut *encoder),
+                    None => intermediary.resolved_texture(),
+                };
+                let capturer = &capture_data.texture_capturer;
+                let snapshot = match render_data.intermediary {
+                    false => capturer.capture(device, &mut *encoder, texture),
+                    true => {
+                        let tonemapping = render_data.tonemapping();
+                        capturer.capture_tonemapped(device, &mut *encoder, texture, tonemapping)
+                    }
+                };
+                let metadata = capture_data.seed.as_ref().map(|seed| CaptureMetadata {
+                    seed: seed.load(atomic::Ordering::Relaxed),
+                    rng: rand::SeededRng::ALGORITHM.to_string(),
+                });
+                snapshot_capture = Some((path, threaded, snapshot, metadata));
             }
         }
 
         // Convert the linear sRGBA image to the swapchain image.
         //
         // To do so, we sample the linear sRGBA image and draw it to the swapchain image using
-        // two triangles and a fragment shader.
-        {
+        // two triangles and a fragment shader, tonemapping the colors along the way.
+        if let Some(ref texture_reshaper) = render_data.texture_reshaper {
             let mut encoder = raw_frame.command_encoder();
-            render_data
-                .texture_reshaper
-                .encode_render_pass(raw_frame.swap_chain_texture(), &mut *encoder);
+            if let Ok(mut guard) = render_data.tonemapping.lock() {
+                let (tonemapping, ref mut changed) = *guard;
+                if *changed {
+                    let device = raw_frame.device_queue_pair().device();
+                    texture_reshaper.encode_update_tonemapping(device, &mut *encoder, tonemapping);
+                    *changed = false;
+                }
+            }
+            texture_reshaper.encode_render_pass(raw_frame.swap_chain_texture(), &mut *encoder);
         }
 
         // Submit all commands on the device queue.
         raw_frame.submit_inner();
 
         // If the user did specify capturing the frame, submit the asynchronous read.
-        if let Some((path, threaded, snapshot)) = snapshot_capture {
+        if let Some((path, threaded, snapshot, metadata)) = snapshot_capture {
             match threaded {
                 false => {
                     snapshot.read(move |result| match result {
                         Err(e) => eprintln!("failed to async read captured frame: {:?}", e),
-                        Ok(image) => {
-                            if let Err(e) = image.save(&path) {
-                                eprintln!(
-                                    "failed to save captured frame to \"{}\": {}",
-                                    path.display(),
-                                    e
-                                );
-                            }
-                        }
+                        Ok(image) => save_capture(image.save(&path), &path, metadata.as_ref()),
                     });
                 }
                 true => {
                     snapshot.read_threaded(move |result| match result {
                         Err(e) => eprintln!("failed to async read captured frame: {:?}", e),
-                        Ok(image) => {
-                            if let Err(e) = image.save(&path) {
-                                eprintln!(
-                                    "failed to save captured frame to \"{}\": {}",
-                                    path.display(),
-                                    e
-                                );
-                            }
-                        }
+                        Ok(image) => save_capture(image.save(&path), &path, metadata.as_ref()),
                     });
                 }
             }
@@ -174,32 +225,27 @@ impl<'swap_chain> Frame<'swap_chain> {
     /// graphics pipeline render pass that is used.
     /// - Allow for the user's rendered image to persist between frames.
     ///
-    /// The exact format of the texture is equal to `Frame::TEXTURE_FORMAT`.
+    /// The exact format of the texture is equal to `Frame::TEXTURE_FORMAT`, or
+    /// `Frame::HDR_TEXTURE_FORMAT` if HDR is enabled for the window.
     ///
     /// If the number of MSAA samples specified is greater than `1` (which it is by default if
     /// supported by the platform), this will be a multisampled texture. After the **view**
     /// function returns, this texture will be resolved to a non-multisampled linear sRGBA texture.
     /// After the texture has been resolved if necessary, it will then be used as a shader input
     /// within a graphics pipeline used to draw the swapchain texture.
+    ///
+    /// If the window was built without a frame intermediary, this is the multisampled texture that
+    /// is resolved directly to the swapchain texture, or a texture of the swapchain format if
+    /// `msaa_samples` is `1`.
     pub fn texture(&self) -> &wgpu::Texture {
-        self.render_data
-            .intermediary_lin_srgba
-            .msaa_texture
-            .as_ref()
-            .map(|(tex, _)| tex)
-            .unwrap_or(&self.render_data.intermediary_lin_srgba.texture)
+        &self.render_data.intermediary_lin_srgba.draw_target().0
     }
 
     /// A full view into the frame's texture.
     ///
     /// See `texture` for details.
     pub fn texture_view(&self) -> &wgpu::TextureView {
-        self.render_data
-            .intermediary_lin_srgba
-            .msaa_texture
-            .as_ref()
-            .map(|(_, view)| view)
-            .unwrap_or(&self.render_data.intermediary_lin_srgba.texture_view)
+        &self.render_data.intermediary_lin_srgba.draw_target().1
     }
 
     /// Returns the resolve target texture in the case that MSAA is enabled.
@@ -207,14 +253,19 @@ impl<'swap_chain> Frame<'swap_chain> {
         if self.render_data.msaa_samples <= 1 {
             None
         } else {
-            Some(&self.render_data.intermediary_lin_srgba.texture_view)
+            match self.render_data.intermediary_lin_srgba.texture {
+                Some((_, ref view)) => Some(view),
+                None => Some(self.raw_frame.swap_chain_texture()),
+            }
         }
     }
 
-    /// The color format of the `Frame`'s intermediary linear sRGBA texture (equal to
-    /// `Frame::TEXTURE_FORMAT`).
+    /// The color format of the `Frame`'s texture.
+    ///
+    /// This is `Frame::TEXTURE_FORMAT` by default, `Frame::HDR_TEXTURE_FORMAT` if HDR is enabled,
+    /// or the swapchain format if the window was built without a frame intermediary.
     pub fn texture_format(&self) -> wgpu::TextureFormat {
-        Self::TEXTURE_FORMAT
+        self.render_data.texture_format
     }
 
     /// The number of MSAA samples of the `Frame`'s intermediary linear sRGBA texture.
@@ -227,6 +278,47 @@ impl<'swap_chain> Frame<'swap_chain> {
         self.render_data.size
     }
 
+    /// Read the contents of the frame's texture into the given pixel buffer, blocking until
+    /// complete.
+    ///
+    /// All commands encoded for the frame so far (e.g. via `draw.to_frame`) are submitted first, so
+    /// the buffer contains everything drawn to the frame up to this point.
+    ///
+    /// If the frame is multisampled, it is first resolved to a single-sampled texture from which
+    /// the pixels are read.
+    ///
+    /// Note: Be careful not to hold a lock to the frame's command encoder or the window's queue
+    /// when calling this method.
+    pub fn load_pixels(&self, pixels: &mut wgpu::PixelBuffer) {
+        let texture = {
+            let mut encoder = self.command_encoder();
+            let intermediary = &self.render_data.intermediary_lin_srgba;
+            intermediary.encode_resolve_readable(&mut *encoder)
+        };
+        self.raw_frame.submit_encoded_commands();
+        let device_queue_pair = self.device_queue_pair();
+        let mut queue = device_queue_pair
+            .queue()
+            .lock()
+            .expect("failed to lock the queue");
+        pixels.load_pixels(device_queue_pair.device(), &mut *queue, texture);
+    }
+
+    /// Write any modified pixels of the given buffer to its texture and then replace the contents
+    /// of the frame with the buffer's texture, stretching it to fit if the sizes differ.
+    pub fn update_pixels(&self, pixels: &mut wgpu::PixelBuffer) {
+        let device = self.device_queue_pair().device();
+        let mut encoder = self.command_encoder();
+        pixels.encode_update_pixels(device, &mut *encoder);
+        pixels.encode_render_pass(
+            device,
+            &mut *encoder,
+            self.texture_view(),
+            self.texture_format(),
+            self.texture_msaa_samples(),
+        );
+    }
+
     /// Short-hand for constructing a `wgpu::RenderPassColorAttachmentDescriptor` for use within a
     /// render pass that targets this frame's texture. The returned descriptor's `attachment` will
     /// the same `wgpu::TextureView` returned by the `Frame::texture` method.
@@ -237,10 +329,7 @@ impl<'swap_chain> Frame<'swap_chain> {
     pub fn color_attachment_descriptor(&self) -> wgpu::RenderPassColorAttachmentDescriptor {
         let load_op = wgpu::LoadOp::Load;
         let store_op = wgpu::StoreOp::Store;
-        let attachment = match self.render_data.intermediary_lin_srgba.msaa_texture {
-            None => &self.render_data.intermediary_lin_srgba.texture_view,
-            Some((_, ref msaa_texture_view)) => msaa_texture_view,
-        };
+        let attachment = self.texture_view();
         let resolve_target = None;
         let clear_color = wgpu::Color::TRANSPARENT;
         wgpu::RenderPassColorAttachmentDescriptor {
@@ -272,7 +361,8 @@ impl<'swap_chain> Frame<'swap_chain> {
     /// Before submission, the frame does the following:
     ///
     /// - If the frame's intermediary linear sRGBA texture is multisampled, resolve it.
-    /// - Write the intermediary linear sRGBA image to the swap chain texture.
+    /// - Write the intermediary linear sRGBA image to the swap chain texture, applying the
+    ///   window's tonemapping.
     ///
     /// It can sometimes be useful to submit the **Frame** before `view` completes in order to read
     /// the frame's texture back to the CPU (e.g. for screen shots, recordings, etc).
@@ -281,6 +371,93 @@ impl<'swap_chain> Frame<'swap_chain> {
     }
 }
 
+impl IntermediaryLinSrgba {
+    // The texture to which graphics are drawn, i.e. the MSAA texture if there is one.
+    fn draw_target(&self) -> &(wgpu::Texture, wgpu::TextureView) {
+        self.msaa_texture
+            .as_ref()
+            .or(self.texture.as_ref())
+            .expect("intermediary has neither an MSAA nor a resolved texture")
+    }
+
+    // The single-sampled texture from which the frame may be read once resolved.
+    fn resolved_texture(&self) -> &wgpu::Texture {
+        &self
+            .texture
+            .as_ref()
+            .or(self.readback.as_ref())
+            .expect("intermediary has no single-sampled texture")
+            .0
+    }
+
+    // Resolve the MSAA texture, if any, into the single-sampled texture from which the frame may
+    // be read, returning that texture.
+    fn encode_resolve_readable(&self, encoder: &mut wgpu::CommandEncoder) -> &wgpu::Texture {
+        if let Some((_, ref msaa_texture_view)) = self.msaa_texture {
+            let (_, ref resolve_target) = *self
+                .texture
+                .as_ref()
+                .or(self.readback.as_ref())
+                .expect("intermediary has no single-sampled texture");
+            wgpu::resolve_texture(msaa_texture_view, resolve_target, encoder);
+        }
+        self.resolved_texture()
+    }
+}
+
+impl CaptureData {
+    pub(crate) fn new(seed: Option<Arc<AtomicU64>>) -> Self {
+        CaptureData {
+            next_frame_path: Default::default(),
+            texture_capturer: Default::default(),
+            seed,
+        }
+    }
+}
+
+impl CaptureMetadata {
+    /// The path of the metadata file for the image captured to the given path.
+    pub fn path(image_path: &Path) -> PathBuf {
+        image_path.with_extension("json")
+    }
+
+    /// Load the metadata for the image captured to the given path.
+    pub fn load(image_path: &Path) -> Result<Self, io::JsonFileError> {
+        io::load_from_json(Self::path(image_path))
+    }
+
+    /// Save the metadata for the image captured to the given path.
+    pub fn save(&self, image_path: &Path) -> Result<(), io::JsonFileError> {
+        io::save_to_json(Self::path(image_path), self)
+    }
+}
+
+// Log the result of saving a captured image and save its metadata, if any, on success.
+fn save_capture<E>(saved: Result<(), E>, path: &Path, metadata: Option<&CaptureMetadata>)
+where
+    E: fmt::Display,
+{
+    if let Err(e) = saved {
+        eprintln!(
+            "failed to save captured frame to \"{}\": {}",
+            path.display(),
+            e
+        );
+        return;
+    }
+    let metadata = match metadata {
+        None => return,
+        Some(metadata) => metadata,
+    };
+    if let Err(e) = metadata.save(path) {
+        eprintln!(
+            "failed to save captured frame metadata to \"{}\": {}",
+            CaptureMetadata::path(path).display(),
+            e
+        );
+    }
+}
+
 impl RenderData {
     /// Initialise the render data.
     ///
@@ -288,28 +465,99 @@ impl RenderData {
     ///
     /// If `msaa_samples` is greater than 1 a `multisampled` texture will also be created. Otherwise the
     /// a regular non-multisampled image will be created.
+    ///
+    /// If `intermediary_format` is `None` and `msaa_samples` is greater than 1, no intermediary
+    /// image is created and frames are drawn to a multisampled texture of the swapchain format
+    /// that is resolved directly to the swapchain texture. If `msaa_samples` is 1, frames are
+    /// drawn to a texture of the swapchain format that is then written to the swapchain texture
+    /// without tonemapping.
     pub(crate) fn new(
         device: &wgpu::Device,
         swap_chain_dims: [u32; 2],
         swap_chain_format: wgpu::TextureFormat,
         msaa_samples: u32,
+        intermediary_format: Option<wgpu::TextureFormat>,
+        tonemapping: wgpu::Tonemapping,
+        clear: bool,
     ) -> Self {
-        let intermediary_lin_srgba =
-            create_intermediary_lin_srgba(device, swap_chain_dims, msaa_samples);
-        let src_sample_count = 1;
-        let swap_chain_sample_count = 1;
-        let texture_reshaper = wgpu::TextureReshaper::new(
+        let texture_format = intermediary_format.unwrap_or(swap_chain_format);
+        let intermediary_lin_srgba = create_intermediary_lin_srgba(
             device,
-            &intermediary_lin_srgba.texture_view,
-            src_sample_count,
-            swap_chain_sample_count,
-            swap_chain_format,
+            swap_chain_dims,
+            msaa_samples,
+            texture_format,
+            intermediary_format.is_some(),
         );
+        let texture_reshaper = intermediary_lin_srgba
+            .texture
+            .as_ref()
+            .map(|(_, texture_view)| {
+                let swap_chain_sample_count = 1;
+                match intermediary_format {
+                    Some(_) => wgpu::TextureReshaper::with_tonemapping(
+                        device,
+                        texture_view,
+                        swap_chain_sample_count,
+                        swap_chain_format,
+                        tonemapping,
+                    ),
+                    None => wgpu::TextureReshaper::new(
+                        device,
+                        texture_view,
+                        1,
+                        swap_chain_sample_count,
+                        swap_chain_format,
+                    ),
+                }
+            });
         RenderData {
+            intermediary: intermediary_format.is_some(),
             intermediary_lin_srgba,
             texture_reshaper,
             size: swap_chain_dims,
             msaa_samples,
+            texture_format,
+            clear,
+            tonemapping: Mutex::new((tonemapping, false)),
+        }
+    }
+
+    /// The format of the frame's texture.
+    pub(crate) fn texture_format(&self) -> wgpu::TextureFormat {
+        self.texture_format
+    }
+
+    /// The format of the intermediary linear sRGBA texture, or `None` if frames are drawn
+    /// directly to the swapchain texture.
+    pub(crate) fn intermediary_format(&self) -> Option<wgpu::TextureFormat> {
+        if self.intermediary {
+            Some(self.texture_format)
+        } else {
+            None
+        }
+    }
+
+    /// Whether or not the frame's texture is cleared at the beginning of each frame.
+    pub(crate) fn clear(&self) -> bool {
+        self.clear
+    }
+
+    /// The tonemapping applied when writing the intermediary texture to the swap chain.
+    pub(crate) fn tonemapping(&self) -> wgpu::Tonemapping {
+        self.tonemapping
+            .lock()
+            .map(|guard| guard.0)
+            .unwrap_or_default()
+    }
+
+    /// Change the tonemapping applied when writing the intermediary texture to the swap chain.
+    ///
+    /// The change is applied upon the next frame's submission.
+    pub(crate) fn set_tonemapping(&self, tonemapping: wgpu::Tonemapping) {
+        if let Ok(mut guard) = self.tonemapping.lock() {
+            if guard.0 != tonemapping {
+                *guard = (tonemapping, true);
+            }
         }
     }
 }
@@ -326,20 +574,29 @@ fn create_lin_srgba_msaa_texture(
     device: &wgpu::Device,
     swap_chain_dims: [u32; 2],
     msaa_samples: u32,
+    format: wgpu::TextureFormat,
 ) -> wgpu::Texture {
     wgpu::TextureBuilder::new()
         .size(swap_chain_dims)
         .sample_count(msaa_samples)
-        .usage(wgpu::TextureUsage::OUTPUT_ATTACHMENT)
-        .format(Frame::TEXTURE_FORMAT)
+        .usage(wgpu::TextureUsage::OUTPUT_ATTACHMENT | wgpu::TextureUsage::SAMPLED)
+        .format(format)
         .build(device)
 }
 
-fn create_lin_srgba_texture(device: &wgpu::Device, swap_chain_dims: [u32; 2]) -> wgpu::Texture {
+fn create_lin_srgba_texture(
+    device: &wgpu::Device,
+    swap_chain_dims: [u32; 2],
+    format: wgpu::TextureFormat,
+) -> wgpu::Texture {
     wgpu::TextureBuilder::new()
         .size(swap_chain_dims)
-        .format(Frame::TEXTURE_FORMAT)
-        .usage(wgpu::TextureUsage::OUTPUT_ATTACHMENT | wgpu::TextureUsage::SAMPLED)
+        .format(format)
+        .usage(
+            wgpu::TextureUsage::OUTPUT_ATTACHMENT
+                | wgpu::TextureUsage::SAMPLED
+                | wgpu::TextureUsage::COPY_SRC,
+        )
         .build(device)
 }
 
@@ -347,20 +604,32 @@ fn create_intermediary_lin_srgba(
     device: &wgpu::Device,
     swap_chain_dims: [u32; 2],
     msaa_samples: u32,
+    format: wgpu::TextureFormat,
+    intermediary: bool,
 ) -> IntermediaryLinSrgba {
     let msaa_texture = match msaa_samples {
         0 | 1 => None,
         _ => {
-            let texture = create_lin_srgba_msaa_texture(device, swap_chain_dims, msaa_samples);
+            let texture =
+                create_lin_srgba_msaa_texture(device, swap_chain_dims, msaa_samples, format);
             let texture_view = texture.create_default_view();
             Some((texture, texture_view))
         }
     };
-    let texture = create_lin_srgba_texture(device, swap_chain_dims);
-    let texture_view = texture.create_default_view();
+    // A single-sampled texture is always required so that the frame may be captured and read.
+    // Without an intermediary, the MSAA texture is resolved to it only when necessary.
+    let resolved = || {
+        let texture = create_lin_srgba_texture(device, swap_chain_dims, format);
+        let texture_view = texture.create_default_view();
+        Some((texture, texture_view))
+    };
+    let (texture, readback) = match intermediary || msaa_texture.is_none() {
+        true => (resolved(), None),
+        false => (None, resolved()),
+    };
     IntermediaryLinSrgba {
         msaa_texture,
         texture,
-        texture_view,
+        readback,
     }
 }
@@ -78,6 +78,23 @@ impl<'swap_chain> RawFrame<'swap_chain> {
         queue.submit(&[command_buffer]);
     }
 
+    // Submit the commands encoded so far, replacing the command encoder with a new one.
+    //
+    // Allows for reading back the results of the commands before the end of the **view** function.
+    pub(crate) fn submit_encoded_commands(&self) {
+        let device = self.device_queue_pair.device();
+        let ce_desc = wgpu::CommandEncoderDescriptor::default();
+        let new_encoder = device.create_command_encoder(&ce_desc);
+        let command_encoder = std::mem::replace(&mut *self.command_encoder(), new_encoder);
+        let command_buffer = command_encoder.finish();
+        let mut queue = self
+            .device_queue_pair
+            .queue()
+            .lock()
+            .expect("failed to lock the queue");
+        queue.submit(&[command_buffer]);
+    }
+
     // Allow the `Frame` to check if the raw frame has already been submitted on drop.
     pub(crate) fn is_submitted(&self) -> bool {
         self.command_encoder.is_none()
@@ -0,0 +1,525 @@
+//! Mapping named actions to keyboard, mouse and scroll input.
+//!
+//! An **ActionMap** allows a sketch to respond to actions like `"toggle_gui"` or `"next_scene"`
+//! rather than hard-coding specific keys. This allows performers to remap controls without
+//! recompiling, e.g. by loading the bindings from a TOML file:
+//!
+//! ```toml
+//! toggle_gui = ["G", "Ctrl+H"]
+//! next_scene = ["Right", "MouseLeft", "ScrollDown"]
+//! ```
+//!
+//! Each binding is written as zero or more modifiers (`Shift`, `Ctrl`, `Alt` or `Logo`) followed
+//! by a trigger, separated by `+`. The trigger is either the name of a `Key` variant, a mouse
+//! button (`MouseLeft`, `MouseRight`, `MouseMiddle` or `Mouse<n>`) or a scroll direction
+//! (`ScrollUp`, `ScrollDown`, `ScrollLeft` or `ScrollRight`).
+//!
+//! Once loaded, the map may be given to the app via `App::set_action_map` and queried via
+//! `App::action` and `App::action_active`.
+
+use crate::event::{Key, ModifiersState, MouseButton};
+use crate::io;
+use crate::serde_derive::{Deserialize, Serialize};
+use crate::state;
+use serde::de::IntoDeserializer;
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::str::FromStr;
+use std::{error, fmt};
+
+/// A map from named actions to the input bindings that trigger them.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ActionMap {
+    actions: BTreeMap<String, Vec<Binding>>,
+}
+
+/// A single input binding that may trigger an action.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Binding {
+    /// A key pressed while exactly the given modifiers are held.
+    Key { key: Key, mods: Modifiers },
+    /// A mouse button pressed while exactly the given modifiers are held.
+    Mouse {
+        button: MouseButton,
+        mods: Modifiers,
+    },
+    /// Scrolling in the given direction while exactly the given modifiers are held.
+    Scroll {
+        direction: ScrollDirection,
+        mods: Modifiers,
+    },
+}
+
+/// The set of modifier keys that must be held for a **Binding** to apply.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Modifiers {
+    pub shift: bool,
+    pub ctrl: bool,
+    pub alt: bool,
+    pub logo: bool,
+}
+
+/// The direction of a scroll **Binding**.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ScrollDirection {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+/// Errors that might occur while parsing a **Binding** from a string.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ParseBindingError {
+    /// The binding contained no trigger.
+    Empty,
+    /// The given modifier is not one of `Shift`, `Ctrl`, `Alt` or `Logo`.
+    UnknownModifier(String),
+    /// The given trigger is not a known key, mouse button or scroll direction.
+    UnknownTrigger(String),
+}
+
+impl ActionMap {
+    /// Create a new, empty action map.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Load an action map from the TOML file at the given path.
+    pub fn load<P>(path: P) -> Result<Self, io::TomlFileLoadError>
+    where
+        P: AsRef<Path>,
+    {
+        io::load_from_toml(path)
+    }
+
+    /// Save the action map as TOML to the given path.
+    pub fn save<P>(&self, path: P) -> Result<(), io::TomlFileSaveError>
+    where
+        P: AsRef<Path>,
+    {
+        io::save_to_toml(path, self)
+    }
+
+    /// Add a binding for the given action, returning the map.
+    pub fn bind<S>(mut self, action: S, binding: Binding) -> Self
+    where
+        S: Into<String>,
+    {
+        self.insert(action, binding);
+        self
+    }
+
+    /// Add a binding for the given action.
+    pub fn insert<S>(&mut self, action: S, binding: Binding)
+    where
+        S: Into<String>,
+    {
+        let bindings = self.actions.entry(action.into()).or_insert_with(Vec::new);
+        if !bindings.contains(&binding) {
+            bindings.push(binding);
+        }
+    }
+
+    /// Remove all bindings for the given action, returning them if there were any.
+    pub fn remove(&mut self, action: &str) -> Option<Vec<Binding>> {
+        self.actions.remove(action)
+    }
+
+    /// The bindings for the given action.
+    pub fn bindings(&self, action: &str) -> &[Binding] {
+        self.actions.get(action).map(|b| &b[..]).unwrap_or(&[])
+    }
+
+    /// An iterator yielding the name of each action along with its bindings.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &[Binding])> {
+        self.actions.iter().map(|(name, b)| (&name[..], &b[..]))
+    }
+
+    /// Whether or not any binding for the given action was triggered since the end of the
+    /// previous update, e.g. one of its keys was just pressed.
+    pub fn is_triggered(&self, action: &str, keys: &state::Keys, mouse: &state::Mouse) -> bool {
+        self.bindings(action)
+            .iter()
+            .any(|b| b.is_triggered(keys, mouse))
+    }
+
+    /// Whether or not any binding for the given action is currently held, e.g. one of its keys is
+    /// down.
+    pub fn is_active(&self, action: &str, keys: &state::Keys, mouse: &state::Mouse) -> bool {
+        self.bindings(action)
+            .iter()
+            .any(|b| b.is_active(keys, mouse))
+    }
+
+    /// An iterator yielding the names of all actions triggered since the end of the previous
+    /// update.
+    pub fn triggered<'a>(
+        &'a self,
+        keys: &'a state::Keys,
+        mouse: &'a state::Mouse,
+    ) -> impl 'a + Iterator<Item = &'a str> {
+        self.actions
+            .iter()
+            .filter(move |(_, b)| b.iter().any(|b| b.is_triggered(keys, mouse)))
+            .map(|(name, _)| &name[..])
+    }
+}
+
+impl Binding {
+    /// A binding for the given key with no modifiers.
+    pub fn key(key: Key) -> Self {
+        let mods = Modifiers::default();
+        Binding::Key { key, mods }
+    }
+
+    /// A binding for the given mouse button with no modifiers.
+    pub fn mouse(button: MouseButton) -> Self {
+        let mods = Modifiers::default();
+        Binding::Mouse { button, mods }
+    }
+
+    /// A binding for scrolling in the given direction with no modifiers.
+    pub fn scroll(direction: ScrollDirection) -> Self {
+        let mods = Modifiers::default();
+        Binding::Scroll { direction, mods }
+    }
+
+    /// The same binding, but requiring the given modifiers.
+    pub fn with_mods(self, mods: Modifiers) -> Self {
+        match self {
+            Binding::Key { key, .. } => Binding::Key { key, mods },
+            Binding::Mouse { button, .. } => Binding::Mouse { button, mods },
+            Binding::Scroll { direction, .. } => Binding::Scroll { direction, mods },
+        }
+    }
+
+    /// The modifiers that must be held for the binding to apply.
+    pub fn mods(&self) -> Modifiers {
+        match *self {
+            Binding::Key { mods, .. }
+            | Binding::Mouse { mods, .. }
+            | Binding::Scroll { mods, .. } => mods,
+        }
+    }
+
+    /// Whether or not the binding was triggered since the end of the previous update.
+    ///
+    /// Scroll bindings are triggered for any update in which scrolling in their direction
+    /// occurred.
+    pub fn is_triggered(&self, keys: &state::Keys, mouse: &state::Mouse) -> bool {
+        if !self.mods().matches(&keys.mods) {
+            return false;
+        }
+        match *self {
+            Binding::Key { key, .. } => keys.just_pressed(key),
+            Binding::Mouse { button, .. } => mouse.just_pressed(button),
+            Binding::Scroll { direction, .. } => direction.matches(mouse),
+        }
+    }
+
+    /// Whether or not the binding is currently held.
+    pub fn is_active(&self, keys: &state::Keys, mouse: &state::Mouse) -> bool {
+        if !self.mods().matches(&keys.mods) {
+            return false;
+        }
+        match *self {
+            Binding::Key { key, .. } => keys.is_down(key),
+            Binding::Mouse { button, .. } => mouse.buttons[button].is_down(),
+            Binding::Scroll { direction, .. } => direction.matches(mouse),
+        }
+    }
+}
+
+impl Modifiers {
+    /// Whether or not exactly these modifiers are held in the given state.
+    pub fn matches(&self, mods: &ModifiersState) -> bool {
+        self.shift == mods.shift()
+            && self.ctrl == mods.ctrl()
+            && self.alt == mods.alt()
+            && self.logo == mods.logo()
+    }
+}
+
+impl ScrollDirection {
+    // Whether or not the mouse scrolled in this direction since the end of the previous update.
+    fn matches(&self, mouse: &state::Mouse) -> bool {
+        let scroll = mouse.scroll();
+        match *self {
+            ScrollDirection::Up => scroll.y > 0.0,
+            ScrollDirection::Down => scroll.y < 0.0,
+            ScrollDirection::Left => scroll.x < 0.0,
+            ScrollDirection::Right => scroll.x > 0.0,
+        }
+    }
+}
+
+impl From<Key> for Binding {
+    fn from(key: Key) -> Self {
+        Binding::key(key)
+    }
+}
+
+impl From<MouseButton> for Binding {
+    fn from(button: MouseButton) -> Self {
+        Binding::mouse(button)
+    }
+}
+
+impl From<ScrollDirection> for Binding {
+    fn from(direction: ScrollDirection) -> Self {
+        Binding::scroll(direction)
+    }
+}
+
+impl FromStr for Binding {
+    type Err = ParseBindingError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts: Vec<&str> = s.split('+').map(str::trim).collect();
+        let trigger = match parts.pop() {
+            Some(trigger) if !trigger.is_empty() => trigger,
+            _ => return Err(ParseBindingError::Empty),
+        };
+        let mut mods = Modifiers::default();
+        for part in parts {
+            match &part.to_lowercase()[..] {
+                "shift" => mods.shift = true,
+                "ctrl" | "control" => mods.ctrl = true,
+                "alt" | "option" => mods.alt = true,
+                "logo" | "cmd" | "super" => mods.logo = true,
+                _ => return Err(ParseBindingError::UnknownModifier(part.to_string())),
+            }
+        }
+        let binding = match trigger {
+            "MouseLeft" => Binding::mouse(MouseButton::Left),
+            "MouseRight" => Binding::mouse(MouseButton::Right),
+            "MouseMiddle" => Binding::mouse(MouseButton::Middle),
+            "ScrollUp" => Binding::scroll(ScrollDirection::Up),
+            "ScrollDown" => Binding::scroll(ScrollDirection::Down),
+            "ScrollLeft" => Binding::scroll(ScrollDirection::Left),
+            "ScrollRight" => Binding::scroll(ScrollDirection::Right),
+            _ => match trigger.trim_start_matches("Mouse").parse::<u8>() {
+                Ok(n) if trigger.starts_with("Mouse") => Binding::mouse(MouseButton::Other(n)),
+                _ => {
+                    let de: serde::de::value::StrDeserializer<serde::de::value::Error> =
+                        trigger.into_deserializer();
+                    match <Key as serde::Deserialize>::deserialize(de) {
+                        Ok(key) => Binding::key(key),
+                        Err(_) => {
+                            let trigger = trigger.to_string();
+                            return Err(ParseBindingError::UnknownTrigger(trigger));
+                        }
+                    }
+                }
+            },
+        };
+        Ok(binding.with_mods(mods))
+    }
+}
+
+impl fmt::Display for Binding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mods = self.mods();
+        let names = [
+            (mods.shift, "Shift"),
+            (mods.ctrl, "Ctrl"),
+            (mods.alt, "Alt"),
+            (mods.logo, "Logo"),
+        ];
+        for &(_, name) in names.iter().filter(|(held, _)| *held) {
+            write!(f, "{}+", name)?;
+        }
+        match *self {
+            Binding::Key { key, .. } => write!(f, "{:?}", key),
+            Binding::Mouse { button, .. } => match button {
+                MouseButton::Left => write!(f, "MouseLeft"),
+                MouseButton::Right => write!(f, "MouseRight"),
+                MouseButton::Middle => write!(f, "MouseMiddle"),
+                MouseButton::Other(n) => write!(f, "Mouse{}", n),
+            },
+            Binding::Scroll { direction, .. } => write!(f, "Scroll{:?}", direction),
+        }
+    }
+}
+
+impl serde::Serialize for Binding {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for Binding {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let s: String = serde::Deserialize::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+impl error::Error for ParseBindingError {}
+
+impl fmt::Display for ParseBindingError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ParseBindingError::Empty => write!(f, "binding contains no key, button or scroll"),
+            ParseBindingError::UnknownModifier(ref s) => write!(f, "unknown modifier \"{}\"", s),
+            ParseBindingError::UnknownTrigger(ref s) => {
+                write!(f, "unknown key, button or scroll \"{}\"", s)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(s: &str) -> Result<Binding, ParseBindingError> {
+        s.parse()
+    }
+
+    fn ctrl() -> Modifiers {
+        Modifiers {
+            ctrl: true,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn parse_triggers() {
+        assert_eq!(parse("G"), Ok(Binding::key(Key::G)));
+        assert_eq!(parse("Right"), Ok(Binding::key(Key::Right)));
+        assert_eq!(parse("MouseLeft"), Ok(Binding::mouse(MouseButton::Left)));
+        assert_eq!(parse("Mouse4"), Ok(Binding::mouse(MouseButton::Other(4))));
+        let scroll_down = Binding::scroll(ScrollDirection::Down);
+        assert_eq!(parse("ScrollDown"), Ok(scroll_down));
+    }
+
+    #[test]
+    fn parse_modifiers() {
+        assert_eq!(parse("Ctrl+H"), Ok(Binding::key(Key::H).with_mods(ctrl())));
+        assert_eq!(parse("control + H"), parse("Ctrl+H"));
+        let mods = Modifiers {
+            shift: true,
+            logo: true,
+            ..Default::default()
+        };
+        let expected = Binding::mouse(MouseButton::Right).with_mods(mods);
+        assert_eq!(parse("Shift+Cmd+MouseRight"), Ok(expected));
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(parse(""), Err(ParseBindingError::Empty));
+        assert_eq!(parse("Ctrl+"), Err(ParseBindingError::Empty));
+        let unknown_mod = ParseBindingError::UnknownModifier("Hyper".to_string());
+        assert_eq!(parse("Hyper+H"), Err(unknown_mod));
+        let unknown_key = ParseBindingError::UnknownTrigger("NotAKey".to_string());
+        assert_eq!(parse("NotAKey"), Err(unknown_key));
+        let unknown_mouse = ParseBindingError::UnknownTrigger("MouseFoo".to_string());
+        assert_eq!(parse("Ctrl+MouseFoo"), Err(unknown_mouse));
+    }
+
+    #[test]
+    fn display_round_trip() {
+        let all = Modifiers {
+            shift: true,
+            ctrl: true,
+            alt: true,
+            logo: true,
+        };
+        let bindings = [
+            Binding::key(Key::Key1),
+            Binding::key(Key::H).with_mods(ctrl()),
+            Binding::mouse(MouseButton::Middle),
+            Binding::mouse(MouseButton::Other(7)).with_mods(all),
+            Binding::scroll(ScrollDirection::Left).with_mods(ctrl()),
+        ];
+        for &binding in bindings.iter() {
+            assert_eq!(parse(&binding.to_string()), Ok(binding));
+        }
+        assert_eq!(Binding::key(Key::H).with_mods(ctrl()).to_string(), "Ctrl+H");
+    }
+
+    #[test]
+    fn modifiers_match_exactly() {
+        let none = Modifiers::default();
+        assert!(none.matches(&ModifiersState::empty()));
+        assert!(!none.matches(&ModifiersState::CTRL));
+        assert!(ctrl().matches(&ModifiersState::CTRL));
+        assert!(!ctrl().matches(&ModifiersState::empty()));
+        assert!(!ctrl().matches(&(ModifiersState::CTRL | ModifiersState::SHIFT)));
+        let all = Modifiers {
+            shift: true,
+            ctrl: true,
+            alt: true,
+            logo: true,
+        };
+        assert!(all.matches(&ModifiersState::all()));
+    }
+
+    #[test]
+    fn triggered_requires_exact_modifiers() {
+        let map = ActionMap::new()
+            .bind("plain", Binding::key(Key::H))
+            .bind("ctrl", Binding::key(Key::H).with_mods(ctrl()));
+        let mouse = state::Mouse::new();
+        let mut keys = state::Keys::default();
+        keys.press(Key::H);
+        let triggered: Vec<_> = map.triggered(&keys, &mouse).collect();
+        assert_eq!(triggered, vec!["plain"]);
+        keys.mods = ModifiersState::CTRL;
+        let triggered: Vec<_> = map.triggered(&keys, &mouse).collect();
+        assert_eq!(triggered, vec!["ctrl"]);
+        keys.mods = ModifiersState::CTRL | ModifiersState::ALT;
+        assert_eq!(map.triggered(&keys, &mouse).count(), 0);
+        assert!(!map.is_active("ctrl", &keys, &mouse));
+    }
+
+    #[test]
+    fn load_toml() {
+        let toml = r#"
+            toggle_gui = ["G", "Ctrl+H"]
+            next_scene = ["Right", "MouseLeft", "ScrollDown"]
+        "#;
+        let path = std::env::temp_dir().join("nannou_input_test_load_toml.toml");
+        std::fs::write(&path, toml).unwrap();
+        let map = ActionMap::load(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+        let toggle_gui = [Binding::key(Key::G), Binding::key(Key::H).with_mods(ctrl())];
+        assert_eq!(map.bindings("toggle_gui"), &toggle_gui[..]);
+        let next_scene = [
+            Binding::key(Key::Right),
+            Binding::mouse(MouseButton::Left),
+            Binding::scroll(ScrollDirection::Down),
+        ];
+        assert_eq!(map.bindings("next_scene"), &next_scene[..]);
+        assert!(map.bindings("missing").is_empty());
+    }
+
+    #[test]
+    fn load_toml_rejects_unknown_keys() {
+        let path = std::env::temp_dir().join("nannou_input_test_unknown_key.toml");
+        std::fs::write(&path, r#"toggle_gui = ["Ctrl+NotAKey"]"#).unwrap();
+        let result = ActionMap::load(&path);
+        std::fs::remove_file(&path).ok();
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn toml_round_trip() {
+        let map = ActionMap::new()
+            .bind("toggle_gui", Binding::key(Key::G))
+            .bind("toggle_gui", Binding::key(Key::H).with_mods(ctrl()))
+            .bind("zoom", Binding::scroll(ScrollDirection::Up));
+        let s = toml::to_string(&map).unwrap();
+        let loaded: ActionMap = toml::from_str(&s).unwrap();
+        assert_eq!(loaded, map);
+    }
+}
@@ -28,6 +28,7 @@ pub use self::ui::Ui;
 pub use crate::app::{App, LoopMode};
 pub use crate::draw::Draw;
 
+pub mod anim;
 pub mod app;
 pub mod color;
 pub mod draw;
@@ -36,12 +37,16 @@ pub mod event;
 pub mod frame;
 pub mod geom;
 pub mod image;
+pub mod input;
 pub mod io;
 pub mod math;
 pub mod mesh;
 pub mod noise;
+pub mod param;
 pub mod prelude;
+pub mod preset;
 pub mod rand;
+pub mod record;
 pub mod state;
 pub mod text;
 pub mod time;
@@ -0,0 +1,145 @@
+//! Traits used by `#[derive(Params)]` to map the fields of a struct to parameters.
+//!
+//! Deriving `Params` for a struct implements **Fields**, registering a parameter for each field
+//! and copying values between the fields and a **Params** instance. Deriving `Params` for an enum
+//! whose variants have no data implements **Field**, allowing it to be used as a dropdown.
+//!
+//! ```ignore
+//! #[derive(Params)]
+//! struct Settings {
+//!     #[param(range = 1.0..=200.0, step = 1.0, label = "Radius")]
+//!     radius: f32,
+//!     visible: bool,
+//!     color: Srgba,
+//!     shape: Shape,
+//!     #[param(skip)]
+//!     cache: Vec<Point2>,
+//! }
+//!
+//! #[derive(Params)]
+//! enum Shape {
+//!     Circle,
+//!     Square,
+//! }
+//! ```
+
+use super::{Kind, Params, Value};
+use crate::color::{Srgb, Srgba};
+
+/// Options specified via a field's `#[param(...)]` attribute.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Attrs {
+    /// The inclusive range specified via `range = min..=max`.
+    pub range: Option<(f32, f32)>,
+    /// The step specified via `step = n`.
+    pub step: Option<f32>,
+}
+
+/// A type that may be represented by a single parameter.
+pub trait Field: Sized {
+    /// The kind of parameter used to represent the type.
+    fn kind(attrs: &Attrs) -> Kind;
+    /// Convert the field to a parameter value.
+    fn to_value(&self) -> Value;
+    /// Produce the field from a parameter value.
+    fn from_value(value: &Value) -> Option<Self>;
+}
+
+/// A type whose fields may each be represented by a parameter.
+///
+/// Typically implemented via `#[derive(Params)]`.
+pub trait Fields {
+    /// Register a parameter for each field, using the current field values as defaults.
+    fn register(&self, params: &Params);
+    /// Update each field with the current value of its parameter.
+    fn read(&mut self, params: &Params);
+    /// Set each parameter to the current value of its field.
+    fn write(&self, params: &Params);
+}
+
+// The range used for floats when no range is specified.
+const DEFAULT_RANGE: (f32, f32) = (0.0, 1.0);
+
+impl Field for f32 {
+    fn kind(attrs: &Attrs) -> Kind {
+        let (min, max) = attrs.range.unwrap_or(DEFAULT_RANGE);
+        let step = attrs.step;
+        Kind::Float { min, max, step }
+    }
+
+    fn to_value(&self) -> Value {
+        Value::Float(*self)
+    }
+
+    fn from_value(value: &Value) -> Option<Self> {
+        match *value {
+            Value::Float(f) => Some(f),
+            _ => None,
+        }
+    }
+}
+
+impl Field for f64 {
+    fn kind(attrs: &Attrs) -> Kind {
+        f32::kind(attrs)
+    }
+
+    fn to_value(&self) -> Value {
+        Value::Float(*self as f32)
+    }
+
+    fn from_value(value: &Value) -> Option<Self> {
+        f32::from_value(value).map(|f| f as f64)
+    }
+}
+
+impl Field for bool {
+    fn kind(_attrs: &Attrs) -> Kind {
+        Kind::Bool
+    }
+
+    fn to_value(&self) -> Value {
+        Value::Bool(*self)
+    }
+
+    fn from_value(value: &Value) -> Option<Self> {
+        match *value {
+            Value::Bool(b) => Some(b),
+            _ => None,
+        }
+    }
+}
+
+impl Field for Srgba {
+    fn kind(_attrs: &Attrs) -> Kind {
+        Kind::Color
+    }
+
+    fn to_value(&self) -> Value {
+        Value::from(*self)
+    }
+
+    fn from_value(value: &Value) -> Option<Self> {
+        match *value {
+            Value::Color([r, g, b, a]) => Some(Srgba::new(r, g, b, a)),
+            _ => None,
+        }
+    }
+}
+
+impl Field for Srgb {
+    fn kind(_attrs: &Attrs) -> Kind {
+        Kind::Color
+    }
+
+    fn to_value(&self) -> Value {
+        Value::Color([self.red, self.green, self.blue, 1.0])
+    }
+
+    fn from_value(value: &Value) -> Option<Self> {
+        match *value {
+            Value::Color([r, g, b, _]) => Some(Srgb::new(r, g, b)),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,712 @@
+//! Named, typed parameters that may be controlled from outside of the sketch.
+//!
+//! A **Params** instance is a shared handle to a set of parameters, each with a unique name, a
+//! **Kind** (e.g. a float within a range, a bool, a color or one of a set of variants) and a
+//! current **Value**. Sketches register their parameters once within `model` and read the current
+//! values wherever they are needed, e.g. `params.float("radius")`.
+//!
+//! As the handle may be cloned and shared between threads, parameters may be driven externally.
+//! With the `osc` feature enabled, `osc::Server` serves all parameters over OSC so that tools
+//! like TouchOSC, Max or any other local process may query and control them.
+//!
+//! The values of all parameters may be captured as a **Snapshot**, which may be saved to and
+//! loaded from JSON.
+//!
+//! Rather than registering each parameter by hand, `#[derive(Params)]` may be used to expose the
+//! fields of a struct as parameters (see the `field` module). The `ui` module provides a
+//! **Panel** that displays a widget for each parameter within a `Ui`.
+
+use crate::color::Srgba;
+use crate::io;
+use crate::serde_derive::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::{error, fmt};
+
+pub use self::field::{Attrs, Field, Fields};
+pub use self::ui::Panel;
+pub use nannou_derive::Params;
+
+pub mod field;
+#[cfg(feature = "osc")]
+pub mod osc;
+pub mod ui;
+
+/// A shared handle to a set of named parameters.
+///
+/// Cloning the handle produces another handle to the same set of parameters.
+#[derive(Clone, Debug, Default)]
+pub struct Params {
+    inner: Arc<Mutex<Inner>>,
+}
+
+#[derive(Debug, Default)]
+struct Inner {
+    // All parameters in the order in which they were added.
+    params: Vec<Param>,
+    // The index of each parameter within `params`.
+    indices: HashMap<String, usize>,
+    // Incremented each time a value changes.
+    generation: u64,
+}
+
+/// A single named parameter.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Param {
+    /// The unique name of the parameter.
+    pub name: String,
+    /// A human readable label, e.g. for displaying within a GUI. Defaults to the name.
+    pub label: String,
+    /// The kind of value described by the parameter.
+    pub kind: Kind,
+    /// The value of the parameter upon registration and reset.
+    pub default: Value,
+    /// The current value of the parameter.
+    pub value: Value,
+}
+
+/// The kinds of parameters that may be registered.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Kind {
+    /// A float within the given inclusive range. Values outside of the range are clamped.
+    ///
+    /// If a `step` is given, values are rounded to the nearest multiple of `step` from `min`.
+    Float {
+        min: f32,
+        max: f32,
+        step: Option<f32>,
+    },
+    /// A boolean toggle.
+    Bool,
+    /// A non-linear sRGBA color.
+    Color,
+    /// One of the given named variants.
+    Enum { variants: Vec<String> },
+}
+
+/// The value of a single parameter.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Value {
+    Float(f32),
+    Bool(bool),
+    /// Non-linear sRGBA components, each within the range `0.0..=1.0`.
+    Color([f32; 4]),
+    /// The index of the variant.
+    Enum(usize),
+}
+
+/// The values of a set of parameters at a single moment in time.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Snapshot {
+    /// The value of each parameter by name.
+    pub values: BTreeMap<String, Value>,
+}
+
+/// Errors that might occur while setting the value of a parameter.
+#[derive(Clone, Debug, PartialEq)]
+pub enum SetError {
+    /// No parameter with the given name exists.
+    UnknownParam(String),
+    /// The value does not match the kind of the parameter.
+    WrongKind { name: String, value: Value },
+    /// The enum variant index is out of range.
+    UnknownVariant { name: String, index: usize },
+}
+
+impl Params {
+    /// Create a new, empty set of parameters.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a float parameter within the given inclusive range.
+    ///
+    /// If a parameter with the same name already exists, it is replaced.
+    pub fn add_float(&self, name: &str, min: f32, max: f32, default: f32) -> &Self {
+        let kind = Kind::Float {
+            min,
+            max,
+            step: None,
+        };
+        self.insert(Param::new(name, kind, Value::Float(default)))
+    }
+
+    /// Register a boolean parameter.
+    pub fn add_bool(&self, name: &str, default: bool) -> &Self {
+        self.insert(Param::new(name, Kind::Bool, Value::Bool(default)))
+    }
+
+    /// Register a color parameter.
+    pub fn add_color(&self, name: &str, default: Srgba) -> &Self {
+        self.insert(Param::new(name, Kind::Color, Value::from(default)))
+    }
+
+    /// Register a parameter whose value is one of the given variants.
+    ///
+    /// **Panics** if `default` is not a valid index into `variants`.
+    pub fn add_enum(&self, name: &str, variants: &[&str], default: usize) -> &Self {
+        assert!(
+            default < variants.len(),
+            "default variant index out of range"
+        );
+        let variants = variants.iter().map(|s| s.to_string()).collect();
+        let kind = Kind::Enum { variants };
+        self.insert(Param::new(name, kind, Value::Enum(default)))
+    }
+
+    /// Register the given parameter.
+    ///
+    /// If a parameter with the same name already exists, it is replaced.
+    ///
+    /// **Panics** if the parameter's default value does not match its kind.
+    pub fn insert(&self, mut param: Param) -> &Self {
+        param.default = param
+            .kind
+            .validate(&param.name, param.default)
+            .expect("default value does not match the kind of the parameter");
+        param.value = param.default.clone();
+        let mut inner = self.lock();
+        match inner.indices.get(&param.name).cloned() {
+            Some(ix) => inner.params[ix] = param,
+            None => {
+                let ix = inner.params.len();
+                inner.indices.insert(param.name.clone(), ix);
+                inner.params.push(param);
+            }
+        }
+        inner.generation += 1;
+        self
+    }
+
+    /// Remove the parameter with the given name, returning it if it existed.
+    pub fn remove(&self, name: &str) -> Option<Param> {
+        let mut inner = self.lock();
+        let ix = inner.indices.remove(name)?;
+        let param = inner.params.remove(ix);
+        for index in inner.indices.values_mut() {
+            if *index > ix {
+                *index -= 1;
+            }
+        }
+        inner.generation += 1;
+        Some(param)
+    }
+
+    /// A copy of the parameter with the given name.
+    pub fn param(&self, name: &str) -> Option<Param> {
+        let inner = self.lock();
+        inner.param(name).cloned()
+    }
+
+    /// A copy of all parameters in the order in which they were added.
+    pub fn params(&self) -> Vec<Param> {
+        self.lock().params.clone()
+    }
+
+    /// The names of all parameters in the order in which they were added.
+    pub fn names(&self) -> Vec<String> {
+        self.lock().params.iter().map(|p| p.name.clone()).collect()
+    }
+
+    /// The current value of the parameter with the given name.
+    pub fn get(&self, name: &str) -> Option<Value> {
+        self.lock().param(name).map(|p| p.value.clone())
+    }
+
+    /// The current value of the float parameter with the given name.
+    pub fn float(&self, name: &str) -> Option<f32> {
+        match self.get(name) {
+            Some(Value::Float(f)) => Some(f),
+            _ => None,
+        }
+    }
+
+    /// The current value of the bool parameter with the given name.
+    pub fn bool(&self, name: &str) -> Option<bool> {
+        match self.get(name) {
+            Some(Value::Bool(b)) => Some(b),
+            _ => None,
+        }
+    }
+
+    /// The current value of the color parameter with the given name.
+    pub fn color(&self, name: &str) -> Option<Srgba> {
+        match self.get(name) {
+            Some(Value::Color([r, g, b, a])) => Some(Srgba::new(r, g, b, a)),
+            _ => None,
+        }
+    }
+
+    /// The index of the current variant of the enum parameter with the given name.
+    pub fn enum_index(&self, name: &str) -> Option<usize> {
+        match self.get(name) {
+            Some(Value::Enum(ix)) => Some(ix),
+            _ => None,
+        }
+    }
+
+    /// The name of the current variant of the enum parameter with the given name.
+    pub fn enum_variant(&self, name: &str) -> Option<String> {
+        let inner = self.lock();
+        match inner.param(name) {
+            Some(&Param {
+                kind: Kind::Enum { ref variants },
+                value: Value::Enum(ix),
+                ..
+            }) => variants.get(ix).cloned(),
+            _ => None,
+        }
+    }
+
+    /// Set the value of the parameter with the given name.
+    ///
+    /// Float values are clamped to the parameter's range.
+    pub fn set<V>(&self, name: &str, value: V) -> Result<(), SetError>
+    where
+        V: Into<Value>,
+    {
+        let mut inner = self.lock();
+        inner.set(name, value.into())
+    }
+
+    /// Reset the parameter with the given name to its default value.
+    pub fn reset(&self, name: &str) -> Result<(), SetError> {
+        let mut inner = self.lock();
+        let default = match inner.param(name) {
+            None => return Err(SetError::UnknownParam(name.to_string())),
+            Some(param) => param.default.clone(),
+        };
+        inner.set(name, default)
+    }
+
+    /// Reset all parameters to their default values.
+    pub fn reset_all(&self) {
+        let mut inner = self.lock();
+        for param in inner.params.iter_mut() {
+            param.value = param.default.clone();
+        }
+        inner.generation += 1;
+    }
+
+    /// A counter that is incremented each time a parameter is added, removed or changed.
+    ///
+    /// Useful for cheaply checking whether or not anything has changed, e.g. after an external
+    /// controller has set some values.
+    pub fn generation(&self) -> u64 {
+        self.lock().generation
+    }
+
+    /// Capture the current values of all parameters.
+    pub fn snapshot(&self) -> Snapshot {
+        let inner = self.lock();
+        let values = inner
+            .params
+            .iter()
+            .map(|p| (p.name.clone(), p.value.clone()))
+            .collect();
+        Snapshot { values }
+    }
+
+    /// Apply the values within the given snapshot.
+    ///
+    /// Values for unknown parameters or of the wrong kind are ignored.
+    pub fn apply(&self, snapshot: &Snapshot) {
+        let mut inner = self.lock();
+        for (name, value) in &snapshot.values {
+            inner.set(name, value.clone()).ok();
+        }
+    }
+
+    /// Save a snapshot of the current values as JSON to the given path.
+    pub fn save<P>(&self, path: P) -> Result<(), io::JsonFileError>
+    where
+        P: AsRef<Path>,
+    {
+        self.snapshot().save(path)
+    }
+
+    /// Load a snapshot from the JSON file at the given path and apply it.
+    pub fn load<P>(&self, path: P) -> Result<(), io::JsonFileError>
+    where
+        P: AsRef<Path>,
+    {
+        let snapshot = Snapshot::load(path)?;
+        self.apply(&snapshot);
+        Ok(())
+    }
+
+    fn lock(&self) -> MutexGuard<Inner> {
+        self.inner.lock().expect("failed to acquire params lock")
+    }
+}
+
+impl Param {
+    /// Describe a new parameter with the given name, kind and default value.
+    pub fn new(name: &str, kind: Kind, default: Value) -> Self {
+        Param {
+            name: name.to_string(),
+            label: name.to_string(),
+            kind,
+            value: default.clone(),
+            default,
+        }
+    }
+
+    /// Specify the human readable label for the parameter.
+    pub fn with_label(mut self, label: &str) -> Self {
+        self.label = label.to_string();
+        self
+    }
+}
+
+impl Inner {
+    fn param(&self, name: &str) -> Option<&Param> {
+        self.indices.get(name).map(|&ix| &self.params[ix])
+    }
+
+    fn set(&mut self, name: &str, value: Value) -> Result<(), SetError> {
+        let ix = match self.indices.get(name) {
+            None => return Err(SetError::UnknownParam(name.to_string())),
+            Some(&ix) => ix,
+        };
+        let param = &mut self.params[ix];
+        let value = param.kind.validate(name, value)?;
+        if param.value != value {
+            param.value = value;
+            self.generation += 1;
+        }
+        Ok(())
+    }
+}
+
+impl Kind {
+    // Check that the value is valid for this kind, clamping floats to the range.
+    fn validate(&self, name: &str, value: Value) -> Result<Value, SetError> {
+        match (self, value) {
+            (&Kind::Float { min, max, step }, Value::Float(f)) => {
+                let f = match step {
+                    Some(step) if step > 0.0 => min + ((f - min) / step).round() * step,
+                    _ => f,
+                };
+                Ok(Value::Float(f.max(min).min(max)))
+            }
+            (&Kind::Bool, Value::Bool(b)) => Ok(Value::Bool(b)),
+            (&Kind::Color, Value::Color(c)) => {
+                let mut c = c;
+                for channel in c.iter_mut() {
+                    *channel = channel.max(0.0).min(1.0);
+                }
+                Ok(Value::Color(c))
+            }
+            (&Kind::Enum { ref variants }, Value::Enum(index)) => {
+                if index < variants.len() {
+                    Ok(Value::Enum(index))
+                } else {
+                    let name = name.to_string();
+                    Err(SetError::UnknownVariant { name, index })
+                }
+            }
+            (_, value) => {
+                let name = name.to_string();
+                Err(SetError::WrongKind { name, value })
+            }
+        }
+    }
+}
+
+impl Snapshot {
+    /// Interpolate between this snapshot and `other` by the given amount, where `0.0` produces
+    /// `self` and `1.0` produces `other`.
+    ///
+    /// Float and color values are interpolated linearly. Bool and enum values switch at the
+    /// halfway point. Values only present within one of the snapshots are retained as is.
+    pub fn lerp(&self, other: &Self, amount: f32) -> Self {
+        let mut values = other.values.clone();
+        for (name, a) in &self.values {
+            let value = match other.values.get(name) {
+                Some(b) => a.lerp(b, amount),
+                None => a.clone(),
+            };
+            values.insert(name.clone(), value);
+        }
+        Snapshot { values }
+    }
+
+    /// Load a snapshot from the JSON file at the given path.
+    pub fn load<P>(path: P) -> Result<Self, io::JsonFileError>
+    where
+        P: AsRef<Path>,
+    {
+        io::load_from_json(path)
+    }
+
+    /// Save the snapshot as JSON to the given path.
+    pub fn save<P>(&self, path: P) -> Result<(), io::JsonFileError>
+    where
+        P: AsRef<Path>,
+    {
+        io::save_to_json(path, self)
+    }
+}
+
+impl Value {
+    /// Interpolate between this value and `other` by the given amount.
+    ///
+    /// Float and color values are interpolated linearly. All other values, including those of
+    /// mismatched kinds, switch from `self` to `other` at the halfway point.
+    pub fn lerp(&self, other: &Self, amount: f32) -> Self {
+        let lerp = |a: f32, b: f32| a + (b - a) * amount;
+        match (self, other) {
+            (&Value::Float(a), &Value::Float(b)) => Value::Float(lerp(a, b)),
+            (&Value::Color(a), &Value::Color(b)) => {
+                let mut c = a;
+                for (i, channel) in c.iter_mut().enumerate() {
+                    *channel = lerp(a[i], b[i]);
+                }
+                Value::Color(c)
+            }
+            _ if amount < 0.5 => self.clone(),
+            _ => other.clone(),
+        }
+    }
+}
+
+impl From<f32> for Value {
+    fn from(f: f32) -> Self {
+        Value::Float(f)
+    }
+}
+
+impl From<bool> for Value {
+    fn from(b: bool) -> Self {
+        Value::Bool(b)
+    }
+}
+
+impl From<Srgba> for Value {
+    fn from(c: Srgba) -> Self {
+        Value::Color([c.red, c.green, c.blue, c.alpha])
+    }
+}
+
+impl error::Error for SetError {}
+
+impl fmt::Display for SetError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            SetError::UnknownParam(ref name) => write!(f, "no parameter named \"{}\"", name),
+            SetError::WrongKind {
+                ref name,
+                ref value,
+            } => write!(f, "invalid value {:?} for parameter \"{}\"", value, name),
+            SetError::UnknownVariant { ref name, index } => write!(
+                f,
+                "variant index {} out of range for parameter \"{}\"",
+                index, name
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn float(min: f32, max: f32, step: Option<f32>) -> Kind {
+        Kind::Float { min, max, step }
+    }
+
+    #[test]
+    fn validate_clamps_floats() {
+        let kind = float(0.0, 10.0, None);
+        assert_eq!(kind.validate("f", Value::Float(3.3)), Ok(Value::Float(3.3)));
+        assert_eq!(
+            kind.validate("f", Value::Float(12.0)),
+            Ok(Value::Float(10.0))
+        );
+        assert_eq!(
+            kind.validate("f", Value::Float(-5.0)),
+            Ok(Value::Float(0.0))
+        );
+    }
+
+    #[test]
+    fn validate_rounds_to_step() {
+        let kind = float(0.0, 10.0, Some(0.5));
+        assert_eq!(kind.validate("f", Value::Float(3.3)), Ok(Value::Float(3.5)));
+        assert_eq!(kind.validate("f", Value::Float(3.2)), Ok(Value::Float(3.0)));
+        // Steps are taken from `min`, and the result is still clamped to the range.
+        let kind = float(1.0, 10.0, Some(2.0));
+        assert_eq!(kind.validate("f", Value::Float(4.2)), Ok(Value::Float(5.0)));
+        assert_eq!(
+            kind.validate("f", Value::Float(10.5)),
+            Ok(Value::Float(10.0))
+        );
+        // A non-positive step is ignored.
+        let kind = float(0.0, 1.0, Some(0.0));
+        assert_eq!(kind.validate("f", Value::Float(0.3)), Ok(Value::Float(0.3)));
+    }
+
+    #[test]
+    fn validate_clamps_colors() {
+        let value = Kind::Color.validate("c", Value::Color([1.5, -0.5, 0.25, 1.0]));
+        assert_eq!(value, Ok(Value::Color([1.0, 0.0, 0.25, 1.0])));
+    }
+
+    #[test]
+    fn validate_enum_range() {
+        let variants = vec!["a".to_string(), "b".to_string()];
+        let kind = Kind::Enum { variants };
+        assert_eq!(kind.validate("e", Value::Enum(1)), Ok(Value::Enum(1)));
+        let err = SetError::UnknownVariant {
+            name: "e".to_string(),
+            index: 2,
+        };
+        assert_eq!(kind.validate("e", Value::Enum(2)), Err(err));
+    }
+
+    #[test]
+    fn validate_wrong_kind() {
+        let err = SetError::WrongKind {
+            name: "b".to_string(),
+            value: Value::Float(1.0),
+        };
+        assert_eq!(Kind::Bool.validate("b", Value::Float(1.0)), Err(err));
+        let kind = float(0.0, 1.0, None);
+        assert!(kind.validate("f", Value::Enum(0)).is_err());
+        assert!(Kind::Color.validate("c", Value::Bool(true)).is_err());
+    }
+
+    #[test]
+    fn set_errors() {
+        let params = Params::new();
+        params.add_float("radius", 0.0, 100.0, 10.0);
+        params.add_enum("shape", &["circle", "square"], 0);
+        let err = SetError::UnknownParam("missing".to_string());
+        assert_eq!(params.set("missing", 1.0), Err(err));
+        assert!(params.set("radius", true).is_err());
+        assert!(params.set("shape", Value::Enum(2)).is_err());
+        assert_eq!(params.float("radius"), Some(10.0));
+        assert_eq!(params.enum_index("shape"), Some(0));
+    }
+
+    #[test]
+    fn value_lerp() {
+        assert_eq!(
+            Value::Float(0.0).lerp(&Value::Float(4.0), 0.25),
+            Value::Float(1.0)
+        );
+        let a = Value::Color([0.0, 0.0, 1.0, 1.0]);
+        let b = Value::Color([1.0, 0.5, 0.0, 1.0]);
+        assert_eq!(a.lerp(&b, 0.5), Value::Color([0.5, 0.25, 0.5, 1.0]));
+        let (a, b) = (Value::Bool(false), Value::Bool(true));
+        assert_eq!(a.lerp(&b, 0.49), a);
+        assert_eq!(a.lerp(&b, 0.5), b);
+        let (a, b) = (Value::Enum(0), Value::Enum(3));
+        assert_eq!(a.lerp(&b, 0.25), a);
+        assert_eq!(a.lerp(&b, 0.75), b);
+        // Mismatched kinds switch at the halfway point.
+        let (a, b) = (Value::Float(0.0), Value::Bool(true));
+        assert_eq!(a.lerp(&b, 0.25), a);
+        assert_eq!(a.lerp(&b, 0.75), b);
+    }
+
+    #[test]
+    fn snapshot_lerp_and_apply() {
+        let params = Params::new();
+        params.add_float("radius", 0.0, 100.0, 10.0);
+        params.add_bool("visible", false);
+        let a = params.snapshot();
+        params.set("radius", 50.0).unwrap();
+        params.set("visible", true).unwrap();
+        let b = params.snapshot();
+
+        let mid = a.lerp(&b, 0.5);
+        assert_eq!(mid.values["radius"], Value::Float(30.0));
+        assert_eq!(mid.values["visible"], Value::Bool(true));
+
+        // Values only present in one snapshot are retained.
+        let mut c = Snapshot::default();
+        c.values.insert("speed".to_string(), Value::Float(2.0));
+        let merged = a.lerp(&c, 0.5);
+        assert_eq!(merged.values["radius"], Value::Float(10.0));
+        assert_eq!(merged.values["speed"], Value::Float(2.0));
+
+        // Unknown params and values of the wrong kind are ignored when applied.
+        let mut snapshot = a.clone();
+        snapshot
+            .values
+            .insert("speed".to_string(), Value::Float(2.0));
+        snapshot
+            .values
+            .insert("visible".to_string(), Value::Float(1.0));
+        params.apply(&snapshot);
+        assert_eq!(params.float("radius"), Some(10.0));
+        assert_eq!(params.bool("visible"), Some(true));
+        assert!(params.get("speed").is_none());
+    }
+
+    #[test]
+    fn remove_fixes_indices() {
+        let params = Params::new();
+        params.add_float("a", 0.0, 1.0, 0.1);
+        params.add_float("b", 0.0, 1.0, 0.2);
+        params.add_float("c", 0.0, 1.0, 0.3);
+        let removed = params.remove("a").unwrap();
+        assert_eq!(removed.name, "a");
+        assert!(params.remove("a").is_none());
+        assert_eq!(params.names(), vec!["b", "c"]);
+        assert_eq!(params.float("b"), Some(0.2));
+        assert_eq!(params.float("c"), Some(0.3));
+        params.set("c", 0.9).unwrap();
+        assert_eq!(params.float("c"), Some(0.9));
+        assert_eq!(params.float("b"), Some(0.2));
+        params.add_float("a", 0.0, 1.0, 0.4);
+        assert_eq!(params.names(), vec!["b", "c", "a"]);
+        assert_eq!(params.float("a"), Some(0.4));
+    }
+
+    #[test]
+    fn insert_replaces_in_place() {
+        let params = Params::new();
+        params.add_float("a", 0.0, 1.0, 0.1);
+        params.add_bool("b", false);
+        params.add_float("a", 0.0, 10.0, 5.0);
+        assert_eq!(params.names(), vec!["a", "b"]);
+        assert_eq!(params.float("a"), Some(5.0));
+    }
+
+    #[test]
+    fn generation_bumps() {
+        let params = Params::new();
+        let mut generation = params.generation();
+        let mut bumped = |params: &Params| {
+            let g = params.generation();
+            let bumped = g != generation;
+            generation = g;
+            bumped
+        };
+        params.add_float("radius", 0.0, 100.0, 10.0);
+        assert!(bumped(&params));
+        params.set("radius", 20.0).unwrap();
+        assert!(bumped(&params));
+        // Setting the same value, or a value that is clamped to the same value, is not a change.
+        params.set("radius", 20.0).unwrap();
+        assert!(!bumped(&params));
+        params.set("radius", 200.0).unwrap();
+        assert!(bumped(&params));
+        params.set("radius", 300.0).unwrap();
+        assert!(!bumped(&params));
+        params.set("radius", true).ok();
+        assert!(!bumped(&params));
+        params.reset("radius").unwrap();
+        assert!(bumped(&params));
+        params.reset_all();
+        assert!(bumped(&params));
+        params.remove("radius");
+        assert!(bumped(&params));
+        params.remove("radius");
+        assert!(!bumped(&params));
+    }
+}
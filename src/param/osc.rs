@@ -0,0 +1,234 @@
+//! Serving **Params** over OSC.
+//!
+//! A **Server** listens for OSC packets on a local UDP port on its own thread. By default only
+//! packets sent from the same machine are received as the server binds to the loopback address.
+//! Use `Server::bind_addr` with an address such as `0.0.0.0:<port>` to allow remote control from
+//! other machines on the network.
+//!
+//! Each parameter is addressed by its name beneath a namespace, e.g. with the default namespace a
+//! parameter named `radius` is addressed via `/nannou/radius`.
+//!
+//! - A message with arguments sets the value of the parameter.
+//! - A message without arguments is a query. The current value is sent back to the address from
+//!   which the query was received, using the same OSC address.
+//! - A message to the namespace itself without arguments queries all parameters.
+//!
+//! Arguments are converted to parameter values as follows:
+//!
+//! - **Float**: a single `Float`, `Double`, `Int` or `Long`.
+//! - **Bool**: a single `Bool`, or an `Int` or `Float` where non-zero is `true`.
+//! - **Color**: three or four `Float`s within the range `0.0..=1.0`, or a single OSC `Color`.
+//! - **Enum**: an `Int` variant index or a `String` variant name.
+
+use super::{Kind, Params, Value};
+use nannou_osc::{self as osc, CommunicationError, Message, Packet, Type};
+use std::net::{Ipv4Addr, SocketAddr};
+use std::sync::atomic::{self, AtomicBool};
+use std::sync::Arc;
+use std::{thread, time};
+
+/// The namespace used by `Server::bind`.
+pub const DEFAULT_NAMESPACE: &str = "/nannou";
+
+// How long the receiving thread sleeps between checks for new packets.
+const POLL_INTERVAL: time::Duration = time::Duration::from_millis(1);
+
+/// Serves a set of **Params** over OSC on its own thread.
+///
+/// The thread is stopped and joined when the **Server** is dropped.
+pub struct Server {
+    addr: SocketAddr,
+    namespace: String,
+    is_running: Arc<AtomicBool>,
+    thread: Option<thread::JoinHandle<()>>,
+}
+
+impl Server {
+    /// Serve the given params on the given UDP port of the loopback address using the default
+    /// namespace.
+    ///
+    /// If `port` is `0`, a free port is chosen by the OS and may be retrieved via `local_addr`.
+    pub fn bind(params: &Params, port: u16) -> Result<Self, CommunicationError> {
+        Self::bind_with_namespace(params, port, DEFAULT_NAMESPACE)
+    }
+
+    /// Serve the given params on the given UDP port of the loopback address beneath the given
+    /// address namespace.
+    pub fn bind_with_namespace(
+        params: &Params,
+        port: u16,
+        namespace: &str,
+    ) -> Result<Self, CommunicationError> {
+        let addr = SocketAddr::from((Ipv4Addr::LOCALHOST, port));
+        Self::bind_addr(params, addr, namespace)
+    }
+
+    /// Serve the given params on the given socket address beneath the given address namespace.
+    ///
+    /// Binding to an unspecified address such as `0.0.0.0` allows any host on the network to
+    /// query and set parameters.
+    pub fn bind_addr(
+        params: &Params,
+        addr: SocketAddr,
+        namespace: &str,
+    ) -> Result<Self, CommunicationError> {
+        let receiver = osc::Receiver::bind_to(addr)?;
+        let addr = receiver.local_addr()?;
+        let sender = osc::sender()?;
+        let namespace = namespace.trim_end_matches('/').to_string();
+        let is_running = Arc::new(AtomicBool::new(true));
+
+        let thread = {
+            let params = params.clone();
+            let namespace = namespace.clone();
+            let is_running = is_running.clone();
+            thread::Builder::new()
+                .name("nannou_param_osc".into())
+                .spawn(move || {
+                    while is_running.load(atomic::Ordering::Relaxed) {
+                        for (packet, addr) in receiver.try_iter() {
+                            for msg in unfold_packet(packet) {
+                                let replies = handle_msg(&params, &namespace, msg);
+                                for reply in replies {
+                                    sender.send(reply, addr).ok();
+                                }
+                            }
+                        }
+                        thread::sleep(POLL_INTERVAL);
+                    }
+                })
+                .expect("failed to spawn OSC param thread")
+        };
+
+        Ok(Server {
+            addr,
+            namespace,
+            is_running,
+            thread: Some(thread),
+        })
+    }
+
+    /// The local UDP port on which the server is listening.
+    pub fn port(&self) -> u16 {
+        self.addr.port()
+    }
+
+    /// The address namespace beneath which parameters are served.
+    pub fn namespace(&self) -> &str {
+        &self.namespace
+    }
+
+    /// The local socket address on which the server is listening.
+    pub fn local_addr(&self) -> SocketAddr {
+        self.addr
+    }
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        self.is_running.store(false, atomic::Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            thread.join().ok();
+        }
+    }
+}
+
+/// Convert the given OSC arguments to a value suitable for the given kind of parameter.
+pub fn value_from_args(kind: &Kind, args: &[Type]) -> Option<Value> {
+    let value = match (kind, args) {
+        (&Kind::Float { .. }, &[ref arg]) => Value::Float(arg_to_f32(arg)?),
+        (&Kind::Bool, &[Type::Bool(b)]) => Value::Bool(b),
+        (&Kind::Bool, &[ref arg]) => Value::Bool(arg_to_f32(arg)? != 0.0),
+        (&Kind::Color, &[Type::Color(ref c)]) => {
+            let f = |u: u8| u as f32 / 255.0;
+            Value::Color([f(c.red), f(c.green), f(c.blue), f(c.alpha)])
+        }
+        (&Kind::Color, &[ref r, ref g, ref b]) => {
+            Value::Color([arg_to_f32(r)?, arg_to_f32(g)?, arg_to_f32(b)?, 1.0])
+        }
+        (&Kind::Color, &[ref r, ref g, ref b, ref a]) => Value::Color([
+            arg_to_f32(r)?,
+            arg_to_f32(g)?,
+            arg_to_f32(b)?,
+            arg_to_f32(a)?,
+        ]),
+        (&Kind::Enum { ref variants }, &[Type::String(ref s)]) => {
+            Value::Enum(variants.iter().position(|v| v == s)?)
+        }
+        (&Kind::Enum { .. }, &[Type::Int(i)]) if i >= 0 => Value::Enum(i as usize),
+        _ => return None,
+    };
+    Some(value)
+}
+
+/// Convert the given value to a list of OSC arguments.
+pub fn value_to_args(value: &Value) -> Vec<Type> {
+    match *value {
+        Value::Float(f) => vec![Type::Float(f)],
+        Value::Bool(b) => vec![Type::Bool(b)],
+        Value::Color(c) => c.iter().map(|&f| Type::Float(f)).collect(),
+        Value::Enum(ix) => vec![Type::Int(ix as i32)],
+    }
+}
+
+fn arg_to_f32(arg: &Type) -> Option<f32> {
+    match *arg {
+        Type::Float(f) => Some(f),
+        Type::Double(d) => Some(d as f32),
+        Type::Int(i) => Some(i as f32),
+        Type::Long(l) => Some(l as f32),
+        _ => None,
+    }
+}
+
+// Apply the given message to the params, producing any necessary replies.
+fn handle_msg(params: &Params, namespace: &str, msg: Message) -> Vec<Message> {
+    let args = msg.args.unwrap_or_else(Vec::new);
+
+    // Query all parameters.
+    if msg.addr == namespace {
+        if !args.is_empty() {
+            return vec![];
+        }
+        return params
+            .params()
+            .iter()
+            .map(|p| reply(namespace, &p.name, &p.value))
+            .collect();
+    }
+
+    let prefix = format!("{}/", namespace);
+    if !msg.addr.starts_with(&prefix) {
+        return vec![];
+    }
+    let name = &msg.addr[prefix.len()..];
+    let param = match params.param(name) {
+        None => return vec![],
+        Some(param) => param,
+    };
+
+    // Query a single parameter.
+    if args.is_empty() {
+        return vec![reply(namespace, name, &param.value)];
+    }
+
+    if let Some(value) = value_from_args(&param.kind, &args) {
+        params.set(name, value).ok();
+    }
+    vec![]
+}
+
+fn reply(namespace: &str, name: &str, value: &Value) -> Message {
+    Message {
+        addr: format!("{}/{}", namespace, name),
+        args: Some(value_to_args(value)),
+    }
+}
+
+// Flatten the packet into its messages, recursing into bundles.
+fn unfold_packet(packet: Packet) -> Vec<Message> {
+    match packet {
+        Packet::Message(msg) => vec![msg],
+        Packet::Bundle(bundle) => bundle.content.into_iter().flat_map(unfold_packet).collect(),
+    }
+}
@@ -0,0 +1,385 @@
+//! A GUI panel for viewing and editing **Params** via the conrod **Ui**.
+//!
+//! The **Panel** displays a widget for each parameter in the order in which they were registered:
+//!
+//! - **Float** parameters are displayed as sliders.
+//! - **Bool** parameters are displayed as toggles.
+//! - **Color** parameters are displayed as a swatch, a saturation/value pad and sliders for hue
+//!   and alpha.
+//! - **Enum** parameters are displayed as dropdown lists.
+//!
+//! Optionally, buttons for saving and loading a preset snapshot may be displayed. Any error that
+//! occurs while saving or loading is displayed beneath the buttons.
+
+use super::{Kind, Param, Params, Value};
+use crate::ui::{self, widget, Colorable, Labelable, Positionable, Scalar, Sizeable, Widget};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Displays a widget for each parameter within a `Ui`.
+///
+/// ```ignore
+/// let mut ui = app.new_ui().window(window_id).build().unwrap();
+/// let panel = param::Panel::new(&mut ui, &params).presets_path("presets.json");
+///
+/// // Within `update`.
+/// if model.panel.set(&mut model.ui.set_widgets()) {
+///     model.settings.read(&model.params);
+/// }
+/// ```
+#[derive(Debug)]
+pub struct Panel {
+    params: Params,
+    ids: Ids,
+    presets_path: Option<PathBuf>,
+    width: Scalar,
+    // The most recent HSV value displayed for each color parameter, along with the RGBA value
+    // from which it was produced.
+    hsv: HashMap<String, ([f32; 4], [f32; 3])>,
+    // The error produced by the most recent attempt to save or load a preset.
+    status: Option<String>,
+}
+
+#[derive(Debug)]
+struct Ids {
+    canvas: widget::Id,
+    reset: widget::Id,
+    save: widget::Id,
+    load: widget::Id,
+    status: widget::Id,
+    // The widgets used for each parameter by name.
+    params: HashMap<String, ParamIds>,
+}
+
+#[derive(Copy, Clone, Debug)]
+struct ParamIds {
+    label: widget::Id,
+    swatch: widget::Id,
+    widgets: [widget::Id; 4],
+}
+
+impl Panel {
+    /// The default width of the panel in points.
+    pub const DEFAULT_WIDTH: Scalar = 240.0;
+
+    const PAD: Scalar = 10.0;
+    const WIDGET_H: Scalar = 24.0;
+    const FONT_SIZE: ui::FontSize = 12;
+
+    /// Create a panel displaying the given params within the given `Ui`.
+    pub fn new(ui: &mut ui::Ui, params: &Params) -> Self {
+        let mut gen = ui.widget_id_generator();
+        let ids = Ids {
+            canvas: gen.next(),
+            reset: gen.next(),
+            save: gen.next(),
+            load: gen.next(),
+            status: gen.next(),
+            params: HashMap::new(),
+        };
+        Panel {
+            params: params.clone(),
+            ids,
+            presets_path: None,
+            width: Self::DEFAULT_WIDTH,
+            hsv: HashMap::new(),
+            status: None,
+        }
+    }
+
+    /// Display buttons for saving a snapshot of the params to and loading it from the given path.
+    pub fn presets_path<P>(mut self, path: P) -> Self
+    where
+        P: AsRef<Path>,
+    {
+        self.presets_path = Some(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Specify the width of the panel.
+    pub fn width(mut self, width: Scalar) -> Self {
+        self.width = width;
+        self
+    }
+
+    /// The params displayed by the panel.
+    pub fn params(&self) -> &Params {
+        &self.params
+    }
+
+    /// Instantiate the panel's widgets, applying any changes made via the GUI to the params.
+    ///
+    /// Returns `true` if any parameter was changed via the panel.
+    pub fn set(&mut self, ui: &mut ui::UiCell) -> bool {
+        let generation = self.params.generation();
+        let canvas = self.ids.canvas;
+
+        widget::Canvas::new()
+            .top_left_of(ui.window)
+            .w(self.width)
+            .h_of(ui.window)
+            .pad(Self::PAD)
+            .scroll_kids_vertically()
+            .rgba(0.1, 0.1, 0.1, 0.9)
+            .set(canvas, ui);
+
+        let mut prev = None;
+        for param in self.params.params() {
+            let ids = match self.ids.params.get(&param.name) {
+                Some(&ids) => ids,
+                None => {
+                    let mut gen = ui.widget_id_generator();
+                    let ids = ParamIds {
+                        label: gen.next(),
+                        swatch: gen.next(),
+                        widgets: [gen.next(), gen.next(), gen.next(), gen.next()],
+                    };
+                    self.ids.params.insert(param.name.clone(), ids);
+                    ids
+                }
+            };
+            let hsv = &mut self.hsv;
+            prev = Some(set_param(&self.params, &param, ids, hsv, canvas, prev, ui));
+        }
+
+        // The reset and preset buttons.
+        let button = |label, prev: Option<widget::Id>| {
+            let button = widget::Button::new()
+                .label(label)
+                .label_font_size(Self::FONT_SIZE)
+                .kid_area_w_of(canvas)
+                .h(Self::WIDGET_H);
+            match prev {
+                None => button.mid_top_of(canvas),
+                Some(_) => button.down(Self::PAD),
+            }
+        };
+        if button("Reset", prev).set(self.ids.reset, ui).was_clicked() {
+            self.params.reset_all();
+        }
+        if let Some(ref path) = self.presets_path {
+            if button("Save Preset", Some(self.ids.reset))
+                .set(self.ids.save, ui)
+                .was_clicked()
+            {
+                self.status = match self.params.save(path) {
+                    Ok(()) => None,
+                    Err(err) => {
+                        let path = path.display();
+                        Some(format!("Failed to save preset to \"{}\": {}", path, err))
+                    }
+                };
+            }
+            if button("Load Preset", Some(self.ids.save))
+                .set(self.ids.load, ui)
+                .was_clicked()
+            {
+                self.status = match self.params.load(path) {
+                    Ok(()) => None,
+                    Err(err) => {
+                        let path = path.display();
+                        Some(format!("Failed to load preset from \"{}\": {}", path, err))
+                    }
+                };
+            }
+            if let Some(ref status) = self.status {
+                widget::Text::new(status)
+                    .font_size(Self::FONT_SIZE)
+                    .kid_area_w_of(canvas)
+                    .wrap_by_word()
+                    .down_from(self.ids.load, Self::PAD)
+                    .rgb(1.0, 0.4, 0.4)
+                    .set(self.ids.status, ui);
+            }
+        }
+
+        self.params.generation() != generation
+    }
+}
+
+// Instantiate the widgets for a single parameter, returning the ID of the last widget.
+fn set_param(
+    params: &Params,
+    param: &Param,
+    ids: ParamIds,
+    hsv: &mut HashMap<String, ([f32; 4], [f32; 3])>,
+    canvas: widget::Id,
+    prev: Option<widget::Id>,
+    ui: &mut ui::UiCell,
+) -> widget::Id {
+    // Position the first widget for the parameter below the previous parameter's widgets.
+    fn place<W>(w: W, canvas: widget::Id, prev: Option<widget::Id>) -> W
+    where
+        W: Positionable + Sizeable,
+    {
+        let w = w.kid_area_w_of(canvas).h(Panel::WIDGET_H);
+        match prev {
+            None => w.mid_top_of(canvas),
+            Some(_) => w.down(Panel::PAD),
+        }
+    }
+
+    let name = &param.name;
+    match (&param.kind, &param.value) {
+        (&Kind::Float { min, max, .. }, &Value::Float(value)) => {
+            let label = format!("{}: {:.3}", param.label, value);
+            let slider = widget::Slider::new(value, min, max)
+                .label(&label)
+                .label_font_size(Panel::FONT_SIZE);
+            for value in place(slider, canvas, prev).set(ids.widgets[0], ui) {
+                params.set(name, value).ok();
+            }
+            ids.widgets[0]
+        }
+
+        (&Kind::Bool, &Value::Bool(value)) => {
+            let toggle = widget::Toggle::new(value)
+                .label(&param.label)
+                .label_font_size(Panel::FONT_SIZE);
+            for value in place(toggle, canvas, prev).set(ids.widgets[0], ui) {
+                params.set(name, value).ok();
+            }
+            ids.widgets[0]
+        }
+
+        (&Kind::Color, &Value::Color(rgba)) => {
+            let [r, g, b, a] = rgba;
+            let swatch = widget::Rectangle::fill([0.0, 0.0]).rgba(r, g, b, a);
+            place(swatch, canvas, prev).set(ids.swatch, ui);
+            widget::Text::new(&param.label)
+                .font_size(Panel::FONT_SIZE)
+                .middle_of(ids.swatch)
+                .set(ids.label, ui);
+
+            // Hue and saturation cannot be recovered from greys and black, so reuse the previous
+            // HSV value for as long as the color remains unchanged.
+            let [h, s, v] = match hsv.get(name) {
+                Some(&(prev_rgba, prev_hsv)) if prev_rgba == rgba => prev_hsv,
+                _ => rgb_to_hsv([r, g, b]),
+            };
+            let (mut new_hsv, mut new_a) = ([h, s, v], a);
+            for (s, v) in widget::XYPad::new(s, 0.0, 1.0, v, 0.0, 1.0)
+                .label("S / V")
+                .label_font_size(Panel::FONT_SIZE)
+                .value_font_size(Panel::FONT_SIZE)
+                .kid_area_w_of(canvas)
+                .h(Panel::WIDGET_H * 4.0)
+                .down_from(ids.swatch, Panel::PAD / 2.0)
+                .set(ids.widgets[0], ui)
+            {
+                new_hsv[1] = s;
+                new_hsv[2] = v;
+            }
+            let slider = |value, channel, below| {
+                widget::Slider::new(value, 0.0, 1.0)
+                    .label(channel)
+                    .label_font_size(Panel::FONT_SIZE)
+                    .kid_area_w_of(canvas)
+                    .h(Panel::WIDGET_H)
+                    .down_from(below, Panel::PAD / 2.0)
+            };
+            for h in slider(h, "H", ids.widgets[0]).set(ids.widgets[1], ui) {
+                new_hsv[0] = h;
+            }
+            for a in slider(a, "A", ids.widgets[1]).set(ids.widgets[2], ui) {
+                new_a = a;
+            }
+
+            let mut rgba = rgba;
+            if new_hsv != [h, s, v] || new_a != a {
+                let [r, g, b] = hsv_to_rgb(new_hsv);
+                rgba = [r, g, b, new_a];
+                params.set(name, Value::Color(rgba)).ok();
+            }
+            hsv.insert(name.clone(), (rgba, new_hsv));
+            ids.widgets[2]
+        }
+
+        (&Kind::Enum { ref variants }, &Value::Enum(index)) => {
+            let text = widget::Text::new(&param.label).font_size(Panel::FONT_SIZE);
+            place(text, canvas, prev).set(ids.label, ui);
+            for index in widget::DropDownList::new(&variants[..], Some(index))
+                .label_font_size(Panel::FONT_SIZE)
+                .kid_area_w_of(canvas)
+                .h(Panel::WIDGET_H)
+                .down_from(ids.label, Panel::PAD / 2.0)
+                .set(ids.widgets[0], ui)
+            {
+                params.set(name, Value::Enum(index)).ok();
+            }
+            ids.widgets[0]
+        }
+
+        // The value should always match the kind.
+        _ => prev.unwrap_or(canvas),
+    }
+}
+
+// Convert the given RGB color to HSV, where all components are within the range `0.0..=1.0`.
+fn rgb_to_hsv([r, g, b]: [f32; 3]) -> [f32; 3] {
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let delta = max - min;
+    let h = if delta == 0.0 {
+        0.0
+    } else if max == r {
+        ((g - b) / delta).rem_euclid(6.0) / 6.0
+    } else if max == g {
+        ((b - r) / delta + 2.0) / 6.0
+    } else {
+        ((r - g) / delta + 4.0) / 6.0
+    };
+    let s = if max == 0.0 { 0.0 } else { delta / max };
+    [h, s, max]
+}
+
+// Convert the given HSV color to RGB, where all components are within the range `0.0..=1.0`.
+fn hsv_to_rgb([h, s, v]: [f32; 3]) -> [f32; 3] {
+    let h = (h * 6.0).rem_euclid(6.0);
+    let c = v * s;
+    let x = c * (1.0 - (h % 2.0 - 1.0).abs());
+    let m = v - c;
+    let (r, g, b) = match h as u32 {
+        0 => (c, x, 0.0),
+        1 => (x, c, 0.0),
+        2 => (0.0, c, x),
+        3 => (0.0, x, c),
+        4 => (x, 0.0, c),
+        _ => (c, 0.0, x),
+    };
+    [r + m, g + m, b + m]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{hsv_to_rgb, rgb_to_hsv};
+
+    fn near(a: [f32; 3], b: [f32; 3]) -> bool {
+        a.iter().zip(&b).all(|(a, b)| (a - b).abs() < 1e-5)
+    }
+
+    #[test]
+    fn hsv_primaries() {
+        assert!(near(rgb_to_hsv([1.0, 0.0, 0.0]), [0.0, 1.0, 1.0]));
+        assert!(near(rgb_to_hsv([0.0, 1.0, 0.0]), [1.0 / 3.0, 1.0, 1.0]));
+        assert!(near(rgb_to_hsv([0.0, 0.0, 0.5]), [2.0 / 3.0, 1.0, 0.5]));
+        assert!(near(rgb_to_hsv([0.5, 0.5, 0.5]), [0.0, 0.0, 0.5]));
+        assert!(near(hsv_to_rgb([1.0, 1.0, 1.0]), [1.0, 0.0, 0.0]));
+        assert!(near(hsv_to_rgb([0.5, 0.0, 0.25]), [0.25, 0.25, 0.25]));
+    }
+
+    #[test]
+    fn hsv_round_trip() {
+        let colors = [
+            [0.2, 0.4, 0.6],
+            [0.9, 0.1, 0.3],
+            [0.3, 0.8, 0.2],
+            [0.75, 0.75, 0.1],
+            [0.05, 0.6, 0.6],
+            [1.0, 0.0, 1.0],
+        ];
+        for &rgb in colors.iter() {
+            assert!(near(hsv_to_rgb(rgb_to_hsv(rgb)), rgb), "{:?}", rgb);
+        }
+    }
+}
@@ -0,0 +1,617 @@
+//! Storing, recalling and morphing between named snapshots of serializable state.
+//!
+//! A **Presets** collection stores each named preset as its own JSON or TOML file within a
+//! directory. Any type implementing `Serialize` and `Deserialize` may be used as a preset, e.g. a
+//! sketch's model or a `param::Snapshot`.
+//!
+//! A **Morph** smoothly transitions between two presets over time. Numeric fields (including
+//! those nested within structs, sequences and maps) are interpolated using an `ease` curve, while
+//! all other fields switch from the first preset to the second at the halfway point.
+//!
+//! ```ignore
+//! let mut presets = Presets::<Model>::open("presets", Format::Json)?;
+//! for (path, err) in presets.errors() {
+//!     eprintln!("skipped \"{}\": {}", path.display(), err);
+//! }
+//! presets.save("calm", model.clone())?;
+//!
+//! // Later, morph from the current state to the "calm" preset over four seconds.
+//! let target = presets.get("calm").unwrap();
+//! let duration = Duration::from_secs(4);
+//! model.morph = Some(Morph::new(&model.state, target, duration, ease::cubic::ease_in_out)?);
+//!
+//! // Within `update`.
+//! if let Some(ref mut morph) = model.morph {
+//!     morph.update(update.since_last);
+//!     model.state = morph.value()?;
+//! }
+//! ```
+
+use crate::anim::Ease;
+use crate::io;
+use crate::time::DurationF64;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{self, Map, Number, Value};
+use std::collections::BTreeMap;
+use std::marker::PhantomData;
+use std::path::{Component, Path, PathBuf};
+use std::time::Duration;
+use std::{error, fmt, fs};
+
+/// The file format used to store presets.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Format {
+    Json,
+    Toml,
+}
+
+/// A collection of named presets, each stored as a file within a directory.
+#[derive(Debug)]
+pub struct Presets<T> {
+    directory: PathBuf,
+    format: Format,
+    presets: BTreeMap<String, T>,
+    errors: Vec<(PathBuf, Error)>,
+}
+
+/// A transition between two states over time.
+#[derive(Clone, Debug)]
+pub struct Morph<T> {
+    from: Value,
+    to: Value,
+    elapsed: f64,
+    duration: f64,
+    ease: Ease,
+    state: PhantomData<T>,
+}
+
+/// Errors that might occur while working with presets.
+#[derive(Debug)]
+pub enum Error {
+    Io(std::io::Error),
+    Json(serde_json::Error),
+    TomlSave(toml::ser::Error),
+    TomlLoad(toml::de::Error),
+    /// No preset exists with the given name.
+    UnknownPreset(String),
+    /// The name is empty or contains path separators or components, e.g. `..`.
+    InvalidName(String),
+}
+
+impl Format {
+    /// The file extension used for presets stored in this format.
+    pub fn extension(&self) -> &'static str {
+        match *self {
+            Format::Json => "json",
+            Format::Toml => "toml",
+        }
+    }
+}
+
+impl<T> Presets<T>
+where
+    T: Serialize + DeserializeOwned,
+{
+    /// Open the preset collection within the given directory, loading all existing presets.
+    ///
+    /// The directory is created if it does not yet exist. Files with an extension other than the
+    /// one used by `format` are ignored. Files that fail to load are skipped, see `errors`.
+    pub fn open<P>(directory: P, format: Format) -> Result<Self, Error>
+    where
+        P: AsRef<Path>,
+    {
+        let directory = directory.as_ref().to_path_buf();
+        fs::create_dir_all(&directory)?;
+        let mut presets = Presets {
+            directory,
+            format,
+            presets: BTreeMap::new(),
+            errors: vec![],
+        };
+        presets.reload()?;
+        Ok(presets)
+    }
+
+    /// Re-load all presets from the directory, discarding those whose files no longer exist.
+    ///
+    /// Files that fail to load are skipped and their errors are made available via `errors`.
+    /// An error is only returned if the directory itself cannot be read.
+    pub fn reload(&mut self) -> Result<(), Error> {
+        self.presets.clear();
+        self.errors.clear();
+        for entry in fs::read_dir(&self.directory)? {
+            let path = entry?.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some(self.format.extension()) {
+                continue;
+            }
+            let name = match path.file_stem().and_then(|stem| stem.to_str()) {
+                None => continue,
+                Some(name) => name.to_string(),
+            };
+            match load(&path, self.format) {
+                Ok(preset) => {
+                    self.presets.insert(name, preset);
+                }
+                Err(err) => self.errors.push((path, err)),
+            }
+        }
+        self.errors.sort_by(|a, b| a.0.cmp(&b.0));
+        Ok(())
+    }
+
+    /// Store the given preset under the given name, writing it to the directory.
+    ///
+    /// Any existing preset with the same name is replaced.
+    pub fn save(&mut self, name: &str, preset: T) -> Result<(), Error> {
+        save(&self.path(name)?, self.format, &preset)?;
+        self.presets.insert(name.to_string(), preset);
+        Ok(())
+    }
+
+    /// Remove the preset with the given name, deleting its file.
+    pub fn remove(&mut self, name: &str) -> Result<T, Error> {
+        let preset = self
+            .presets
+            .remove(name)
+            .ok_or_else(|| Error::UnknownPreset(name.to_string()))?;
+        let path = self.path(name)?;
+        if path.exists() {
+            fs::remove_file(path)?;
+        }
+        Ok(preset)
+    }
+
+    /// Produce a **Morph** from the preset named `from` to the preset named `to`.
+    pub fn morph(
+        &self,
+        from: &str,
+        to: &str,
+        duration: Duration,
+        ease: Ease,
+    ) -> Result<Morph<T>, Error> {
+        let from = self.get_or_err(from)?;
+        let to = self.get_or_err(to)?;
+        let morph = Morph::new(from, to, duration, ease)?;
+        Ok(morph)
+    }
+
+    fn get_or_err(&self, name: &str) -> Result<&T, Error> {
+        self.get(name)
+            .ok_or_else(|| Error::UnknownPreset(name.to_string()))
+    }
+}
+
+impl<T> Presets<T> {
+    /// The directory in which presets are stored.
+    pub fn directory(&self) -> &Path {
+        &self.directory
+    }
+
+    /// The format in which presets are stored.
+    pub fn format(&self) -> Format {
+        self.format
+    }
+
+    /// The path to the file used to store the preset with the given name.
+    ///
+    /// Returns an error if the name would refer to a file outside of the preset directory, e.g.
+    /// if it contains a path separator or is `..`.
+    pub fn path(&self, name: &str) -> Result<PathBuf, Error> {
+        if !is_valid_name(name) {
+            return Err(Error::InvalidName(name.to_string()));
+        }
+        let file_name = format!("{}.{}", name, self.format.extension());
+        Ok(self.directory.join(file_name))
+    }
+
+    /// The names of all presets in alphabetical order.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.presets.keys().map(|name| &name[..])
+    }
+
+    /// The number of presets.
+    pub fn len(&self) -> usize {
+        self.presets.len()
+    }
+
+    /// Whether or not the collection contains any presets.
+    pub fn is_empty(&self) -> bool {
+        self.presets.is_empty()
+    }
+
+    /// Recall the preset with the given name.
+    pub fn get(&self, name: &str) -> Option<&T> {
+        self.presets.get(name)
+    }
+
+    /// The path and error of each file that failed to load during the most recent `open` or
+    /// `reload`, ordered by path.
+    pub fn errors(&self) -> &[(PathBuf, Error)] {
+        &self.errors
+    }
+}
+
+impl<T> Morph<T>
+where
+    T: Serialize + DeserializeOwned,
+{
+    /// Begin a transition from `from` to `to` that completes over the given duration.
+    pub fn new(
+        from: &T,
+        to: &T,
+        duration: Duration,
+        ease: Ease,
+    ) -> Result<Self, serde_json::Error> {
+        let from = serde_json::to_value(from)?;
+        let to = serde_json::to_value(to)?;
+        Ok(Morph {
+            from,
+            to,
+            elapsed: 0.0,
+            duration: duration.secs(),
+            ease,
+            state: PhantomData,
+        })
+    }
+
+    /// Advance the transition by the given duration, e.g. `update.since_last`.
+    pub fn update(&mut self, since_last: Duration) {
+        self.elapsed = (self.elapsed + since_last.secs()).min(self.duration);
+    }
+
+    /// Restart the transition from the beginning.
+    pub fn restart(&mut self) {
+        self.elapsed = 0.0;
+    }
+
+    /// The linear progress of the transition in the range `0.0..=1.0`.
+    pub fn progress(&self) -> f64 {
+        if self.duration <= 0.0 {
+            return 1.0;
+        }
+        self.elapsed / self.duration
+    }
+
+    /// Whether or not the transition has completed.
+    pub fn is_complete(&self) -> bool {
+        self.progress() >= 1.0
+    }
+
+    /// The eased position of the transition, typically in the range `0.0..=1.0`.
+    ///
+    /// Some curves (e.g. `ease::back` or `ease::elastic`) may temporarily exceed this range.
+    pub fn amount(&self) -> f64 {
+        if self.is_complete() {
+            return 1.0;
+        }
+        (self.ease)(self.elapsed, 0.0, 1.0, self.duration)
+    }
+
+    /// The current state of the transition.
+    pub fn value(&self) -> Result<T, serde_json::Error> {
+        let value = lerp_value(&self.from, &self.to, self.amount());
+        serde_json::from_value(value)
+    }
+}
+
+/// Interpolate between two states by the given amount, where `0.0` produces `a` and `1.0`
+/// produces `b`.
+///
+/// Numeric fields are interpolated linearly, with integers rounded to the nearest value. All other
+/// fields are taken from `a` if `amount` is less than `0.5`, otherwise from `b`.
+pub fn lerp<T>(a: &T, b: &T, amount: f64) -> Result<T, serde_json::Error>
+where
+    T: Serialize + DeserializeOwned,
+{
+    let a = serde_json::to_value(a)?;
+    let b = serde_json::to_value(b)?;
+    serde_json::from_value(lerp_value(&a, &b, amount))
+}
+
+// Interpolate between two serialized values.
+fn lerp_value(a: &Value, b: &Value, amount: f64) -> Value {
+    let pick = || if amount < 0.5 { a.clone() } else { b.clone() };
+    match (a, b) {
+        (&Value::Number(ref na), &Value::Number(ref nb)) => lerp_number(na, nb, amount),
+        (&Value::Array(ref va), &Value::Array(ref vb)) if va.len() == vb.len() => {
+            let elems = va
+                .iter()
+                .zip(vb)
+                .map(|(a, b)| lerp_value(a, b, amount))
+                .collect();
+            Value::Array(elems)
+        }
+        (&Value::Object(ref ma), &Value::Object(ref mb)) => {
+            let mut map = Map::new();
+            for (key, a) in ma {
+                let value = match mb.get(key) {
+                    Some(b) => lerp_value(a, b, amount),
+                    None => a.clone(),
+                };
+                map.insert(key.clone(), value);
+            }
+            for (key, b) in mb {
+                if !map.contains_key(key) {
+                    map.insert(key.clone(), b.clone());
+                }
+            }
+            Value::Object(map)
+        }
+        _ => pick(),
+    }
+}
+
+// Interpolate between two numbers, rounding if both are integers.
+fn lerp_number(a: &Number, b: &Number, amount: f64) -> Value {
+    let fa = a.as_f64().unwrap_or(0.0);
+    let fb = b.as_f64().unwrap_or(0.0);
+    let f = fa + (fb - fa) * amount;
+    if a.is_u64() && b.is_u64() {
+        Value::from(f.round().max(0.0) as u64)
+    } else if (a.is_i64() || a.is_u64()) && (b.is_i64() || b.is_u64()) {
+        Value::from(f.round() as i64)
+    } else {
+        Number::from_f64(f)
+            .map(Value::Number)
+            .unwrap_or_else(|| Value::Number(a.clone()))
+    }
+}
+
+// Whether or not the name refers to a single file name within the preset directory.
+//
+// Both `/` and `\` are rejected on all platforms so that preset names remain portable.
+fn is_valid_name(name: &str) -> bool {
+    if name.is_empty() || name.contains(|c| c == '/' || c == '\\') {
+        return false;
+    }
+    let mut components = Path::new(name).components();
+    match (components.next(), components.next()) {
+        (Some(Component::Normal(c)), None) => c == name,
+        _ => false,
+    }
+}
+
+fn load<T>(path: &Path, format: Format) -> Result<T, Error>
+where
+    T: DeserializeOwned,
+{
+    let preset = match format {
+        Format::Json => io::load_from_json(path)?,
+        Format::Toml => io::load_from_toml(path)?,
+    };
+    Ok(preset)
+}
+
+fn save<T>(path: &Path, format: Format, preset: &T) -> Result<(), Error>
+where
+    T: Serialize,
+{
+    match format {
+        Format::Json => io::save_to_json(path, preset)?,
+        Format::Toml => io::save_to_toml(path, preset)?,
+    }
+    Ok(())
+}
+
+impl From<std::io::Error> for Error {
+    fn from(err: std::io::Error) -> Self {
+        Error::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(err: serde_json::Error) -> Self {
+        Error::Json(err)
+    }
+}
+
+impl From<io::JsonFileError> for Error {
+    fn from(err: io::JsonFileError) -> Self {
+        match err {
+            io::FileError::Io(err) => Error::Io(err),
+            io::FileError::Format(err) => Error::Json(err),
+        }
+    }
+}
+
+impl From<io::TomlFileSaveError> for Error {
+    fn from(err: io::TomlFileSaveError) -> Self {
+        match err {
+            io::FileError::Io(err) => Error::Io(err),
+            io::FileError::Format(err) => Error::TomlSave(err),
+        }
+    }
+}
+
+impl From<io::TomlFileLoadError> for Error {
+    fn from(err: io::TomlFileLoadError) -> Self {
+        match err {
+            io::FileError::Io(err) => Error::Io(err),
+            io::FileError::Format(err) => Error::TomlLoad(err),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn cause(&self) -> Option<&dyn error::Error> {
+        match *self {
+            Error::Io(ref err) => Some(err),
+            Error::Json(ref err) => Some(err),
+            Error::TomlSave(ref err) => Some(err),
+            Error::TomlLoad(ref err) => Some(err),
+            Error::UnknownPreset(_) | Error::InvalidName(_) => None,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::Io(ref err) => fmt::Display::fmt(err, f),
+            Error::Json(ref err) => fmt::Display::fmt(err, f),
+            Error::TomlSave(ref err) => fmt::Display::fmt(err, f),
+            Error::TomlLoad(ref err) => fmt::Display::fmt(err, f),
+            Error::UnknownPreset(ref name) => write!(f, "no preset named \"{}\"", name),
+            Error::InvalidName(ref name) => write!(f, "invalid preset name \"{}\"", name),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::anim;
+    use crate::serde_derive::{Deserialize, Serialize};
+    use serde_json::json;
+
+    #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+    struct State {
+        radius: f32,
+        count: u32,
+        name: String,
+    }
+
+    fn state(radius: f32, count: u32, name: &str) -> State {
+        let name = name.to_string();
+        State {
+            radius,
+            count,
+            name,
+        }
+    }
+
+    // A fresh directory within the system's temporary directory.
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(name);
+        fs::remove_dir_all(&dir).ok();
+        dir
+    }
+
+    #[test]
+    fn lerp_numbers() {
+        let lerp = |a: Value, b: Value, amount| lerp_value(&a, &b, amount);
+        assert_eq!(lerp(json!(0.0), json!(10.0), 0.25), json!(2.5));
+        assert_eq!(lerp(json!(0), json!(10), 0.26), json!(3));
+        assert_eq!(lerp(json!(0), json!(10), 0.24), json!(2));
+        assert_eq!(lerp(json!(-4), json!(4), 0.25), json!(-2));
+        assert_eq!(lerp(json!(2), json!(3.0), 0.5), json!(2.5));
+        assert_eq!(lerp(json!(10), json!(0), 2.0), json!(0));
+    }
+
+    #[test]
+    fn lerp_objects_merge_keys() {
+        let a = json!({ "x": 0.0, "only_a": true, "label": "a" });
+        let b = json!({ "x": 1.0, "only_b": 4, "label": "b" });
+        let expected = json!({ "x": 0.25, "only_a": true, "only_b": 4, "label": "a" });
+        assert_eq!(lerp_value(&a, &b, 0.25), expected);
+        let expected = json!({ "x": 0.75, "only_a": true, "only_b": 4, "label": "b" });
+        assert_eq!(lerp_value(&a, &b, 0.75), expected);
+    }
+
+    #[test]
+    fn lerp_arrays() {
+        let a = json!([0.0, 10.0]);
+        let b = json!([1.0, 20.0]);
+        assert_eq!(lerp_value(&a, &b, 0.5), json!([0.5, 15.0]));
+        // Arrays of differing lengths switch at the halfway point.
+        let c = json!([1.0, 20.0, 30.0]);
+        assert_eq!(lerp_value(&a, &c, 0.49), a);
+        assert_eq!(lerp_value(&a, &c, 0.5), c);
+    }
+
+    #[test]
+    fn lerp_state() {
+        let a = state(0.0, 0, "a");
+        let b = state(10.0, 4, "b");
+        assert_eq!(lerp(&a, &b, 0.5).unwrap(), state(5.0, 2, "b"));
+    }
+
+    #[test]
+    fn morph_progress() {
+        let a = state(0.0, 0, "a");
+        let b = state(10.0, 10, "b");
+        let duration = Duration::from_secs(2);
+        let mut morph = Morph::new(&a, &b, duration, anim::linear).unwrap();
+        assert_eq!(morph.progress(), 0.0);
+        assert_eq!(morph.value().unwrap(), a);
+        morph.update(Duration::from_millis(500));
+        assert_eq!(morph.progress(), 0.25);
+        assert_eq!(morph.amount(), 0.25);
+        assert_eq!(morph.value().unwrap(), state(2.5, 3, "a"));
+        morph.update(Duration::from_secs(10));
+        assert_eq!(morph.progress(), 1.0);
+        assert_eq!(morph.amount(), 1.0);
+        assert!(morph.is_complete());
+        assert_eq!(morph.value().unwrap(), b);
+        morph.restart();
+        assert_eq!(morph.progress(), 0.0);
+    }
+
+    #[test]
+    fn morph_zero_duration() {
+        let a = state(0.0, 0, "a");
+        let b = state(10.0, 10, "b");
+        let morph = Morph::new(&a, &b, Duration::from_secs(0), anim::linear).unwrap();
+        assert_eq!(morph.progress(), 1.0);
+        assert_eq!(morph.amount(), 1.0);
+        assert_eq!(morph.value().unwrap(), b);
+    }
+
+    #[test]
+    fn save_and_reload() {
+        for &format in &[Format::Json, Format::Toml] {
+            let dir = temp_dir(&format!("nannou_preset_test_{}", format.extension()));
+            let mut presets = Presets::<State>::open(&dir, format).unwrap();
+            assert!(presets.is_empty());
+            presets.save("calm", state(1.0, 2, "calm")).unwrap();
+            presets.save("wild", state(9.5, 7, "wild")).unwrap();
+            assert!(presets.save("../escape", state(0.0, 0, "")).is_err());
+
+            let mut reloaded = Presets::<State>::open(&dir, format).unwrap();
+            assert_eq!(reloaded.names().collect::<Vec<_>>(), vec!["calm", "wild"]);
+            assert_eq!(reloaded.get("calm"), presets.get("calm"));
+            assert_eq!(reloaded.get("wild"), presets.get("wild"));
+            assert!(reloaded.errors().is_empty());
+
+            reloaded.remove("calm").unwrap();
+            presets.reload().unwrap();
+            assert_eq!(presets.names().collect::<Vec<_>>(), vec!["wild"]);
+            fs::remove_dir_all(&dir).ok();
+        }
+    }
+
+    #[test]
+    fn reload_skips_malformed_files() {
+        let dir = temp_dir("nannou_preset_test_malformed");
+        let mut presets = Presets::<State>::open(&dir, Format::Json).unwrap();
+        presets.save("calm", state(1.0, 2, "calm")).unwrap();
+        fs::write(dir.join("broken.json"), "{ \"radius\": ").unwrap();
+        fs::write(dir.join("wrong.json"), "{ \"radius\": 1.0 }").unwrap();
+        fs::write(dir.join("ignored.txt"), "not a preset").unwrap();
+
+        let presets = Presets::<State>::open(&dir, Format::Json).unwrap();
+        assert_eq!(presets.names().collect::<Vec<_>>(), vec!["calm"]);
+        let paths: Vec<_> = presets.errors().iter().map(|(p, _)| p.clone()).collect();
+        assert_eq!(paths, vec![dir.join("broken.json"), dir.join("wrong.json")]);
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn valid_names() {
+        assert!(is_valid_name("calm"));
+        assert!(is_valid_name("scene 2.final"));
+        assert!(is_valid_name("..hidden"));
+    }
+
+    #[test]
+    fn invalid_names() {
+        assert!(!is_valid_name(""));
+        assert!(!is_valid_name("."));
+        assert!(!is_valid_name(".."));
+        assert!(!is_valid_name("../calm"));
+        assert!(!is_valid_name("nested/calm"));
+        assert!(!is_valid_name("nested\\calm"));
+        assert!(!is_valid_name("/etc/passwd"));
+    }
+}
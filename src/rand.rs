@@ -1,11 +1,22 @@
 //! Items related to randomness and random number generators. Also provides some high-level helper
 //! functions including [**random_f32()**](./fn.random_f32.html), [**random_f64()**](./fn.random_f64.html)
 //! and [**random_range(min, max)**](./fn.random_f32.html).
+//!
+//! The functions within this module use the thread-local generator and in turn produce different
+//! values on every run. For reproducible results, use the **SeededRng** owned by the `App` via
+//! `app.rng()`, whose seed may be specified via `app::Builder::seed`, the `--seed` command line
+//! argument or the `NANNOU_SEED` environment variable.
+//!
+//! The seed is printed at startup. It is recorded in the metadata of captured frames only when
+//! enabled via `app::Builder::capture_metadata`, as writing a metadata file alongside every
+//! captured frame would double the number of files written by existing capture loops.
 
 pub use rand;
 
 pub use self::rand::*;
 
+use rand_chacha::ChaCha8Rng;
+
 /// A wrapper function around the `random` function that avoids the need for specifying a type in
 /// the case that it cannot be inferred. The primary purpose for this is to simplify the random API
 /// for new rust users.
@@ -37,3 +48,250 @@ where
     let (min, max) = if min <= max { (min, max) } else { (max, min) };
     rand::thread_rng().gen_range(min, max)
 }
+
+/// The environment variable from which the app's seed may be specified, e.g. `NANNOU_SEED=42`.
+pub const SEED_ENV_VAR: &str = "NANNOU_SEED";
+
+/// The command line argument from which the app's seed may be specified, e.g. `--seed 42` or
+/// `--seed=42`.
+pub const SEED_ARG: &str = "--seed";
+
+/// A seedable random number generator, producing the same sequence of values for the same seed.
+///
+/// The generator uses the ChaCha algorithm with 8 rounds. Unlike `rngs::StdRng`, whose algorithm
+/// may change between versions of `rand`, the sequence produced for a given seed is stable across
+/// platforms and releases.
+///
+/// The `App` owns a **SeededRng**, accessible via `app.rng()`. Independent streams may be derived
+/// from it by name via `stream`, so that adding random calls to one part of a sketch does not
+/// change the values produced elsewhere.
+///
+/// As **SeededRng** implements `RngCore`, all methods of the `Rng` trait are also available.
+#[derive(Clone, Debug)]
+pub struct SeededRng {
+    seed: u64,
+    rng: ChaCha8Rng,
+}
+
+impl SeededRng {
+    /// The name of the algorithm used to generate values, as recorded alongside captured frames.
+    pub const ALGORITHM: &'static str = "ChaCha8";
+
+    /// A generator seeded with the given value.
+    pub fn new(seed: u64) -> Self {
+        let rng = ChaCha8Rng::seed_from_u64(seed);
+        SeededRng { seed, rng }
+    }
+
+    /// A generator seeded with a random value produced by the thread-local generator.
+    pub fn from_entropy() -> Self {
+        Self::new(random())
+    }
+
+    /// The seed with which the generator was created.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Derive an independent generator for the given name.
+    ///
+    /// The derived generator depends only on this generator's seed and the name, not on how many
+    /// values have been produced so far.
+    pub fn stream(&self, name: &str) -> Self {
+        Self::new(stream_seed(self.seed, name))
+    }
+
+    /// A random `f32` within the range `[0.0, 1.0)`.
+    pub fn random_f32(&mut self) -> f32 {
+        self.gen()
+    }
+
+    /// A random `f64` within the range `[0.0, 1.0)`.
+    pub fn random_f64(&mut self) -> f64 {
+        self.gen()
+    }
+
+    /// A random value within the range `[min, max)`. See the `random_range` function.
+    pub fn random_range<T>(&mut self, min: T, max: T) -> T
+    where
+        T: PartialOrd + distributions::uniform::SampleUniform,
+    {
+        let (min, max) = if min <= max { (min, max) } else { (max, min) };
+        self.gen_range(min, max)
+    }
+
+    /// A random value from a normal distribution with the given mean and standard deviation.
+    pub fn gaussian(&mut self, mean: f64, std_dev: f64) -> f64 {
+        // The Box-Muller transform. `1.0 - u` avoids `ln(0.0)`.
+        let u1: f64 = 1.0 - self.random_f64();
+        let u2: f64 = self.random_f64();
+        let z = (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos();
+        mean + z * std_dev
+    }
+
+    /// A reference to a random element of the given slice, or `None` if it is empty.
+    pub fn choose<'a, T>(&mut self, slice: &'a [T]) -> Option<&'a T> {
+        seq::SliceRandom::choose(slice, self)
+    }
+
+    /// Randomly shuffle the given slice in place.
+    pub fn shuffle<T>(&mut self, slice: &mut [T]) {
+        seq::SliceRandom::shuffle(slice, self)
+    }
+}
+
+impl RngCore for SeededRng {
+    fn next_u32(&mut self) -> u32 {
+        self.rng.next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.rng.next_u64()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.rng.fill_bytes(dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        self.rng.try_fill_bytes(dest)
+    }
+}
+
+/// The seed specified via the `--seed` command line argument or the `NANNOU_SEED` environment
+/// variable, in that order of priority.
+pub fn seed_override() -> Option<u64> {
+    let env_var = std::env::var(SEED_ENV_VAR).ok();
+    parse_seed(std::env::args().skip(1), env_var.as_ref().map(|s| &s[..]))
+}
+
+// Parse the seed from the given command line arguments, falling back to the value of the
+// environment variable. Malformed values are ignored.
+fn parse_seed<I>(args: I, env_var: Option<&str>) -> Option<u64>
+where
+    I: IntoIterator<Item = String>,
+{
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if arg == SEED_ARG {
+            if let Some(seed) = args.next().and_then(|s| s.parse().ok()) {
+                return Some(seed);
+            }
+        } else if arg.starts_with(SEED_ARG) && arg[SEED_ARG.len()..].starts_with('=') {
+            if let Ok(seed) = arg[SEED_ARG.len() + 1..].parse() {
+                return Some(seed);
+            }
+        }
+    }
+    env_var.and_then(|s| s.parse().ok())
+}
+
+// Derive the seed for a named stream using FNV-1a followed by a splitmix64 finalizer, producing
+// the same result on every platform.
+fn stream_seed(seed: u64, name: &str) -> u64 {
+    let mut hash = 0xcbf2_9ce4_8422_2325 ^ seed;
+    for byte in name.bytes() {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    hash = (hash ^ (hash >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    hash = (hash ^ (hash >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    hash ^ (hash >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|s| s.to_string()).collect()
+    }
+
+    fn sequence(rng: &mut SeededRng) -> Vec<u64> {
+        (0..16).map(|_| rng.next_u64()).collect()
+    }
+
+    #[test]
+    fn same_seed_same_sequence() {
+        let a = sequence(&mut SeededRng::new(42));
+        let b = sequence(&mut SeededRng::new(42));
+        assert_eq!(a, b);
+        assert_ne!(a, sequence(&mut SeededRng::new(43)));
+    }
+
+    #[test]
+    fn same_stream_same_sequence() {
+        let rng = SeededRng::new(42);
+        let a = sequence(&mut rng.stream("particles"));
+        let mut advanced = rng.clone();
+        sequence(&mut advanced);
+        let b = sequence(&mut advanced.stream("particles"));
+        assert_eq!(a, b);
+        assert_eq!(
+            rng.stream("particles").seed(),
+            advanced.stream("particles").seed()
+        );
+    }
+
+    #[test]
+    fn different_streams_diverge() {
+        let rng = SeededRng::new(42);
+        let particles = sequence(&mut rng.stream("particles"));
+        assert_ne!(particles, sequence(&mut rng.stream("colors")));
+        assert_ne!(particles, sequence(&mut rng.clone()));
+        assert_ne!(
+            particles,
+            sequence(&mut SeededRng::new(43).stream("particles"))
+        );
+    }
+
+    #[test]
+    fn convenience_functions_are_reproducible() {
+        let mut a = SeededRng::new(7);
+        let mut b = SeededRng::new(7);
+        let mut slice_a = [1, 2, 3, 4, 5, 6, 7, 8];
+        let mut slice_b = slice_a;
+        a.shuffle(&mut slice_a);
+        b.shuffle(&mut slice_b);
+        assert_eq!(slice_a, slice_b);
+        assert_eq!(a.choose(&slice_a), b.choose(&slice_b));
+        assert_eq!(a.gaussian(0.0, 1.0), b.gaussian(0.0, 1.0));
+        let x = a.random_range(10.0, -10.0);
+        assert_eq!(x, b.random_range(-10.0, 10.0));
+        assert!((-10.0..10.0).contains(&x));
+        let f = a.random_f32();
+        assert_eq!(f, b.random_f32());
+        assert!((0.0..1.0).contains(&f));
+    }
+
+    #[test]
+    fn parse_seed_args() {
+        assert_eq!(parse_seed(args(&["--seed", "42"]), None), Some(42));
+        assert_eq!(parse_seed(args(&["--seed=42"]), None), Some(42));
+        assert_eq!(parse_seed(args(&["--other", "--seed", "7"]), None), Some(7));
+        assert_eq!(parse_seed(args(&["--seed", "7"]), Some("42")), Some(7));
+        assert_eq!(parse_seed(args(&[]), None), None);
+    }
+
+    #[test]
+    fn parse_seed_env_var() {
+        assert_eq!(parse_seed(args(&[]), Some("42")), Some(42));
+        assert_eq!(
+            parse_seed(args(&["--other"]), Some("18446744073709551615")),
+            Some(u64::MAX)
+        );
+    }
+
+    #[test]
+    fn parse_seed_rejects_malformed_values() {
+        assert_eq!(parse_seed(args(&["--seed", "abc"]), None), None);
+        assert_eq!(parse_seed(args(&["--seed"]), None), None);
+        assert_eq!(parse_seed(args(&["--seed="]), None), None);
+        assert_eq!(parse_seed(args(&["--seed=-1"]), None), None);
+        assert_eq!(parse_seed(args(&["--seed=1.5"]), None), None);
+        assert_eq!(parse_seed(args(&["--seeds=4"]), None), None);
+        assert_eq!(parse_seed(args(&["--seed", "abc"]), Some("9")), Some(9));
+        assert_eq!(parse_seed(args(&[]), Some("")), None);
+        assert_eq!(parse_seed(args(&[]), Some("18446744073709551616")), None);
+    }
+}
@@ -0,0 +1,420 @@
+//! Recording and replaying user input for deterministic sketch sessions.
+//!
+//! When an app is built with `app::Builder::record`, every simplified input **WindowEvent** and
+//! every change to the modifier keys is stored along with the **Update** during which it
+//! occurred. The **Recording** is written to a JSON file via `io::save_to_json` when the app
+//! exits.
+//!
+//! When an app is built with `app::Builder::replay`, the recorded events are emitted in place of
+//! live input, each before the same update in which it was originally received. Using
+//! `Timing::Recorded`, the recorded update timestamps are used in place of the wall clock so that
+//! a session may be reproduced exactly regardless of how long each frame takes to render, e.g.
+//! while re-rendering a performance at a higher resolution.
+//!
+//! Once all recorded events have been replayed, live input resumes.
+
+use crate::event::{ModifiersState, Update, WindowEvent};
+use crate::io;
+use crate::serde_derive::{Deserialize, Serialize};
+use crate::window;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// A sequence of input events along with the timing of each update of the app loop.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Recording {
+    /// The **Update** emitted on each pass of the app loop, in order.
+    pub updates: Vec<Update>,
+    /// All recorded input events, in the order in which they were received.
+    pub events: Vec<RecordedEvent>,
+}
+
+/// A single recorded input event.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RecordedEvent {
+    /// The number of updates that had been emitted before the event was received.
+    pub update: u64,
+    /// The duration since the start of the app loop as of the most recent update.
+    pub since_start: Duration,
+    /// The index of the window that received the event, in the order in which windows were
+    /// created.
+    pub window: usize,
+    /// The recorded input.
+    pub event: RecordedInput,
+}
+
+/// The input stored by a **RecordedEvent**.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum RecordedInput {
+    /// A simplified window event.
+    Window(WindowEvent),
+    /// The state of the modifier keys changed.
+    ///
+    /// Modifier changes are delivered as device events, so they are recorded against the window
+    /// that was focused at the time.
+    ModifiersChanged(ModifiersState),
+}
+
+/// Describes how time advances while replaying a **Recording**.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Timing {
+    /// Updates are timed via the wall clock as usual.
+    Realtime,
+    /// Each update uses the timestamp of the corresponding recorded update, regardless of how
+    /// much time has actually passed. This is similar to an offline render.
+    Recorded,
+}
+
+// The recording or replay session owned by the `App`.
+#[derive(Debug)]
+pub(crate) enum Session {
+    None,
+    Recording {
+        path: PathBuf,
+        recording: Recording,
+        windows: Vec<window::Id>,
+    },
+    Replaying {
+        recording: Recording,
+        timing: Timing,
+        next_event: usize,
+        windows: Vec<window::Id>,
+    },
+}
+
+impl Recording {
+    /// Load a recording from the JSON file at the given path.
+    pub fn load<P>(path: P) -> Result<Self, io::JsonFileError>
+    where
+        P: AsRef<Path>,
+    {
+        io::load_from_json(path)
+    }
+
+    /// Save the recording as JSON to the given path.
+    pub fn save<P>(&self, path: P) -> Result<(), io::JsonFileError>
+    where
+        P: AsRef<Path>,
+    {
+        io::save_to_json(path, self)
+    }
+}
+
+impl Default for Timing {
+    fn default() -> Self {
+        Timing::Realtime
+    }
+}
+
+impl Session {
+    // Begin a session that records to the given path upon `finish`.
+    pub(crate) fn record(path: PathBuf) -> Self {
+        Session::Recording {
+            path,
+            recording: Default::default(),
+            windows: vec![],
+        }
+    }
+
+    // Begin a session that replays the given recording.
+    pub(crate) fn replay(recording: Recording, timing: Timing) -> Self {
+        Session::Replaying {
+            recording,
+            timing,
+            next_event: 0,
+            windows: vec![],
+        }
+    }
+
+    // Whether or not live input should be ignored in favour of the recorded input.
+    pub(crate) fn is_replaying_input(&self) -> bool {
+        match *self {
+            Session::Replaying {
+                ref recording,
+                next_event,
+                ..
+            } => next_event < recording.events.len(),
+            _ => false,
+        }
+    }
+
+    // Register a newly created window so that it may be identified by its index.
+    pub(crate) fn window_created(&mut self, id: window::Id) {
+        match *self {
+            Session::None => (),
+            Session::Recording {
+                ref mut windows, ..
+            }
+            | Session::Replaying {
+                ref mut windows, ..
+            } => windows.push(id),
+        }
+    }
+
+    // Produce the update to emit for the given update number.
+    //
+    // When replaying with recorded timing, the recorded update is returned in place of the given
+    // wall clock update. When recording, the update is stored.
+    pub(crate) fn update(&mut self, nth_update: u64, update: Update) -> Update {
+        match *self {
+            Session::None => update,
+            Session::Recording {
+                ref mut recording, ..
+            } => {
+                recording.updates.push(update);
+                update
+            }
+            Session::Replaying {
+                ref recording,
+                timing,
+                ..
+            } => match timing {
+                Timing::Realtime => update,
+                Timing::Recorded => recorded_update(&recording.updates, nth_update),
+            },
+        }
+    }
+
+    // Record an input event received by the given window.
+    pub(crate) fn record_event(
+        &mut self,
+        window_id: window::Id,
+        event: &WindowEvent,
+        since_start: Duration,
+    ) {
+        if !is_recordable(event) {
+            return;
+        }
+        let input = RecordedInput::Window(event.clone());
+        self.record_input(window_id, input, since_start);
+    }
+
+    // Record a change in the state of the modifier keys while the given window is focused.
+    pub(crate) fn record_modifiers(
+        &mut self,
+        window_id: window::Id,
+        mods: ModifiersState,
+        since_start: Duration,
+    ) {
+        let input = RecordedInput::ModifiersChanged(mods);
+        self.record_input(window_id, input, since_start);
+    }
+
+    fn record_input(&mut self, window_id: window::Id, event: RecordedInput, since_start: Duration) {
+        if let Session::Recording {
+            ref mut recording,
+            ref windows,
+            ..
+        } = *self
+        {
+            let window = match windows.iter().position(|&id| id == window_id) {
+                None => return,
+                Some(ix) => ix,
+            };
+            let update = recording.updates.len() as u64;
+            recording.events.push(RecordedEvent {
+                update,
+                since_start,
+                window,
+                event,
+            });
+        }
+    }
+
+    // Pop the next recorded event due before the given update, along with the window to which it
+    // should be delivered.
+    pub(crate) fn next_replayed_event(
+        &mut self,
+        nth_update: u64,
+    ) -> Option<(window::Id, RecordedInput)> {
+        if let Session::Replaying {
+            ref recording,
+            ref mut next_event,
+            ref windows,
+            ..
+        } = *self
+        {
+            while let Some(recorded) = recording.events.get(*next_event) {
+                if recorded.update > nth_update {
+                    return None;
+                }
+                *next_event += 1;
+                if let Some(&id) = windows.get(recorded.window) {
+                    return Some((id, recorded.event.clone()));
+                }
+            }
+        }
+        None
+    }
+
+    // End the session, writing the recording to its file if necessary.
+    pub(crate) fn finish(&mut self) {
+        if let Session::Recording {
+            ref path,
+            ref recording,
+            ..
+        } = *self
+        {
+            if let Err(err) = recording.save(path) {
+                eprintln!(
+                    "failed to save input recording to \"{}\": {}",
+                    path.display(),
+                    err
+                );
+            }
+        }
+        *self = Session::None;
+    }
+}
+
+/// Whether or not the given event is considered input and in turn is recorded and replayed.
+///
+/// Events describing the state of the window itself (e.g. `Resized`, `Focused`, `Closed`) are
+/// always delivered live. `TouchPressure` events are not recorded as they refer to a specific
+/// device.
+pub fn is_recordable(event: &WindowEvent) -> bool {
+    match *event {
+        WindowEvent::KeyPressed(_)
+        | WindowEvent::KeyReleased(_)
+        | WindowEvent::MouseMoved(_)
+        | WindowEvent::MousePressed(_)
+        | WindowEvent::MouseReleased(_)
+        | WindowEvent::MouseEntered
+        | WindowEvent::MouseExited
+        | WindowEvent::MouseWheel(..)
+        | WindowEvent::HoveredFile(_)
+        | WindowEvent::DroppedFile(_)
+        | WindowEvent::HoveredFileCancelled
+        | WindowEvent::Touch(_) => true,
+        WindowEvent::Moved(_)
+        | WindowEvent::Resized(_)
+        | WindowEvent::TouchPressure(_)
+        | WindowEvent::Focused
+        | WindowEvent::Unfocused
+        | WindowEvent::Closed => false,
+    }
+}
+
+// The recorded update for the given update number.
+//
+// Beyond the end of the recording, time continues to advance at the rate of the final update.
+fn recorded_update(updates: &[Update], nth_update: u64) -> Update {
+    let n = nth_update as usize;
+    match updates.get(n) {
+        Some(&update) => update,
+        None => match updates.last() {
+            None => Update {
+                since_last: Duration::from_secs(0),
+                since_start: Duration::from_secs(0),
+            },
+            Some(last) => {
+                let extra = (n + 1 - updates.len()) as u32;
+                Update {
+                    since_last: last.since_last,
+                    since_start: last.since_start + last.since_last * extra,
+                }
+            }
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::{Key, MouseButton, MouseScrollDelta, TouchPhase};
+    use crate::geom::{pt2, vec2};
+
+    fn update(millis: u64, since_last_millis: u64) -> Update {
+        Update {
+            since_last: Duration::from_millis(since_last_millis),
+            since_start: Duration::from_millis(millis),
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let a = unsafe { window::Id::dummy() };
+        let path = std::env::temp_dir().join("nannou_record_test_round_trip.json");
+
+        // Record a short session.
+        let updates = vec![update(16, 16), update(40, 24), update(57, 17)];
+        let wheel =
+            WindowEvent::MouseWheel(MouseScrollDelta::LineDelta(0.0, 1.0), TouchPhase::Moved);
+        let mut session = Session::record(path.clone());
+        session.window_created(a);
+        session.record_event(
+            a,
+            &WindowEvent::KeyPressed(Key::A),
+            Duration::from_millis(0),
+        );
+        assert_eq!(session.update(0, updates[0]), updates[0]);
+        session.record_event(
+            a,
+            &WindowEvent::MouseMoved(pt2(1.0, 2.0)),
+            updates[0].since_start,
+        );
+        session.record_event(
+            a,
+            &WindowEvent::Resized(vec2(1.0, 1.0)),
+            updates[0].since_start,
+        );
+        session.record_modifiers(a, ModifiersState::SHIFT, updates[0].since_start);
+        assert_eq!(session.update(1, updates[1]), updates[1]);
+        session.record_event(
+            a,
+            &WindowEvent::MousePressed(MouseButton::Left),
+            updates[1].since_start,
+        );
+        session.record_event(a, &wheel, updates[1].since_start);
+        assert_eq!(session.update(2, updates[2]), updates[2]);
+        let recording = match session {
+            Session::Recording { ref recording, .. } => recording.clone(),
+            _ => unreachable!(),
+        };
+        session.finish();
+
+        // Non-input events are not recorded.
+        assert_eq!(recording.updates, updates);
+        assert_eq!(recording.events.len(), 5);
+        let loaded = Recording::load(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+        assert_eq!(loaded, recording);
+
+        // Replay the recording using recorded timing, regardless of the wall clock.
+        let mut session = Session::replay(loaded, Timing::Recorded);
+        let b = unsafe { window::Id::dummy() };
+        session.window_created(b);
+        assert!(session.is_replaying_input());
+        let mut replayed = vec![];
+        for nth in 0..updates.len() as u64 {
+            while let Some((id, input)) = session.next_replayed_event(nth) {
+                assert_eq!(id, b);
+                replayed.push((nth, input));
+            }
+            assert_eq!(
+                session.update(nth, update(1000, 1000)),
+                updates[nth as usize]
+            );
+        }
+        assert!(!session.is_replaying_input());
+        let expected: Vec<_> = recording
+            .events
+            .iter()
+            .map(|e| (e.update, e.event.clone()))
+            .collect();
+        assert_eq!(replayed, expected);
+        assert_eq!(
+            replayed[2].1,
+            RecordedInput::ModifiersChanged(ModifiersState::SHIFT)
+        );
+    }
+
+    #[test]
+    fn recorded_update_beyond_end() {
+        let updates = [update(16, 16), update(40, 24)];
+        assert_eq!(recorded_update(&updates, 1), updates[1]);
+        assert_eq!(recorded_update(&updates, 2), update(64, 24));
+        assert_eq!(recorded_update(&updates, 4), update(112, 24));
+        assert_eq!(recorded_update(&[], 3), update(0, 0));
+    }
+}
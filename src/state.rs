@@ -1,10 +1,11 @@
 //! Small tracked parts of the application state. Includes [**window**](mod.window.html),
-//! [**keys**](mod.keys.html), [**mouse**](mod.mouse.html), [**time**](mod.mouse.html) - each of
-//! which are stored in the **App**.
+//! [**keys**](mod.keys.html), [**mouse**](mod.mouse.html), [**touch**](mod.touch.html),
+//! [**time**](mod.mouse.html) - each of which are stored in the **App**.
 
 pub use self::keys::Keys;
 pub use self::mouse::Mouse;
 pub use self::time::Time;
+pub use self::touch::Touches;
 pub use self::window::Window;
 
 /// Tracked state related to the focused window.
@@ -48,6 +49,9 @@ pub mod keys {
         pub mods: ModifiersState,
         /// The state of all keys as tracked via the nannou App event handling.
         pub down: Down,
+        // Keys pressed and released since the end of the previous update.
+        pub(crate) pressed: HashSet<Key>,
+        pub(crate) released: HashSet<Key>,
     }
 
     /// The set of keys that are currently pressed.
@@ -56,20 +60,122 @@ pub mod keys {
         pub(crate) keys: HashSet<Key>,
     }
 
+    impl Keys {
+        /// Whether or not the given key is currently down.
+        pub fn is_down(&self, key: Key) -> bool {
+            self.down.contains(&key)
+        }
+
+        /// Whether or not the given key was pressed since the end of the previous update.
+        ///
+        /// Unlike `is_down`, this is only `true` for the update following the press, making it
+        /// useful for triggering an action once per press. Key repeats are ignored.
+        pub fn just_pressed(&self, key: Key) -> bool {
+            self.pressed.contains(&key)
+        }
+
+        /// Whether or not the given key was released since the end of the previous update.
+        pub fn just_released(&self, key: Key) -> bool {
+            self.released.contains(&key)
+        }
+
+        /// The set of keys pressed since the end of the previous update.
+        pub fn pressed(&self) -> &HashSet<Key> {
+            &self.pressed
+        }
+
+        /// The set of keys released since the end of the previous update.
+        pub fn released(&self) -> &HashSet<Key> {
+            &self.released
+        }
+
+        // Register a key press.
+        pub(crate) fn press(&mut self, key: Key) {
+            if self.down.keys.insert(key) {
+                self.pressed.insert(key);
+            }
+        }
+
+        // Register a key release.
+        pub(crate) fn release(&mut self, key: Key) {
+            if self.down.keys.remove(&key) {
+                self.released.insert(key);
+            }
+        }
+
+        // Clear the keys pressed and released at the end of an update.
+        pub(crate) fn clear_edges(&mut self) {
+            self.pressed.clear();
+            self.released.clear();
+        }
+    }
+
     impl Deref for Down {
         type Target = HashSet<Key>;
         fn deref(&self) -> &Self::Target {
             &self.keys
         }
     }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn edges_clear_across_updates() {
+            let mut keys = Keys::default();
+            keys.press(Key::A);
+            assert!(keys.is_down(Key::A));
+            assert!(keys.just_pressed(Key::A));
+            keys.clear_edges();
+            assert!(keys.is_down(Key::A));
+            assert!(!keys.just_pressed(Key::A));
+            keys.release(Key::A);
+            assert!(!keys.is_down(Key::A));
+            assert!(keys.just_released(Key::A));
+            keys.clear_edges();
+            assert!(!keys.just_released(Key::A));
+            assert!(keys.pressed().is_empty() && keys.released().is_empty());
+        }
+
+        #[test]
+        fn repeats_are_ignored() {
+            let mut keys = Keys::default();
+            keys.press(Key::A);
+            keys.clear_edges();
+            keys.press(Key::A);
+            assert!(!keys.just_pressed(Key::A));
+        }
+
+        #[test]
+        fn release_without_press() {
+            let mut keys = Keys::default();
+            keys.release(Key::A);
+            assert!(!keys.just_released(Key::A));
+            assert!(keys.released().is_empty());
+        }
+
+        #[test]
+        fn press_and_release_within_an_update() {
+            let mut keys = Keys::default();
+            keys.press(Key::Space);
+            keys.release(Key::Space);
+            assert!(!keys.is_down(Key::Space));
+            assert!(keys.just_pressed(Key::Space));
+            assert!(keys.just_released(Key::Space));
+        }
+    }
 }
 
 /// Tracked state related to the mouse.
 pub mod mouse {
-    use crate::geom::{self, Point2};
-    use crate::math::BaseFloat;
+    use crate::event::Update;
+    use crate::geom::{self, Point2, Vector2};
+    use crate::math::{BaseFloat, NumCast};
+    use crate::time::DurationF64;
     use crate::window;
     use std;
+    use std::time::Duration;
 
     /// The default scalar value used for positions.
     pub type DefaultScalar = geom::scalar::Default;
@@ -80,6 +186,9 @@ pub mod mouse {
     /// The max total number of buttons on a mouse.
     pub const NUM_BUTTONS: usize = 9;
 
+    /// The distance in points scrolled per line for mice that scroll by lines rather than pixels.
+    pub const POINTS_PER_SCROLL_LINE: f64 = 20.0;
+
     /// The state of the `Mouse` at a single moment in time.
     #[derive(Copy, Clone, Debug, PartialEq)]
     pub struct Mouse<S = DefaultScalar> {
@@ -91,6 +200,32 @@ pub mod mouse {
         pub y: S,
         /// A map describing the state of each mouse button.
         pub buttons: ButtonMap,
+        // Buttons pressed and released since the end of the previous update.
+        pub(crate) just_pressed: [bool; NUM_BUTTONS],
+        pub(crate) just_released: [bool; NUM_BUTTONS],
+        // The app time at which each button was last pressed.
+        pub(crate) press_times: [Duration; NUM_BUTTONS],
+        // The app time as of the most recent update.
+        pub(crate) time: Duration,
+        // The position as of the most recent update.
+        pub(crate) update_position: Point2<S>,
+        // Whether or not the mouse has moved since the app started.
+        pub(crate) moved: bool,
+        pub(crate) velocity: Vector2<S>,
+        pub(crate) scroll: Vector2<S>,
+    }
+
+    /// A mouse drag, i.e. the movement of the mouse while a button is held down.
+    #[derive(Copy, Clone, Debug, PartialEq)]
+    pub struct Drag<S = DefaultScalar> {
+        /// The button that is held down.
+        pub button: Button,
+        /// The position at which the button was pressed.
+        pub start: Point2<S>,
+        /// The current position of the mouse.
+        pub end: Point2<S>,
+        /// The duration for which the button has been held down as of the most recent update.
+        pub duration: Duration,
     }
 
     /// Whether the button is up or down.
@@ -127,6 +262,23 @@ pub mod mouse {
                 buttons: ButtonMap::new(),
                 x: S::zero(),
                 y: S::zero(),
+                just_pressed: [false; NUM_BUTTONS],
+                just_released: [false; NUM_BUTTONS],
+                press_times: [Duration::from_secs(0); NUM_BUTTONS],
+                time: Duration::from_secs(0),
+                update_position: Point2 {
+                    x: S::zero(),
+                    y: S::zero(),
+                },
+                moved: false,
+                velocity: Vector2 {
+                    x: S::zero(),
+                    y: S::zero(),
+                },
+                scroll: Vector2 {
+                    x: S::zero(),
+                    y: S::zero(),
+                },
             }
         }
 
@@ -137,6 +289,121 @@ pub mod mouse {
                 y: self.y,
             }
         }
+
+        /// Whether or not the given button was pressed since the end of the previous update.
+        pub fn just_pressed(&self, button: Button) -> bool {
+            self.just_pressed[button_to_idx(button)]
+        }
+
+        /// Whether or not the given button was released since the end of the previous update.
+        pub fn just_released(&self, button: Button) -> bool {
+            self.just_released[button_to_idx(button)]
+        }
+
+        /// The velocity of the mouse in points per second, measured between the two most recent
+        /// updates.
+        pub fn velocity(&self) -> Vector2<S> {
+            self.velocity
+        }
+
+        /// The distance scrolled in points since the end of the previous update.
+        ///
+        /// Scrolling by lines is converted to points via `POINTS_PER_SCROLL_LINE`.
+        pub fn scroll(&self) -> Vector2<S> {
+            self.scroll
+        }
+
+        // Move the mouse to the given position.
+        //
+        // The first movement also seeds the position of the most recent update so that the
+        // velocity does not jump from the origin to the initial position of the cursor.
+        pub(crate) fn move_to(&mut self, position: Point2<S>) {
+            if !self.moved {
+                self.update_position = position;
+                self.moved = true;
+            }
+            self.x = position.x;
+            self.y = position.y;
+        }
+
+        // Accumulate a scroll delta in points.
+        pub(crate) fn scroll_by(&mut self, delta: Vector2<S>) {
+            self.scroll = self.scroll + delta;
+        }
+
+        // Update the time and velocity at the beginning of an update.
+        pub(crate) fn update(&mut self, update: &Update) {
+            let secs = update.since_last.secs();
+            let position = self.position();
+            if secs > 0.0 {
+                let secs: S = NumCast::from(secs).unwrap();
+                let delta = position - self.update_position;
+                self.velocity = Vector2 {
+                    x: delta.x / secs,
+                    y: delta.y / secs,
+                };
+            }
+            self.update_position = position;
+            self.time = update.since_start;
+        }
+
+        // Clear the buttons pressed and released along with the scroll at the end of an update.
+        pub(crate) fn clear_edges(&mut self) {
+            self.just_pressed = [false; NUM_BUTTONS];
+            self.just_released = [false; NUM_BUTTONS];
+            self.scroll = Vector2 {
+                x: S::zero(),
+                y: S::zero(),
+            };
+        }
+    }
+
+    impl Mouse {
+        /// The drag state of the given button if it is currently down.
+        pub fn drag(&self, button: Button) -> Option<Drag> {
+            self.buttons[button].if_down().map(|start| {
+                let pressed_at = self.press_times[button_to_idx(button)];
+                let duration = self.time.checked_sub(pressed_at).unwrap_or_default();
+                Drag {
+                    button,
+                    start,
+                    end: self.position(),
+                    duration,
+                }
+            })
+        }
+
+        /// An iterator yielding the drag state of all buttons that are currently down.
+        pub fn drags<'a>(&'a self) -> impl 'a + Iterator<Item = Drag> {
+            self.buttons
+                .pressed()
+                .filter_map(move |(button, _)| self.drag(button))
+        }
+
+        // Register a button press at the current position.
+        pub(crate) fn press(&mut self, button: Button) {
+            let idx = button_to_idx(button);
+            let p = self.position();
+            self.buttons.press(button, p);
+            self.just_pressed[idx] = true;
+            self.press_times[idx] = self.time;
+        }
+
+        // Register a button release.
+        pub(crate) fn release(&mut self, button: Button) {
+            self.buttons.release(button);
+            self.just_released[button_to_idx(button)] = true;
+        }
+    }
+
+    impl<S> Drag<S>
+    where
+        S: BaseFloat,
+    {
+        /// The displacement of the mouse from the start of the drag.
+        pub fn delta(&self) -> Vector2<S> {
+            self.end - self.start
+        }
     }
 
     impl<S> ButtonPosition<S>
@@ -278,6 +545,532 @@ pub mod mouse {
             Button::Middle => 8,
         }
     }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::geom::{pt2, vec2};
+
+        // Begin an update at the given time in milliseconds, given the time of the last update.
+        fn update(mouse: &mut Mouse, last_millis: u64, millis: u64) {
+            let update = Update {
+                since_last: Duration::from_millis(millis - last_millis),
+                since_start: Duration::from_millis(millis),
+            };
+            mouse.update(&update);
+        }
+
+        #[test]
+        fn button_edges_clear_across_updates() {
+            let mut mouse: Mouse = Mouse::new();
+            mouse.press(Button::Left);
+            assert!(mouse.just_pressed(Button::Left));
+            assert!(!mouse.just_pressed(Button::Right));
+            mouse.clear_edges();
+            assert!(!mouse.just_pressed(Button::Left));
+            assert!(mouse.buttons.left().is_down());
+            mouse.release(Button::Left);
+            assert!(mouse.just_released(Button::Left));
+            mouse.clear_edges();
+            assert!(!mouse.just_released(Button::Left));
+            assert!(mouse.buttons.left().is_up());
+        }
+
+        #[test]
+        fn scroll_accumulates_until_cleared() {
+            let mut mouse: Mouse = Mouse::new();
+            mouse.scroll_by(vec2(0.0, 20.0));
+            mouse.scroll_by(vec2(5.0, -5.0));
+            assert_eq!(mouse.scroll(), vec2(5.0, 15.0));
+            mouse.clear_edges();
+            assert_eq!(mouse.scroll(), vec2(0.0, 0.0));
+        }
+
+        #[test]
+        fn drag() {
+            let mut mouse: Mouse = Mouse::new();
+            mouse.move_to(pt2(10.0, 20.0));
+            update(&mut mouse, 0, 100);
+            mouse.press(Button::Left);
+            assert!(mouse.drag(Button::Right).is_none());
+            mouse.move_to(pt2(40.0, -20.0));
+            update(&mut mouse, 100, 350);
+            let drag = mouse.drag(Button::Left).unwrap();
+            assert_eq!(drag.button, Button::Left);
+            assert_eq!(drag.start, pt2(10.0, 20.0));
+            assert_eq!(drag.end, pt2(40.0, -20.0));
+            assert_eq!(drag.delta(), vec2(30.0, -40.0));
+            assert_eq!(drag.duration, Duration::from_millis(250));
+            assert_eq!(mouse.drags().count(), 1);
+            mouse.release(Button::Left);
+            assert!(mouse.drag(Button::Left).is_none());
+            assert_eq!(mouse.drags().count(), 0);
+        }
+
+        #[test]
+        fn velocity() {
+            let mut mouse: Mouse = Mouse::new();
+            mouse.move_to(pt2(0.0, 0.0));
+            update(&mut mouse, 0, 100);
+            mouse.move_to(pt2(50.0, -25.0));
+            update(&mut mouse, 100, 600);
+            assert_eq!(mouse.velocity(), vec2(100.0, -50.0));
+            update(&mut mouse, 600, 700);
+            assert_eq!(mouse.velocity(), vec2(0.0, 0.0));
+        }
+
+        #[test]
+        fn first_movement_does_not_spike_velocity() {
+            let mut mouse: Mouse = Mouse::new();
+            update(&mut mouse, 0, 100);
+            mouse.move_to(pt2(300.0, 200.0));
+            update(&mut mouse, 100, 200);
+            assert_eq!(mouse.velocity(), vec2(0.0, 0.0));
+            mouse.move_to(pt2(310.0, 200.0));
+            update(&mut mouse, 200, 300);
+            assert_eq!(mouse.velocity(), vec2(100.0, 0.0));
+        }
+    }
+}
+
+/// Tracked state related to touches, along with the recognition of gestures.
+///
+/// Gestures are recognised as follows:
+///
+/// - **Pan**: any movement of the active touches, describing the movement of their centroid.
+/// - **Pinch** and **Rotate**: movement of the two touches with the lowest IDs relative to each
+///   other.
+/// - **Tap**: a lone touch released within `TAP_MAX_DURATION` without moving further than
+///   `TAP_MAX_DISTANCE` from where it started.
+/// - **DoubleTap**: a tap within `DOUBLE_TAP_MAX_INTERVAL` and `DOUBLE_TAP_MAX_DISTANCE` of a
+///   previous tap.
+/// - **LongPress**: a lone touch held for `LONG_PRESS_DURATION` without moving further than
+///   `TAP_MAX_DISTANCE`. As this depends on the passing of time, it is recognised upon update.
+pub mod touch {
+    use crate::event::{Gesture, TouchPhase};
+    use crate::geom::{self, Point2, Vector2};
+    use crate::math::InnerSpace;
+    use crate::window;
+    use std::collections::BTreeMap;
+    use std::time::Duration;
+
+    /// The scalar value used for positions.
+    pub type DefaultScalar = geom::scalar::Default;
+
+    /// The maximum duration of a touch recognised as a tap.
+    pub const TAP_MAX_DURATION: Duration = Duration::from_millis(300);
+    /// The maximum distance in points a touch may move and still be recognised as a tap or long
+    /// press.
+    pub const TAP_MAX_DISTANCE: DefaultScalar = 10.0;
+    /// The maximum duration between two taps recognised as a double tap.
+    pub const DOUBLE_TAP_MAX_INTERVAL: Duration = Duration::from_millis(300);
+    /// The maximum distance in points between two taps recognised as a double tap.
+    pub const DOUBLE_TAP_MAX_DISTANCE: DefaultScalar = 30.0;
+    /// The duration for which a touch must be held to be recognised as a long press.
+    pub const LONG_PRESS_DURATION: Duration = Duration::from_millis(500);
+
+    /// The state of all active touches.
+    #[derive(Clone, Debug, Default)]
+    pub struct Touches {
+        /// The ID of the window that received the most recent touch.
+        pub window: Option<window::Id>,
+        active: BTreeMap<u64, Touch>,
+        // The position and time of the most recent tap.
+        last_tap: Option<(Point2, Duration)>,
+        // The app time as of the most recent update.
+        time: Duration,
+        // Gestures recognised since they were last emitted.
+        pub(crate) pending: Vec<(window::Id, Gesture)>,
+    }
+
+    /// A single active touch.
+    #[derive(Copy, Clone, Debug, PartialEq)]
+    pub struct Touch {
+        /// The unique ID associated with this touch.
+        pub id: u64,
+        /// The position at which the touch started.
+        pub start: Point2,
+        /// The current position of the touch.
+        pub position: Point2,
+        /// The app time at which the touch started, as of the most recent update.
+        pub start_time: Duration,
+        // Whether or not the touch has moved beyond the tap distance.
+        moved: bool,
+        // Whether or not another touch was active at the same time.
+        multi: bool,
+        // Whether or not a long press has been recognised for this touch.
+        long_pressed: bool,
+    }
+
+    impl Touches {
+        /// The number of active touches.
+        pub fn len(&self) -> usize {
+            self.active.len()
+        }
+
+        /// Whether or not there are no active touches.
+        pub fn is_empty(&self) -> bool {
+            self.active.is_empty()
+        }
+
+        /// The active touch with the given ID.
+        pub fn get(&self, id: u64) -> Option<&Touch> {
+            self.active.get(&id)
+        }
+
+        /// An iterator yielding all active touches in order of their IDs.
+        pub fn iter(&self) -> impl Iterator<Item = &Touch> {
+            self.active.values()
+        }
+
+        /// The centroid of all active touches.
+        pub fn centroid(&self) -> Option<Point2> {
+            if self.active.is_empty() {
+                return None;
+            }
+            let total = self
+                .active
+                .values()
+                .fold(Vector2::zero(), |acc, t| acc + t.position);
+            Some(total / self.active.len() as DefaultScalar)
+        }
+
+        // Track the given touch event, recognising any gestures.
+        pub(crate) fn touch(
+            &mut self,
+            window_id: window::Id,
+            id: u64,
+            phase: TouchPhase,
+            position: Point2,
+        ) {
+            self.window = Some(window_id);
+            match phase {
+                TouchPhase::Started => {
+                    let multi = !self.active.is_empty();
+                    for touch in self.active.values_mut() {
+                        touch.multi = true;
+                    }
+                    let touch = Touch {
+                        id,
+                        start: position,
+                        position,
+                        start_time: self.time,
+                        moved: false,
+                        multi,
+                        long_pressed: false,
+                    };
+                    self.active.insert(id, touch);
+                }
+                TouchPhase::Moved => self.moved(window_id, id, position),
+                TouchPhase::Ended => {
+                    if let Some(touch) = self.active.remove(&id) {
+                        self.ended(window_id, touch);
+                    }
+                }
+                TouchPhase::Cancelled => {
+                    self.active.remove(&id);
+                }
+            }
+        }
+
+        // Recognise long presses at the beginning of an update.
+        pub(crate) fn update(&mut self, since_start: Duration) {
+            self.time = since_start;
+            let window_id = match self.window {
+                None => return,
+                Some(id) => id,
+            };
+            for touch in self.active.values_mut() {
+                let held = since_start
+                    .checked_sub(touch.start_time)
+                    .unwrap_or_default();
+                let still = !touch.moved && !touch.multi;
+                if still && !touch.long_pressed && held >= LONG_PRESS_DURATION {
+                    touch.long_pressed = true;
+                    let gesture = Gesture::LongPress(touch.position);
+                    self.pending.push((window_id, gesture));
+                }
+            }
+        }
+
+        fn moved(&mut self, window_id: window::Id, id: u64, position: Point2) {
+            let prev_centroid = self.centroid();
+            let prev_pair = self.pair();
+            match self.active.get_mut(&id) {
+                None => return,
+                Some(touch) => {
+                    touch.position = position;
+                    if (position - touch.start).magnitude() > TAP_MAX_DISTANCE {
+                        touch.moved = true;
+                    }
+                }
+            }
+
+            if let (Some(a), Some(b)) = (prev_centroid, self.centroid()) {
+                let delta = b - a;
+                if delta != Vector2::zero() {
+                    let touches = self.active.len();
+                    self.pending
+                        .push((window_id, Gesture::Pan { delta, touches }));
+                }
+            }
+
+            if let (Some((a0, b0)), Some((a1, b1))) = (prev_pair, self.pair()) {
+                let center = (a1 + b1) * 0.5;
+                let (d0, d1) = (b0 - a0, b1 - a1);
+                let (len0, len1) = (d0.magnitude(), d1.magnitude());
+                if len0 > 0.0 && len1 > 0.0 {
+                    let scale = len1 / len0;
+                    if scale != 1.0 {
+                        let gesture = Gesture::Pinch { center, scale };
+                        self.pending.push((window_id, gesture));
+                    }
+                    let radians = wrap_angle(d1.angle() - d0.angle());
+                    if radians != 0.0 {
+                        let gesture = Gesture::Rotate { center, radians };
+                        self.pending.push((window_id, gesture));
+                    }
+                }
+            }
+        }
+
+        fn ended(&mut self, window_id: window::Id, touch: Touch) {
+            let duration = self.time.checked_sub(touch.start_time).unwrap_or_default();
+            if touch.moved || touch.multi || touch.long_pressed || duration > TAP_MAX_DURATION {
+                return;
+            }
+            let position = touch.position;
+            self.pending.push((window_id, Gesture::Tap(position)));
+            let is_double = match self.last_tap {
+                None => false,
+                Some((prev_position, prev_time)) => {
+                    let interval = self.time.checked_sub(prev_time).unwrap_or_default();
+                    let distance = (position - prev_position).magnitude();
+                    interval <= DOUBLE_TAP_MAX_INTERVAL && distance <= DOUBLE_TAP_MAX_DISTANCE
+                }
+            };
+            if is_double {
+                self.pending.push((window_id, Gesture::DoubleTap(position)));
+                self.last_tap = None;
+            } else {
+                self.last_tap = Some((position, self.time));
+            }
+        }
+
+        // The positions of the two touches with the lowest IDs.
+        fn pair(&self) -> Option<(Point2, Point2)> {
+            let mut touches = self.active.values();
+            match (touches.next(), touches.next()) {
+                (Some(a), Some(b)) => Some((a.position, b.position)),
+                _ => None,
+            }
+        }
+    }
+
+    impl Touch {
+        /// The displacement of the touch from where it started.
+        pub fn delta(&self) -> Vector2 {
+            self.position - self.start
+        }
+    }
+
+    // Wrap the given angle to the range `-PI..=PI`.
+    fn wrap_angle(radians: DefaultScalar) -> DefaultScalar {
+        use std::f32::consts::PI;
+        let mut radians = radians % (2.0 * PI);
+        if radians > PI {
+            radians -= 2.0 * PI;
+        } else if radians < -PI {
+            radians += 2.0 * PI;
+        }
+        radians
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::event::TouchEvent;
+        use crate::geom::{pt2, vec2};
+        use std::f32::consts::PI;
+
+        fn window_id() -> window::Id {
+            unsafe { window::Id::dummy() }
+        }
+
+        fn event(id: u64, phase: TouchPhase, [x, y]: [DefaultScalar; 2]) -> TouchEvent {
+            let position = pt2(x, y);
+            TouchEvent {
+                id,
+                phase,
+                position,
+            }
+        }
+
+        // Update the touches to the given time in milliseconds and feed the given events,
+        // returning all gestures recognised along the way.
+        fn feed(touches: &mut Touches, millis: u64, events: &[TouchEvent]) -> Vec<Gesture> {
+            touches.update(Duration::from_millis(millis));
+            for e in events {
+                touches.touch(window_id(), e.id, e.phase, e.position);
+            }
+            touches.pending.drain(..).map(|(_, g)| g).collect()
+        }
+
+        fn tap(touches: &mut Touches, millis: u64, [x, y]: [DefaultScalar; 2]) -> Vec<Gesture> {
+            let start = event(0, TouchPhase::Started, [x, y]);
+            let end = event(0, TouchPhase::Ended, [x, y]);
+            assert!(feed(touches, millis, &[start]).is_empty());
+            feed(touches, millis + 50, &[end])
+        }
+
+        #[test]
+        fn single_tap() {
+            let mut touches = Touches::default();
+            let gestures = tap(&mut touches, 0, [4.0, 2.0]);
+            assert_eq!(gestures, vec![Gesture::Tap(pt2(4.0, 2.0))]);
+            assert!(touches.is_empty());
+        }
+
+        #[test]
+        fn slow_release_is_not_a_tap() {
+            let mut touches = Touches::default();
+            let p = [0.0, 0.0];
+            feed(&mut touches, 0, &[event(0, TouchPhase::Started, p)]);
+            let gestures = feed(&mut touches, 400, &[event(0, TouchPhase::Ended, p)]);
+            assert!(gestures.is_empty());
+        }
+
+        #[test]
+        fn double_tap() {
+            let mut touches = Touches::default();
+            tap(&mut touches, 0, [0.0, 0.0]);
+            let gestures = tap(&mut touches, 200, [5.0, 5.0]);
+            let expected = vec![
+                Gesture::Tap(pt2(5.0, 5.0)),
+                Gesture::DoubleTap(pt2(5.0, 5.0)),
+            ];
+            assert_eq!(gestures, expected);
+            // A third tap begins a new sequence rather than producing another double tap.
+            let gestures = tap(&mut touches, 400, [5.0, 5.0]);
+            assert_eq!(gestures, vec![Gesture::Tap(pt2(5.0, 5.0))]);
+        }
+
+        #[test]
+        fn double_tap_requires_short_interval() {
+            let mut touches = Touches::default();
+            tap(&mut touches, 0, [0.0, 0.0]);
+            let gestures = tap(&mut touches, 400, [0.0, 0.0]);
+            assert_eq!(gestures, vec![Gesture::Tap(pt2(0.0, 0.0))]);
+        }
+
+        #[test]
+        fn double_tap_requires_nearby_taps() {
+            let mut touches = Touches::default();
+            tap(&mut touches, 0, [0.0, 0.0]);
+            let gestures = tap(&mut touches, 100, [50.0, 0.0]);
+            assert_eq!(gestures, vec![Gesture::Tap(pt2(50.0, 0.0))]);
+        }
+
+        #[test]
+        fn long_press() {
+            let mut touches = Touches::default();
+            let p = [1.0, 2.0];
+            feed(&mut touches, 0, &[event(0, TouchPhase::Started, p)]);
+            assert!(feed(&mut touches, 499, &[]).is_empty());
+            let gestures = feed(&mut touches, 500, &[]);
+            assert_eq!(gestures, vec![Gesture::LongPress(pt2(1.0, 2.0))]);
+            // The long press is only recognised once and is not followed by a tap.
+            assert!(feed(&mut touches, 600, &[]).is_empty());
+            let gestures = feed(&mut touches, 700, &[event(0, TouchPhase::Ended, p)]);
+            assert!(gestures.is_empty());
+        }
+
+        #[test]
+        fn moving_cancels_long_press() {
+            let mut touches = Touches::default();
+            feed(
+                &mut touches,
+                0,
+                &[event(0, TouchPhase::Started, [0.0, 0.0])],
+            );
+            let gestures = feed(
+                &mut touches,
+                100,
+                &[event(0, TouchPhase::Moved, [20.0, 0.0])],
+            );
+            let pan = Gesture::Pan {
+                delta: vec2(20.0, 0.0),
+                touches: 1,
+            };
+            assert_eq!(gestures, vec![pan]);
+            assert!(feed(&mut touches, 600, &[]).is_empty());
+        }
+
+        #[test]
+        fn pinch() {
+            let mut touches = Touches::default();
+            let start = [
+                event(0, TouchPhase::Started, [-10.0, 0.0]),
+                event(1, TouchPhase::Started, [10.0, 0.0]),
+            ];
+            assert!(feed(&mut touches, 0, &start).is_empty());
+            let gestures = feed(
+                &mut touches,
+                16,
+                &[event(1, TouchPhase::Moved, [30.0, 0.0])],
+            );
+            let expected = vec![
+                Gesture::Pan {
+                    delta: vec2(10.0, 0.0),
+                    touches: 2,
+                },
+                Gesture::Pinch {
+                    center: pt2(10.0, 0.0),
+                    scale: 2.0,
+                },
+            ];
+            assert_eq!(gestures, expected);
+            // Neither touch is recognised as a tap upon release.
+            let end = [
+                event(0, TouchPhase::Ended, [-10.0, 0.0]),
+                event(1, TouchPhase::Ended, [30.0, 0.0]),
+            ];
+            assert!(feed(&mut touches, 32, &end).is_empty());
+        }
+
+        #[test]
+        fn rotate() {
+            let mut touches = Touches::default();
+            let start = [
+                event(0, TouchPhase::Started, [-10.0, 0.0]),
+                event(1, TouchPhase::Started, [10.0, 0.0]),
+            ];
+            feed(&mut touches, 0, &start);
+            let gestures = feed(
+                &mut touches,
+                16,
+                &[event(1, TouchPhase::Moved, [-10.0, 20.0])],
+            );
+            let expected = vec![
+                Gesture::Pan {
+                    delta: vec2(-10.0, 10.0),
+                    touches: 2,
+                },
+                Gesture::Rotate {
+                    center: pt2(-10.0, 10.0),
+                    radians: PI / 2.0,
+                },
+            ];
+            assert_eq!(gestures, expected);
+        }
+
+        #[test]
+        fn wrap_angle_range() {
+            assert_eq!(wrap_angle(0.5), 0.5);
+            assert!((wrap_angle(1.5 * PI) + 0.5 * PI).abs() < 1e-5);
+            assert!((wrap_angle(-1.5 * PI) - 0.5 * PI).abs() < 1e-5);
+        }
+    }
 }
 
 /// Tracked durations related to the App.
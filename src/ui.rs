@@ -255,7 +255,7 @@ impl<'a> Builder<'a> {
         let device = window.swap_chain_device().clone();
 
         // Initialise the renderer which draws conrod::render::Primitives to the frame.
-        let texture_format = Frame::TEXTURE_FORMAT;
+        let texture_format = window.frame_texture_format();
         let renderer = match glyph_cache_dimensions {
             Some((w, h)) => {
                 let dims = [w as _, h as _];
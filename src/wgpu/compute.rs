@@ -0,0 +1,458 @@
+//! Items aimed at reducing the boilerplate involved in running compute shaders.
+//!
+//! A compute **Pipeline** is built from a SPIR-V shader module along with the resources that the
+//! shader accesses, each declared by its binding index within bind group `0`. Typed **Buffer**s
+//! may be written from and read back to the CPU, or used directly as the instance data for
+//! `Draw::instances_from_buffer` without leaving the GPU.
+//!
+//! ```ignore
+//! let usage = wgpu::BufferUsage::STORAGE | wgpu::BufferUsage::VERTEX | wgpu::BufferUsage::COPY_SRC;
+//! let particles = wgpu::compute::Buffer::from_slice(device, &initial, usage);
+//! let uniforms = wgpu::compute::Buffer::uniform(device, &Uniforms { time: 0.0 });
+//! let pipeline = wgpu::compute::Builder::new(&cs_mod)
+//!     .storage_buffer(0, &particles)
+//!     .uniform_buffer(1, &uniforms)
+//!     .build(device);
+//! pipeline.encode_dispatch(&mut encoder, [PARTICLE_COUNT, 1, 1]);
+//! let read = particles.encode_read(device, &mut encoder);
+//! queue.submit(&[encoder.finish()]);
+//! read.read(|result| { /* ... */ });
+//! ```
+
+use crate::wgpu;
+use std::marker::PhantomData;
+use std::ops::Range;
+use std::sync::Arc;
+
+/// A GPU buffer containing `len` elements of type `T`.
+///
+/// A **Buffer** is cheap to clone as the inner `wgpu::Buffer` is shared.
+#[derive(Debug)]
+pub struct Buffer<T> {
+    inner: Arc<wgpu::Buffer>,
+    len: usize,
+    usage: wgpu::BufferUsage,
+    ty: PhantomData<T>,
+}
+
+/// A buffer containing a copy of a **Buffer**'s elements that may be mapped for reading.
+///
+/// Produced via `Buffer::encode_read`.
+#[derive(Debug)]
+pub struct BufferRead<T> {
+    buffer: wgpu::Buffer,
+    len: usize,
+    ty: PhantomData<T>,
+}
+
+/// A type aimed at simplifying the construction of a compute **Pipeline**.
+#[derive(Debug)]
+pub struct Builder<'a> {
+    module: &'a wgpu::ShaderModule,
+    entry_point: &'a str,
+    resources: Vec<(u32, Resource<'a>)>,
+}
+
+/// A compute pipeline along with the bind group describing the resources it accesses.
+#[derive(Debug)]
+pub struct Pipeline {
+    pipeline: wgpu::ComputePipeline,
+    bind_group_layout: wgpu::BindGroupLayout,
+    bind_group: wgpu::BindGroup,
+}
+
+// A resource accessed by the compute shader.
+#[derive(Debug)]
+enum Resource<'a> {
+    StorageBuffer {
+        buffer: &'a wgpu::Buffer,
+        range: Range<wgpu::BufferAddress>,
+        readonly: bool,
+    },
+    UniformBuffer {
+        buffer: &'a wgpu::Buffer,
+        range: Range<wgpu::BufferAddress>,
+    },
+    SampledTexture(&'a wgpu::TextureView),
+    StorageTexture(&'a wgpu::TextureView),
+    Sampler(&'a wgpu::Sampler),
+}
+
+impl<T> Buffer<T>
+where
+    T: 'static + Copy,
+{
+    /// Create a buffer with space for `len` elements.
+    ///
+    /// The initial contents of the buffer are undefined.
+    pub fn new(device: &wgpu::Device, len: usize, usage: wgpu::BufferUsage) -> Self {
+        let size = (len * std::mem::size_of::<T>()) as wgpu::BufferAddress;
+        let desc = wgpu::BufferDescriptor { size, usage };
+        let buffer = device.create_buffer(&desc);
+        Self::from_inner(buffer, len, usage)
+    }
+
+    /// Create a buffer initialised with the given elements.
+    pub fn from_slice(device: &wgpu::Device, data: &[T], usage: wgpu::BufferUsage) -> Self {
+        let buffer = device
+            .create_buffer_mapped(data.len(), usage)
+            .fill_from_slice(data);
+        Self::from_inner(buffer, data.len(), usage)
+    }
+
+    /// Create a uniform buffer initialised with the given value.
+    ///
+    /// The buffer may be updated via `encode_write`.
+    pub fn uniform(device: &wgpu::Device, value: &T) -> Self {
+        let usage = wgpu::BufferUsage::UNIFORM | wgpu::BufferUsage::COPY_DST;
+        Self::from_slice(device, std::slice::from_ref(value), usage)
+    }
+
+    fn from_inner(buffer: wgpu::Buffer, len: usize, usage: wgpu::BufferUsage) -> Self {
+        Buffer {
+            inner: Arc::new(buffer),
+            len,
+            usage,
+            ty: PhantomData,
+        }
+    }
+
+    /// The number of elements in the buffer.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether or not the buffer contains no elements.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The size of the buffer in bytes.
+    pub fn size_bytes(&self) -> wgpu::BufferAddress {
+        (self.len * std::mem::size_of::<T>()) as wgpu::BufferAddress
+    }
+
+    /// The usage with which the buffer was created.
+    pub fn usage(&self) -> wgpu::BufferUsage {
+        self.usage
+    }
+
+    /// A reference to the inner `wgpu::Buffer`.
+    pub fn inner(&self) -> &wgpu::Buffer {
+        &self.inner
+    }
+
+    /// Whether or not both handles refer to the same buffer.
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.inner, &other.inner)
+    }
+
+    /// Encode a copy of the given elements to the start of the buffer.
+    ///
+    /// The buffer must have been created with `BufferUsage::COPY_DST`.
+    ///
+    /// Panics if `data` contains more elements than the buffer.
+    pub fn encode_write(
+        &self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        data: &[T],
+    ) {
+        assert!(
+            data.len() <= self.len,
+            "attempted to write {} elements to a buffer of length {}",
+            data.len(),
+            self.len,
+        );
+        if data.is_empty() {
+            return;
+        }
+        let size = (data.len() * std::mem::size_of::<T>()) as wgpu::BufferAddress;
+        let staging = device
+            .create_buffer_mapped(data.len(), wgpu::BufferUsage::COPY_SRC)
+            .fill_from_slice(data);
+        encoder.copy_buffer_to_buffer(&staging, 0, &self.inner, 0, size);
+    }
+
+    /// Encode a copy of the buffer's contents to a new buffer that may be mapped for reading.
+    ///
+    /// The buffer must have been created with `BufferUsage::COPY_SRC`.
+    ///
+    /// NOTE: `read` should not be called on the returned buffer until the encoded commands have
+    /// been submitted to the device queue.
+    pub fn encode_read(
+        &self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+    ) -> BufferRead<T> {
+        let size = self.size_bytes();
+        let desc = wgpu::BufferDescriptor {
+            size,
+            usage: wgpu::BufferUsage::MAP_READ | wgpu::BufferUsage::COPY_DST,
+        };
+        let buffer = device.create_buffer(&desc);
+        encoder.copy_buffer_to_buffer(&self.inner, 0, &buffer, 0, size);
+        BufferRead {
+            buffer,
+            len: self.len,
+            ty: PhantomData,
+        }
+    }
+}
+
+impl<T> BufferRead<T>
+where
+    T: 'static + Copy,
+{
+    /// The number of elements in the buffer.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether or not the buffer contains no elements.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Asynchronously maps the buffer to host memory and, once mapped, calls the given user
+    /// callback with the elements.
+    ///
+    /// Note: The given callback will not be called until the memory is mapped and the device is
+    /// polled. You should not rely on the callback being called immediately.
+    pub fn read<F>(&self, callback: F)
+    where
+        F: 'static + FnOnce(Result<&[T], ()>),
+    {
+        let size = (self.len * std::mem::size_of::<T>()) as wgpu::BufferAddress;
+        self.buffer
+            .map_read_async(0, size, move |result: wgpu::BufferMapAsyncResult<&[T]>| {
+                callback(result.map(|mapping| mapping.data))
+            })
+    }
+
+    /// A reference to the inner `wgpu::Buffer`.
+    pub fn inner(&self) -> &wgpu::Buffer {
+        &self.buffer
+    }
+}
+
+impl<'a> Builder<'a> {
+    /// The default name of the compute shader's entry point.
+    pub const DEFAULT_ENTRY_POINT: &'static str = "main";
+
+    /// Begin building a compute pipeline for the given SPIR-V shader module.
+    pub fn new(module: &'a wgpu::ShaderModule) -> Self {
+        Builder {
+            module,
+            entry_point: Self::DEFAULT_ENTRY_POINT,
+            resources: vec![],
+        }
+    }
+
+    /// The name of the compute shader's entry point.
+    pub fn entry_point(mut self, entry_point: &'a str) -> Self {
+        self.entry_point = entry_point;
+        self
+    }
+
+    /// A storage buffer that may be read and written by the shader at the given binding.
+    ///
+    /// The buffer must have been created with `BufferUsage::STORAGE`.
+    pub fn storage_buffer<T>(self, binding: u32, buffer: &'a Buffer<T>) -> Self
+    where
+        T: 'static + Copy,
+    {
+        self.storage_buffer_inner(binding, buffer, false)
+    }
+
+    /// A storage buffer that may only be read by the shader at the given binding.
+    pub fn readonly_storage_buffer<T>(self, binding: u32, buffer: &'a Buffer<T>) -> Self
+    where
+        T: 'static + Copy,
+    {
+        self.storage_buffer_inner(binding, buffer, true)
+    }
+
+    fn storage_buffer_inner<T>(self, binding: u32, buffer: &'a Buffer<T>, readonly: bool) -> Self
+    where
+        T: 'static + Copy,
+    {
+        let resource = Resource::StorageBuffer {
+            buffer: buffer.inner(),
+            range: 0..buffer.size_bytes(),
+            readonly,
+        };
+        self.resource(binding, resource)
+    }
+
+    /// A uniform buffer at the given binding.
+    ///
+    /// The buffer must have been created with `BufferUsage::UNIFORM`, e.g. via `Buffer::uniform`.
+    pub fn uniform_buffer<T>(self, binding: u32, buffer: &'a Buffer<T>) -> Self
+    where
+        T: 'static + Copy,
+    {
+        let resource = Resource::UniformBuffer {
+            buffer: buffer.inner(),
+            range: 0..buffer.size_bytes(),
+        };
+        self.resource(binding, resource)
+    }
+
+    /// A 2D texture that may be sampled by the shader at the given binding.
+    pub fn sampled_texture(self, binding: u32, view: &'a wgpu::TextureView) -> Self {
+        self.resource(binding, Resource::SampledTexture(view))
+    }
+
+    /// A 2D texture that may be read and written by the shader at the given binding.
+    ///
+    /// The texture must have been created with `TextureUsage::STORAGE`.
+    pub fn storage_texture(self, binding: u32, view: &'a wgpu::TextureView) -> Self {
+        self.resource(binding, Resource::StorageTexture(view))
+    }
+
+    /// A sampler at the given binding.
+    pub fn sampler(self, binding: u32, sampler: &'a wgpu::Sampler) -> Self {
+        self.resource(binding, Resource::Sampler(sampler))
+    }
+
+    fn resource(mut self, binding: u32, resource: Resource<'a>) -> Self {
+        self.resources.retain(|&(b, _)| b != binding);
+        self.resources.push((binding, resource));
+        self
+    }
+
+    /// Build the compute pipeline along with its bind group.
+    pub fn build(self, device: &wgpu::Device) -> Pipeline {
+        let Builder {
+            module,
+            entry_point,
+            resources,
+        } = self;
+
+        let layout_bindings: Vec<_> = resources
+            .iter()
+            .map(|&(binding, ref resource)| wgpu::BindGroupLayoutBinding {
+                binding,
+                visibility: wgpu::ShaderStage::COMPUTE,
+                ty: resource.binding_type(),
+            })
+            .collect();
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            bindings: &layout_bindings,
+        });
+
+        let bindings: Vec<_> = resources
+            .iter()
+            .map(|&(binding, ref resource)| wgpu::Binding {
+                binding,
+                resource: resource.binding_resource(),
+            })
+            .collect();
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &bind_group_layout,
+            bindings: &bindings,
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            bind_group_layouts: &[&bind_group_layout],
+        });
+        let compute_stage = wgpu::ProgrammableStageDescriptor {
+            module,
+            entry_point,
+        };
+        let desc = wgpu::ComputePipelineDescriptor {
+            layout: &pipeline_layout,
+            compute_stage,
+        };
+        let pipeline = device.create_compute_pipeline(&desc);
+
+        Pipeline {
+            pipeline,
+            bind_group_layout,
+            bind_group,
+        }
+    }
+}
+
+impl Pipeline {
+    /// Encode a compute pass dispatching the given number of work groups in each dimension.
+    pub fn encode_dispatch(&self, encoder: &mut wgpu::CommandEncoder, [x, y, z]: [u32; 3]) {
+        let mut cpass = encoder.begin_compute_pass();
+        cpass.set_pipeline(&self.pipeline);
+        cpass.set_bind_group(0, &self.bind_group, &[]);
+        cpass.dispatch(x, y, z);
+    }
+
+    /// Dispatch the given number of work groups in each dimension.
+    ///
+    /// This is a short-hand for `encode_dispatch` followed by submitting the commands.
+    pub fn dispatch(&self, device: &wgpu::Device, queue: &mut wgpu::Queue, work_groups: [u32; 3]) {
+        let ce_desc = wgpu::CommandEncoderDescriptor::default();
+        let mut encoder = device.create_command_encoder(&ce_desc);
+        self.encode_dispatch(&mut encoder, work_groups);
+        queue.submit(&[encoder.finish()]);
+    }
+
+    /// A reference to the inner `wgpu::ComputePipeline`.
+    pub fn inner(&self) -> &wgpu::ComputePipeline {
+        &self.pipeline
+    }
+
+    /// The layout of the bind group describing the pipeline's resources.
+    pub fn bind_group_layout(&self) -> &wgpu::BindGroupLayout {
+        &self.bind_group_layout
+    }
+
+    /// The bind group describing the pipeline's resources.
+    pub fn bind_group(&self) -> &wgpu::BindGroup {
+        &self.bind_group
+    }
+}
+
+impl<'a> Resource<'a> {
+    fn binding_type(&self) -> wgpu::BindingType {
+        match *self {
+            Resource::StorageBuffer { readonly, .. } => wgpu::BindingType::StorageBuffer {
+                dynamic: false,
+                readonly,
+            },
+            Resource::UniformBuffer { .. } => wgpu::BindingType::UniformBuffer { dynamic: false },
+            Resource::SampledTexture(_) => wgpu::BindingType::SampledTexture {
+                multisampled: false,
+                dimension: wgpu::TextureViewDimension::D2,
+            },
+            Resource::StorageTexture(_) => wgpu::BindingType::StorageTexture {
+                dimension: wgpu::TextureViewDimension::D2,
+            },
+            Resource::Sampler(_) => wgpu::BindingType::Sampler,
+        }
+    }
+
+    fn binding_resource(&self) -> wgpu::BindingResource<'a> {
+        match *self {
+            Resource::StorageBuffer {
+                buffer, ref range, ..
+            }
+            | Resource::UniformBuffer { buffer, ref range } => wgpu::BindingResource::Buffer {
+                buffer,
+                range: range.clone(),
+            },
+            Resource::SampledTexture(view) | Resource::StorageTexture(view) => {
+                wgpu::BindingResource::TextureView(view)
+            }
+            Resource::Sampler(sampler) => wgpu::BindingResource::Sampler(sampler),
+        }
+    }
+}
+
+impl<T> Clone for Buffer<T> {
+    fn clone(&self) -> Self {
+        Buffer {
+            inner: self.inner.clone(),
+            len: self.len,
+            usage: self.usage,
+            ty: PhantomData,
+        }
+    }
+}
@@ -15,8 +15,11 @@
 //! - The [WebGPU specification](https://gpuweb.github.io/gpuweb/).
 //! - WebGPU [on wikipedia](https://en.wikipedia.org/wiki/WebGPU).
 
+pub mod compute;
 mod device_map;
 mod sampler_builder;
+#[cfg(feature = "shader-hot-reload")]
+pub mod shader;
 mod texture;
 
 // Re-export all of `wgpu` along with its documentation.
@@ -26,7 +29,12 @@ mod texture;
 pub use self::device_map::{
     ActiveAdapter, AdapterMap, AdapterMapKey, DeviceMap, DeviceMapKey, DeviceQueuePair,
 };
-pub use self::sampler_builder::SamplerBuilder;
+pub use self::sampler_builder::{sampler_descriptor_eq, SamplerBuilder};
+pub use self::texture::atlas::{
+    Animation as TextureAtlasAnimation, Atlas as TextureAtlas, Builder as TextureAtlasBuilder,
+    Key as TextureAtlasKey, ManifestError as TextureAtlasManifestError,
+    PackError as TextureAtlasPackError, Region as TextureAtlasRegion,
+};
 pub use self::texture::capturer::{
     Capturer as TextureCapturer, Rgba8AsyncMapping, Snapshot as TextureSnapshot,
 };
@@ -34,11 +42,13 @@ pub use self::texture::image::{
     format_from_image_color_type as texture_format_from_image_color_type, BufferImage,
     ImageAsyncMapping,
 };
-pub use self::texture::reshaper::Reshaper as TextureReshaper;
+pub use self::texture::pixel_buffer::PixelBuffer;
+pub use self::texture::reshaper::{Reshaper as TextureReshaper, TonemapOperator, Tonemapping};
 pub use self::texture::{
     descriptor_eq as texture_descriptor_eq, extent_3d_eq,
-    format_size_bytes as texture_format_size_bytes, BufferBytes, Builder as TextureBuilder,
-    Texture,
+    format_size_bytes as texture_format_size_bytes,
+    format_supports_mipmap_generation as texture_format_supports_mipmap_generation,
+    mip_level_count_for_size, BufferBytes, Builder as TextureBuilder, Texture,
 };
 #[doc(inline)]
 pub use wgpu::{
@@ -52,6 +52,15 @@ impl SamplerBuilder {
         self
     }
 
+    /// Short-hand for specifying the same address mode for all of the `u`, `v` and `w` axes.
+    ///
+    /// E.g. `AddressMode::Repeat` for tiling a texture, or `AddressMode::MirrorRepeat`.
+    pub fn address_mode(self, mode: wgpu::AddressMode) -> Self {
+        self.address_mode_u(mode)
+            .address_mode_v(mode)
+            .address_mode_w(mode)
+    }
+
     /// How the implementation should sample from the image when it is respectively larger than the
     /// original.
     pub fn mag_filter(mut self, filter: wgpu::FilterMode) -> Self {
@@ -66,6 +75,13 @@ impl SamplerBuilder {
         self
     }
 
+    /// Short-hand for specifying both the `mag_filter` and `min_filter`.
+    ///
+    /// E.g. `FilterMode::Nearest` for crisp pixel art.
+    pub fn filter(self, filter: wgpu::FilterMode) -> Self {
+        self.mag_filter(filter).min_filter(filter)
+    }
+
     /// How the implementation should choose which mipmap to use.
     pub fn mipmap_filter(mut self, filter: wgpu::FilterMode) -> Self {
         self.descriptor.mipmap_filter = filter;
@@ -119,3 +135,18 @@ impl From<wgpu::SamplerDescriptor> for SamplerBuilder {
         SamplerBuilder { descriptor }
     }
 }
+
+/// Whether or not the two sampler descriptors describe the same sampler.
+///
+/// Useful for caching samplers, as `SamplerDescriptor` does not implement `PartialEq`.
+pub fn sampler_descriptor_eq(a: &wgpu::SamplerDescriptor, b: &wgpu::SamplerDescriptor) -> bool {
+    a.address_mode_u == b.address_mode_u
+        && a.address_mode_v == b.address_mode_v
+        && a.address_mode_w == b.address_mode_w
+        && a.mag_filter == b.mag_filter
+        && a.min_filter == b.min_filter
+        && a.mipmap_filter == b.mipmap_filter
+        && a.lod_min_clamp == b.lod_min_clamp
+        && a.lod_max_clamp == b.lod_max_clamp
+        && a.compare_function == b.compare_function
+}
@@ -0,0 +1,409 @@
+//! Items related to compiling GLSL shaders at runtime and reloading them when they change.
+//!
+//! Requires the `shader-hot-reload` feature, which compiles GLSL to SPIR-V via `shaderc`. Note
+//! that WGSL is not yet supported, as `wgpu` 0.4 only accepts SPIR-V.
+//!
+//! A **Watched** shader is compiled upon creation and recompiled whenever `update` finds that its
+//! source file has been modified. If compilation fails, the error is reported and the last
+//! successfully compiled module remains in use, so a typo never crashes a running sketch. Objects
+//! that depend on one or more watched shaders (e.g. render pipelines) may be wrapped in a
+//! **Dependent** so that they are rebuilt whenever any of their shaders change.
+//!
+//! ```ignore
+//! // In `model`.
+//! let mut vs = wgpu::shader::Watched::new(device, "shaders/shader.vert")?;
+//! let mut fs = wgpu::shader::Watched::new(device, "shaders/shader.frag")?;
+//! let mut pipeline = wgpu::shader::Dependent::new(&[&vs, &fs], |m| pipeline(device, m));
+//!
+//! // In `update`.
+//! vs.update(device);
+//! fs.update(device);
+//! pipeline.update(&[&vs, &fs], |m| pipeline(device, m));
+//! ```
+
+use crate::wgpu;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use std::{error, fmt, fs, io, ops};
+
+/// The pipeline stage for which a shader is compiled.
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+pub enum Stage {
+    Vertex,
+    Fragment,
+    Compute,
+}
+
+/// A GLSL shader compiled from a file that is recompiled when the file changes.
+pub struct Watched {
+    source: Source,
+    module: wgpu::ShaderModule,
+    compiler: shaderc::Compiler,
+}
+
+// Tracks the modifications and compilation results of a shader source file, independently of the
+// device and compiler.
+#[derive(Debug)]
+struct Source {
+    path: PathBuf,
+    stage: Stage,
+    modified: Option<SystemTime>,
+    generation: u64,
+    error: Option<Error>,
+}
+
+/// An object built from one or more **Watched** shaders that is rebuilt when any of them change.
+#[derive(Debug)]
+pub struct Dependent<T> {
+    value: T,
+    generations: Vec<u64>,
+}
+
+/// Errors that might occur while loading or compiling a shader.
+#[derive(Debug)]
+pub enum Error {
+    /// Failed to read the shader source file.
+    Io(io::Error),
+    /// The stage could not be inferred from the file extension.
+    UnknownStage(PathBuf),
+    /// Failed to initialise the shader compiler.
+    NoCompiler,
+    /// The GLSL failed to compile. Contains the compiler's error messages.
+    Compile(String),
+}
+
+impl Stage {
+    /// Infer the stage from a file extension, i.e. `vert`, `frag` or `comp`.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("vert") => Some(Stage::Vertex),
+            Some("frag") => Some(Stage::Fragment),
+            Some("comp") => Some(Stage::Compute),
+            _ => None,
+        }
+    }
+
+    fn shader_kind(&self) -> shaderc::ShaderKind {
+        match *self {
+            Stage::Vertex => shaderc::ShaderKind::Vertex,
+            Stage::Fragment => shaderc::ShaderKind::Fragment,
+            Stage::Compute => shaderc::ShaderKind::Compute,
+        }
+    }
+}
+
+impl Watched {
+    /// Compile the GLSL shader at the given path, inferring its stage from the file extension.
+    ///
+    /// Unlike `update`, this returns an error if the initial compilation fails as there is no
+    /// previous module to fall back to.
+    pub fn new<P>(device: &wgpu::Device, path: P) -> Result<Self, Error>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        let stage = Stage::from_path(path).ok_or_else(|| Error::UnknownStage(path.into()))?;
+        Self::with_stage(device, path, stage)
+    }
+
+    /// Compile the GLSL shader at the given path for the given stage.
+    pub fn with_stage<P>(device: &wgpu::Device, path: P, stage: Stage) -> Result<Self, Error>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref().to_path_buf();
+        let mut compiler = shaderc::Compiler::new().ok_or(Error::NoCompiler)?;
+        let modified = modified(&path);
+        let spirv = compile_file(&mut compiler, &path, stage)?;
+        let module = device.create_shader_module(&spirv);
+        let source = Source {
+            path,
+            stage,
+            modified,
+            generation: 0,
+            error: None,
+        };
+        Ok(Watched {
+            source,
+            module,
+            compiler,
+        })
+    }
+
+    /// Recompile the shader if its source file has been modified since it was last compiled.
+    ///
+    /// Returns `true` if a new module was compiled. If compilation fails, the error is stored for
+    /// retrieval via `error` and the previous module remains in use.
+    pub fn update(&mut self, device: &wgpu::Device) -> bool {
+        let compiler = &mut self.compiler;
+        match self
+            .source
+            .recompile_if_modified(|path, stage| compile_file(compiler, path, stage))
+        {
+            Some(spirv) => {
+                self.module = device.create_shader_module(&spirv);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// The most recently successfully compiled shader module.
+    pub fn module(&self) -> &wgpu::ShaderModule {
+        &self.module
+    }
+
+    /// The path to the GLSL source file.
+    pub fn path(&self) -> &Path {
+        &self.source.path
+    }
+
+    /// The stage for which the shader is compiled.
+    pub fn stage(&self) -> Stage {
+        self.source.stage
+    }
+
+    /// The number of times the shader has been successfully recompiled since it was created.
+    pub fn generation(&self) -> u64 {
+        self.source.generation
+    }
+
+    /// The error produced by the most recent compilation attempt, if it failed.
+    pub fn error(&self) -> Option<&Error> {
+        self.source.error.as_ref()
+    }
+}
+
+impl Source {
+    // Recompile the source via `compile` if the file has been modified since it was last checked.
+    //
+    // Returns the SPIR-V upon success, bumping the generation and clearing any previous error.
+    // Upon failure the error is stored until the next successful compilation.
+    fn recompile_if_modified<F>(&mut self, compile: F) -> Option<Vec<u32>>
+    where
+        F: FnOnce(&Path, Stage) -> Result<Vec<u32>, Error>,
+    {
+        let modified = modified(&self.path);
+        if modified == self.modified {
+            return None;
+        }
+        self.modified = modified;
+        match compile(&self.path, self.stage) {
+            Ok(spirv) => {
+                self.generation += 1;
+                self.error = None;
+                Some(spirv)
+            }
+            Err(err) => {
+                self.error = Some(err);
+                None
+            }
+        }
+    }
+}
+
+impl<T> Dependent<T> {
+    /// Build the value from the modules of the given shaders.
+    pub fn new<F>(shaders: &[&Watched], build: F) -> Self
+    where
+        F: FnOnce(&[&wgpu::ShaderModule]) -> T,
+    {
+        let modules: Vec<_> = shaders.iter().map(|s| s.module()).collect();
+        let value = build(&modules);
+        let generations = shaders.iter().map(|s| s.generation()).collect();
+        Dependent { value, generations }
+    }
+
+    /// Rebuild the value if any of the given shaders have been recompiled since it was built.
+    ///
+    /// The shaders should be given in the same order as they were to `new`. Returns `true` if the
+    /// value was rebuilt.
+    pub fn update<F>(&mut self, shaders: &[&Watched], build: F) -> bool
+    where
+        F: FnOnce(&[&wgpu::ShaderModule]) -> T,
+    {
+        let changed = shaders.len() != self.generations.len()
+            || shaders
+                .iter()
+                .zip(&self.generations)
+                .any(|(s, &g)| s.generation() != g);
+        if changed {
+            *self = Self::new(shaders, build);
+        }
+        changed
+    }
+
+    /// Consume the **Dependent** and return the inner value.
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+/// Compile the given GLSL source to SPIR-V.
+///
+/// The `name` is used to identify the source within error messages.
+pub fn compile_glsl(source: &str, stage: Stage, name: &str) -> Result<Vec<u32>, Error> {
+    let mut compiler = shaderc::Compiler::new().ok_or(Error::NoCompiler)?;
+    compile(&mut compiler, source, stage, name)
+}
+
+fn compile(
+    compiler: &mut shaderc::Compiler,
+    source: &str,
+    stage: Stage,
+    name: &str,
+) -> Result<Vec<u32>, Error> {
+    let artifact = compiler
+        .compile_into_spirv(source, stage.shader_kind(), name, "main", None)
+        .map_err(|err| Error::Compile(err.to_string()))?;
+    Ok(artifact.as_binary().to_vec())
+}
+
+fn compile_file(
+    compiler: &mut shaderc::Compiler,
+    path: &Path,
+    stage: Stage,
+) -> Result<Vec<u32>, Error> {
+    let source = fs::read_to_string(path)?;
+    let name = path.to_string_lossy();
+    compile(compiler, &source, stage, &name)
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+impl fmt::Debug for Watched {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Watched")
+            .field("source", &self.source)
+            .finish()
+    }
+}
+
+impl<T> ops::Deref for Dependent<T> {
+    type Target = T;
+    fn deref(&self) -> &Self::Target {
+        &self.value
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        Error::Io(err)
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            Error::Io(ref err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::Io(ref err) => fmt::Display::fmt(err, f),
+            Error::UnknownStage(ref path) => write!(
+                f,
+                "could not infer the shader stage of \"{}\" from its extension",
+                path.display()
+            ),
+            Error::NoCompiler => write!(f, "failed to initialise the shader compiler"),
+            Error::Compile(ref msg) => write!(f, "{}", msg),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A source for the given path that has never been compiled, so that the next check attempts a
+    // compilation if the file exists.
+    fn source(path: PathBuf) -> Source {
+        Source {
+            path,
+            stage: Stage::Fragment,
+            modified: None,
+            generation: 0,
+            error: None,
+        }
+    }
+
+    fn temp_file(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("nannou_shader_test_{}.frag", name));
+        fs::write(&path, "void main() {}").unwrap();
+        path
+    }
+
+    #[test]
+    fn stage_from_path() {
+        let stage = |path: &str| Stage::from_path(Path::new(path));
+        assert_eq!(stage("shaders/shader.vert"), Some(Stage::Vertex));
+        assert_eq!(stage("shader.frag"), Some(Stage::Fragment));
+        assert_eq!(stage("shader.comp"), Some(Stage::Compute));
+        assert_eq!(stage("shader.glsl"), None);
+        assert_eq!(stage("shader"), None);
+        assert_eq!(stage("vert"), None);
+    }
+
+    #[test]
+    fn unmodified_source_is_not_recompiled() {
+        let path = temp_file("unmodified");
+        let mut source = source(path.clone());
+        source.modified = modified(&path);
+        let spirv = source.recompile_if_modified(|_, _| panic!("recompiled unmodified source"));
+        assert!(spirv.is_none());
+        assert_eq!(source.generation, 0);
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn error_is_stored_then_cleared() {
+        let path = temp_file("error");
+        let mut source = source(path.clone());
+
+        // A failed compilation stores the error and leaves the generation unchanged.
+        let compile_err = |_: &Path, _| Err(Error::Compile("syntax error".into()));
+        assert!(source.recompile_if_modified(compile_err).is_none());
+        assert_eq!(source.generation, 0);
+        match source.error {
+            Some(Error::Compile(ref msg)) => assert_eq!(msg, "syntax error"),
+            ref err => panic!("unexpected error: {:?}", err),
+        }
+
+        // The error remains until the next compilation attempt.
+        assert!(source.recompile_if_modified(compile_err).is_none());
+        assert!(source.error.is_some());
+
+        // A successful compilation clears the error and bumps the generation.
+        source.modified = None;
+        let spirv = source.recompile_if_modified(|p, stage| {
+            assert_eq!(p, path.as_path());
+            assert_eq!(stage, Stage::Fragment);
+            Ok(vec![1, 2, 3])
+        });
+        assert_eq!(spirv, Some(vec![1, 2, 3]));
+        assert_eq!(source.generation, 1);
+        assert!(source.error.is_none());
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn removed_source_reports_io_error() {
+        let path = temp_file("removed");
+        let mut source = source(path.clone());
+        source.modified = modified(&path);
+        fs::remove_file(&path).unwrap();
+        let spirv = source.recompile_if_modified(|p, _| Ok(fs::read(p).map(|_| vec![])?));
+        assert!(spirv.is_none());
+        match source.error {
+            Some(Error::Io(_)) => (),
+            ref err => panic!("unexpected error: {:?}", err),
+        }
+    }
+}
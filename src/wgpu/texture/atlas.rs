@@ -0,0 +1,677 @@
+//! Items related to packing many images into a single texture and loading sprite sheets.
+//!
+//! An **Atlas** is a single texture along with a list of **Region**s describing the location of
+//! each sub-image in pixels. Regions may be looked up by index (e.g. the frames of a sprite sheet)
+//! or by name (e.g. the file names within a TexturePacker manifest).
+
+use crate::serde_derive::Deserialize;
+use crate::wgpu;
+use image::{DynamicImage, RgbaImage};
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+use std::sync::Arc;
+use std::{error, fmt, fs, io};
+
+/// Many images packed into a single texture, each accessible via a **Region**.
+///
+/// The texture is non-linear sRGBA-8 with premultiplied alpha. An **Atlas** is cheap to clone as
+/// the texture and regions are shared.
+#[derive(Clone, Debug)]
+pub struct Atlas {
+    inner: Arc<Inner>,
+}
+
+#[derive(Debug)]
+struct Inner {
+    texture: wgpu::Texture,
+    regions: Vec<Region>,
+    names: HashMap<String, usize>,
+}
+
+/// A rectangular region of an **Atlas** in pixels, relative to the top left of the texture.
+#[derive(Copy, Clone, Debug, Deserialize, Eq, Hash, PartialEq)]
+pub struct Region {
+    pub x: u32,
+    pub y: u32,
+    pub w: u32,
+    pub h: u32,
+}
+
+/// Identifies a **Region** of an **Atlas** either by name or by index.
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+pub enum Key<'a> {
+    Name(&'a str),
+    Index(usize),
+}
+
+/// A type used for packing many images into a single **Atlas**.
+///
+/// Images are packed into rows ordered by height within the smallest power-of-two sized square
+/// texture in which they fit. If no such square within the maximum size fits the images, a square
+/// of exactly the maximum size is tried, which need not be a power of two.
+#[derive(Debug)]
+pub struct Builder {
+    images: Vec<(String, RgbaImage)>,
+    padding: u32,
+    max_size: u32,
+}
+
+/// A sequence of **Atlas** region indices played back at a fixed rate.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Animation {
+    frames: Vec<usize>,
+    fps: f32,
+    looping: bool,
+}
+
+/// Errors that might occur while packing images into an **Atlas**.
+#[derive(Debug)]
+pub enum PackError {
+    /// No images were added to the **Builder**.
+    NoImages,
+    /// The images do not fit within a texture of the maximum size.
+    TooLarge { max_size: u32 },
+}
+
+/// Errors that might occur while loading an **Atlas** from a TexturePacker manifest.
+#[derive(Debug)]
+pub enum ManifestError {
+    Io(io::Error),
+    Json(serde_json::Error),
+    Image(image::ImageError),
+    /// The manifest's `meta.image` field was missing when loading from a file.
+    NoImagePath,
+    /// The named frame lies outside of the bounds of the image.
+    FrameOutOfBounds(String),
+}
+
+// A TexturePacker style manifest in either the "JSON (Hash)" or "JSON (Array)" format.
+#[derive(Deserialize)]
+struct Manifest {
+    frames: ManifestFrames,
+    #[serde(default)]
+    meta: ManifestMeta,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ManifestFrames {
+    Hash(BTreeMap<String, ManifestFrame>),
+    Array(Vec<ManifestArrayFrame>),
+}
+
+#[derive(Deserialize)]
+struct ManifestArrayFrame {
+    filename: String,
+    #[serde(flatten)]
+    frame: ManifestFrame,
+}
+
+#[derive(Deserialize)]
+struct ManifestFrame {
+    frame: Region,
+}
+
+#[derive(Default, Deserialize)]
+struct ManifestMeta {
+    image: Option<String>,
+}
+
+impl Atlas {
+    /// Load a sprite sheet laid out as a uniform grid with the given number of columns and rows.
+    ///
+    /// Regions are indexed from left to right, top to bottom and are unnamed.
+    pub fn load_grid(
+        device: &wgpu::Device,
+        queue: &mut wgpu::Queue,
+        image: &DynamicImage,
+        [columns, rows]: [u32; 2],
+    ) -> Self {
+        let image = image.to_rgba();
+        let (w, h) = image.dimensions();
+        let (cell_w, cell_h) = (w / columns.max(1), h / rows.max(1));
+        let regions = (0..rows)
+            .flat_map(|row| (0..columns).map(move |col| (col, row)))
+            .map(|(col, row)| Region {
+                x: col * cell_w,
+                y: row * cell_h,
+                w: cell_w,
+                h: cell_h,
+            })
+            .collect();
+        load(device, queue, image, regions, HashMap::new())
+    }
+
+    /// Load a sprite sheet described by a TexturePacker style JSON manifest.
+    ///
+    /// Both the "JSON (Hash)" and "JSON (Array)" formats are supported. Regions are named after
+    /// the manifest's frames. Hash frames are indexed in order of their name while array frames
+    /// retain the order in which they appear.
+    pub fn load_texture_packer(
+        device: &wgpu::Device,
+        queue: &mut wgpu::Queue,
+        image: &DynamicImage,
+        json: &str,
+    ) -> Result<Self, ManifestError> {
+        let manifest: Manifest = serde_json::from_str(json)?;
+        let image = image.to_rgba();
+        let (w, h) = image.dimensions();
+        let frames: Vec<(String, Region)> = match manifest.frames {
+            ManifestFrames::Hash(map) => map.into_iter().map(|(k, f)| (k, f.frame)).collect(),
+            ManifestFrames::Array(vec) => vec
+                .into_iter()
+                .map(|f| (f.filename, f.frame.frame))
+                .collect(),
+        };
+        let mut regions = Vec::with_capacity(frames.len());
+        let mut names = HashMap::with_capacity(frames.len());
+        for (name, region) in frames {
+            if !region_in_bounds(&region, [w, h]) {
+                return Err(ManifestError::FrameOutOfBounds(name));
+            }
+            names.insert(name, regions.len());
+            regions.push(region);
+        }
+        Ok(load(device, queue, image, regions, names))
+    }
+
+    /// Load a sprite sheet from the TexturePacker style JSON manifest at the given path.
+    ///
+    /// The image is loaded from the path specified by the manifest's `meta.image` field, relative
+    /// to the directory containing the manifest.
+    pub fn load_texture_packer_file(
+        device: &wgpu::Device,
+        queue: &mut wgpu::Queue,
+        json_path: &Path,
+    ) -> Result<Self, ManifestError> {
+        let json = fs::read_to_string(json_path)?;
+        let manifest: Manifest = serde_json::from_str(&json)?;
+        let image_path = manifest.meta.image.ok_or(ManifestError::NoImagePath)?;
+        let dir = json_path.parent().unwrap_or_else(|| Path::new(""));
+        let image = image::open(dir.join(image_path))?;
+        Self::load_texture_packer(device, queue, &image, &json)
+    }
+
+    /// Wrap an existing texture as an atlas with a single unnamed region covering all of it.
+    ///
+    /// This allows for drawing textures produced on the GPU, e.g. by a compute shader, via
+    /// `draw.sprite(&atlas, 0)`. The texture must have been created with `TextureUsage::SAMPLED`
+    /// and is assumed to contain premultiplied alpha.
+    pub fn from_texture(texture: wgpu::Texture) -> Self {
+        let [w, h] = texture.size();
+        let regions = vec![Region { x: 0, y: 0, w, h }];
+        let names = HashMap::new();
+        let inner = Arc::new(Inner {
+            texture,
+            regions,
+            names,
+        });
+        Atlas { inner }
+    }
+
+    /// The texture containing all packed images.
+    pub fn texture(&self) -> &wgpu::Texture {
+        &self.inner.texture
+    }
+
+    /// The size of the atlas texture in pixels.
+    pub fn size(&self) -> [u32; 2] {
+        self.inner.texture.size()
+    }
+
+    /// The number of regions within the atlas.
+    pub fn len(&self) -> usize {
+        self.inner.regions.len()
+    }
+
+    /// Whether or not the atlas contains no regions.
+    pub fn is_empty(&self) -> bool {
+        self.inner.regions.is_empty()
+    }
+
+    /// All regions in order of their index.
+    pub fn regions(&self) -> &[Region] {
+        &self.inner.regions
+    }
+
+    /// The region associated with the given name or index.
+    pub fn region<'a, K>(&self, key: K) -> Option<Region>
+    where
+        K: Into<Key<'a>>,
+    {
+        let index = match key.into() {
+            Key::Index(index) => index,
+            Key::Name(name) => self.index_of(name)?,
+        };
+        self.inner.regions.get(index).cloned()
+    }
+
+    /// The index of the region with the given name.
+    pub fn index_of(&self, name: &str) -> Option<usize> {
+        self.inner.names.get(name).cloned()
+    }
+
+    /// The indices of all regions whose name begins with the given prefix, sorted by name.
+    ///
+    /// Useful for collecting the frames of an animation, e.g. `atlas.indices_with_prefix("walk")`.
+    pub fn indices_with_prefix(&self, prefix: &str) -> Vec<usize> {
+        let mut named: Vec<_> = self
+            .inner
+            .names
+            .iter()
+            .filter(|(name, _)| name.starts_with(prefix))
+            .collect();
+        named.sort();
+        named.into_iter().map(|(_, &index)| index).collect()
+    }
+
+    /// Whether or not both handles refer to the same atlas.
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.inner, &other.inner)
+    }
+}
+
+impl Region {
+    /// The texture coordinates of the top left and bottom right corners of the region within an
+    /// atlas of the given size.
+    pub fn tex_coords(&self, [w, h]: [u32; 2]) -> [[f32; 2]; 2] {
+        let (w, h) = (w as f32, h as f32);
+        let top_left = [self.x as f32 / w, self.y as f32 / h];
+        let bottom_right = [(self.x + self.w) as f32 / w, (self.y + self.h) as f32 / h];
+        [top_left, bottom_right]
+    }
+}
+
+impl Builder {
+    /// The default number of transparent pixels between packed images.
+    ///
+    /// This avoids colors from neighbouring images bleeding in when sampling with filtering.
+    pub const DEFAULT_PADDING: u32 = 1;
+    /// The default maximum width and height of the atlas texture.
+    pub const DEFAULT_MAX_SIZE: u32 = 4096;
+
+    /// Begin building a new **Atlas**.
+    pub fn new() -> Self {
+        Builder {
+            images: vec![],
+            padding: Self::DEFAULT_PADDING,
+            max_size: Self::DEFAULT_MAX_SIZE,
+        }
+    }
+
+    /// The number of transparent pixels between packed images.
+    pub fn padding(mut self, padding: u32) -> Self {
+        self.padding = padding;
+        self
+    }
+
+    /// The maximum width and height of the atlas texture.
+    ///
+    /// This need not be a power of two, in which case it is used as the side length when the
+    /// images do not fit within a smaller power-of-two sized texture.
+    pub fn max_size(mut self, max_size: u32) -> Self {
+        self.max_size = max_size;
+        self
+    }
+
+    /// Add an image to the atlas with the given name.
+    ///
+    /// Regions are indexed in the order in which they are added.
+    pub fn image<T>(mut self, name: T, image: &DynamicImage) -> Self
+    where
+        T: Into<String>,
+    {
+        self.images.push((name.into(), image.to_rgba()));
+        self
+    }
+
+    /// Pack the images and load the resulting texture using the given device queue.
+    pub fn build(self, device: &wgpu::Device, queue: &mut wgpu::Queue) -> Result<Atlas, PackError> {
+        let cmd_encoder_desc = wgpu::CommandEncoderDescriptor::default();
+        let mut encoder = device.create_command_encoder(&cmd_encoder_desc);
+        let atlas = self.encode(device, &mut encoder)?;
+        queue.submit(&[encoder.finish()]);
+        Ok(atlas)
+    }
+
+    /// Pack the images and encode the commands necessary to load the resulting texture.
+    ///
+    /// NOTE: The atlas texture will remain empty until the given `encoder` has its command buffer
+    /// submitted to the given `device`'s queue.
+    pub fn encode(
+        self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+    ) -> Result<Atlas, PackError> {
+        let Builder {
+            images,
+            padding,
+            max_size,
+        } = self;
+        if images.is_empty() {
+            return Err(PackError::NoImages);
+        }
+        let sizes: Vec<[u32; 2]> = images
+            .iter()
+            .map(|(_, image)| [image.width(), image.height()])
+            .collect();
+        let (side, positions) =
+            pack(&sizes, padding, max_size).ok_or(PackError::TooLarge { max_size })?;
+        let mut canvas = RgbaImage::new(side, side);
+        let mut regions = Vec::with_capacity(images.len());
+        let mut names = HashMap::with_capacity(images.len());
+        for ((name, image), [x, y]) in images.into_iter().zip(positions) {
+            image::imageops::replace(&mut canvas, &image, x, y);
+            let region = Region {
+                x,
+                y,
+                w: image.width(),
+                h: image.height(),
+            };
+            names.insert(name, regions.len());
+            regions.push(region);
+        }
+        Ok(encode_load(device, encoder, canvas, regions, names))
+    }
+}
+
+impl Animation {
+    /// An animation that plays the given atlas region indices at the given frames per second.
+    ///
+    /// By default, the animation loops.
+    pub fn new<I>(frames: I, fps: f32) -> Self
+    where
+        I: IntoIterator<Item = usize>,
+    {
+        let frames = frames.into_iter().collect();
+        let looping = true;
+        Animation {
+            frames,
+            fps,
+            looping,
+        }
+    }
+
+    /// Whether or not the animation returns to the first frame after the last. If `false`, the
+    /// animation holds the last frame.
+    pub fn looping(mut self, looping: bool) -> Self {
+        self.looping = looping;
+        self
+    }
+
+    /// The duration of a single playthrough in seconds.
+    pub fn duration_secs(&self) -> f32 {
+        self.frames.len() as f32 / self.fps
+    }
+
+    /// The atlas region index to display at the given number of seconds since the animation
+    /// began.
+    ///
+    /// **Panics** if the animation has no frames.
+    pub fn frame_at(&self, secs: f32) -> usize {
+        let len = self.frames.len();
+        assert!(len > 0, "animation has no frames");
+        let step = (secs.max(0.0) * self.fps) as usize;
+        let ix = if self.looping {
+            step % len
+        } else {
+            step.min(len - 1)
+        };
+        self.frames[ix]
+    }
+}
+
+impl Default for Builder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a> From<&'a str> for Key<'a> {
+    fn from(name: &'a str) -> Self {
+        Key::Name(name)
+    }
+}
+
+impl<'a> From<&'a String> for Key<'a> {
+    fn from(name: &'a String) -> Self {
+        Key::Name(name)
+    }
+}
+
+impl<'a> From<usize> for Key<'a> {
+    fn from(index: usize) -> Self {
+        Key::Index(index)
+    }
+}
+
+impl From<io::Error> for ManifestError {
+    fn from(err: io::Error) -> Self {
+        ManifestError::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for ManifestError {
+    fn from(err: serde_json::Error) -> Self {
+        ManifestError::Json(err)
+    }
+}
+
+impl From<image::ImageError> for ManifestError {
+    fn from(err: image::ImageError) -> Self {
+        ManifestError::Image(err)
+    }
+}
+
+impl error::Error for PackError {}
+
+impl error::Error for ManifestError {
+    fn cause(&self) -> Option<&dyn error::Error> {
+        match *self {
+            ManifestError::Io(ref err) => Some(err),
+            ManifestError::Json(ref err) => Some(err),
+            ManifestError::Image(ref err) => Some(err),
+            ManifestError::NoImagePath | ManifestError::FrameOutOfBounds(_) => None,
+        }
+    }
+}
+
+impl fmt::Display for PackError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            PackError::NoImages => write!(f, "no images to pack"),
+            PackError::TooLarge { max_size } => write!(
+                f,
+                "images do not fit within an atlas of {}x{} pixels",
+                max_size, max_size
+            ),
+        }
+    }
+}
+
+impl fmt::Display for ManifestError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ManifestError::Io(ref err) => fmt::Display::fmt(err, f),
+            ManifestError::Json(ref err) => fmt::Display::fmt(err, f),
+            ManifestError::Image(ref err) => fmt::Display::fmt(err, f),
+            ManifestError::NoImagePath => write!(f, "manifest does not specify `meta.image`"),
+            ManifestError::FrameOutOfBounds(ref name) => {
+                write!(f, "frame \"{}\" lies outside of the image", name)
+            }
+        }
+    }
+}
+
+// Load the atlas texture using the given device queue.
+fn load(
+    device: &wgpu::Device,
+    queue: &mut wgpu::Queue,
+    image: RgbaImage,
+    regions: Vec<Region>,
+    names: HashMap<String, usize>,
+) -> Atlas {
+    let cmd_encoder_desc = wgpu::CommandEncoderDescriptor::default();
+    let mut encoder = device.create_command_encoder(&cmd_encoder_desc);
+    let atlas = encode_load(device, &mut encoder, image, regions, names);
+    queue.submit(&[encoder.finish()]);
+    atlas
+}
+
+// Premultiply the image's alpha and encode the commands necessary to load it into a texture.
+fn encode_load(
+    device: &wgpu::Device,
+    encoder: &mut wgpu::CommandEncoder,
+    mut image: RgbaImage,
+    regions: Vec<Region>,
+    names: HashMap<String, usize>,
+) -> Atlas {
+    for pixel in image.pixels_mut() {
+        let a = pixel[3] as u32;
+        for c in 0..3 {
+            pixel[c] = ((pixel[c] as u32 * a + 127) / 255) as u8;
+        }
+    }
+    let usage = wgpu::TextureUsage::SAMPLED;
+    let texture = wgpu::Texture::encode_load_from_image_buffer(device, encoder, usage, &image);
+    let inner = Arc::new(Inner {
+        texture,
+        regions,
+        names,
+    });
+    Atlas { inner }
+}
+
+// Find the smallest power-of-two sized square in which the images of the given sizes fit, falling
+// back to a square of `max_size` when it is not a power of two.
+//
+// Returns the side length along with the top left position of each image.
+fn pack(sizes: &[[u32; 2]], padding: u32, max_size: u32) -> Option<(u32, Vec<[u32; 2]>)> {
+    // Pack the tallest images first so that each row wastes as little space as possible.
+    let mut order: Vec<usize> = (0..sizes.len()).collect();
+    order.sort_by(|&a, &b| sizes[b][1].cmp(&sizes[a][1]));
+    let mut side = std::cmp::min(64, max_size);
+    loop {
+        if let Some(positions) = pack_rows(sizes, &order, padding, side) {
+            return Some((side, positions));
+        }
+        if side >= max_size {
+            return None;
+        }
+        side = std::cmp::min(side.saturating_mul(2), max_size);
+    }
+}
+
+// Pack the images in the given order into rows within a square of the given side length.
+fn pack_rows(
+    sizes: &[[u32; 2]],
+    order: &[usize],
+    padding: u32,
+    side: u32,
+) -> Option<Vec<[u32; 2]>> {
+    let mut positions = vec![[0, 0]; sizes.len()];
+    let (mut x, mut y, mut row_h) = (padding, padding, 0);
+    for &i in order {
+        let [w, h] = sizes[i];
+        if !span_fits(x, w, padding, side) {
+            x = padding;
+            y = y.checked_add(row_h)?.checked_add(padding)?;
+            row_h = 0;
+        }
+        if !span_fits(x, w, padding, side) || !span_fits(y, h, padding, side) {
+            return None;
+        }
+        positions[i] = [x, y];
+        x += w + padding;
+        row_h = std::cmp::max(row_h, h);
+    }
+    Some(positions)
+}
+
+// Whether or not a span of the given length at the given position, followed by the given
+// padding, ends within the given side length.
+fn span_fits(position: u32, len: u32, padding: u32, side: u32) -> bool {
+    position
+        .checked_add(len)
+        .and_then(|end| end.checked_add(padding))
+        .map(|end| end <= side)
+        .unwrap_or(false)
+}
+
+// Whether or not the region lies entirely within an image of the given dimensions.
+fn region_in_bounds(region: &Region, [w, h]: [u32; 2]) -> bool {
+    span_fits(region.x, region.w, 0, w) && span_fits(region.y, region.h, 0, h)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{pack, region_in_bounds, Region};
+
+    // Whether or not any two of the packed rectangles overlap.
+    fn overlapping(sizes: &[[u32; 2]], positions: &[[u32; 2]]) -> bool {
+        let rects: Vec<_> = sizes.iter().zip(positions).collect();
+        rects
+            .iter()
+            .enumerate()
+            .any(|(i, &(&[aw, ah], &[ax, ay]))| {
+                rects[i + 1..].iter().any(|&(&[bw, bh], &[bx, by])| {
+                    ax < bx + bw && bx < ax + aw && ay < by + bh && by < ay + ah
+                })
+            })
+    }
+
+    #[test]
+    fn pack_fits_within_side() {
+        let sizes = [[30, 20], [10, 40], [50, 50], [8, 8], [64, 12]];
+        let padding = 2;
+        let (side, positions) = pack(&sizes, padding, 1024).unwrap();
+        assert!(side.is_power_of_two());
+        for (&[w, h], &[x, y]) in sizes.iter().zip(&positions) {
+            assert!(x >= padding && y >= padding);
+            assert!(x + w + padding <= side && y + h + padding <= side);
+        }
+        assert!(!overlapping(&sizes, &positions));
+    }
+
+    #[test]
+    fn pack_grows_side_until_fit() {
+        let sizes = vec![[32, 32]; 16];
+        let (side, positions) = pack(&sizes, 0, 1024).unwrap();
+        assert_eq!(side, 128);
+        assert!(!overlapping(&sizes, &positions));
+    }
+
+    #[test]
+    fn pack_respects_small_max_size() {
+        assert_eq!(pack(&[[16, 16]], 0, 32).map(|(side, _)| side), Some(32));
+        assert_eq!(pack(&[[40, 16]], 0, 32), None);
+    }
+
+    #[test]
+    fn pack_falls_back_to_max_size() {
+        assert_eq!(pack(&[[90, 90]], 0, 100).map(|(side, _)| side), Some(100));
+        assert_eq!(pack(&[[60, 60]], 0, 100).map(|(side, _)| side), Some(64));
+    }
+
+    #[test]
+    fn pack_empty() {
+        assert_eq!(pack(&[], 1, 1024), Some((64, vec![])));
+    }
+
+    #[test]
+    fn pack_huge_sizes_do_not_overflow() {
+        assert_eq!(pack(&[[std::u32::MAX, 1]], 4, std::u32::MAX), None);
+        assert_eq!(pack(&[[1, 1]], std::u32::MAX, std::u32::MAX), None);
+    }
+
+    #[test]
+    fn region_bounds() {
+        let region = |x, y, w, h| Region { x, y, w, h };
+        assert!(region_in_bounds(&region(0, 0, 64, 64), [64, 64]));
+        assert!(region_in_bounds(&region(32, 16, 32, 48), [64, 64]));
+        assert!(!region_in_bounds(&region(33, 0, 32, 32), [64, 64]));
+        assert!(!region_in_bounds(&region(0, 1, 1, 64), [64, 64]));
+        assert!(!region_in_bounds(&region(std::u32::MAX, 0, 2, 1), [64, 64]));
+        assert!(!region_in_bounds(&region(0, 1, 1, std::u32::MAX), [64, 64]));
+    }
+}
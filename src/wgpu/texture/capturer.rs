@@ -42,6 +42,8 @@ struct ConverterDataPair {
     src_descriptor: wgpu::TextureDescriptor,
     reshaper: wgpu::TextureReshaper,
     dst_texture: wgpu::Texture,
+    // The tonemapping applied by the reshaper, if any.
+    tonemapping: Option<wgpu::Tonemapping>,
 }
 
 /// An alias for the image buffer that can be read from a captured **Snapshot**.
@@ -84,22 +86,62 @@ impl Capturer {
         encoder: &mut wgpu::CommandEncoder,
         src_texture: &wgpu::Texture,
     ) -> Snapshot {
-        let buffer_image = if src_texture.format() != Self::DST_FORMAT {
+        self.capture_inner(device, encoder, src_texture, None)
+    }
+
+    /// Capture the given texture, applying the given tonemapping while converting it to the
+    /// capturer's format.
+    ///
+    /// This produces the same colors as those written to the swap chain by a `Frame` using the
+    /// same tonemapping, e.g. for high dynamic range textures. The texture must not be
+    /// multisampled.
+    pub fn capture_tonemapped(
+        &self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        src_texture: &wgpu::Texture,
+        tonemapping: wgpu::Tonemapping,
+    ) -> Snapshot {
+        self.capture_inner(device, encoder, src_texture, Some(tonemapping))
+    }
+
+    fn capture_inner(
+        &self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        src_texture: &wgpu::Texture,
+        tonemapping: Option<wgpu::Tonemapping>,
+    ) -> Snapshot {
+        let buffer_image = if src_texture.format() != Self::DST_FORMAT || tonemapping.is_some() {
             let mut converter_data_pair = self
                 .converter_data_pair
                 .lock()
                 .expect("failed to lock converter");
 
             // Create converter and target texture if they don't exist.
-            let converter_data_pair = converter_data_pair
-                .get_or_insert_with(|| create_converter_data_pair(device, src_texture));
+            let converter_data_pair = converter_data_pair.get_or_insert_with(|| {
+                create_converter_data_pair(device, src_texture, tonemapping)
+            });
 
-            // If the texture has changed in some way, recreate the converter.
+            // If the texture or the use of tonemapping has changed, recreate the converter.
             if !wgpu::texture_descriptor_eq(
                 src_texture.descriptor(),
                 &converter_data_pair.src_descriptor,
-            ) {
-                *converter_data_pair = create_converter_data_pair(device, src_texture);
+            ) || converter_data_pair.tonemapping.is_some() != tonemapping.is_some()
+            {
+                *converter_data_pair = create_converter_data_pair(device, src_texture, tonemapping);
+            }
+
+            // If only the tonemapping parameters have changed, update them.
+            if let Some(tonemapping) = tonemapping {
+                if converter_data_pair.tonemapping != Some(tonemapping) {
+                    converter_data_pair.reshaper.encode_update_tonemapping(
+                        device,
+                        encoder,
+                        tonemapping,
+                    );
+                    converter_data_pair.tonemapping = Some(tonemapping);
+                }
             }
 
             // Encode the texture format conversion.
@@ -243,6 +285,7 @@ impl<'a> AsRef<[u8]> for Rgba8AsyncMapping<'a> {
 fn create_converter_data_pair(
     device: &wgpu::Device,
     src_texture: &wgpu::Texture,
+    tonemapping: Option<wgpu::Tonemapping>,
 ) -> ConverterDataPair {
     // Create the destination format texture.
     let dst_texture = wgpu::TextureBuilder::from(src_texture.descriptor_cloned())
@@ -256,13 +299,22 @@ fn create_converter_data_pair(
     let src_view = src_texture.create_default_view();
     let dst_sample_count = 1;
     let dst_format = dst_texture.format();
-    let reshaper = wgpu::TextureReshaper::new(
-        device,
-        &src_view,
-        src_sample_count,
-        dst_sample_count,
-        dst_format,
-    );
+    let reshaper = match tonemapping {
+        None => wgpu::TextureReshaper::new(
+            device,
+            &src_view,
+            src_sample_count,
+            dst_sample_count,
+            dst_format,
+        ),
+        Some(tonemapping) => wgpu::TextureReshaper::with_tonemapping(
+            device,
+            &src_view,
+            dst_sample_count,
+            dst_format,
+            tonemapping,
+        ),
+    };
 
     // Keep track of the `src_descriptor` to check if we need to recreate the converter.
     let src_descriptor = src_texture.descriptor_cloned();
@@ -271,5 +323,6 @@ fn create_converter_data_pair(
         src_descriptor,
         reshaper,
         dst_texture,
+        tonemapping,
     }
 }
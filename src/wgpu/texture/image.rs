@@ -46,6 +46,77 @@ impl wgpu::TextureBuilder {
     {
         builder_from_image_view(image_view)
     }
+
+    /// Build the texture and load the given image buffer into its first mip level using the given
+    /// device queue.
+    ///
+    /// See `encode_load_from_image_buffer` for details.
+    pub fn load_from_image_buffer<P, Container>(
+        self,
+        device: &wgpu::Device,
+        queue: &mut wgpu::Queue,
+        buffer: &image::ImageBuffer<P, Container>,
+    ) -> wgpu::Texture
+    where
+        P: 'static + Pixel,
+        Container: std::ops::Deref<Target = [P::Subpixel]>,
+    {
+        let cmd_encoder_desc = wgpu::CommandEncoderDescriptor::default();
+        let mut encoder = device.create_command_encoder(&cmd_encoder_desc);
+        let texture = self.encode_load_from_image_buffer(device, &mut encoder, buffer);
+        queue.submit(&[encoder.finish()]);
+        texture
+    }
+
+    /// Build the texture and encode the necessary commands to load the given image buffer into
+    /// its first mip level.
+    ///
+    /// The builder must describe a texture of the same size and format as the image, e.g. as
+    /// produced by `from_image_view`. The `COPY_DST` usage is added automatically. If the builder
+    /// specifies `mipmaps`, the remaining levels are generated from the image.
+    ///
+    /// ```ignore
+    /// let texture = wgpu::TextureBuilder::from_image_view(&image)
+    ///     .usage(wgpu::TextureUsage::SAMPLED)
+    ///     .mipmaps()
+    ///     .load_from_image_buffer(device, queue, &image);
+    /// ```
+    ///
+    /// NOTE: The returned texture will remain empty until the given `encoder` has its command
+    /// buffer submitted to the given `device`'s queue.
+    pub fn encode_load_from_image_buffer<P, Container>(
+        self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        buffer: &image::ImageBuffer<P, Container>,
+    ) -> wgpu::Texture
+    where
+        P: 'static + Pixel,
+        Container: std::ops::Deref<Target = [P::Subpixel]>,
+    {
+        // Create the texture.
+        let usage = self.descriptor.usage;
+        let texture = self
+            .usage(wgpu::TextureUsage::COPY_DST | usage)
+            .build(device);
+
+        // Upload the pixel data.
+        let subpixel_data: &[P::Subpixel] = std::ops::Deref::deref(buffer);
+        let buffer = device
+            .create_buffer_mapped(subpixel_data.len(), wgpu::BufferUsage::COPY_SRC)
+            .fill_from_slice(subpixel_data);
+
+        // Submit command for copying pixel data to the texture.
+        let buffer_copy_view = texture.create_default_buffer_copy_view(&buffer);
+        let texture_copy_view = texture.create_default_copy_view();
+        let extent = texture.extent();
+        encoder.copy_buffer_to_texture(buffer_copy_view, texture_copy_view, extent);
+
+        // Produce the remaining mip levels from the first.
+        texture.encode_generate_mipmaps(device, encoder);
+
+        texture
+    }
 }
 
 impl wgpu::Texture {
@@ -412,24 +483,9 @@ where
     P: 'static + Pixel,
     Container: std::ops::Deref<Target = [P::Subpixel]>,
 {
-    // Create the texture.
-    let texture = wgpu::TextureBuilder::from_image_view(buffer)
-        .usage(wgpu::TextureUsage::COPY_DST | usage)
-        .build(device);
-
-    // Upload the pixel data.
-    let subpixel_data: &[P::Subpixel] = std::ops::Deref::deref(buffer);
-    let buffer = device
-        .create_buffer_mapped(subpixel_data.len(), wgpu::BufferUsage::COPY_SRC)
-        .fill_from_slice(subpixel_data);
-
-    // Submit command for copying pixel data to the texture.
-    let buffer_copy_view = texture.create_default_buffer_copy_view(&buffer);
-    let texture_copy_view = texture.create_default_copy_view();
-    let extent = texture.extent();
-    encoder.copy_buffer_to_texture(buffer_copy_view, texture_copy_view, extent);
-
-    texture
+    wgpu::TextureBuilder::from_image_view(buffer)
+        .usage(usage)
+        .encode_load_from_image_buffer(device, encoder, buffer)
 }
 
 /// Load a texture array directly from a sequence of image buffers.
@@ -1,8 +1,10 @@
 use crate::wgpu::{self, TextureHandle};
 use std::ops::Deref;
 
+pub mod atlas;
 pub mod capturer;
 pub mod image;
+pub mod pixel_buffer;
 pub mod reshaper;
 
 /// A convenient wrapper around a handle to a texture on the GPU along with its descriptor.
@@ -25,6 +27,8 @@ pub struct Texture {
 #[derive(Debug)]
 pub struct Builder {
     descriptor: wgpu::TextureDescriptor,
+    // Whether or not the usages required to generate the mipmaps should be added upon `build`.
+    mipmaps: bool,
 }
 
 /// A wrapper around a `wgpu::Buffer` containing bytes of a known length.
@@ -231,6 +235,57 @@ impl Texture {
         }
     }
 
+    /// A view of the single mip level at the given index.
+    pub fn create_mip_level_view(&self, level: u32) -> wgpu::TextureView {
+        let desc = wgpu::TextureViewDescriptor {
+            base_mip_level: level,
+            level_count: 1,
+            ..self.create_default_view_descriptor()
+        };
+        self.texture.create_view(&desc)
+    }
+
+    /// Encode the commands necessary to generate the texture's mipmaps from its first mip level.
+    ///
+    /// Each level is produced by rendering the previous level at half the size with linear
+    /// filtering. As a result, the texture must have been created with the `OUTPUT_ATTACHMENT`
+    /// and `SAMPLED` usages, which the **TextureBuilder** adds when `mipmaps` is specified, and a
+    /// format supported by `wgpu::texture_format_supports_mipmap_generation`.
+    ///
+    /// Does nothing if the texture only has a single mip level, or if its format or usage do not
+    /// support mipmap generation.
+    pub fn encode_generate_mipmaps(
+        &self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+    ) {
+        let required = wgpu::TextureUsage::OUTPUT_ATTACHMENT | wgpu::TextureUsage::SAMPLED;
+        if self.mip_level_count() <= 1
+            || !self.usage().contains(required)
+            || !format_supports_mipmap_generation(self.format())
+        {
+            return;
+        }
+        // A single pipeline is shared by all levels, as they only differ by their source view.
+        let mut src_view = self.create_mip_level_view(0);
+        let reshaper = wgpu::TextureReshaper::new(device, &src_view, 1, 1, self.format());
+        for level in 1..self.mip_level_count() {
+            let dst_view = self.create_mip_level_view(level);
+            reshaper.encode_render_pass_from(device, &src_view, &dst_view, encoder);
+            src_view = dst_view;
+        }
+    }
+
+    /// Generate the texture's mipmaps from its first mip level.
+    ///
+    /// This is a short-hand for `encode_generate_mipmaps` followed by submitting the commands.
+    pub fn generate_mipmaps(&self, device: &wgpu::Device, queue: &mut wgpu::Queue) {
+        let ce_desc = wgpu::CommandEncoderDescriptor::default();
+        let mut encoder = device.create_command_encoder(&ce_desc);
+        self.encode_generate_mipmaps(device, &mut encoder);
+        queue.submit(&[encoder.finish()]);
+    }
+
     /// Creates a `TextureCopyView` ready for copying to or from the entire texture.
     pub fn create_default_copy_view(&self) -> wgpu::TextureCopyView {
         wgpu::TextureCopyView {
@@ -326,11 +381,30 @@ impl Builder {
         self
     }
 
+    /// Specify the number of mip levels.
+    ///
+    /// No usages are added for generating the mipmaps. Use `mipmaps` for a texture whose mipmaps
+    /// should be generated via `Texture::encode_generate_mipmaps`.
     pub fn mip_level_count(mut self, count: u32) -> Self {
         self.descriptor.mip_level_count = count;
         self
     }
 
+    /// Specify the full chain of mip levels for the current size, down to a single pixel.
+    ///
+    /// If the format supports mipmap generation, the `OUTPUT_ATTACHMENT` and `SAMPLED` usages are
+    /// added upon `build` so that the mipmaps may be generated via
+    /// `Texture::encode_generate_mipmaps`. Textures loaded from images generate their mipmaps
+    /// automatically.
+    ///
+    /// As the count depends on the size, this should be called after the size is specified.
+    pub fn mipmaps(mut self) -> Self {
+        let count =
+            mip_level_count_for_size([self.descriptor.size.width, self.descriptor.size.height]);
+        self.mipmaps = true;
+        self.mip_level_count(count)
+    }
+
     /// Specify the number of samples per pixel in the case that the texture is multisampled.
     pub fn sample_count(mut self, count: u32) -> Self {
         self.descriptor.sample_count = count;
@@ -364,7 +438,14 @@ impl Builder {
     }
 
     /// Build the texture resulting from the specified parameters with the given device.
-    pub fn build(self, device: &wgpu::Device) -> Texture {
+    pub fn build(mut self, device: &wgpu::Device) -> Texture {
+        if self.mipmaps
+            && self.descriptor.mip_level_count > 1
+            && format_supports_mipmap_generation(self.descriptor.format)
+        {
+            self.descriptor.usage |=
+                wgpu::TextureUsage::OUTPUT_ATTACHMENT | wgpu::TextureUsage::SAMPLED;
+        }
         let texture = device.create_texture(&self.descriptor);
         let descriptor = self.into();
         Texture {
@@ -425,13 +506,17 @@ impl Default for Builder {
     fn default() -> Self {
         Self {
             descriptor: Self::DEFAULT_DESCRIPTOR,
+            mipmaps: false,
         }
     }
 }
 
 impl From<wgpu::TextureDescriptor> for Builder {
     fn from(descriptor: wgpu::TextureDescriptor) -> Self {
-        Self { descriptor }
+        Self {
+            descriptor,
+            mipmaps: false,
+        }
     }
 }
 
@@ -457,6 +542,25 @@ pub fn format_size_bytes(format: wgpu::TextureFormat) -> u32 {
     }
 }
 
+/// Whether or not mipmaps may be generated for textures of the given format.
+///
+/// Generation renders each level from the previous one with linear filtering, so the format must
+/// be a color format that can be both rendered to and filtered. Depth, integer and signed
+/// normalized formats are not supported.
+pub fn format_supports_mipmap_generation(format: wgpu::TextureFormat) -> bool {
+    use crate::wgpu::TextureFormat::*;
+    match format {
+        R8Unorm | Rg8Unorm | Rgba8Unorm | Rgba8UnormSrgb | Bgra8Unorm | Bgra8UnormSrgb
+        | Rgb10a2Unorm | R16Float | Rg16Float | Rgba16Float => true,
+        _ => false,
+    }
+}
+
+/// The number of mip levels required to reduce a texture of the given size to a single pixel.
+pub fn mip_level_count_for_size([width, height]: [u32; 2]) -> u32 {
+    32 - std::cmp::max(width, height).max(1).leading_zeros()
+}
+
 /// Returns `true` if the given `wgpu::Extent3d`s are equal.
 pub fn extent_3d_eq(a: &wgpu::Extent3d, b: &wgpu::Extent3d) -> bool {
     a.width == b.width && a.height == b.height && a.depth == b.depth
@@ -0,0 +1,255 @@
+//! A CPU-side copy of a texture's pixels that may be read and modified before being written back.
+//!
+//! A **PixelBuffer** is useful for sketches that operate on individual pixels, e.g. pixel sorting,
+//! dithering or cellular automata. Pixels are loaded from any texture (including a **Frame**'s)
+//! via `load_pixels`, modified via `get` and `set`, and written back via `update_pixels`. Only the
+//! rows modified since the last update are uploaded.
+
+use crate::wgpu;
+use image::{DynamicImage, RgbaImage};
+use std::ops::Range;
+use std::sync::{Arc, Mutex};
+
+/// A non-linear sRGBA-8 image stored on the CPU along with a texture to which it is uploaded.
+#[derive(Debug)]
+pub struct PixelBuffer {
+    image: RgbaImage,
+    texture: wgpu::Texture,
+    texture_view: wgpu::TextureView,
+    // The range of rows that have been modified since the last upload.
+    dirty_rows: Option<Range<u32>>,
+    capturer: wgpu::TextureCapturer,
+    // Used to write the texture to textures of other formats, along with the destination format
+    // and sample count for which it was created.
+    reshaper: Option<(wgpu::TextureFormat, u32, wgpu::TextureReshaper)>,
+}
+
+impl PixelBuffer {
+    /// The format of the pixel buffer's texture.
+    pub const TEXTURE_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8UnormSrgb;
+
+    /// Create a new pixel buffer of the given size in pixels with all pixels transparent black.
+    pub fn new(device: &wgpu::Device, [width, height]: [u32; 2]) -> Self {
+        Self::from_image_buffer(device, RgbaImage::new(width, height))
+    }
+
+    /// Create a new pixel buffer with a copy of the given image.
+    pub fn from_image(device: &wgpu::Device, image: &DynamicImage) -> Self {
+        Self::from_image_buffer(device, image.to_rgba())
+    }
+
+    /// Create a new pixel buffer from the given non-linear sRGBA image.
+    ///
+    /// The image is uploaded to the texture upon the first call to `update_pixels`.
+    pub fn from_image_buffer(device: &wgpu::Device, image: RgbaImage) -> Self {
+        let (width, height) = image.dimensions();
+        let texture = create_texture(device, [width, height]);
+        let texture_view = texture.create_default_view();
+        PixelBuffer {
+            image,
+            texture,
+            texture_view,
+            dirty_rows: Some(0..height),
+            capturer: Default::default(),
+            reshaper: None,
+        }
+    }
+
+    /// The size of the buffer in pixels.
+    pub fn size(&self) -> [u32; 2] {
+        let (w, h) = self.image.dimensions();
+        [w, h]
+    }
+
+    /// The texture to which the pixels are written upon `update_pixels`.
+    pub fn texture(&self) -> &wgpu::Texture {
+        &self.texture
+    }
+
+    /// A view of the texture returned by `texture`.
+    pub fn texture_view(&self) -> &wgpu::TextureView {
+        &self.texture_view
+    }
+
+    /// The CPU-side image.
+    pub fn image(&self) -> &RgbaImage {
+        &self.image
+    }
+
+    /// Mutable access to the CPU-side image.
+    ///
+    /// As the buffer can't know which pixels are modified, the whole image is marked as dirty.
+    pub fn image_mut(&mut self) -> &mut RgbaImage {
+        self.mark_dirty(0..self.image.height());
+        &mut self.image
+    }
+
+    /// The pixel at the given coordinates, relative to the top left of the image.
+    ///
+    /// Panics if the coordinates are out of bounds.
+    pub fn get(&self, x: u32, y: u32) -> image::Rgba<u8> {
+        *self.image.get_pixel(x, y)
+    }
+
+    /// Set the pixel at the given coordinates, relative to the top left of the image.
+    ///
+    /// Panics if the coordinates are out of bounds.
+    pub fn set<P>(&mut self, x: u32, y: u32, rgba: P)
+    where
+        P: Into<image::Rgba<u8>>,
+    {
+        self.image.put_pixel(x, y, rgba.into());
+        self.mark_dirty(y..y + 1);
+    }
+
+    /// The range of rows modified since the pixels were last written to the texture, if any.
+    pub fn dirty_rows(&self) -> Option<Range<u32>> {
+        self.dirty_rows.clone()
+    }
+
+    /// Mark the given range of rows as modified so that they are uploaded upon the next update.
+    pub fn mark_dirty(&mut self, rows: Range<u32>) {
+        let rows = match self.dirty_rows.take() {
+            None => rows,
+            Some(dirty) => dirty.start.min(rows.start)..dirty.end.max(rows.end),
+        };
+        self.dirty_rows = Some(rows);
+    }
+
+    /// Encode the commands necessary to write all modified rows to the texture.
+    ///
+    /// Does nothing if no pixels have been modified since the last update.
+    pub fn encode_update_pixels(
+        &mut self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+    ) {
+        let rows = match self.dirty_rows.take() {
+            Some(ref rows) if rows.start < rows.end => rows.clone(),
+            _ => return,
+        };
+        let width = self.image.width();
+        let row_len = width as usize * 4;
+        let start = rows.start as usize * row_len;
+        let end = rows.end as usize * row_len;
+        let data = &self.image[start..end];
+        let buffer = device
+            .create_buffer_mapped(data.len(), wgpu::BufferUsage::COPY_SRC)
+            .fill_from_slice(data);
+        let height = rows.end - rows.start;
+        let buffer_copy_view = wgpu::BufferCopyView {
+            buffer: &buffer,
+            offset: 0,
+            row_pitch: row_len as u32,
+            image_height: height,
+        };
+        let texture_copy_view = wgpu::TextureCopyView {
+            origin: wgpu::Origin3d {
+                x: 0.0,
+                y: rows.start as f32,
+                z: 0.0,
+            },
+            ..self.texture.create_default_copy_view()
+        };
+        let extent = wgpu::Extent3d {
+            width,
+            height,
+            depth: 1,
+        };
+        encoder.copy_buffer_to_texture(buffer_copy_view, texture_copy_view, extent);
+    }
+
+    /// Write all modified rows to the texture.
+    ///
+    /// This is a short-hand for `encode_update_pixels` followed by submitting the commands.
+    pub fn update_pixels(&mut self, device: &wgpu::Device, queue: &mut wgpu::Queue) {
+        let ce_desc = wgpu::CommandEncoderDescriptor::default();
+        let mut encoder = device.create_command_encoder(&ce_desc);
+        self.encode_update_pixels(device, &mut encoder);
+        queue.submit(&[encoder.finish()]);
+    }
+
+    /// Read the contents of the given texture into the CPU-side image, blocking until complete.
+    ///
+    /// The texture may be of any format or sample count, in which case it is first converted to
+    /// non-linear sRGBA-8. If the size of the texture differs, the buffer is resized to match.
+    ///
+    /// Any commands previously encoded to other encoders must already be submitted for their
+    /// results to be visible. `Frame::load_pixels` takes care of this for a **Frame**.
+    pub fn load_pixels(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &mut wgpu::Queue,
+        texture: &wgpu::Texture,
+    ) {
+        let ce_desc = wgpu::CommandEncoderDescriptor::default();
+        let mut encoder = device.create_command_encoder(&ce_desc);
+        let snapshot = self.capturer.capture(device, &mut encoder, texture);
+        queue.submit(&[encoder.finish()]);
+
+        let loaded = Arc::new(Mutex::new(None));
+        let loaded2 = loaded.clone();
+        snapshot.read(move |result| {
+            let image = result.expect("failed to map texture memory").to_owned();
+            *loaded2.lock().unwrap() = Some(image);
+        });
+        device.poll(true);
+        let image = loaded
+            .lock()
+            .unwrap()
+            .take()
+            .expect("texture memory was not mapped after polling the device");
+
+        let (width, height) = image.dimensions();
+        if self.size() != [width, height] {
+            self.texture = create_texture(device, [width, height]);
+            self.texture_view = self.texture.create_default_view();
+            self.reshaper = None;
+        }
+        self.image = image;
+        self.dirty_rows = Some(0..height);
+    }
+
+    /// Encode a render pass that writes the buffer's texture to the given destination texture,
+    /// converting to the destination format and sample count and stretching to fit if necessary.
+    ///
+    /// Note that modified pixels must first be written to the texture via `encode_update_pixels`.
+    pub fn encode_render_pass(
+        &mut self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        dst_view: &wgpu::TextureView,
+        dst_format: wgpu::TextureFormat,
+        dst_sample_count: u32,
+    ) {
+        match self.reshaper {
+            Some((format, count, _)) if format == dst_format && count == dst_sample_count => (),
+            _ => {
+                let src_sample_count = 1;
+                let reshaper = wgpu::TextureReshaper::new(
+                    device,
+                    &self.texture_view,
+                    src_sample_count,
+                    dst_sample_count,
+                    dst_format,
+                );
+                self.reshaper = Some((dst_format, dst_sample_count, reshaper));
+            }
+        }
+        if let Some((_, _, ref reshaper)) = self.reshaper {
+            reshaper.encode_render_pass(dst_view, encoder);
+        }
+    }
+}
+
+fn create_texture(device: &wgpu::Device, size: [u32; 2]) -> wgpu::Texture {
+    wgpu::TextureBuilder::new()
+        .size(size)
+        .format(PixelBuffer::TEXTURE_FORMAT)
+        .usage(
+            wgpu::TextureUsage::COPY_DST
+                | wgpu::TextureUsage::COPY_SRC
+                | wgpu::TextureUsage::SAMPLED,
+        )
+        .build(device)
+}
@@ -6,6 +6,9 @@ use crate::wgpu;
 /// The `src_texture` must have the `TextureUsage::SAMPLED` enabled.
 ///
 /// The `dst_texture` must have the `TextureUsage::OUTPUT_ATTACHMENT` enabled.
+///
+/// A reshaper created via `with_tonemapping` maps the colors of the source texture (which may
+/// exceed `1.0` for floating point formats) to the displayable range while writing them.
 #[derive(Debug)]
 pub struct Reshaper {
     _vs_mod: wgpu::ShaderModule,
@@ -16,6 +19,8 @@ pub struct Reshaper {
     sampler: wgpu::Sampler,
     uniform_buffer: Option<wgpu::Buffer>,
     vertex_buffer: wgpu::Buffer,
+    // Whether or not the uniform buffer contains tonemapping parameters.
+    tonemapping: bool,
 }
 
 #[repr(C)]
@@ -24,12 +29,76 @@ struct Vertex {
     pub position: [f32; 2],
 }
 
+/// Describes how the high dynamic range colors of a texture are mapped to the displayable range.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Tonemapping {
+    /// The curve used to compress colors into the `[0.0, 1.0]` range.
+    pub operator: TonemapOperator,
+    /// Colors are multiplied by the exposure before the operator is applied.
+    pub exposure: f32,
+}
+
+/// The curve used to compress high dynamic range colors into the displayable range.
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+pub enum TonemapOperator {
+    /// Colors are clamped to the `[0.0, 1.0]` range.
+    None,
+    /// The simple Reinhard operator, `c / (c + 1)`. Never fully saturates.
+    Reinhard,
+    /// An approximation of the ACES filmic curve, providing a punchier, more contrasted result.
+    Aces,
+}
+
 #[repr(C)]
 #[derive(Copy, Clone)]
 struct Uniforms {
     sample_count: u32,
 }
 
+#[repr(C)]
+#[derive(Copy, Clone)]
+struct TonemapUniforms {
+    operator: u32,
+    exposure: f32,
+}
+
+impl Tonemapping {
+    /// The default exposure, leaving colors unchanged.
+    pub const DEFAULT_EXPOSURE: f32 = 1.0;
+
+    /// Map colors using the given operator at the default exposure.
+    pub fn new(operator: TonemapOperator) -> Self {
+        Tonemapping {
+            operator,
+            exposure: Self::DEFAULT_EXPOSURE,
+        }
+    }
+
+    /// Specify the exposure by which colors are multiplied before the operator is applied.
+    pub fn exposure(mut self, exposure: f32) -> Self {
+        self.exposure = exposure;
+        self
+    }
+
+    fn uniforms(&self) -> TonemapUniforms {
+        let operator = match self.operator {
+            TonemapOperator::None => 0,
+            TonemapOperator::Reinhard => 1,
+            TonemapOperator::Aces => 2,
+        };
+        TonemapUniforms {
+            operator,
+            exposure: self.exposure,
+        }
+    }
+}
+
+impl Default for Tonemapping {
+    fn default() -> Self {
+        Self::new(TonemapOperator::None)
+    }
+}
+
 impl Reshaper {
     /// Construct a new `Reshaper`.
     pub fn new(
@@ -38,6 +107,47 @@ impl Reshaper {
         src_sample_count: u32,
         dst_sample_count: u32,
         dst_format: wgpu::TextureFormat,
+    ) -> Self {
+        Self::new_inner(
+            device,
+            src_texture,
+            src_sample_count,
+            dst_sample_count,
+            dst_format,
+            None,
+        )
+    }
+
+    /// Construct a new `Reshaper` that applies the given tonemapping while writing to the
+    /// destination texture.
+    ///
+    /// The tonemapping may be changed later via `encode_update_tonemapping`. The source texture
+    /// must not be multisampled, i.e. it must be resolved first.
+    pub fn with_tonemapping(
+        device: &wgpu::Device,
+        src_texture: &wgpu::TextureView,
+        dst_sample_count: u32,
+        dst_format: wgpu::TextureFormat,
+        tonemapping: Tonemapping,
+    ) -> Self {
+        let src_sample_count = 1;
+        Self::new_inner(
+            device,
+            src_texture,
+            src_sample_count,
+            dst_sample_count,
+            dst_format,
+            Some(tonemapping),
+        )
+    }
+
+    fn new_inner(
+        device: &wgpu::Device,
+        src_texture: &wgpu::TextureView,
+        src_sample_count: u32,
+        dst_sample_count: u32,
+        dst_format: wgpu::TextureFormat,
+        tonemapping: Option<Tonemapping>,
     ) -> Self {
         // Load shader modules.
         let vs = include_bytes!("shaders/vert.spv");
@@ -45,6 +155,7 @@ impl Reshaper {
             .expect("failed to read hard-coded SPIRV");
         let vs_mod = device.create_shader_module(&vs_spirv);
         let fs = match src_sample_count {
+            1 if tonemapping.is_some() => &include_bytes!("shaders/frag_tonemap.spv")[..],
             1 => &include_bytes!("shaders/frag.spv")[..],
             2 => &include_bytes!("shaders/frag_msaa2.spv")[..],
             4 => &include_bytes!("shaders/frag_msaa4.spv")[..],
@@ -60,7 +171,8 @@ impl Reshaper {
         let sampler = wgpu::SamplerBuilder::new().build(device);
 
         // Create the render pipeline.
-        let bind_group_layout = bind_group_layout(device, src_sample_count);
+        let has_uniforms = tonemapping.is_some() || !unrolled_sample_count(src_sample_count);
+        let bind_group_layout = bind_group_layout(device, src_sample_count, has_uniforms);
         let pipeline_layout = pipeline_layout(device, &bind_group_layout);
         let render_pipeline = render_pipeline(
             device,
@@ -71,11 +183,18 @@ impl Reshaper {
             dst_format,
         );
 
-        // Create the uniform buffer to pass the sample count if we don't have an unrolled resolve
-        // fragment shader for it.
-        let uniform_buffer = match unrolled_sample_count(src_sample_count) {
-            true => None,
-            false => {
+        // Create the uniform buffer to pass the tonemapping parameters, or the sample count if we
+        // don't have an unrolled resolve fragment shader for it.
+        let uniform_buffer = match tonemapping {
+            Some(tonemapping) => {
+                let usage = wgpu::BufferUsage::UNIFORM | wgpu::BufferUsage::COPY_DST;
+                let buffer = device
+                    .create_buffer_mapped(1, usage)
+                    .fill_from_slice(&[tonemapping.uniforms()]);
+                Some(buffer)
+            }
+            None if unrolled_sample_count(src_sample_count) => None,
+            None => {
                 let uniforms = Uniforms {
                     sample_count: src_sample_count,
                 };
@@ -87,12 +206,17 @@ impl Reshaper {
         };
 
         // Create the bind group.
+        let uniforms_size_bytes = match tonemapping {
+            Some(_) => std::mem::size_of::<TonemapUniforms>(),
+            None => std::mem::size_of::<Uniforms>(),
+        } as wgpu::BufferAddress;
         let bind_group = bind_group(
             device,
             &bind_group_layout,
             src_texture,
             &sampler,
             uniform_buffer.as_ref(),
+            uniforms_size_bytes,
         );
 
         // Create the vertex buffer.
@@ -109,6 +233,28 @@ impl Reshaper {
             sampler,
             uniform_buffer,
             vertex_buffer,
+            tonemapping: tonemapping.is_some(),
+        }
+    }
+
+    /// Encode the commands necessary to change the tonemapping applied by the reshaper.
+    ///
+    /// Does nothing if the reshaper was not created via `with_tonemapping`.
+    pub fn encode_update_tonemapping(
+        &self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        tonemapping: Tonemapping,
+    ) {
+        if !self.tonemapping {
+            return;
+        }
+        if let Some(ref uniform_buffer) = self.uniform_buffer {
+            let size = std::mem::size_of::<TonemapUniforms>() as wgpu::BufferAddress;
+            let buffer = device
+                .create_buffer_mapped(1, wgpu::BufferUsage::COPY_SRC)
+                .fill_from_slice(&[tonemapping.uniforms()]);
+            encoder.copy_buffer_to_buffer(&buffer, 0, uniform_buffer, 0, size);
         }
     }
 
@@ -118,6 +264,41 @@ impl Reshaper {
         &self,
         dst_texture: &wgpu::TextureView,
         encoder: &mut wgpu::CommandEncoder,
+    ) {
+        self.encode_render_pass_inner(&self.bind_group, dst_texture, encoder);
+    }
+
+    // Submits a render pass writing the given source texture to the destination texture, reusing
+    // the reshaper's pipeline rather than the source texture it was created with.
+    //
+    // The source texture must have the same sample count as the original.
+    pub(crate) fn encode_render_pass_from(
+        &self,
+        device: &wgpu::Device,
+        src_texture: &wgpu::TextureView,
+        dst_texture: &wgpu::TextureView,
+        encoder: &mut wgpu::CommandEncoder,
+    ) {
+        let uniforms_size_bytes = match self.tonemapping {
+            true => std::mem::size_of::<TonemapUniforms>(),
+            false => std::mem::size_of::<Uniforms>(),
+        } as wgpu::BufferAddress;
+        let bind_group = bind_group(
+            device,
+            &self.bind_group_layout,
+            src_texture,
+            &self.sampler,
+            self.uniform_buffer.as_ref(),
+            uniforms_size_bytes,
+        );
+        self.encode_render_pass_inner(&bind_group, dst_texture, encoder);
+    }
+
+    fn encode_render_pass_inner(
+        &self,
+        bind_group: &wgpu::BindGroup,
+        dst_texture: &wgpu::TextureView,
+        encoder: &mut wgpu::CommandEncoder,
     ) {
         let vertex_range = 0..VERTICES.len() as u32;
         let instance_range = 0..1;
@@ -135,7 +316,7 @@ impl Reshaper {
         let mut render_pass = encoder.begin_render_pass(&render_pass_desc);
         render_pass.set_pipeline(&self.render_pipeline);
         render_pass.set_vertex_buffers(0, &[(&self.vertex_buffer, 0)]);
-        render_pass.set_bind_group(0, &self.bind_group, &[]);
+        render_pass.set_bind_group(0, bind_group, &[]);
         render_pass.draw(vertex_range, instance_range);
     }
 }
@@ -171,7 +352,11 @@ fn vertex_attrs() -> [wgpu::VertexAttributeDescriptor; 1] {
     }]
 }
 
-fn bind_group_layout(device: &wgpu::Device, src_sample_count: u32) -> wgpu::BindGroupLayout {
+fn bind_group_layout(
+    device: &wgpu::Device,
+    src_sample_count: u32,
+    has_uniforms: bool,
+) -> wgpu::BindGroupLayout {
     let texture_binding = wgpu::BindGroupLayoutBinding {
         binding: 0,
         visibility: wgpu::ShaderStage::FRAGMENT,
@@ -185,9 +370,9 @@ fn bind_group_layout(device: &wgpu::Device, src_sample_count: u32) -> wgpu::Bind
         visibility: wgpu::ShaderStage::FRAGMENT,
         ty: wgpu::BindingType::Sampler,
     };
-    let uniforms_binding = match unrolled_sample_count(src_sample_count) {
-        true => None,
-        false => Some(wgpu::BindGroupLayoutBinding {
+    let uniforms_binding = match has_uniforms {
+        false => None,
+        true => Some(wgpu::BindGroupLayoutBinding {
             binding: 2,
             visibility: wgpu::ShaderStage::FRAGMENT,
             ty: wgpu::BindingType::UniformBuffer { dynamic: false },
@@ -209,6 +394,7 @@ fn bind_group(
     texture: &wgpu::TextureView,
     sampler: &wgpu::Sampler,
     uniform_buffer: Option<&wgpu::Buffer>,
+    buffer_size_bytes: wgpu::BufferAddress,
 ) -> wgpu::BindGroup {
     let texture_binding = wgpu::Binding {
         binding: 0,
@@ -222,7 +408,7 @@ fn bind_group(
         binding: 2,
         resource: wgpu::BindingResource::Buffer {
             buffer,
-            range: 0..std::mem::size_of::<Uniforms>() as wgpu::BufferAddress,
+            range: 0..buffer_size_bytes,
         },
     });
     let bindings = match uniforms_binding {
@@ -0,0 +1,44 @@
+// NOTE: This shader requires being manually compiled to SPIR-V in order to
+// avoid having downstream users require building shaderc and compiling the
+// shader themselves. If you update this shader, be sure to also re-compile it
+// and update `frag_tonemap.spv`. You can do so using `glslangValidator` with
+// the following command:
+// `glslangValidator -V -o frag_tonemap.spv shader_tonemap.frag`
+
+#version 450
+
+layout(location = 0) in vec2 tex_coords;
+layout(location = 0) out vec4 f_color;
+
+layout(set = 0, binding = 0) uniform texture2D tex;
+layout(set = 0, binding = 1) uniform sampler tex_sampler;
+layout(set = 0, binding = 2) uniform Data {
+    uint operator;
+    float exposure;
+} uniforms;
+
+// Matches the `TonemapOperator` enum.
+const uint OPERATOR_NONE = 0;
+const uint OPERATOR_REINHARD = 1;
+const uint OPERATOR_ACES = 2;
+
+// Krzysztof Narkowicz's fit of the ACES filmic curve.
+vec3 aces(vec3 x) {
+    const float a = 2.51;
+    const float b = 0.03;
+    const float c = 2.43;
+    const float d = 0.59;
+    const float e = 0.14;
+    return clamp((x * (a * x + b)) / (x * (c * x + d) + e), 0.0, 1.0);
+}
+
+void main() {
+    vec4 color = texture(sampler2D(tex, tex_sampler), tex_coords);
+    vec3 rgb = max(color.rgb * uniforms.exposure, vec3(0.0));
+    if (uniforms.operator == OPERATOR_REINHARD) {
+        rgb = rgb / (rgb + vec3(1.0));
+    } else if (uniforms.operator == OPERATOR_ACES) {
+        rgb = aces(rgb);
+    }
+    f_color = vec4(clamp(rgb, 0.0, 1.0), clamp(color.a, 0.0, 1.0));
+}
@@ -1,8 +1,10 @@
 //! The nannou [**Window**](./struct.Window.html) API. Create a new window via `.app.new_window()`.
 //! This produces a [**Builder**](./struct.Builder.html) which can be used to build a window.
 
+use crate::draw;
 use crate::event::{
-    Key, MouseButton, MouseScrollDelta, TouchEvent, TouchPhase, TouchpadPressure, WindowEvent,
+    Gesture, Key, MouseButton, MouseScrollDelta, TouchEvent, TouchPhase, TouchpadPressure,
+    WindowEvent,
 };
 use crate::frame::{self, Frame, RawFrame};
 use crate::geom;
@@ -33,6 +35,12 @@ pub struct Builder<'app> {
     device_desc: Option<wgpu::DeviceDescriptor>,
     user_functions: UserFunctions,
     msaa_samples: Option<u32>,
+    frame_texture_format: wgpu::TextureFormat,
+    frame_intermediary: bool,
+    clear_frames: bool,
+    tonemapping: wgpu::Tonemapping,
+    mirror: Option<Id>,
+    viewport: Option<geom::Rect>,
 }
 
 /// For storing all user functions within the window.
@@ -52,6 +60,7 @@ pub(crate) struct UserFunctions {
     pub(crate) moved: Option<MovedFnAny>,
     pub(crate) resized: Option<ResizedFnAny>,
     pub(crate) touch: Option<TouchFnAny>,
+    pub(crate) gesture: Option<GestureFnAny>,
     pub(crate) touchpad_pressure: Option<TouchpadPressureFnAny>,
     pub(crate) hovered_file: Option<HoveredFileFnAny>,
     pub(crate) hovered_file_cancelled: Option<HoveredFileCancelledFnAny>,
@@ -122,6 +131,9 @@ pub type ResizedFn<Model> = fn(&App, &mut Model, Vector2);
 /// A function for processing touch events.
 pub type TouchFn<Model> = fn(&App, &mut Model, TouchEvent);
 
+/// A function for processing gestures recognised from touch events.
+pub type GestureFn<Model> = fn(&App, &mut Model, Gesture);
+
 /// A function for processing touchpad pressure events.
 pub type TouchpadPressureFn<Model> = fn(&App, &mut Model, TouchpadPressure);
 
@@ -202,6 +214,7 @@ fn_any!(MouseWheelFn<M>, MouseWheelFnAny);
 fn_any!(MovedFn<M>, MovedFnAny);
 fn_any!(ResizedFn<M>, ResizedFnAny);
 fn_any!(TouchFn<M>, TouchFnAny);
+fn_any!(GestureFn<M>, GestureFnAny);
 fn_any!(TouchpadPressureFn<M>, TouchpadPressureFnAny);
 fn_any!(HoveredFileFn<M>, HoveredFileFnAny);
 fn_any!(HoveredFileCancelledFn<M>, HoveredFileCancelledFnAny);
@@ -225,6 +238,8 @@ pub struct Window {
     pub(crate) frame_count: u64,
     pub(crate) user_functions: UserFunctions,
     pub(crate) tracked_state: TrackedState,
+    mirror: Option<Id>,
+    viewport: Option<geom::Rect>,
 }
 
 // Data related to `Frame`s produced for this window's swapchain textures.
@@ -342,6 +357,12 @@ impl<'app> Builder<'app> {
             device_desc: None,
             user_functions: Default::default(),
             msaa_samples: None,
+            frame_texture_format: Frame::TEXTURE_FORMAT,
+            frame_intermediary: true,
+            clear_frames: false,
+            tonemapping: Default::default(),
+            mirror: None,
+            viewport: None,
         }
     }
 
@@ -396,6 +417,106 @@ impl<'app> Builder<'app> {
         self
     }
 
+    /// Whether or not the `Frame`'s intermediary image uses a floating point format, allowing
+    /// colors to exceed `1.0` (e.g. via additive blending).
+    ///
+    /// When enabled, the intermediary image uses the `Frame::HDR_TEXTURE_FORMAT` rather than the
+    /// `Frame::TEXTURE_FORMAT`. Colors are mapped to the displayable range by the window's
+    /// tonemapping when the image is written to the swap chain. By default, this is `false`.
+    ///
+    /// Frames captured via `capture_frame` are written with the same tonemapping, so that captured
+    /// images match those displayed. The `Frame`'s texture itself (e.g. as read via
+    /// `Frame::load_pixels`) contains the colors prior to tonemapping.
+    ///
+    /// **Note:** Like `msaa_samples`, this has no meaning for windows with a **raw_view**.
+    pub fn hdr(self, hdr: bool) -> Self {
+        let format = match hdr {
+            true => Frame::HDR_TEXTURE_FORMAT,
+            false => Frame::TEXTURE_FORMAT,
+        };
+        self.frame_texture_format(format)
+    }
+
+    /// Specify the texture format of the `Frame`'s intermediary image.
+    ///
+    /// By default, this is `Frame::TEXTURE_FORMAT`. A lower bit depth format such as
+    /// `Rgba8UnormSrgb` may be used to reduce memory and bandwidth usage.
+    ///
+    /// **Note:** Like `msaa_samples`, this has no meaning for windows with a **raw_view**.
+    pub fn frame_texture_format(mut self, format: wgpu::TextureFormat) -> Self {
+        self.frame_texture_format = format;
+        self
+    }
+
+    /// Whether or not the `Frame` is drawn to an intermediary image before being written to the
+    /// swap chain image.
+    ///
+    /// By default, this is `true`. When `false`, the `Frame` is drawn directly to the swap chain
+    /// image via a multisampled image of the swap chain's format that is resolved directly to the
+    /// swap chain image. This saves an image allocation and a full screen pass each frame, which
+    /// can be significant on integrated GPUs. However:
+    ///
+    /// - The frame's texture format is that of the swap chain rather than `frame_texture_format`.
+    /// - No tonemapping is applied.
+    /// - The contents of the frame do not persist between frames.
+    /// - If `msaa_samples` is `1`, an image of the swap chain's format is still required in order
+    ///   to support `capture_frame` and `Frame::load_pixels`, so only the format conversion and
+    ///   tonemapping are skipped.
+    /// - If `msaa_samples` is greater than `1`, a single-sampled image of the swap chain's format
+    ///   is allocated for `capture_frame` and `Frame::load_pixels`. The multisampled image is only
+    ///   resolved to it when the frame is captured or read, so the full screen pass is still
+    ///   avoided on all other frames.
+    pub fn frame_intermediary(mut self, intermediary: bool) -> Self {
+        self.frame_intermediary = intermediary;
+        self
+    }
+
+    /// Whether or not the `Frame`'s image is cleared to transparent at the beginning of each
+    /// frame.
+    ///
+    /// By default, this is `false` and the contents of the image persist between frames, allowing
+    /// for graphics to accumulate over time.
+    pub fn clear_frames(mut self, clear: bool) -> Self {
+        self.clear_frames = clear;
+        self
+    }
+
+    /// The tonemapping applied when writing the `Frame`'s intermediary image to the swap chain,
+    /// e.g. `wgpu::Tonemapping::new(wgpu::TonemapOperator::Aces).exposure(1.5)`.
+    ///
+    /// This is most useful in combination with `hdr`. By default, colors are simply clamped. The
+    /// tonemapping may be changed later via `Window::set_tonemapping`.
+    pub fn tonemapping(mut self, tonemapping: wgpu::Tonemapping) -> Self {
+        self.tonemapping = tonemapping;
+        self
+    }
+
+    /// Mirror the output of the window with the given ID.
+    ///
+    /// Rather than calling `view` for this window, the app's **Draw** is rendered to this window
+    /// each time it is drawn to the source window via `app.draw()`. The geometry is tessellated
+    /// once and shared between all mirroring windows. The region of the drawing shown is
+    /// determined by `viewport`, defaulting to that of the source window.
+    ///
+    /// This is useful for spanning a single drawing across multiple displays, e.g. for
+    /// installations and projection mapping, or for showing a preview of a fullscreen output.
+    pub fn mirror(mut self, source: Id) -> Self {
+        self.mirror = Some(source);
+        self
+    }
+
+    /// The region of the **Draw** coordinate space that is mapped to the window's frame, in
+    /// points.
+    ///
+    /// By default, this is the window's `rect`, centred on the origin. Specifying a viewport
+    /// offset from the origin allows for multiple windows to each show a portion of a larger
+    /// drawing, e.g. `geom::Rect::from_x_y_w_h(-960.0, 0.0, 1920.0, 1080.0)` and
+    /// `geom::Rect::from_x_y_w_h(960.0, 0.0, 1920.0, 1080.0)` for two displays side by side.
+    pub fn viewport(mut self, rect: geom::Rect) -> Self {
+        self.viewport = Some(rect);
+        self
+    }
+
     /// Provide a simple function for drawing to the window.
     ///
     /// This is similar to `view` but does not provide access to user data via a Model type. This
@@ -552,6 +673,16 @@ impl<'app> Builder<'app> {
         self
     }
 
+    /// A function for processing gestures recognised from touch events associated with this
+    /// window, e.g. pinch, rotate, pan, tap, double tap and long press.
+    pub fn gesture<M>(mut self, f: GestureFn<M>) -> Self
+    where
+        M: 'static,
+    {
+        self.user_functions.gesture = Some(GestureFnAny::from_fn_ptr(f));
+        self
+    }
+
     /// A function for processing touchpad pressure events associated with this window.
     pub fn touchpad_pressure<M>(mut self, f: TouchpadPressureFn<M>) -> Self
     where
@@ -645,6 +776,12 @@ impl<'app> Builder<'app> {
             device_desc,
             user_functions,
             msaa_samples,
+            frame_texture_format,
+            frame_intermediary,
+            clear_frames,
+            tonemapping,
+            mirror,
+            viewport,
         } = self;
 
         // If the title was not set, default to the "nannou - <exe_name>".
@@ -758,13 +895,24 @@ impl<'app> Builder<'app> {
                 let msaa_samples = msaa_samples.unwrap_or(Frame::DEFAULT_MSAA_SAMPLES);
                 // TODO: Verity that requested sample count is valid for surface?
                 let swap_chain_dims = [swap_chain_desc.width, swap_chain_desc.height];
+                let intermediary_format = match frame_intermediary {
+                    true => Some(frame_texture_format),
+                    false => None,
+                };
                 let render = frame::RenderData::new(
                     &device,
                     swap_chain_dims,
                     swap_chain_desc.format,
                     msaa_samples,
+                    intermediary_format,
+                    tonemapping,
+                    clear_frames,
                 );
-                let capture = frame::CaptureData::default();
+                let seed = match app.capture_metadata.get() {
+                    true => Some(app.seed.clone()),
+                    false => None,
+                };
+                let capture = frame::CaptureData::new(seed);
                 let frame_data = FrameData { render, capture };
                 (Some(frame_data), msaa_samples)
             }
@@ -793,8 +941,11 @@ impl<'app> Builder<'app> {
             frame_count,
             user_functions,
             tracked_state,
+            mirror,
+            viewport,
         };
         app.windows.borrow_mut().insert(window_id, window);
+        app.input_session.borrow_mut().window_created(window_id);
 
         // If this is the first window, set it as the app's "focused" window.
         if app.windows.borrow().len() == 1 {
@@ -817,6 +968,12 @@ impl<'app> Builder<'app> {
             swap_chain_builder,
             user_functions,
             msaa_samples,
+            frame_texture_format,
+            frame_intermediary,
+            clear_frames,
+            tonemapping,
+            mirror,
+            viewport,
         } = self;
         let window = map(window);
         Builder {
@@ -828,6 +985,12 @@ impl<'app> Builder<'app> {
             swap_chain_builder,
             user_functions,
             msaa_samples,
+            frame_texture_format,
+            frame_intermediary,
+            clear_frames,
+            tonemapping,
+            mirror,
+            viewport,
         }
     }
 
@@ -1267,6 +1430,62 @@ impl Window {
         self.msaa_samples
     }
 
+    /// The texture format of the image associated with the `view` function's `Frame` type.
+    ///
+    /// This is the format specified via `frame_texture_format` (`Frame::TEXTURE_FORMAT` by
+    /// default), or the swap chain format if the window was built without a frame intermediary.
+    pub fn frame_texture_format(&self) -> wgpu::TextureFormat {
+        self.frame_data
+            .as_ref()
+            .map(|data| data.render.texture_format())
+            .unwrap_or(Frame::TEXTURE_FORMAT)
+    }
+
+    /// The tonemapping applied when writing the `Frame`'s image to the swap chain.
+    ///
+    /// **Note:** If the user specified a `raw_view` function, no tonemapping is applied.
+    pub fn tonemapping(&self) -> wgpu::Tonemapping {
+        self.frame_data
+            .as_ref()
+            .map(|data| data.render.tonemapping())
+            .unwrap_or_default()
+    }
+
+    /// Change the tonemapping applied when writing the `Frame`'s image to the swap chain, e.g. to
+    /// adjust the exposure over time.
+    ///
+    /// The change is applied upon the next frame's submission.
+    pub fn set_tonemapping(&self, tonemapping: wgpu::Tonemapping) {
+        if let Some(ref data) = self.frame_data {
+            data.render.set_tonemapping(tonemapping);
+        }
+    }
+
+    /// The ID of the window whose output this window mirrors, if any.
+    pub fn mirror(&self) -> Option<Id> {
+        self.mirror
+    }
+
+    /// The region of the **Draw** coordinate space mapped to the window's frame, if one was
+    /// specified.
+    ///
+    /// If `None`, the window's `rect` is used.
+    pub fn viewport(&self) -> Option<geom::Rect> {
+        self.viewport
+    }
+
+    /// Begin building a new offscreen **Layer** on the window's device.
+    ///
+    /// The layer's size, format, MSAA samples and scale factor default to those of the window.
+    pub fn new_layer(&self) -> draw::layer::Builder {
+        let (w, h) = self.inner_size_pixels();
+        draw::layer::Builder::new(self.device_queue_pair.clone())
+            .size(w, h)
+            .format(self.frame_texture_format())
+            .msaa_samples(self.msaa_samples)
+            .scale_factor(self.scale_factor())
+    }
+
     // Custom methods.
 
     // A utility function to simplify the recreation of a swap_chain.
@@ -1279,12 +1498,15 @@ impl Window {
             self.swap_chain_device()
                 .create_swap_chain(&self.surface, &self.swap_chain.descriptor),
         );
-        if self.frame_data.is_some() {
+        if let Some(ref frame_data) = self.frame_data {
             let render_data = frame::RenderData::new(
                 self.swap_chain_device(),
                 size_px,
                 self.swap_chain.descriptor.format,
                 self.msaa_samples,
+                frame_data.render.intermediary_format(),
+                frame_data.render.tonemapping(),
+                frame_data.render.clear(),
             );
             self.frame_data.as_mut().unwrap().render = render_data;
         }
@@ -0,0 +1,93 @@
+use nannou::param::{Fields, Kind, Params, Value};
+
+#[derive(nannou::param::Params)]
+struct Settings {
+    #[param(range = 1.0..=200.0, step = 1.0, label = "Radius")]
+    radius: f32,
+    #[param(skip)]
+    cache: Vec<f32>,
+    speed: f64,
+    visible: bool,
+    shape: Shape,
+}
+
+#[derive(Debug, PartialEq, nannou::param::Params)]
+enum Shape {
+    Circle,
+    #[param(label = "Box")]
+    Square,
+    Triangle,
+}
+
+fn settings() -> Settings {
+    Settings {
+        radius: 10.0,
+        cache: vec![1.0],
+        speed: 0.5,
+        visible: true,
+        shape: Shape::Square,
+    }
+}
+
+#[test]
+fn register_in_field_order() {
+    let params = Params::new();
+    settings().register(&params);
+    assert_eq!(params.names(), vec!["radius", "speed", "visible", "shape"]);
+}
+
+#[test]
+fn field_options() {
+    let params = Params::new();
+    settings().register(&params);
+    let radius = params.param("radius").unwrap();
+    assert_eq!(radius.label, "Radius");
+    let kind = Kind::Float {
+        min: 1.0,
+        max: 200.0,
+        step: Some(1.0),
+    };
+    assert_eq!(radius.kind, kind);
+    assert_eq!(radius.default, Value::Float(10.0));
+    let speed = params.param("speed").unwrap();
+    assert_eq!(speed.label, "speed");
+    let kind = Kind::Float {
+        min: 0.0,
+        max: 1.0,
+        step: None,
+    };
+    assert_eq!(speed.kind, kind);
+    assert!(params.param("cache").is_none());
+}
+
+#[test]
+fn enum_variants() {
+    let params = Params::new();
+    settings().register(&params);
+    let shape = params.param("shape").unwrap();
+    let variants = vec!["Circle".to_string(), "Box".into(), "Triangle".into()];
+    assert_eq!(shape.kind, Kind::Enum { variants });
+    assert_eq!(shape.default, Value::Enum(1));
+    assert_eq!(params.enum_variant("shape"), Some("Box".to_string()));
+}
+
+#[test]
+fn read_and_write() {
+    let params = Params::new();
+    let mut settings = settings();
+    settings.register(&params);
+    params.set("radius", Value::Float(50.4)).unwrap();
+    params.set("shape", Value::Enum(2)).unwrap();
+    params.set("visible", false).unwrap();
+    settings.read(&params);
+    assert_eq!(settings.radius, 50.0);
+    assert_eq!(settings.shape, Shape::Triangle);
+    assert!(!settings.visible);
+    assert_eq!(settings.cache, vec![1.0]);
+
+    settings.shape = Shape::Circle;
+    settings.speed = 0.25;
+    settings.write(&params);
+    assert_eq!(params.get("shape"), Some(Value::Enum(0)));
+    assert_eq!(params.float("speed"), Some(0.25));
+}
@@ -0,0 +1,36 @@
+#![cfg(feature = "osc")]
+
+use nannou::param::{osc::Server, Params, Value};
+use nannou_osc as osc;
+use std::{thread, time};
+
+#[test]
+fn osc_loopback_test() {
+    let params = Params::new();
+    params.add_float("radius", 0.0, 100.0, 10.0);
+    params.add_bool("visible", false);
+    params.add_enum("shape", &["circle", "square"], 0);
+    // Let the OS choose a free port so that the test does not collide with other processes.
+    let server = Server::bind(&params, 0).unwrap();
+    assert_ne!(server.local_addr().port(), 0);
+
+    let sender = osc::sender().unwrap().connect(server.local_addr()).unwrap();
+    sender
+        .send(("/nannou/radius", vec![osc::Type::Float(250.0)]))
+        .unwrap();
+    sender
+        .send(("/nannou/visible", vec![osc::Type::Int(1)]))
+        .unwrap();
+    sender
+        .send(("/nannou/shape", vec![osc::Type::String("square".into())]))
+        .unwrap();
+
+    let start = time::Instant::now();
+    while params.enum_index("shape") != Some(1) {
+        assert!(start.elapsed() < time::Duration::from_secs(2));
+        thread::sleep(time::Duration::from_millis(5));
+    }
+    assert_eq!(params.float("radius"), Some(100.0));
+    assert_eq!(params.bool("visible"), Some(true));
+    assert_eq!(params.get("shape"), Some(Value::Enum(1)));
+}